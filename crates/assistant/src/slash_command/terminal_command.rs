@@ -8,6 +8,7 @@ use assistant_slash_command::{
 };
 use gpui::{AppContext, Task, View, WeakView};
 use language::{BufferSnapshot, CodeLabel, LspAdapterDelegate};
+use terminal::shell_integration::CommandStatus;
 use terminal_view::{terminal_panel::TerminalPanel, TerminalView};
 use ui::prelude::*;
 use workspace::{dock::Panel, Workspace};
@@ -76,20 +77,34 @@ impl SlashCommand for TerminalSlashCommand {
             return Task::ready(Err(anyhow::anyhow!("no active terminal")));
         };
 
-        let line_count = arguments
-            .get(0)
-            .and_then(|s| s.parse::<usize>().ok())
-            .unwrap_or(DEFAULT_CONTEXT_LINES);
-
-        let lines = active_terminal
-            .read(cx)
-            .model()
-            .read(cx)
-            .last_n_non_empty_lines(line_count);
+        let line_count = arguments.get(0).and_then(|s| s.parse::<usize>().ok());
+        let terminal = active_terminal.read(cx).model().read(cx);
 
         let mut text = String::new();
-        text.push_str("Terminal output:\n");
-        text.push_str(&lines.join("\n"));
+        // With shell integration, the output of the last command is the most relevant context,
+        // unless a specific amount of lines was requested.
+        let last_command = if line_count.is_none() {
+            terminal.last_command().zip(terminal.last_command_output())
+        } else {
+            None
+        };
+        if let Some((command, output)) = last_command {
+            text.push_str(&format!("Last terminal command: `{command}`\n"));
+            match terminal.last_command_status() {
+                Some(CommandStatus::Succeeded) => text.push_str("Exit code: 0\n"),
+                Some(CommandStatus::Failed { exit_code }) => {
+                    text.push_str(&format!("Exit code: {exit_code}\n"))
+                }
+                _ => {}
+            }
+            text.push_str("Command output:\n");
+            text.push_str(&output);
+        } else {
+            let lines =
+                terminal.last_n_non_empty_lines(line_count.unwrap_or(DEFAULT_CONTEXT_LINES));
+            text.push_str("Terminal output:\n");
+            text.push_str(&lines.join("\n"));
+        }
         let range = 0..text.len();
 
         Task::ready(Ok(SlashCommandOutput {
@@ -1,12 +1,18 @@
-use editor::Editor;
+use editor::{scroll::Autoscroll, Editor};
 use gpui::{
-    Element, EventEmitter, FocusableView, IntoElement, ParentElement, Render, StyledText,
-    Subscription, ViewContext,
+    anchored, deferred, DismissEvent, Element, EventEmitter, FocusableView, IntoElement,
+    ParentElement, Pixels, Point, Render, StyledText, Subscription, View, ViewContext,
+    WindowContext,
+};
+use language::{OffsetRangeExt, OutlineItem};
+use project::{ProjectPath, WorktreeId};
+use std::{
+    cmp,
+    path::{Path, MAIN_SEPARATOR, MAIN_SEPARATOR_STR},
+    sync::Arc,
 };
-use itertools::Itertools;
-use std::cmp;
 use theme::ActiveTheme;
-use ui::{prelude::*, ButtonLike, ButtonStyle, Label, Tooltip};
+use ui::{prelude::*, ButtonLike, ButtonStyle, ContextMenu, Label};
 use workspace::{
     item::{BreadcrumbText, ItemEvent, ItemHandle},
     ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView,
@@ -16,6 +22,37 @@ pub struct Breadcrumbs {
     pane_focused: bool,
     active_item: Option<Box<dyn ItemHandle>>,
     subscription: Option<Subscription>,
+    context_menu: Option<(View<ContextMenu>, Point<Pixels>, Subscription)>,
+}
+
+/// A breadcrumb, along with the dropdown it opens when clicked.
+struct Segment {
+    text: BreadcrumbText,
+    menu: Option<SegmentMenu>,
+    /// Whether the segment continues the path of the previous one.
+    in_path: bool,
+}
+
+#[derive(Clone)]
+enum SegmentMenu {
+    /// The entries of a worktree directory, marking the one the segment stands for.
+    Entries {
+        worktree_id: WorktreeId,
+        directory: Arc<Path>,
+        selected: Option<Arc<Path>>,
+    },
+    /// The outline items next to the enclosing symbol at the given nesting level.
+    Symbols(usize),
+}
+
+impl Segment {
+    fn plain(text: BreadcrumbText) -> Self {
+        Self {
+            text,
+            menu: None,
+            in_path: false,
+        }
+    }
 }
 
 impl Default for Breadcrumbs {
@@ -30,8 +67,302 @@ impl Breadcrumbs {
             pane_focused: false,
             active_item: Default::default(),
             subscription: Default::default(),
+            context_menu: None,
+        }
+    }
+
+    fn active_editor(&self) -> Option<View<Editor>> {
+        self.active_item.as_ref()?.downcast::<Editor>()
+    }
+
+    fn deploy_segment_menu(
+        &mut self,
+        menu: SegmentMenu,
+        position: Point<Pixels>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(editor) = self.active_editor() else {
+            return;
+        };
+        let context_menu = match menu {
+            SegmentMenu::Entries {
+                worktree_id,
+                directory,
+                selected,
+            } => self.entries_menu(editor, worktree_id, directory, selected, position, cx),
+            SegmentMenu::Symbols(depth) => symbols_menu(editor, depth, cx),
+        };
+        let Some(context_menu) = context_menu else {
+            return;
+        };
+
+        cx.focus_view(&context_menu);
+        let subscription = cx.subscribe(&context_menu, |breadcrumbs, _, _: &DismissEvent, cx| {
+            breadcrumbs.context_menu.take();
+            cx.notify();
+        });
+        self.context_menu = Some((context_menu, position, subscription));
+        cx.notify();
+    }
+
+    /// Lists the files and directories of a worktree directory: picking a file opens it, picking a
+    /// directory lists its own entries.
+    fn entries_menu(
+        &self,
+        editor: View<Editor>,
+        worktree_id: WorktreeId,
+        directory: Arc<Path>,
+        selected: Option<Arc<Path>>,
+        position: Point<Pixels>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<ContextMenu>> {
+        let workspace = editor.read(cx).workspace()?;
+        let worktree = workspace
+            .read(cx)
+            .project()
+            .read(cx)
+            .worktree_for_id(worktree_id, cx)?;
+        let mut entries = worktree
+            .read(cx)
+            .child_entries(&directory)
+            .map(|entry| (entry.path.clone(), entry.is_dir()))
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            return None;
         }
+        entries.sort_by_key(|(_, is_dir)| !is_dir);
+
+        let breadcrumbs = cx.view().downgrade();
+        Some(ContextMenu::build(cx, |menu, _| {
+            entries.into_iter().fold(menu, |menu, (path, is_dir)| {
+                let mut label = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                if is_dir {
+                    label.push(MAIN_SEPARATOR);
+                }
+                let toggled = selected.as_ref() == Some(&path);
+                let breadcrumbs = breadcrumbs.clone();
+                let workspace = workspace.downgrade();
+                menu.toggleable_entry(label, toggled, IconPosition::Start, None, move |cx| {
+                    if is_dir {
+                        let menu = SegmentMenu::Entries {
+                            worktree_id,
+                            directory: path.clone(),
+                            selected: None,
+                        };
+                        breadcrumbs
+                            .update(cx, |breadcrumbs, cx| {
+                                breadcrumbs.deploy_segment_menu(menu, position, cx)
+                            })
+                            .ok();
+                    } else {
+                        let project_path = ProjectPath {
+                            worktree_id,
+                            path: path.clone(),
+                        };
+                        workspace
+                            .update(cx, |workspace, cx| {
+                                workspace
+                                    .open_path(project_path, None, true, cx)
+                                    .detach_and_log_err(cx);
+                            })
+                            .ok();
+                    }
+                })
+            })
+        }))
+    }
+}
+
+/// Lists the outline items sharing the parent of the enclosing symbol at the given nesting level:
+/// picking one moves the cursor to it.
+fn symbols_menu(
+    editor: View<Editor>,
+    depth: usize,
+    cx: &mut WindowContext,
+) -> Option<View<ContextMenu>> {
+    let (excerpt_id, symbols, current_ix) = {
+        let editor = editor.read(cx);
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let cursor = snapshot.anchor_before(editor.selections.newest_anchor().head());
+        let excerpt_id = cursor.excerpt_id;
+        let buffer = snapshot.buffer_for_excerpt(excerpt_id)?;
+        let in_excerpt = |item: &&OutlineItem<language::Anchor>| {
+            snapshot
+                .anchor_in_excerpt(excerpt_id, item.range.start)
+                .zip(snapshot.anchor_in_excerpt(excerpt_id, item.range.end))
+                .is_some()
+        };
+        // The breadcrumbs only show the enclosing symbols that fit in the cursor's excerpt.
+        let enclosing = buffer
+            .symbols_containing(cursor.text_anchor, None)?
+            .into_iter()
+            .filter(|item| in_excerpt(&item))
+            .nth(depth)?;
+        let items = buffer.outline_items_containing(0..buffer.len(), false, None)?;
+        let enclosing_range = enclosing.range.to_offset(buffer);
+        let ix = items.iter().position(|item| {
+            item.depth == enclosing.depth && item.range.to_offset(buffer) == enclosing_range
+        })?;
+        let mut current_ix = 0;
+        let symbols = sibling_items(&items, ix)
+            .filter(in_excerpt)
+            .enumerate()
+            .map(|(sibling_ix, item)| {
+                if item.range.to_offset(buffer) == enclosing_range {
+                    current_ix = sibling_ix;
+                }
+                (item.text.clone(), item.range.start)
+            })
+            .collect::<Vec<_>>();
+        (excerpt_id, symbols, current_ix)
+    };
+
+    let focus_handle = editor.focus_handle(cx);
+    Some(ContextMenu::build(cx, |menu, _| {
+        let menu = symbols.into_iter().enumerate().fold(
+            menu.context(focus_handle),
+            |menu, (ix, (text, position))| {
+                let editor = editor.clone();
+                menu.toggleable_entry(
+                    text,
+                    ix == current_ix,
+                    IconPosition::Start,
+                    None,
+                    move |cx| {
+                        editor.update(cx, |editor, cx| {
+                            let snapshot = editor.buffer().read(cx).snapshot(cx);
+                            if let Some(position) = snapshot.anchor_in_excerpt(excerpt_id, position)
+                            {
+                                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                                    s.select_ranges([position..position])
+                                });
+                            }
+                            editor.focus(cx);
+                        });
+                    },
+                )
+            },
+        );
+        menu.separator().entry(
+            "Show Symbol Outline",
+            Some(Box::new(editor::actions::ToggleOutline)),
+            move |cx| outline::toggle(editor.clone(), &editor::actions::ToggleOutline, cx),
+        )
+    }))
+}
+
+/// The items nested in the same parent as the item at `ix`, in document order.
+fn sibling_items<T>(
+    items: &[OutlineItem<T>],
+    ix: usize,
+) -> impl Iterator<Item = &OutlineItem<T>> + '_ {
+    let depth = items[ix].depth;
+    let start = items[..ix]
+        .iter()
+        .rposition(|item| item.depth < depth)
+        .map_or(0, |parent_ix| parent_ix + 1);
+    let end = items[ix..]
+        .iter()
+        .position(|item| item.depth < depth)
+        .map_or(items.len(), |end| ix + end);
+    items[start..end]
+        .iter()
+        .filter(move |item| item.depth == depth)
+}
+
+/// Splits the editor's breadcrumbs into the components of its file path, each listing the other
+/// entries of its directory, followed by the enclosing symbols.
+fn editor_segments(
+    editor: &View<Editor>,
+    breadcrumbs: Vec<BreadcrumbText>,
+    cx: &WindowContext,
+) -> Vec<Segment> {
+    let mut breadcrumbs = breadcrumbs.into_iter();
+    let Some(path_breadcrumb) = breadcrumbs.next() else {
+        return Vec::new();
+    };
+    let editor = editor.read(cx);
+    let project_path = editor
+        .selections
+        .newest_anchor()
+        .head()
+        .buffer_id
+        .and_then(|buffer_id| editor.buffer().read(cx).buffer(buffer_id))
+        .and_then(|buffer| project::ProjectItem::project_path(buffer.read(cx), cx));
+
+    let mut segments = path_segments(path_breadcrumb, project_path);
+    segments.extend(breadcrumbs.enumerate().map(|(depth, text)| Segment {
+        text,
+        menu: Some(SegmentMenu::Symbols(depth)),
+        in_path: false,
+    }));
+    segments
+}
+
+fn path_segments(breadcrumb: BreadcrumbText, project_path: Option<ProjectPath>) -> Vec<Segment> {
+    let Some(project_path) = project_path else {
+        return vec![Segment::plain(breadcrumb)];
+    };
+    // The path may be preceded by the worktree's root name, or replaced by a custom header.
+    let path = project_path.path.to_string_lossy();
+    let root_name = match breadcrumb.text.strip_suffix(path.as_ref()) {
+        Some("") => Some(None),
+        Some(prefix) if !path.is_empty() => prefix
+            .strip_suffix(MAIN_SEPARATOR)
+            .map(|root_name| Some(root_name.to_string())),
+        _ => None,
+    };
+    let Some(root_name) = root_name else {
+        return vec![Segment::plain(breadcrumb)];
+    };
+
+    let worktree_id = project_path.worktree_id;
+    let segment = |text: String, menu: SegmentMenu| Segment {
+        text: BreadcrumbText {
+            text,
+            highlights: None,
+            font: breadcrumb.font.clone(),
+        },
+        menu: Some(menu),
+        in_path: true,
+    };
+    let mut segments = Vec::new();
+    if let Some(root_name) = root_name {
+        segments.push(segment(
+            root_name,
+            SegmentMenu::Entries {
+                worktree_id,
+                directory: Arc::from(Path::new("")),
+                selected: None,
+            },
+        ));
+    }
+    for ancestor in project_path
+        .path
+        .ancestors()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+    {
+        let (Some(name), Some(directory)) = (ancestor.file_name(), ancestor.parent()) else {
+            continue;
+        };
+        segments.push(segment(
+            name.to_string_lossy().to_string(),
+            SegmentMenu::Entries {
+                worktree_id,
+                directory: Arc::from(directory),
+                selected: Some(Arc::from(ancestor)),
+            },
+        ));
+    }
+    if let Some(first) = segments.first_mut() {
+        first.in_path = false;
     }
+    segments
 }
 
 impl EventEmitter<ToolbarItemEvent> for Breadcrumbs {}
@@ -43,9 +374,13 @@ impl Render for Breadcrumbs {
         let Some(active_item) = self.active_item.as_ref() else {
             return element;
         };
-        let Some(mut segments) = active_item.breadcrumbs(cx.theme(), cx) else {
+        let Some(breadcrumbs) = active_item.breadcrumbs(cx.theme(), cx) else {
             return element;
         };
+        let mut segments = match active_item.downcast::<Editor>() {
+            Some(editor) => editor_segments(&editor, breadcrumbs, cx),
+            None => breadcrumbs.into_iter().map(Segment::plain).collect(),
+        };
 
         let prefix_end_ix = cmp::min(segments.len(), MAX_SEGMENTS / 2);
         let suffix_start_ix = cmp::max(
@@ -55,72 +390,67 @@ impl Render for Breadcrumbs {
         if suffix_start_ix > prefix_end_ix {
             segments.splice(
                 prefix_end_ix..suffix_start_ix,
-                Some(BreadcrumbText {
+                Some(Segment::plain(BreadcrumbText {
                     text: "⋯".into(),
                     highlights: None,
                     font: None,
-                }),
+                })),
             );
         }
 
-        let highlighted_segments = segments.into_iter().map(|segment| {
+        let mut breadcrumbs = Vec::new();
+        for (ix, segment) in segments.into_iter().enumerate() {
+            if ix > 0 {
+                let separator = if segment.in_path {
+                    MAIN_SEPARATOR_STR
+                } else {
+                    "›"
+                };
+                breadcrumbs.push(
+                    Label::new(separator)
+                        .color(Color::Placeholder)
+                        .into_any_element(),
+                );
+            }
+
             let mut text_style = cx.text_style();
-            if let Some(font) = segment.font {
+            if let Some(font) = segment.text.font {
                 text_style.font_family = font.family;
                 text_style.font_features = font.features;
                 text_style.font_style = font.style;
                 text_style.font_weight = font.weight;
             }
             text_style.color = Color::Muted.color(cx);
+            let text = StyledText::new(segment.text.text.replace('\n', "␤"))
+                .with_highlights(&text_style, segment.text.highlights.unwrap_or_default());
 
-            StyledText::new(segment.text.replace('\n', "␤"))
-                .with_highlights(&text_style, segment.highlights.unwrap_or_default())
-                .into_any()
-        });
-        let breadcrumbs = Itertools::intersperse_with(highlighted_segments, || {
-            Label::new("›").color(Color::Placeholder).into_any_element()
-        });
-
-        let breadcrumbs_stack = h_flex().gap_1().children(breadcrumbs);
-        match active_item
-            .downcast::<Editor>()
-            .map(|editor| editor.downgrade())
-        {
-            Some(editor) => element.child(
-                ButtonLike::new("toggle outline view")
-                    .child(breadcrumbs_stack)
+            breadcrumbs.push(match segment.menu {
+                Some(menu) => ButtonLike::new(("breadcrumb", ix))
+                    .child(text)
                     .style(ButtonStyle::Transparent)
-                    .on_click({
-                        let editor = editor.clone();
-                        move |_, cx| {
-                            if let Some(editor) = editor.upgrade() {
-                                outline::toggle(editor, &editor::actions::ToggleOutline, cx)
-                            }
-                        }
-                    })
-                    .tooltip(move |cx| {
-                        if let Some(editor) = editor.upgrade() {
-                            let focus_handle = editor.read(cx).focus_handle(cx);
-                            Tooltip::for_action_in(
-                                "Show symbol outline",
-                                &editor::actions::ToggleOutline,
-                                &focus_handle,
-                                cx,
-                            )
-                        } else {
-                            Tooltip::for_action(
-                                "Show symbol outline",
-                                &editor::actions::ToggleOutline,
-                                cx,
-                            )
-                        }
-                    }),
-            ),
-            None => element
-                // Match the height of the `ButtonLike` in the other arm.
-                .h(rems_from_px(22.))
-                .child(breadcrumbs_stack),
+                    .on_click(
+                        cx.listener(move |breadcrumbs, event: &gpui::ClickEvent, cx| {
+                            breadcrumbs.deploy_segment_menu(menu.clone(), event.down.position, cx)
+                        }),
+                    )
+                    .into_any_element(),
+                None => text.into_any(),
+            });
         }
+
+        element
+            // Match the height of the `ButtonLike` breadcrumbs.
+            .h(rems_from_px(22.))
+            .child(h_flex().gap_1().children(breadcrumbs))
+            .children(self.context_menu.as_ref().map(|(menu, position, _)| {
+                deferred(
+                    anchored()
+                        .position(*position)
+                        .anchor(gpui::Corner::TopLeft)
+                        .child(menu.clone()),
+                )
+                .with_priority(1)
+            }))
     }
 }
 
@@ -162,3 +492,43 @@ impl ToolbarItemView for Breadcrumbs {
         self.pane_focused = pane_focused;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(depth: usize, text: &str) -> OutlineItem<usize> {
+        OutlineItem {
+            depth,
+            range: 0..0,
+            text: text.to_string(),
+            highlight_ranges: Vec::new(),
+            name_ranges: Vec::new(),
+            body_range: None,
+            annotation_range: None,
+        }
+    }
+
+    #[test]
+    fn test_sibling_items() {
+        let items = [
+            item(0, "struct Point"),
+            item(1, "x"),
+            item(1, "y"),
+            item(0, "impl Point"),
+            item(1, "fn new"),
+            item(2, "let origin"),
+            item(1, "fn len"),
+            item(0, "fn main"),
+        ];
+        let siblings = |ix| {
+            sibling_items(&items, ix)
+                .map(|item| item.text.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(siblings(0), ["struct Point", "impl Point", "fn main"]);
+        assert_eq!(siblings(2), ["x", "y"]);
+        assert_eq!(siblings(6), ["fn new", "fn len"]);
+        assert_eq!(siblings(5), ["let origin"]);
+    }
+}
@@ -1,18 +1,14 @@
-use std::{
-    cmp::{self, Reverse},
-    sync::Arc,
-    time::Duration,
-};
+use std::{cmp, sync::Arc, time::Duration};
 
 use client::parse_zed_link;
-use collections::HashMap;
 use command_palette_hooks::{
     CommandInterceptResult, CommandPaletteFilter, CommandPaletteInterceptor,
 };
+use db::frecency::{Frecencies, FrecencyKind, FrecencyScope, FRECENCY};
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
-    Action, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Global,
-    ParentElement, Render, Styled, Task, UpdateGlobal, View, ViewContext, VisualContext, WeakView,
+    Action, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, ParentElement,
+    Render, Styled, Task, View, ViewContext, VisualContext, WeakView,
 };
 use picker::{Picker, PickerDelegate};
 
@@ -25,7 +21,6 @@ use zed_actions::{command_palette::Toggle, OpenZedUrl};
 
 pub fn init(cx: &mut AppContext) {
     client::init_settings(cx);
-    cx.set_global(HitCounts::default());
     command_palette_hooks::init(cx);
     cx.observe_new_views(CommandPalette::register).detach();
 }
@@ -63,12 +58,18 @@ impl CommandPalette {
         let Some(previous_focus_handle) = cx.focused() else {
             return;
         };
+        let workspace_id = workspace.database_id().map(i64::from);
         workspace.toggle_modal(cx, move |cx| {
-            CommandPalette::new(previous_focus_handle, query, cx)
+            CommandPalette::new(previous_focus_handle, query, workspace_id, cx)
         });
     }
 
-    fn new(previous_focus_handle: FocusHandle, query: &str, cx: &mut ViewContext<Self>) -> Self {
+    fn new(
+        previous_focus_handle: FocusHandle,
+        query: &str,
+        workspace_id: Option<i64>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
         let filter = CommandPaletteFilter::try_global(cx);
 
         let commands = cx
@@ -86,14 +87,32 @@ impl CommandPalette {
             })
             .collect();
 
-        let delegate =
-            CommandPaletteDelegate::new(cx.view().downgrade(), commands, previous_focus_handle);
+        let delegate = CommandPaletteDelegate::new(
+            cx.view().downgrade(),
+            commands,
+            previous_focus_handle,
+            workspace_id,
+        );
 
         let picker = cx.new_view(|cx| {
             let picker = Picker::uniform_list(delegate, cx);
             picker.set_query(query, cx);
             picker
         });
+
+        let frecencies = cx
+            .background_executor()
+            .spawn(async move { CommandPaletteDelegate::load_frecencies(workspace_id) });
+        let weak_picker = picker.downgrade();
+        cx.spawn(|_, mut cx| async move {
+            let frecencies = frecencies.await;
+            weak_picker.update(&mut cx, |picker, cx| {
+                picker.delegate.frecencies = Arc::new(frecencies);
+                picker.refresh(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+
         Self { picker }
     }
 
@@ -124,6 +143,8 @@ pub struct CommandPaletteDelegate {
     matches: Vec<StringMatch>,
     selected_ix: usize,
     previous_focus_handle: FocusHandle,
+    workspace_id: Option<i64>,
+    frecencies: Arc<Frecencies>,
     updating_matches: Option<(
         Task<()>,
         postage::dispatch::Receiver<(Vec<Command>, Vec<StringMatch>)>,
@@ -144,19 +165,12 @@ impl Clone for Command {
     }
 }
 
-/// Hit count for each command in the palette.
-/// We only account for commands triggered directly via command palette and not by e.g. keystrokes because
-/// if a user already knows a keystroke for a command, they are unlikely to use a command palette to look for it.
-#[derive(Default, Clone)]
-struct HitCounts(HashMap<String, usize>);
-
-impl Global for HitCounts {}
-
 impl CommandPaletteDelegate {
     fn new(
         command_palette: WeakView<CommandPalette>,
         commands: Vec<Command>,
         previous_focus_handle: FocusHandle,
+        workspace_id: Option<i64>,
     ) -> Self {
         Self {
             command_palette,
@@ -165,10 +179,35 @@ impl CommandPaletteDelegate {
             commands,
             selected_ix: 0,
             previous_focus_handle,
+            workspace_id,
+            frecencies: Arc::default(),
             updating_matches: None,
         }
     }
 
+    /// The frecency of the commands run from the palette, in this workspace and in all of them.
+    /// We only account for commands triggered directly via command palette and not by e.g. keystrokes because
+    /// if a user already knows a keystroke for a command, they are unlikely to use a command palette to look for it.
+    fn load_frecencies(workspace_id: Option<i64>) -> Frecencies {
+        let mut frecencies = workspace_id
+            .and_then(|workspace_id| {
+                FRECENCY
+                    .frecencies(
+                        FrecencyKind::Command,
+                        FrecencyScope::Workspace(workspace_id),
+                    )
+                    .log_err()
+            })
+            .unwrap_or_default();
+        if let Some(global) = FRECENCY
+            .frecencies(FrecencyKind::Command, FrecencyScope::Global)
+            .log_err()
+        {
+            frecencies.merge(global);
+        }
+        frecencies
+    }
+
     fn matches_updated(
         &mut self,
         query: String,
@@ -256,15 +295,15 @@ impl PickerDelegate for CommandPaletteDelegate {
         let (mut tx, mut rx) = postage::dispatch::channel(1);
         let task = cx.background_executor().spawn({
             let mut commands = self.all_commands.clone();
-            let hit_counts = cx.global::<HitCounts>().clone();
+            let frecencies = self.frecencies.clone();
             let executor = cx.background_executor().clone();
             let query = trim_consecutive_whitespaces(query.as_str());
             async move {
-                commands.sort_by_key(|action| {
-                    (
-                        Reverse(hit_counts.0.get(&action.name).cloned()),
-                        action.name.clone(),
-                    )
+                commands.sort_by(|a, b| {
+                    frecencies
+                        .get(&b.name)
+                        .total_cmp(&frecencies.get(&a.name))
+                        .then_with(|| a.name.cmp(&b.name))
                 });
 
                 let candidates = commands
@@ -284,7 +323,7 @@ impl PickerDelegate for CommandPaletteDelegate {
                         })
                         .collect()
                 } else {
-                    fuzzy::match_strings(
+                    let mut matches = fuzzy::match_strings(
                         &candidates,
                         &query,
                         true,
@@ -292,7 +331,12 @@ impl PickerDelegate for CommandPaletteDelegate {
                         &Default::default(),
                         executor,
                     )
-                    .await
+                    .await;
+                    for mat in &mut matches {
+                        mat.score = frecencies.bias(&mat.string, mat.score);
+                    }
+                    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+                    matches
                 };
 
                 tx.send((commands, matches)).await.log_err();
@@ -361,8 +405,21 @@ impl PickerDelegate for CommandPaletteDelegate {
         );
         self.matches.clear();
         self.commands.clear();
-        HitCounts::update_global(cx, |hit_counts, _cx| {
-            *hit_counts.0.entry(command.name).or_default() += 1;
+        let workspace_id = self.workspace_id;
+        let name = command.name;
+        db::write_and_log(cx, move || async move {
+            if let Some(workspace_id) = workspace_id {
+                FRECENCY
+                    .record_use(
+                        FrecencyKind::Command,
+                        FrecencyScope::Workspace(workspace_id),
+                        name.clone(),
+                    )
+                    .await?;
+            }
+            FRECENCY
+                .record_use(FrecencyKind::Command, FrecencyScope::Global, name)
+                .await
         });
         let action = command.action;
         cx.focus(&self.previous_focus_handle);
@@ -1,3 +1,4 @@
+pub mod frecency;
 pub mod kvp;
 pub mod query;
 
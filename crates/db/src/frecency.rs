@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use sqlez::bindable::{Bind, StaticColumnCount};
+use sqlez::statement::Statement;
+use sqlez_macros::sql;
+
+use crate::define_connection;
+
+/// The time after which a use counts for half as much.
+const HALF_LIFE_SECS: f64 = 7. * 24. * 60. * 60.;
+/// Uses older than this are forgotten.
+const MAX_AGE_SECS: i64 = 90 * 24 * 60 * 60;
+/// How much frecency scales up a fuzzy match score.
+const BIAS_WEIGHT: f64 = 0.1;
+
+/// What the keys of a frecency are. Pickers listing the same things share a kind, so that opening
+/// a file from the tab switcher ranks it higher in the file finder too.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrecencyKind {
+    /// Paths relative to their worktree.
+    File,
+    /// Humanized action names.
+    Command,
+    /// Workspace database ids.
+    Project,
+}
+
+impl StaticColumnCount for FrecencyKind {}
+
+impl Bind for FrecencyKind {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        let kind = match self {
+            FrecencyKind::File => "file",
+            FrecencyKind::Command => "command",
+            FrecencyKind::Project => "project",
+        };
+        statement.bind(&kind, start_index)
+    }
+}
+
+/// Where a use is recorded: in a single workspace, or across all of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrecencyScope {
+    Global,
+    Workspace(i64),
+}
+
+impl StaticColumnCount for FrecencyScope {}
+
+impl Bind for FrecencyScope {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        match self {
+            FrecencyScope::Global => statement.bind(&"global", start_index),
+            FrecencyScope::Workspace(id) => statement.bind(&format!("workspace-{id}"), start_index),
+        }
+    }
+}
+
+/// The frecency of the keys of one kind, as of when they were loaded.
+#[derive(Clone, Debug, Default)]
+pub struct Frecencies(HashMap<String, f64>);
+
+impl Frecencies {
+    pub fn get(&self, key: &str) -> f64 {
+        self.0.get(key).copied().unwrap_or_default()
+    }
+
+    /// Adds the frecencies of `other`, e.g. to rank by the uses in a workspace and the global ones.
+    pub fn merge(&mut self, other: Frecencies) {
+        for (key, frecency) in other.0 {
+            *self.0.entry(key).or_default() += frecency;
+        }
+    }
+
+    /// Scales up a fuzzy match score for a key that was used often and recently, so that it wins
+    /// over similarly good matches without burying much better ones.
+    pub fn bias(&self, key: &str, score: f64) -> f64 {
+        score * (1. + BIAS_WEIGHT * self.get(key).ln_1p())
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+fn decayed(score: f64, last_used: i64, now: i64) -> f64 {
+    let age = now.saturating_sub(last_used).max(0) as f64;
+    score * 0.5f64.powf(age / HALF_LIFE_SECS)
+}
+
+define_connection!(pub static ref FRECENCY: FrecencyStore<()> =
+    &[sql!(
+        CREATE TABLE IF NOT EXISTS frecency(
+            kind TEXT NOT NULL,
+            scope TEXT NOT NULL,
+            key TEXT NOT NULL,
+            score REAL NOT NULL,
+            last_used INTEGER NOT NULL,
+            PRIMARY KEY(kind, scope, key)
+        ) STRICT;
+    )];
+);
+
+impl FrecencyStore {
+    /// Records a use of `key` among the keys of `kind`.
+    pub async fn record_use(
+        &self,
+        kind: FrecencyKind,
+        scope: FrecencyScope,
+        key: String,
+    ) -> Result<()> {
+        self.record_use_at(kind, scope, key, now_secs()).await
+    }
+
+    /// Loads the frecency of every key of `kind` used in `scope`.
+    pub fn frecencies(&self, kind: FrecencyKind, scope: FrecencyScope) -> Result<Frecencies> {
+        self.frecencies_at(kind, scope, now_secs())
+    }
+
+    async fn record_use_at(
+        &self,
+        kind: FrecencyKind,
+        scope: FrecencyScope,
+        key: String,
+        now: i64,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("record_frecency", || {
+                let previous = conn.select_row_bound::<_, (f64, i64)>(sql!(
+                    SELECT score, last_used FROM frecency WHERE kind = ? AND scope = ? AND key = ?
+                ))?((kind, scope, key.as_str()))?;
+                let score =
+                    previous.map_or(0., |(score, last_used)| decayed(score, last_used, now)) + 1.;
+                conn.exec_bound(sql!(
+                    INSERT OR REPLACE INTO frecency(kind, scope, key, score, last_used)
+                    VALUES (?, ?, ?, ?, ?)
+                ))?((kind, scope, key.as_str(), score, now))?;
+                conn.exec_bound(sql!(
+                    DELETE FROM frecency WHERE kind = ? AND scope = ? AND last_used < ?
+                ))?((kind, scope, now - MAX_AGE_SECS))
+            })
+        })
+        .await
+    }
+
+    fn frecencies_at(
+        &self,
+        kind: FrecencyKind,
+        scope: FrecencyScope,
+        now: i64,
+    ) -> Result<Frecencies> {
+        let rows = self.select_bound::<_, (String, f64, i64)>(sql!(
+            SELECT key, score, last_used FROM frecency WHERE kind = ? AND scope = ?
+        ))?((kind, scope))?;
+        Ok(Frecencies(
+            rows.into_iter()
+                .map(|(key, score, last_used)| (key, decayed(score, last_used, now)))
+                .collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    #[gpui::test]
+    async fn test_frecency() {
+        let db = FrecencyStore(crate::open_test_db("test_frecency").await);
+        let workspace = FrecencyScope::Workspace(1);
+        let record = |key: &str, now: i64| {
+            db.record_use_at(FrecencyKind::File, workspace, key.to_string(), now)
+        };
+
+        // Used often, but long ago.
+        for _ in 0..4 {
+            record("old.rs", 0).await.unwrap();
+        }
+        // Used less, but recently.
+        for _ in 0..2 {
+            record("new.rs", 20 * DAY).await.unwrap();
+        }
+
+        let frecencies = db
+            .frecencies_at(FrecencyKind::File, workspace, 21 * DAY)
+            .unwrap();
+        assert!(frecencies.get("new.rs") > frecencies.get("old.rs"));
+        assert!(frecencies.get("old.rs") > 0.);
+        assert_eq!(frecencies.get("unused.rs"), 0.);
+        assert!(frecencies.bias("new.rs", 0.5) > frecencies.bias("old.rs", 0.5));
+        assert_eq!(frecencies.bias("unused.rs", 0.5), 0.5);
+
+        // Kinds and scopes are kept apart.
+        let global = db
+            .frecencies_at(FrecencyKind::File, FrecencyScope::Global, 21 * DAY)
+            .unwrap();
+        assert_eq!(global.get("new.rs"), 0.);
+        let commands = db
+            .frecencies_at(FrecencyKind::Command, workspace, 21 * DAY)
+            .unwrap();
+        assert_eq!(commands.get("new.rs"), 0.);
+
+        // Uses older than the maximum age are forgotten on the next write.
+        record("new.rs", 100 * DAY).await.unwrap();
+        let frecencies = db
+            .frecencies_at(FrecencyKind::File, workspace, 100 * DAY)
+            .unwrap();
+        assert_eq!(frecencies.get("old.rs"), 0.);
+        assert!(frecencies.get("new.rs") > 1.);
+    }
+}
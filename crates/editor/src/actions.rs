@@ -161,6 +161,110 @@ pub struct FoldAtLevel {
     pub level: u32,
 }
 
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct ReplayLastMacro {
+    /// How many times to replay the macro. Defaults to once.
+    #[serde(default)]
+    pub times: Option<usize>,
+}
+
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct ReplayMacro {
+    /// The name of the macro in the `keyboard_macros` setting.
+    pub name: String,
+    /// How many times to replay the macro. Defaults to once.
+    #[serde(default)]
+    pub times: Option<usize>,
+}
+
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct SaveLastMacro {
+    /// The name to save the macro under. Defaults to the first free `macro-<n>`.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct SplitSelectionIntoLines {
+    /// Whether to keep the selected text on each line, instead of placing a cursor at its end.
+    #[serde(default)]
+    pub keep_selections: bool,
+}
+
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct AlignOnPattern {
+    /// The text to align the selected lines on. Defaults to `=`.
+    #[serde(default = "default_align_pattern")]
+    pub pattern: String,
+    /// Whether `pattern` is a regular expression.
+    #[serde(default)]
+    pub regex: bool,
+}
+
+fn default_align_pattern() -> String {
+    "=".to_string()
+}
+
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct InsertSequentialNumbers {
+    /// The number inserted at the first selection. Defaults to 1.
+    #[serde(default = "default_sequence_start")]
+    pub start: i64,
+    /// The difference between the numbers at consecutive selections. Defaults to 1.
+    #[serde(default = "default_sequence_step")]
+    pub step: i64,
+    /// The text to insert, in which `{}` is replaced by the number. A minimum width can be
+    /// given as `{:3}` to pad with spaces, or as `{:03}` to pad with zeros. Defaults to `{}`.
+    #[serde(default = "default_sequence_format")]
+    pub format: String,
+}
+
+fn default_sequence_start() -> i64 {
+    1
+}
+
+fn default_sequence_step() -> i64 {
+    1
+}
+
+fn default_sequence_format() -> String {
+    "{}".to_string()
+}
+
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct SelectTextObject {
+    /// The kind of text object to select, such as `inside_parameter` or `around_function`.
+    pub object: language::TextObject,
+}
+
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct MoveToNextTextObject {
+    pub object: language::TextObject,
+}
+
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct MoveToPreviousTextObject {
+    pub object: language::TextObject,
+}
+
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct SwapWithNextTextObject {
+    /// The kind of text object to swap. Defaults to `inside_parameter`.
+    #[serde(default = "default_swapped_text_object")]
+    pub object: language::TextObject,
+}
+
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct SwapWithPreviousTextObject {
+    /// The kind of text object to swap. Defaults to `inside_parameter`.
+    #[serde(default = "default_swapped_text_object")]
+    pub object: language::TextObject,
+}
+
+fn default_swapped_text_object() -> language::TextObject {
+    language::TextObject::InsideParameter
+}
+
 #[derive(PartialEq, Clone, Deserialize, Default)]
 pub struct SpawnNearestTask {
     #[serde(default)]
@@ -177,6 +281,7 @@ pub enum UuidVersion {
 impl_actions!(
     editor,
     [
+        AlignOnPattern,
         ComposeCompletion,
         ConfirmCodeAction,
         ConfirmCompletion,
@@ -187,20 +292,30 @@ impl_actions!(
         ExpandExcerptsUp,
         FoldAt,
         HandleInput,
+        InsertSequentialNumbers,
         MoveDownByLines,
         MovePageDown,
         MovePageUp,
         MoveToBeginningOfLine,
         MoveToEndOfLine,
+        MoveToNextTextObject,
+        MoveToPreviousTextObject,
         MoveUpByLines,
+        ReplayLastMacro,
+        ReplayMacro,
+        SaveLastMacro,
         SelectDownByLines,
         SelectNext,
         SelectPrevious,
+        SelectTextObject,
         SelectToBeginningOfLine,
         SelectToEndOfLine,
         SelectUpByLines,
         SpawnNearestTask,
         ShowCompletions,
+        SplitSelectionIntoLines,
+        SwapWithNextTextObject,
+        SwapWithPreviousTextObject,
         ToggleCodeActions,
         ToggleComments,
         UnfoldAt,
@@ -216,6 +331,7 @@ gpui::actions!(
         AcceptPartialInlineCompletion,
         AddSelectionAbove,
         AddSelectionBelow,
+        AlignSelections,
         ApplyAllDiffHunks,
         ApplyDiffHunk,
         Backspace,
@@ -252,6 +368,7 @@ gpui::actions!(
         DuplicateLineDown,
         DuplicateLineUp,
         DuplicateSelection,
+        ExitLargeFileMode,
         ExpandAllHunkDiffs,
         ExpandMacroRecursively,
         FindAllReferences,
@@ -324,6 +441,7 @@ gpui::actions!(
         Redo,
         RedoSelection,
         Rename,
+        ReplayLastMacroToEndOfFile,
         RestartLanguageServer,
         RevealInFileManager,
         ReverseLines,
@@ -361,7 +479,6 @@ gpui::actions!(
         ShuffleLines,
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
-        SplitSelectionIntoLines,
         SwitchSourceHeader,
         Tab,
         TabPrev,
@@ -373,6 +490,7 @@ gpui::actions!(
         ToggleInlayHints,
         ToggleInlineCompletions,
         ToggleLineNumbers,
+        ToggleMacroRecording,
         ToggleRelativeLineNumbers,
         ToggleSelectionMenu,
         ToggleSoftWrap,
@@ -0,0 +1,331 @@
+use gpui::ViewContext;
+use language::{Point, Selection, SelectionGoal};
+use multi_buffer::MultiBufferRow;
+use regex::Regex;
+use util::ResultExt;
+
+use crate::{scroll::Autoscroll, AlignOnPattern, AlignSelections, Editor, InsertSequentialNumbers};
+
+impl Editor {
+    /// Inserts spaces before the selections so that they start at the same column on every line.
+    /// When lines contain several selections, the first selections of each line are aligned with
+    /// each other, then the second ones, and so on.
+    pub fn align_selections(&mut self, _: &AlignSelections, cx: &mut ViewContext<Self>) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let selections = self.selections.all::<Point>(cx);
+
+        let mut rows = Vec::<(u32, Vec<usize>)>::new();
+        for selection in &selections {
+            let line_start = Point::new(selection.start.row, 0);
+            let column = buffer
+                .text_for_range(line_start..selection.start)
+                .flat_map(str::chars)
+                .count();
+            match rows.last_mut() {
+                Some((row, columns)) if *row == selection.start.row => columns.push(column),
+                _ => rows.push((selection.start.row, vec![column])),
+            }
+        }
+        let padding = alignment_padding(rows.iter().map(|(_, columns)| columns.as_slice()));
+
+        let mut edits = Vec::new();
+        let mut new_selections = Vec::new();
+        let mut selections = selections.into_iter();
+        for padding in padding {
+            let mut shift = 0;
+            for (padding, selection) in padding.into_iter().zip(&mut selections) {
+                if padding > 0 {
+                    edits.push((selection.start..selection.start, " ".repeat(padding)));
+                }
+                shift += padding as u32;
+                let mut start = selection.start;
+                let mut end = selection.end;
+                if end.row == start.row {
+                    end.column += shift;
+                }
+                start.column += shift;
+                new_selections.push(Selection {
+                    id: selection.id,
+                    start,
+                    end,
+                    reversed: selection.reversed,
+                    goal: SelectionGoal::None,
+                });
+            }
+        }
+        if edits.is_empty() {
+            return;
+        }
+
+        self.transact(cx, |this, cx| {
+            this.edit(edits, cx);
+            this.change_selections(Some(Autoscroll::fit()), cx, |s| s.select(new_selections));
+        });
+    }
+
+    /// Inserts spaces before the first match of a pattern on each selected line so that the
+    /// matches start at the same column.
+    pub fn align_on_pattern(&mut self, action: &AlignOnPattern, cx: &mut ViewContext<Self>) {
+        if action.pattern.is_empty() {
+            return;
+        }
+        let pattern = if action.regex {
+            action.pattern.clone()
+        } else {
+            regex::escape(&action.pattern)
+        };
+        let Some(regex) = Regex::new(&pattern).log_err() else {
+            return;
+        };
+
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let mut rows = Vec::new();
+        for selection in self.selections.all::<Point>(cx) {
+            let mut end_row = selection.end.row;
+            if selection.end.column == 0 && end_row > selection.start.row {
+                end_row -= 1;
+            }
+            let start_row = match rows.last() {
+                Some(last_row) if *last_row >= selection.start.row => *last_row + 1,
+                _ => selection.start.row,
+            };
+            rows.extend(start_row..=end_row);
+        }
+
+        let mut matches = Vec::new();
+        for row in rows {
+            let line_len = buffer.line_len(MultiBufferRow(row));
+            let line = buffer
+                .text_for_range(Point::new(row, 0)..Point::new(row, line_len))
+                .collect::<String>();
+            if let Some(found) = regex.find(&line) {
+                let column = line[..found.start()].chars().count();
+                matches.push((Point::new(row, found.start() as u32), column));
+            }
+        }
+        if matches.len() < 2 {
+            return;
+        }
+
+        let padding = alignment_padding(
+            matches
+                .iter()
+                .map(|(_, column)| std::slice::from_ref(column)),
+        );
+        let edits = matches
+            .into_iter()
+            .zip(padding)
+            .filter(|(_, padding)| padding[0] > 0)
+            .map(|((position, _), padding)| (position..position, " ".repeat(padding[0])))
+            .collect::<Vec<_>>();
+        self.transact(cx, |this, cx| this.edit(edits, cx));
+    }
+
+    /// Replaces each selection with a number, counting up from the first selection.
+    pub fn insert_sequential_numbers(
+        &mut self,
+        action: &InsertSequentialNumbers,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let selections = self.selections.all::<Point>(cx);
+        let mut edits = Vec::with_capacity(selections.len());
+        let mut number = action.start;
+        for selection in selections {
+            let Some(text) = format_sequence_number(&action.format, number) else {
+                log::error!("invalid sequential number format {:?}", action.format);
+                return;
+            };
+            edits.push((selection.range(), text));
+            number = number.saturating_add(action.step);
+        }
+
+        self.transact(cx, |this, cx| this.edit(edits, cx));
+    }
+}
+
+/// Returns how many spaces to insert before each column so that the n-th column of every line
+/// ends up at the same position. Each line's columns must be sorted.
+fn alignment_padding<'a>(lines: impl Iterator<Item = &'a [usize]>) -> Vec<Vec<usize>> {
+    let lines = lines.collect::<Vec<_>>();
+    let mut padding = lines
+        .iter()
+        .map(|columns| vec![0; columns.len()])
+        .collect::<Vec<_>>();
+    let mut shifts = vec![0; lines.len()];
+    let max_columns = lines.iter().map(|columns| columns.len()).max().unwrap_or(0);
+    for ix in 0..max_columns {
+        let target = lines
+            .iter()
+            .zip(&shifts)
+            .filter_map(|(columns, shift)| Some(columns.get(ix)? + shift))
+            .max()
+            .unwrap_or(0);
+        for (line_ix, columns) in lines.iter().enumerate() {
+            if let Some(column) = columns.get(ix) {
+                let line_padding = target - (column + shifts[line_ix]);
+                padding[line_ix][ix] = line_padding;
+                shifts[line_ix] += line_padding;
+            }
+        }
+    }
+    padding
+}
+
+/// Replaces the first `{}`, `{:<width>}` or `{:0<width>}` placeholder in `format` with the
+/// number, or returns `None` when there is no valid placeholder.
+fn format_sequence_number(format: &str, number: i64) -> Option<String> {
+    let start = format.find('{')?;
+    let end = start + format[start..].find('}')?;
+    let number = match &format[start + 1..end] {
+        "" => number.to_string(),
+        spec => {
+            let width = spec.strip_prefix(':')?;
+            let zero_padded = width.len() > 1 && width.starts_with('0');
+            let width = width.parse::<usize>().ok()?;
+            if zero_padded {
+                format!("{number:0width$}")
+            } else {
+                format!("{number:width$}")
+            }
+        }
+    };
+    Some(format!(
+        "{}{number}{}",
+        &format[..start],
+        &format[end + 1..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        editor_tests::init_test, test::editor_test_context::EditorTestContext,
+        SplitSelectionIntoLines,
+    };
+    use indoc::indoc;
+
+    #[test]
+    fn test_alignment_padding() {
+        let lines: [&[usize]; 3] = [&[1, 4], &[3], &[0, 2, 5]];
+        assert_eq!(
+            alignment_padding(lines.into_iter()),
+            vec![vec![2, 0], vec![0], vec![3, 1, 0]]
+        );
+    }
+
+    #[test]
+    fn test_format_sequence_number() {
+        assert_eq!(format_sequence_number("{}", 7).as_deref(), Some("7"));
+        assert_eq!(
+            format_sequence_number("item-{:03}:", 7).as_deref(),
+            Some("item-007:")
+        );
+        assert_eq!(format_sequence_number("{:3}", -7).as_deref(), Some(" -7"));
+        assert_eq!(format_sequence_number("{:0}", 7).as_deref(), Some("7"));
+        assert_eq!(format_sequence_number("no placeholder", 7), None);
+        assert_eq!(format_sequence_number("{:x}", 7), None);
+    }
+
+    #[gpui::test]
+    async fn test_align_selections(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+
+        cx.set_state(indoc! {"
+            a ˇb «cˇ»
+            aaa ˇbb ˇc
+            «ˇaa» b
+        "});
+        cx.dispatch_action(AlignSelections);
+        cx.assert_editor_state(indoc! {"
+            a   ˇb  «cˇ»
+            aaa ˇbb ˇc
+                «ˇaa» b
+        "});
+    }
+
+    #[gpui::test]
+    async fn test_align_on_pattern(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+
+        cx.set_state(indoc! {"
+            «let a = 1;
+            let bbb = 2;
+            no match
+            let cc = c == 3;
+            ˇ»let d = 4;
+        "});
+        cx.dispatch_action(AlignOnPattern {
+            pattern: "=".into(),
+            regex: false,
+        });
+        cx.assert_editor_state(indoc! {"
+            «let a   = 1;
+            let bbb = 2;
+            no match
+            let cc  = c == 3;
+            ˇ»let d = 4;
+        "});
+
+        cx.set_state(indoc! {"
+            «a: 1
+            bbb:2ˇ»
+        "});
+        cx.dispatch_action(AlignOnPattern {
+            pattern: r"\d".into(),
+            regex: true,
+        });
+        cx.assert_editor_state(indoc! {"
+            «a:  1
+            bbb:2ˇ»
+        "});
+    }
+
+    #[gpui::test]
+    async fn test_insert_sequential_numbers(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+
+        cx.set_state(indoc! {"
+            item ˇ
+            item «xˇ»
+            item ˇ
+        "});
+        cx.dispatch_action(InsertSequentialNumbers {
+            start: 8,
+            step: 2,
+            format: "{:02}.".into(),
+        });
+        cx.assert_editor_state(indoc! {"
+            item 08.ˇ
+            item 10.ˇ
+            item 12.ˇ
+        "});
+    }
+
+    #[gpui::test]
+    async fn test_split_selection_into_lines_keeping_selections(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+
+        cx.set_state(indoc! {"
+            o«ne
+            two
+            thˇ»ree
+            «fourˇ»
+            ˇfive
+        "});
+        cx.dispatch_action(SplitSelectionIntoLines {
+            keep_selections: true,
+        });
+        cx.assert_editor_state(indoc! {"
+            o«neˇ»
+            «twoˇ»
+            «thˇ»ree
+            «fourˇ»
+            ˇfive
+        "});
+    }
+}
@@ -62,6 +62,14 @@ impl Inlay {
             text: text.into(),
         }
     }
+
+    pub fn replacement_preview<T: Into<Rope>>(id: usize, position: Anchor, text: T) -> Self {
+        Self {
+            id: InlayId::ReplacementPreview(id),
+            position,
+            text: text.into(),
+        }
+    }
 }
 
 impl sum_tree::Item for Transform {
@@ -284,7 +292,9 @@ impl<'a> Iterator for InlayChunks<'a> {
                             }
                         })
                     }
-                    InlayId::Hint(_) => self.highlight_styles.inlay_hint,
+                    InlayId::Hint(_) | InlayId::ReplacementPreview(_) => {
+                        self.highlight_styles.inlay_hint
+                    }
                 };
                 let next_inlay_highlight_endpoint;
                 let offset_in_inlay = self.output_offset - self.transforms.start().0;
@@ -1063,7 +1073,7 @@ fn push_isomorphic(sum_tree: &mut SumTree<Transform>, summary: TextSummary) {
 mod tests {
     use super::*;
     use crate::{
-        display_map::{InlayHighlights, TextHighlights},
+        display_map::{HighlightKey, InlayHighlights, TextHighlights},
         hover_links::InlayHighlight,
         InlayId, MultiBuffer,
     };
@@ -1571,7 +1581,7 @@ mod tests {
             text_highlight_ranges.sort_by_key(|range| (range.start, Reverse(range.end)));
             log::info!("highlighting text ranges {text_highlight_ranges:?}");
             text_highlights.insert(
-                TypeId::of::<()>(),
+                HighlightKey::Type(TypeId::of::<()>()),
                 Arc::new((
                     HighlightStyle::default(),
                     text_highlight_ranges
@@ -17,6 +17,7 @@ mod blame_entry_tooltip;
 mod blink_manager;
 mod clangd_ext;
 mod code_context_menus;
+mod column_editing;
 pub mod display_map;
 mod editor_settings;
 mod editor_settings_controls;
@@ -29,16 +30,20 @@ mod hunk_diff;
 mod indent_guides;
 mod inlay_hint_cache;
 pub mod items;
+mod keyboard_macros;
+mod large_file_banner;
 mod linked_editing_ranges;
 mod lsp_ext;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
 mod proposed_changes_editor;
+mod rainbow_brackets;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
 pub mod tasks;
+mod text_objects;
 
 #[cfg(test)]
 mod editor_tests;
@@ -59,9 +64,10 @@ use clock::ReplicaId;
 use collections::{BTreeMap, Bound, HashMap, HashSet, VecDeque};
 use convert_case::{Case, Casing};
 use display_map::*;
-pub use display_map::{DisplayPoint, FoldPlaceholder};
+pub use display_map::{DisplayPoint, FoldPlaceholder, HighlightKey};
 pub use editor_settings::{
-    CurrentLineHighlight, EditorSettings, ScrollBeyondLastLine, SearchSettings, ShowScrollbar,
+    CurrentLineHighlight, EditorSettings, ScrollBeyondLastLine, SearchSettings, ShowMinimap,
+    ShowScrollbar,
 };
 pub use editor_settings_controls::*;
 use element::LineWithInvisibles;
@@ -96,6 +102,7 @@ pub use inline_completion::Direction;
 use inline_completion::{InlineCompletionProvider, InlineCompletionProviderHandle};
 pub use items::MAX_TAB_TITLE_LEN;
 use itertools::Itertools;
+use keyboard_macros::KeyboardMacros;
 use language::{
     language_settings::{self, all_language_settings, language_settings, InlayHintSettings},
     markdown, point_from_lsp, AutoindentMode, BracketPair, Buffer, Capability, CharKind, CodeLabel,
@@ -103,11 +110,13 @@ use language::{
     Point, Selection, SelectionGoal, TransactionId,
 };
 use language::{point_to_lsp, BufferRow, CharClassifier, Runnable, RunnableRange};
+pub use large_file_banner::LargeFileBanner;
 use linked_editing_ranges::refresh_linked_ranges;
 use mouse_context_menu::MouseContextMenu;
 pub use proposed_changes_editor::{
     ProposedChangeLocation, ProposedChangesEditor, ProposedChangesEditorToolbar,
 };
+use rainbow_brackets::{refresh_rainbow_brackets, RainbowBracketsState};
 use similar::{ChangeTag, TextDiff};
 use std::iter::Peekable;
 use task::{ResolvedTask, TaskTemplate, TaskVariables};
@@ -130,6 +139,7 @@ use multi_buffer::{
 use project::{
     lsp_store::{FormatTarget, FormatTrigger, OpenLspBufferHandle},
     project_settings::{GitGutterSetting, ProjectSettings},
+    search::SearchQuery,
     CodeAction, Completion, CompletionIntent, DocumentHighlight, InlayHint, Location, LocationLink,
     LspStore, Project, ProjectItem, ProjectTransaction, TaskSourceKind,
 };
@@ -260,6 +270,7 @@ pub fn render_parsed_markdown(
 pub(crate) enum InlayId {
     InlineCompletion(usize),
     Hint(usize),
+    ReplacementPreview(usize),
 }
 
 impl InlayId {
@@ -267,6 +278,7 @@ impl InlayId {
         match self {
             Self::InlineCompletion(id) => *id,
             Self::Hint(id) => *id,
+            Self::ReplacementPreview(id) => *id,
         }
     }
 }
@@ -504,17 +516,31 @@ impl EditorActionId {
 type BackgroundHighlight = (fn(&ThemeColors) -> Hsla, Arc<[Range<Anchor>]>);
 type GutterHighlight = (fn(&AppContext) -> Hsla, Arc<[Range<Anchor>]>);
 
+/// The outline items containing the top row of the editor, kept while neither the buffer nor the
+/// scroll position change.
+struct StickyHeadersCache {
+    version: clock::Global,
+    non_text_state_update_count: usize,
+    top_row: DisplayRow,
+    items: Arc<[language::OutlineItem<Anchor>]>,
+}
+
 #[derive(Default)]
 struct ScrollbarMarkerState {
     scrollbar_size: Size<Pixels>,
+    minimap_size: Size<Pixels>,
     dirty: bool,
     markers: Arc<[PaintQuad]>,
+    minimap_markers: Arc<[PaintQuad]>,
     pending_refresh: Option<Task<Result<()>>>,
 }
 
 impl ScrollbarMarkerState {
-    fn should_refresh(&self, scrollbar_size: Size<Pixels>) -> bool {
-        self.pending_refresh.is_none() && (self.scrollbar_size != scrollbar_size || self.dirty)
+    fn should_refresh(&self, scrollbar_size: Size<Pixels>, minimap_size: Size<Pixels>) -> bool {
+        self.pending_refresh.is_none()
+            && (self.scrollbar_size != scrollbar_size
+                || self.minimap_size != minimap_size
+                || self.dirty)
     }
 }
 
@@ -618,7 +644,10 @@ pub struct Editor {
     background_highlights: TreeMap<TypeId, BackgroundHighlight>,
     gutter_highlights: TreeMap<TypeId, GutterHighlight>,
     scrollbar_marker_state: ScrollbarMarkerState,
+    sticky_headers_cache: Option<StickyHeadersCache>,
     active_indent_guides_state: ActiveIndentGuidesState,
+    rainbow_brackets: RainbowBracketsState,
+    keyboard_macros: KeyboardMacros,
     nav_history: Option<ItemNavHistory>,
     context_menu: RefCell<Option<CodeContextMenu>>,
     mouse_context_menu: Option<MouseContextMenu>,
@@ -658,6 +687,7 @@ pub struct Editor {
     inlay_hint_cache: InlayHintCache,
     diff_map: DiffMap,
     next_inlay_id: usize,
+    replacement_preview_inlays: Vec<InlayId>,
     _subscriptions: Vec<Subscription>,
     pixel_position_of_newest_cursor: Option<gpui::Point<Pixels>>,
     gutter_dimensions: GutterDimensions,
@@ -1250,7 +1280,10 @@ impl Editor {
             background_highlights: Default::default(),
             gutter_highlights: TreeMap::default(),
             scrollbar_marker_state: ScrollbarMarkerState::default(),
+            sticky_headers_cache: None,
             active_indent_guides_state: ActiveIndentGuidesState::default(),
+            rainbow_brackets: RainbowBracketsState::default(),
+            keyboard_macros: KeyboardMacros::default(),
             nav_history: None,
             context_menu: RefCell::new(None),
             mouse_context_menu: None,
@@ -1261,6 +1294,7 @@ impl Editor {
             find_all_references_task_sources: Vec::new(),
             next_completion_id: 0,
             next_inlay_id: 0,
+            replacement_preview_inlays: Vec::new(),
             code_action_providers,
             available_code_actions: Default::default(),
             code_actions_task: Default::default(),
@@ -7847,6 +7881,10 @@ impl Editor {
                 }),
                 cx,
             );
+            cx.emit(EditorEvent::Jumped {
+                from: cursor_anchor,
+                kind: JumpKind::Navigation,
+            });
         }
     }
 
@@ -7883,7 +7921,7 @@ impl Editor {
 
     pub fn split_selection_into_lines(
         &mut self,
-        _: &SplitSelectionIntoLines,
+        action: &SplitSelectionIntoLines,
         cx: &mut ViewContext<Self>,
     ) {
         let mut to_unfold = Vec::new();
@@ -7892,11 +7930,23 @@ impl Editor {
             let selections = self.selections.all::<Point>(cx);
             let buffer = self.buffer.read(cx).read(cx);
             for selection in selections {
-                for row in selection.start.row..selection.end.row {
-                    let cursor = Point::new(row, buffer.line_len(MultiBufferRow(row)));
-                    new_selection_ranges.push(cursor..cursor);
+                if action.keep_selections {
+                    let mut line_start = selection.start;
+                    for row in selection.start.row..selection.end.row {
+                        let line_end = Point::new(row, buffer.line_len(MultiBufferRow(row)));
+                        new_selection_ranges.push(line_start..line_end);
+                        line_start = Point::new(row + 1, 0);
+                    }
+                    if line_start < selection.end || selection.start == selection.end {
+                        new_selection_ranges.push(line_start..selection.end);
+                    }
+                } else {
+                    for row in selection.start.row..selection.end.row {
+                        let cursor = Point::new(row, buffer.line_len(MultiBufferRow(row)));
+                        new_selection_ranges.push(cursor..cursor);
+                    }
+                    new_selection_ranges.push(selection.end..selection.end);
                 }
-                new_selection_ranges.push(selection.end..selection.end);
                 to_unfold.push(selection.start..selection.end);
             }
         }
@@ -9552,6 +9602,7 @@ impl Editor {
                         return Navigated::No;
                     };
                     let pane = workspace.read(cx).active_pane().clone();
+                    editor.emit_definition_jump(cx);
 
                     let range = target.range.to_offset(target.buffer.read(cx));
                     let range = editor.range_for_match(&range);
@@ -9637,6 +9688,7 @@ impl Editor {
                                 HoverLink::File(_) => Task::ready(Ok(None)),
                             })
                             .collect::<Vec<_>>();
+                        editor.emit_definition_jump(cx);
                         (title, location_tasks, editor.workspace().clone())
                     })
                     .context("location tasks preparation")?;
@@ -9664,6 +9716,13 @@ impl Editor {
         }
     }
 
+    fn emit_definition_jump(&mut self, cx: &mut ViewContext<Self>) {
+        cx.emit(EditorEvent::Jumped {
+            from: self.selections.newest_anchor().head(),
+            kind: JumpKind::Definition,
+        });
+    }
+
     fn compute_target_location(
         &self,
         lsp_location: lsp::Location,
@@ -9773,6 +9832,7 @@ impl Editor {
             if locations.is_empty() {
                 return anyhow::Ok(Navigated::No);
             }
+            editor.update(&mut cx, |editor, cx| editor.emit_definition_jump(cx))?;
 
             workspace.update(&mut cx, |workspace, cx| {
                 let title = locations
@@ -11117,6 +11177,16 @@ impl Editor {
     }
 
     pub fn soft_wrap_mode(&self, cx: &AppContext) -> SoftWrap {
+        // Wrapping the lines of a large file would require measuring all of them.
+        if self
+            .buffer
+            .read(cx)
+            .as_singleton()
+            .map_or(false, |buffer| buffer.read(cx).is_large_file())
+        {
+            return SoftWrap::None;
+        }
+
         let settings = self.buffer.read(cx).settings_at(0, cx);
         let mode = self.soft_wrap_mode_override.unwrap_or(settings.soft_wrap);
         match mode {
@@ -11133,6 +11203,17 @@ impl Editor {
         }
     }
 
+    /// Enables syntax highlighting, language servers, git diffs and soft wrapping for the
+    /// buffers that were opened in large file mode.
+    pub fn exit_large_file_mode(&mut self, _: &ExitLargeFileMode, cx: &mut ViewContext<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        for buffer in self.buffer.read(cx).all_buffers() {
+            project.update(cx, |project, cx| project.exit_large_file_mode(&buffer, cx));
+        }
+    }
+
     pub fn set_soft_wrap_mode(
         &mut self,
         mode: language_settings::SoftWrap,
@@ -11809,6 +11890,36 @@ impl Editor {
         self.clear_background_highlights::<SearchWithinRange>(cx);
     }
 
+    /// Shows what each of the given search matches would be replaced with, after the match.
+    /// Only structural queries get previews, as their replacements vary between matches.
+    pub fn set_replacement_previews(
+        &mut self,
+        matches: &[Range<Anchor>],
+        query: Option<&SearchQuery>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let mut inlays = Vec::new();
+        if let Some(query) = query.filter(|query| query.is_structural()) {
+            let buffer = self.buffer.read(cx).snapshot(cx);
+            for range in matches {
+                if let Some(replacement) = items::structural_replacement(&buffer, range, query) {
+                    inlays.push(Inlay::replacement_preview(
+                        post_inc(&mut self.next_inlay_id),
+                        range.end,
+                        format!(" → {}", replacement.replace('\n', " ⏎ ")),
+                    ));
+                }
+            }
+        }
+        let to_remove = mem::replace(
+            &mut self.replacement_preview_inlays,
+            inlays.iter().map(|inlay| inlay.id).collect(),
+        );
+        if !to_remove.is_empty() || !inlays.is_empty() {
+            self.splice_inlays(to_remove, inlays, cx);
+        }
+    }
+
     pub fn highlight_background<T: 'static>(
         &mut self,
         ranges: &[Range<Anchor>],
@@ -12117,7 +12228,27 @@ impl Editor {
         cx: &mut ViewContext<Self>,
     ) {
         self.display_map.update(cx, |map, _| {
-            map.highlight_text(TypeId::of::<T>(), ranges, style)
+            map.highlight_text(HighlightKey::Type(TypeId::of::<T>()), ranges, style)
+        });
+        cx.notify();
+    }
+
+    /// Like [`Editor::highlight_text`], but allows several differently styled sets of ranges
+    /// for the same type, distinguished by `key`. They are all cleared together by
+    /// [`Editor::clear_highlights`].
+    pub fn highlight_text_key<T: 'static>(
+        &mut self,
+        key: usize,
+        ranges: Vec<Range<Anchor>>,
+        style: HighlightStyle,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.display_map.update(cx, |map, _| {
+            map.highlight_text(
+                HighlightKey::TypePlus(TypeId::of::<T>(), key),
+                ranges,
+                style,
+            )
         });
         cx.notify();
     }
@@ -12183,6 +12314,8 @@ impl Editor {
             } => {
                 self.scrollbar_marker_state.dirty = true;
                 self.active_indent_guides_state.dirty = true;
+                self.rainbow_brackets.invalidate();
+                refresh_rainbow_brackets(self, cx);
                 self.refresh_active_diagnostics(cx);
                 self.refresh_code_actions(cx);
                 if self.has_active_inline_completion() {
@@ -12264,6 +12397,8 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                self.rainbow_brackets.invalidate();
+                refresh_rainbow_brackets(self, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
@@ -12280,11 +12415,28 @@ impl Editor {
             }
             multi_buffer::Event::Reparsed(buffer_id) => {
                 self.tasks_update_task = Some(self.refresh_runnables(cx));
+                self.rainbow_brackets.invalidate();
+                refresh_rainbow_brackets(self, cx);
 
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, cx);
+                self.rainbow_brackets.invalidate();
+                refresh_rainbow_brackets(self, cx);
+                cx.emit(EditorEvent::Reparsed(*buffer_id));
+                cx.notify();
+            }
+            multi_buffer::Event::LargeFileModeChanged(buffer_id) => {
+                if !self.diff_map.diff_bases.contains_key(buffer_id) {
+                    if let Some((project, buffer)) = self
+                        .project
+                        .as_ref()
+                        .zip(self.buffer.read(cx).buffer(*buffer_id))
+                    {
+                        get_unstaged_changes_for_buffers(project, [buffer], cx);
+                    }
+                }
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
@@ -12323,6 +12475,8 @@ impl Editor {
             )),
             cx,
         );
+        self.rainbow_brackets.invalidate();
+        refresh_rainbow_brackets(self, cx);
 
         let old_cursor_shape = self.cursor_shape;
 
@@ -12841,11 +12995,15 @@ impl Editor {
         self.editor_actions.borrow_mut().insert(
             id,
             Box::new(move |cx| {
+                let editor = cx.view().downgrade();
                 let cx = cx.window_context();
                 let listener = listener.clone();
                 cx.on_action(TypeId::of::<A>(), move |action, phase, cx| {
                     let action = action.downcast_ref().unwrap();
                     if phase == DispatchPhase::Bubble {
+                        editor
+                            .update(cx, |editor, _| editor.record_macro_action(action))
+                            .ok();
                         listener(action, cx)
                     }
                 })
@@ -12964,7 +13122,9 @@ fn get_unstaged_changes_for_buffers(
     let mut tasks = Vec::new();
     project.update(cx, |project, cx| {
         for buffer in buffers {
-            tasks.push(project.open_unstaged_changes(buffer.clone(), cx))
+            if !buffer.read(cx).is_large_file() {
+                tasks.push(project.open_unstaged_changes(buffer.clone(), cx))
+            }
         }
     });
     cx.spawn(|this, mut cx| async move {
@@ -14096,6 +14256,22 @@ pub enum EditorEvent {
     },
     Reloaded,
     CursorShapeChanged,
+    /// The cursor left a position that is worth returning to.
+    Jumped {
+        from: Anchor,
+        kind: JumpKind,
+    },
+}
+
+/// Why a position was recorded in the jump list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum JumpKind {
+    /// The cursor moved far away from the position, or the editor was deactivated.
+    Navigation,
+    /// The editor navigated from the position to a definition or to references.
+    Definition,
+    /// The position was edited.
+    Edit,
 }
 
 impl EventEmitter<EditorEvent> for Editor {}
@@ -14243,6 +14419,7 @@ impl ViewInputHandler for Editor {
                     })
             });
 
+            this.record_macro_input(text, range_to_replace.clone());
             cx.emit(EditorEvent::InputHandled {
                 utf16_range_to_replace: range_to_replace,
                 text: text.into(),
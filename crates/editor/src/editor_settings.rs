@@ -1,3 +1,4 @@
+use collections::HashMap;
 use gpui::AppContext;
 use language::CursorShape;
 use schemars::JsonSchema;
@@ -14,6 +15,8 @@ pub struct EditorSettings {
     pub hover_popover_delay: u64,
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
+    pub minimap: Minimap,
+    pub sticky_scroll: StickyScroll,
     pub gutter: Gutter,
     pub scroll_beyond_last_line: ScrollBeyondLastLine,
     pub vertical_scroll_margin: f32,
@@ -36,6 +39,7 @@ pub struct EditorSettings {
     pub show_signature_help_after_edits: bool,
     pub jupyter: Jupyter,
     pub show_inline_completions_in_menu: bool,
+    pub keyboard_macros: HashMap<String, Vec<KeyboardMacroStep>>,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
@@ -110,6 +114,19 @@ pub struct Scrollbar {
     pub axes: ScrollbarAxes,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Minimap {
+    pub show: ShowMinimap,
+    pub width: f32,
+    pub max_columns: u32,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScroll {
+    pub enabled: bool,
+    pub max_depth: usize,
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct Gutter {
     pub line_numbers: bool,
@@ -135,6 +152,20 @@ pub enum ShowScrollbar {
     Never,
 }
 
+/// When to show the minimap in the editor.
+///
+/// Default: never
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShowMinimap {
+    /// Always show the minimap, reserving space for it next to the text.
+    Always,
+    /// Show the minimap over the text while the mouse is above it.
+    Hover,
+    /// Never show the minimap.
+    Never,
+}
+
 /// Forcefully enable or disable the scrollbar for each axis
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -177,6 +208,18 @@ pub enum ScrollBeyondLastLine {
     VerticalScrollMargin,
 }
 
+/// A step of a keyboard macro saved in the settings.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum KeyboardMacroStep {
+    /// Dispatches an action that takes no arguments, e.g. `{ "action": "editor::MoveDown" }`.
+    Action { action: String },
+    /// Simulates a keystroke, e.g. `{ "keystroke": "ctrl-shift-k" }`.
+    Keystroke { keystroke: String },
+    /// Inserts text as if it was typed.
+    Text { text: String },
+}
+
 /// Default options for buffer and project search items.
 #[derive(Copy, Clone, Default, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct SearchSettings {
@@ -223,6 +266,10 @@ pub struct EditorSettingsContent {
     pub toolbar: Option<ToolbarContent>,
     /// Scrollbar related settings
     pub scrollbar: Option<ScrollbarContent>,
+    /// Minimap related settings
+    pub minimap: Option<MinimapContent>,
+    /// Settings for the scopes pinned at the top of the editor
+    pub sticky_scroll: Option<StickyScrollContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
     /// Whether the editor will scroll beyond the last line.
@@ -309,6 +356,11 @@ pub struct EditorSettingsContent {
 
     /// Jupyter REPL settings.
     pub jupyter: Option<JupyterContent>,
+
+    /// Named keyboard macros, which can be bound to keys with the `editor::ReplayMacro` action.
+    ///
+    /// Default: {}
+    pub keyboard_macros: Option<HashMap<String, Vec<KeyboardMacroStep>>>,
 }
 
 // Toolbar related settings
@@ -374,6 +426,38 @@ pub struct ScrollbarAxesContent {
     vertical: Option<bool>,
 }
 
+/// Minimap related settings
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct MinimapContent {
+    /// When to show the minimap in the editor.
+    ///
+    /// Default: never
+    pub show: Option<ShowMinimap>,
+    /// The width of the minimap in pixels.
+    ///
+    /// Default: 100
+    pub width: Option<f32>,
+    /// The number of columns that fit into the width of the minimap.
+    /// Longer lines are cut off.
+    ///
+    /// Default: 100
+    pub max_columns: Option<u32>,
+}
+
+/// Settings for the scopes pinned at the top of the editor
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScrollContent {
+    /// Whether to pin the first lines of the functions, classes and other
+    /// outline items containing the top of the editor.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The maximum number of nested scopes to pin.
+    ///
+    /// Default: 3
+    pub max_depth: Option<usize>,
+}
+
 /// Gutter related settings
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct GutterContent {
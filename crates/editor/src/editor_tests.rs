@@ -4787,7 +4787,7 @@ fn test_split_selection_into_lines(cx: &mut TestAppContext) {
     });
 
     _ = view.update(cx, |view, cx| {
-        view.split_selection_into_lines(&SplitSelectionIntoLines, cx);
+        view.split_selection_into_lines(&SplitSelectionIntoLines::default(), cx);
         assert_eq!(
             view.display_text(cx),
             "aaaaa\nbbbbb\nccc⋯eeee\nfffff\nggggg\n⋯i"
@@ -4809,7 +4809,7 @@ fn test_split_selection_into_lines(cx: &mut TestAppContext) {
                 DisplayPoint::new(DisplayRow(5), 0)..DisplayPoint::new(DisplayRow(0), 1)
             ])
         });
-        view.split_selection_into_lines(&SplitSelectionIntoLines, cx);
+        view.split_selection_into_lines(&SplitSelectionIntoLines::default(), cx);
         assert_eq!(
             view.display_text(cx),
             "aaaaa\nbbbbb\nccccc\nddddd\neeeee\nfffff\nggggg\nhhhhh\niiiii"
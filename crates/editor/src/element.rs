@@ -6,7 +6,7 @@ use crate::{
     },
     editor_settings::{
         CurrentLineHighlight, DoubleClickInMultibuffer, MultiCursorModifier, ScrollBeyondLastLine,
-        ShowScrollbar,
+        ShowMinimap, ShowScrollbar,
     },
     git::blame::{CommitDetails, GitBlame},
     hover_popover::{
@@ -16,13 +16,14 @@ use crate::{
     hunk_status,
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MenuPosition, MouseContextMenu},
-    scroll::{axis_pair, scroll_amount::ScrollAmount, AxisPair},
+    rainbow_brackets::enclosing_bracket_depth,
+    scroll::{axis_pair, scroll_amount::ScrollAmount, Autoscroll, AxisPair},
     BlockId, ChunkReplacement, CursorShape, CustomBlockId, DisplayPoint, DisplayRow,
     DocumentHighlightRead, DocumentHighlightWrite, Editor, EditorMode, EditorSettings,
     EditorSnapshot, EditorStyle, ExpandExcerpts, FocusedBlock, GutterDimensions, HalfPageDown,
     HalfPageUp, HandleInput, HoveredCursor, HoveredHunk, InlineCompletion, JumpData, LineDown,
     LineUp, OpenExcerpts, PageDown, PageUp, Point, RowExt, RowRangeExt, SelectPhase, Selection,
-    SoftWrap, ToPoint, ToggleFold, CURSORS_VISIBLE_FOR, FILE_HEADER_HEIGHT,
+    SoftWrap, StickyHeadersCache, ToPoint, ToggleFold, CURSORS_VISIBLE_FOR, FILE_HEADER_HEIGHT,
     GIT_BLAME_MAX_AUTHOR_CHARS_DISPLAYED, MAX_LINE_LEN, MULTI_BUFFER_EXCERPT_HEADER_HEIGHT,
 };
 use client::ParticipantIndex;
@@ -45,7 +46,7 @@ use language::{
         IndentGuideBackgroundColoring, IndentGuideColoring, IndentGuideSettings,
         ShowWhitespaceSetting,
     },
-    ChunkRendererContext,
+    ChunkRendererContext, OutlineItem,
 };
 use lsp::DiagnosticSeverity;
 use multi_buffer::{
@@ -291,6 +292,14 @@ impl EditorElement {
         });
         register_action(view, cx, Editor::select_line);
         register_action(view, cx, Editor::split_selection_into_lines);
+        register_action(view, cx, Editor::align_selections);
+        register_action(view, cx, Editor::align_on_pattern);
+        register_action(view, cx, Editor::insert_sequential_numbers);
+        register_action(view, cx, Editor::select_text_object);
+        register_action(view, cx, Editor::move_to_next_text_object);
+        register_action(view, cx, Editor::move_to_previous_text_object);
+        register_action(view, cx, Editor::swap_with_next_text_object);
+        register_action(view, cx, Editor::swap_with_previous_text_object);
         register_action(view, cx, Editor::add_selection_above);
         register_action(view, cx, Editor::add_selection_below);
         register_action(view, cx, |editor, action, cx| {
@@ -364,10 +373,16 @@ impl EditorElement {
         register_action(view, cx, Editor::open_excerpts_in_split);
         register_action(view, cx, Editor::open_proposed_changes_editor);
         register_action(view, cx, Editor::toggle_soft_wrap);
+        register_action(view, cx, Editor::exit_large_file_mode);
         register_action(view, cx, Editor::toggle_tab_bar);
         register_action(view, cx, Editor::toggle_line_numbers);
         register_action(view, cx, Editor::toggle_relative_line_numbers);
         register_action(view, cx, Editor::toggle_indent_guides);
+        register_action(view, cx, Editor::toggle_macro_recording);
+        register_action(view, cx, Editor::replay_last_macro);
+        register_action(view, cx, Editor::replay_last_macro_to_end_of_file);
+        register_action(view, cx, Editor::replay_macro);
+        register_action(view, cx, Editor::save_last_macro);
         register_action(view, cx, Editor::toggle_inlay_hints);
         register_action(view, cx, Editor::toggle_inline_completions);
         register_action(view, cx, hover_popover::hover);
@@ -1352,6 +1367,202 @@ impl EditorElement {
         axis_pair(horizontal_scrollbar, vertical_scrollbar)
     }
 
+    /// Returns the outline items containing the top row, running the outline query again only when
+    /// the buffer changed or the editor scrolled to another row.
+    fn sticky_header_items(
+        &self,
+        snapshot: &EditorSnapshot,
+        top_row: DisplayRow,
+        cx: &mut WindowContext,
+    ) -> Option<Arc<[OutlineItem<Anchor>]>> {
+        let (_, _, buffer) = snapshot.buffer_snapshot.as_singleton()?;
+        self.editor.update(cx, |editor, _| {
+            if let Some(cache) = editor.sticky_headers_cache.as_ref().filter(|cache| {
+                cache.top_row == top_row
+                    && cache.non_text_state_update_count == buffer.non_text_state_update_count()
+                    && &cache.version == buffer.version()
+            }) {
+                return Some(cache.items.clone());
+            }
+
+            let top_point = DisplayPoint::new(top_row, 0).to_point(snapshot);
+            let (_, items) = snapshot
+                .buffer_snapshot
+                .symbols_containing(top_point, None)?;
+            let items = Arc::<[OutlineItem<Anchor>]>::from(items);
+            editor.sticky_headers_cache = Some(StickyHeadersCache {
+                version: buffer.version().clone(),
+                non_text_state_update_count: buffer.non_text_state_update_count(),
+                top_row,
+                items: items.clone(),
+            });
+            Some(items)
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_sticky_headers(
+        &self,
+        snapshot: &EditorSnapshot,
+        scroll_position: gpui::Point<f32>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        editor_width: Pixels,
+        text_hitbox: &Hitbox,
+        content_origin: gpui::Point<Pixels>,
+        line_height: Pixels,
+        cx: &mut WindowContext,
+    ) -> Vec<StickyHeaderLayout> {
+        let settings = EditorSettings::get_global(cx).sticky_scroll;
+        if !settings.enabled
+            || snapshot.mode != EditorMode::Full
+            || !self.editor.read(cx).is_singleton(cx)
+        {
+            return Vec::new();
+        }
+
+        let top_row = DisplayRow(scroll_position.y as u32);
+        let Some(items) = self.sticky_header_items(snapshot, top_row, cx) else {
+            return Vec::new();
+        };
+
+        let mut headers = Vec::new();
+        for item in items.iter() {
+            if headers.len() >= settings.max_depth {
+                break;
+            }
+            // The row that the header is drawn over must be within the scope, below its first line.
+            let covered_row = top_row.0 + headers.len() as u32;
+            let start_row = item.range.start.to_display_point(snapshot).row();
+            let end_row = item.range.end.to_display_point(snapshot).row();
+            if start_row.0 >= covered_row || end_row.0 <= covered_row {
+                break;
+            }
+
+            let Some(line) = Self::layout_lines(
+                start_row..start_row.next_row(),
+                snapshot,
+                &self.style,
+                editor_width,
+                |_| false,
+                cx,
+            )
+            .pop() else {
+                break;
+            };
+            let bounds = Bounds::new(
+                point(
+                    text_hitbox.left(),
+                    text_hitbox.top() + line_height * headers.len() as f32,
+                ),
+                size(text_hitbox.size.width, line_height),
+            );
+            headers.push(StickyHeaderLayout {
+                line,
+                origin: point(content_origin.x - scroll_pixel_position.x, bounds.top()),
+                hitbox: cx.insert_hitbox(bounds, false),
+                target: item.range.start,
+            });
+        }
+        headers
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_minimap(
+        &self,
+        snapshot: &EditorSnapshot,
+        text_bounds: Bounds<Pixels>,
+        width: Pixels,
+        scroll_position: gpui::Point<f32>,
+        height_in_lines: f32,
+        max_scroll_top: f32,
+        letter_size: Size<Pixels>,
+        cx: &mut WindowContext,
+    ) -> MinimapLayout {
+        let settings = EditorSettings::get_global(cx).minimap;
+        let right = self.scrollbar_left(&text_bounds);
+        let bounds = Bounds::from_corners(
+            point(right - width, text_bounds.top()),
+            point(right, text_bounds.bottom()),
+        );
+        let max_columns = settings.max_columns.max(1);
+        let column_width = (width - MinimapLayout::MARKER_WIDTH) / max_columns as f32;
+        let line_height = (column_width * (letter_size.height / letter_size.width)).max(px(1.));
+
+        // When the buffer doesn't fit, the minimap scrolls proportionally to the editor, so that
+        // its top and bottom are reached together with the editor's.
+        let row_count = snapshot.max_point().row().next_row();
+        let overflow = (row_count.as_f32() - bounds.size.height / line_height).max(0.);
+        let (scroll_top, thumb_unit_size) = if max_scroll_top > 0. {
+            let scroll_ratio = overflow / max_scroll_top;
+            (
+                scroll_position.y.clamp(0., max_scroll_top) * scroll_ratio,
+                line_height * (1. - scroll_ratio).max(0.1),
+            )
+        } else {
+            (0., line_height)
+        };
+
+        let start_row = DisplayRow(scroll_top.floor() as u32);
+        let end_row = cmp::min(
+            DisplayRow((scroll_top + bounds.size.height / line_height).ceil() as u32 + 1),
+            row_count,
+        );
+        let mut text_quads = Vec::new();
+        let mut push_run = |row: u32, columns: Range<u32>, color: Hsla| {
+            let origin = point(
+                bounds.left() + column_width * columns.start as f32,
+                bounds.top() + (row as f32 - scroll_top) * line_height,
+            );
+            let run_size = size(column_width * columns.len() as f32, line_height * 0.75);
+            text_quads.push(fill(Bounds::new(origin, run_size), color));
+        };
+        let mut row = start_row.0;
+        let mut column = 0;
+        for chunk in snapshot.highlighted_chunks(start_row..end_row, true, &self.style) {
+            if chunk.replacement.is_some() {
+                continue;
+            }
+            let mut color = chunk
+                .style
+                .and_then(|style| style.color)
+                .unwrap_or(self.style.text.color);
+            if let Some(fade_out) = chunk.style.and_then(|style| style.fade_out) {
+                color.fade_out(fade_out);
+            }
+
+            let mut run_start = None;
+            for character in chunk.text.chars() {
+                if character.is_whitespace() || column >= max_columns {
+                    if let Some(start_column) = run_start.take() {
+                        push_run(row, start_column..column, color);
+                    }
+                } else if run_start.is_none() {
+                    run_start = Some(column);
+                }
+
+                if character == '\n' {
+                    row += 1;
+                    column = 0;
+                } else {
+                    column += 1;
+                }
+            }
+            if let Some(start_column) = run_start {
+                push_run(row, start_column..column, color);
+            }
+        }
+
+        MinimapLayout {
+            hitbox: cx.insert_hitbox(bounds, false),
+            show: settings.show,
+            line_height,
+            scroll_top,
+            thumb_unit_size,
+            visible_range: scroll_position.y..scroll_position.y + height_in_lines,
+            text_quads,
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn prepaint_crease_toggles(
         &self,
@@ -1703,13 +1914,29 @@ impl EditorElement {
                         );
 
                         let start_y = content_origin.y + offset_y - scroll_pixel_position.y;
+                        let active = active_indent_guide_indices.contains(&i);
+                        let start = Point::new(indent_guide.multibuffer_row_range.start.0, 0);
+                        let bracket_depth = if active
+                            && snapshot
+                                .buffer_snapshot
+                                .settings_at(start, cx)
+                                .colorize_brackets
+                        {
+                            enclosing_bracket_depth(
+                                &snapshot.buffer_snapshot,
+                                start.to_offset(&snapshot.buffer_snapshot),
+                            )
+                        } else {
+                            None
+                        };
 
                         Some(IndentGuideLayout {
                             origin: point(start_x, start_y),
                             length,
                             single_indent_width,
                             depth: indent_guide.depth,
-                            active: active_indent_guide_indices.contains(&i),
+                            active,
+                            bracket_depth,
                             settings: indent_guide.settings,
                         })
                     } else {
@@ -3701,9 +3928,14 @@ impl EditorElement {
                 (IndentGuideColoring::Fixed, false) => {
                     Some(cx.theme().colors().editor_indent_guide)
                 }
-                (IndentGuideColoring::Fixed, true) => {
-                    Some(cx.theme().colors().editor_indent_guide_active)
-                }
+                // Match the color of the brackets that open the active scope.
+                (IndentGuideColoring::Fixed, true) => Some(
+                    indent_guide
+                        .bracket_depth
+                        .map_or(cx.theme().colors().editor_indent_guide_active, |depth| {
+                            cx.theme().accents().color_for_index(depth as u32)
+                        }),
+                ),
                 (IndentGuideColoring::IndentAware, false) => {
                     Some(faded_color(indent_accent_colors, INDENT_AWARE_ALPHA))
                 }
@@ -4304,10 +4536,11 @@ impl EditorElement {
                         cx.theme().colors().scrollbar_track_border,
                     ));
 
-                    let fast_markers =
-                        self.collect_fast_scrollbar_markers(layout, &scrollbar_layout, cx);
-                    // Refresh slow scrollbar markers in the background. Below, we paint whatever markers have already been computed.
-                    self.refresh_slow_scrollbar_markers(layout, &scrollbar_layout, cx);
+                    let fast_markers = self.collect_fast_scrollbar_markers(
+                        layout,
+                        scrollbar_layout.marker_track(),
+                        cx,
+                    );
 
                     let markers = self.editor.read(cx).scrollbar_marker_state.markers.clone();
                     for marker in markers.iter().chain(&fast_markers) {
@@ -4435,10 +4668,217 @@ impl EditorElement {
         }
     }
 
+    fn paint_sticky_headers(&self, layout: &EditorLayout, cx: &mut WindowContext) {
+        let Some(last_header) = layout.sticky_headers.last() else {
+            return;
+        };
+        let bounds = Bounds::from_corners(
+            layout.sticky_headers[0].hitbox.origin,
+            last_header.hitbox.bottom_right(),
+        );
+        let line_height = layout.position_map.line_height;
+
+        cx.paint_layer(bounds, |cx| {
+            cx.paint_quad(fill(bounds, self.style.background));
+            for header in &layout.sticky_headers {
+                if header.hitbox.is_hovered(cx) {
+                    cx.paint_quad(fill(
+                        header.hitbox.bounds,
+                        cx.theme().colors().editor_active_line_background,
+                    ));
+                }
+                cx.with_content_mask(
+                    Some(ContentMask {
+                        bounds: header.hitbox.bounds,
+                    }),
+                    |cx| {
+                        let mut fragment_origin = header.origin;
+                        for fragment in &header.line.fragments {
+                            match fragment {
+                                LineFragment::Text(line) => {
+                                    line.paint(fragment_origin, line_height, cx).log_err();
+                                    fragment_origin.x += line.width;
+                                }
+                                LineFragment::Element { size, .. } => {
+                                    fragment_origin.x += size.width;
+                                }
+                            }
+                        }
+                    },
+                );
+            }
+            cx.paint_quad(fill(
+                Bounds::from_corners(
+                    point(bounds.left(), bounds.bottom() - px(1.)),
+                    bounds.bottom_right(),
+                ),
+                cx.theme().colors().border_variant,
+            ));
+        });
+
+        for header in &layout.sticky_headers {
+            cx.set_cursor_style(CursorStyle::PointingHand, &header.hitbox);
+            cx.on_mouse_event({
+                let editor = self.editor.clone();
+                let hitbox = header.hitbox.clone();
+                let target = header.target;
+                move |event: &MouseDownEvent, phase, cx| {
+                    if phase == DispatchPhase::Capture
+                        || event.button != MouseButton::Left
+                        || !hitbox.is_hovered(cx)
+                    {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+                            selections.select_anchor_ranges([target..target]);
+                        });
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        }
+    }
+
+    fn paint_minimap(&self, layout: &EditorLayout, cx: &mut WindowContext) {
+        let Some(minimap) = layout.minimap.as_ref() else {
+            return;
+        };
+        let hitbox = minimap.hitbox.clone();
+        let show = minimap.show;
+        let is_dragging = self.editor.read(cx).scroll_manager.is_dragging_minimap();
+        let visible = show == ShowMinimap::Always || is_dragging || hitbox.is_hovered(cx);
+
+        if visible {
+            cx.paint_layer(hitbox.bounds, |cx| {
+                cx.with_content_mask(
+                    Some(ContentMask {
+                        bounds: hitbox.bounds,
+                    }),
+                    |cx| {
+                        cx.paint_quad(fill(hitbox.bounds, self.style.background));
+                        for text_quad in &minimap.text_quads {
+                            cx.paint_quad(text_quad.clone());
+                        }
+
+                        let fast_markers =
+                            self.collect_fast_scrollbar_markers(layout, minimap.marker_track(), cx);
+                        let markers = self
+                            .editor
+                            .read(cx)
+                            .scrollbar_marker_state
+                            .minimap_markers
+                            .clone();
+                        let marker_origin = minimap.marker_origin();
+                        for marker in markers.iter().chain(&fast_markers) {
+                            let mut marker = marker.clone();
+                            marker.bounds.origin += marker_origin;
+                            cx.paint_quad(marker);
+                        }
+
+                        cx.paint_quad(quad(
+                            minimap.thumb_bounds(),
+                            Corners::default(),
+                            cx.theme().colors().scrollbar_thumb_background,
+                            Edges {
+                                top: ScrollbarLayout::BORDER_WIDTH,
+                                right: Pixels::ZERO,
+                                bottom: ScrollbarLayout::BORDER_WIDTH,
+                                left: ScrollbarLayout::BORDER_WIDTH,
+                            },
+                            cx.theme().colors().scrollbar_thumb_border,
+                        ));
+                    },
+                );
+            });
+
+            cx.set_cursor_style(CursorStyle::Arrow, &hitbox);
+        }
+
+        cx.on_mouse_event({
+            let editor = self.editor.clone();
+            let hitbox = hitbox.clone();
+            let thumb_unit_size = minimap.thumb_unit_size;
+            let mut mouse_position = cx.mouse_position();
+            move |event: &MouseMoveEvent, phase, cx| {
+                if phase == DispatchPhase::Capture {
+                    return;
+                }
+
+                editor.update(cx, |editor, cx| {
+                    if editor.scroll_manager.is_dragging_minimap() {
+                        if event.pressed_button == Some(MouseButton::Left) {
+                            let mut position = editor.scroll_position(cx);
+                            position.y += (event.position.y - mouse_position.y) / thumb_unit_size;
+                            if position.y < 0.0 {
+                                position.y = 0.0;
+                            }
+                            editor.set_scroll_position(position, cx);
+                            cx.stop_propagation();
+                        } else {
+                            editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        }
+                    } else if show == ShowMinimap::Hover && hitbox.is_hovered(cx) != visible {
+                        cx.notify();
+                    }
+                    mouse_position = event.position;
+                })
+            }
+        });
+
+        if is_dragging {
+            cx.on_mouse_event({
+                let editor = self.editor.clone();
+                move |_: &MouseUpEvent, phase, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        } else if visible {
+            let thumb_bounds = minimap.thumb_bounds();
+            let scroll_top = minimap.scroll_top;
+            let line_height = minimap.line_height;
+            let visible_rows = minimap.visible_range.end - minimap.visible_range.start;
+            cx.on_mouse_event({
+                let editor = self.editor.clone();
+                let hitbox = hitbox.clone();
+                move |event: &MouseDownEvent, phase, cx| {
+                    if phase == DispatchPhase::Capture
+                        || event.button != MouseButton::Left
+                        || !hitbox.is_hovered(cx)
+                    {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(true, cx);
+
+                        let y = event.position.y;
+                        if y < thumb_bounds.top() || thumb_bounds.bottom() < y {
+                            let mut position = editor.scroll_position(cx);
+                            let clicked_row = scroll_top + (y - hitbox.top()) / line_height;
+                            position.y = (clicked_row - visible_rows / 2.).max(0.);
+                            editor.set_scroll_position(position, cx);
+                        }
+
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        }
+    }
+
     fn collect_fast_scrollbar_markers(
         &self,
         layout: &EditorLayout,
-        scrollbar_layout: &ScrollbarLayout,
+        marker_track: MarkerTrack,
         cx: &mut WindowContext,
     ) -> Vec<PaintQuad> {
         const LIMIT: usize = 100;
@@ -4454,25 +4894,38 @@ impl EditorElement {
                 color: *color,
             })
             .collect_vec();
-        scrollbar_layout.marker_quads_for_ranges(cursor_ranges, None)
+        marker_track.marker_quads_for_ranges(cursor_ranges, None)
     }
 
-    fn refresh_slow_scrollbar_markers(
-        &self,
-        layout: &EditorLayout,
-        scrollbar_layout: &ScrollbarLayout,
-        cx: &mut WindowContext,
-    ) {
+    fn refresh_slow_scrollbar_markers(&self, layout: &EditorLayout, cx: &mut WindowContext) {
+        let scrollbar_layout = layout
+            .scrollbars_layout
+            .vertical
+            .as_ref()
+            .filter(|scrollbar_layout| scrollbar_layout.visible);
+        let scrollbar_track = scrollbar_layout.map(ScrollbarLayout::marker_track);
+        let minimap_track = layout.minimap.as_ref().map(MinimapLayout::marker_track);
+        if scrollbar_track.is_none() && minimap_track.is_none() {
+            return;
+        }
+        let scrollbar_size = scrollbar_layout
+            .map(|scrollbar_layout| scrollbar_layout.hitbox.size)
+            .unwrap_or_default();
+        let minimap_size = layout
+            .minimap
+            .as_ref()
+            .map(|minimap| minimap.hitbox.size)
+            .unwrap_or_default();
+
         self.editor.update(cx, |editor, cx| {
             if !editor.is_singleton(cx)
                 || !editor
                     .scrollbar_marker_state
-                    .should_refresh(scrollbar_layout.hitbox.size)
+                    .should_refresh(scrollbar_size, minimap_size)
             {
                 return;
             }
 
-            let scrollbar_layout = scrollbar_layout.clone();
             let background_highlights = editor.background_highlights.clone();
             let snapshot = layout.position_map.snapshot.clone();
             let theme = cx.theme().clone();
@@ -4481,14 +4934,13 @@ impl EditorElement {
             editor.scrollbar_marker_state.dirty = false;
             editor.scrollbar_marker_state.pending_refresh =
                 Some(cx.spawn(|editor, mut cx| async move {
-                    let scrollbar_size = scrollbar_layout.hitbox.size;
-                    let scrollbar_markers = cx
+                    let (scrollbar_markers, minimap_markers) = cx
                         .background_executor()
                         .spawn(async move {
                             let max_point = snapshot.display_snapshot.buffer_snapshot.max_point();
-                            let mut marker_quads = Vec::new();
+                            let mut marker_row_ranges = Vec::new();
                             if scrollbar_settings.git_diff {
-                                let marker_row_ranges = snapshot
+                                let hunk_row_ranges = snapshot
                                     .diff_map
                                     .diff_hunks(&snapshot.buffer_snapshot)
                                     .map(|hunk| {
@@ -4513,12 +4965,9 @@ impl EditorElement {
                                             end: end_display_row,
                                             color,
                                         }
-                                    });
-
-                                marker_quads.extend(
-                                    scrollbar_layout
-                                        .marker_quads_for_ranges(marker_row_ranges, Some(0)),
-                                );
+                                    })
+                                    .collect::<Vec<_>>();
+                                marker_row_ranges.push((hunk_row_ranges, 0));
                             }
 
                             for (background_highlight_id, (_, background_ranges)) in
@@ -4537,22 +4986,21 @@ impl EditorElement {
                                     if is_symbol_occurrences {
                                         color.fade_out(0.5);
                                     }
-                                    let marker_row_ranges = background_ranges.iter().map(|range| {
-                                        let display_start = range
-                                            .start
-                                            .to_display_point(&snapshot.display_snapshot);
-                                        let display_end =
-                                            range.end.to_display_point(&snapshot.display_snapshot);
-                                        ColoredRange {
-                                            start: display_start.row(),
-                                            end: display_end.row(),
-                                            color,
-                                        }
-                                    });
-                                    marker_quads.extend(
-                                        scrollbar_layout
-                                            .marker_quads_for_ranges(marker_row_ranges, Some(1)),
-                                    );
+                                    let highlight_row_ranges =
+                                        background_ranges.iter().map(|range| {
+                                            let display_start = range
+                                                .start
+                                                .to_display_point(&snapshot.display_snapshot);
+                                            let display_end = range
+                                                .end
+                                                .to_display_point(&snapshot.display_snapshot);
+                                            ColoredRange {
+                                                start: display_start.row(),
+                                                end: display_end.row(),
+                                                color,
+                                            }
+                                        });
+                                    marker_row_ranges.push((highlight_row_ranges.collect(), 1));
                                 }
                             }
 
@@ -4568,40 +5016,54 @@ impl EditorElement {
                                         std::cmp::Reverse(diagnostic.diagnostic.severity)
                                     });
 
-                                let marker_row_ranges = diagnostics.into_iter().map(|diagnostic| {
-                                    let start_display = diagnostic
-                                        .range
-                                        .start
-                                        .to_display_point(&snapshot.display_snapshot);
-                                    let end_display = diagnostic
-                                        .range
-                                        .end
-                                        .to_display_point(&snapshot.display_snapshot);
-                                    let color = match diagnostic.diagnostic.severity {
-                                        DiagnosticSeverity::ERROR => theme.status().error,
-                                        DiagnosticSeverity::WARNING => theme.status().warning,
-                                        DiagnosticSeverity::INFORMATION => theme.status().info,
-                                        _ => theme.status().hint,
-                                    };
-                                    ColoredRange {
-                                        start: start_display.row(),
-                                        end: end_display.row(),
-                                        color,
-                                    }
-                                });
-                                marker_quads.extend(
-                                    scrollbar_layout
-                                        .marker_quads_for_ranges(marker_row_ranges, Some(2)),
-                                );
+                                let diagnostic_row_ranges =
+                                    diagnostics.into_iter().map(|diagnostic| {
+                                        let start_display = diagnostic
+                                            .range
+                                            .start
+                                            .to_display_point(&snapshot.display_snapshot);
+                                        let end_display = diagnostic
+                                            .range
+                                            .end
+                                            .to_display_point(&snapshot.display_snapshot);
+                                        let color = match diagnostic.diagnostic.severity {
+                                            DiagnosticSeverity::ERROR => theme.status().error,
+                                            DiagnosticSeverity::WARNING => theme.status().warning,
+                                            DiagnosticSeverity::INFORMATION => theme.status().info,
+                                            _ => theme.status().hint,
+                                        };
+                                        ColoredRange {
+                                            start: start_display.row(),
+                                            end: end_display.row(),
+                                            color,
+                                        }
+                                    });
+                                marker_row_ranges.push((diagnostic_row_ranges.collect(), 2));
                             }
 
-                            Arc::from(marker_quads)
+                            let marker_quads = |track: Option<MarkerTrack>| -> Arc<[PaintQuad]> {
+                                let Some(track) = track else {
+                                    return Arc::from([]);
+                                };
+                                marker_row_ranges
+                                    .iter()
+                                    .flat_map(|(row_ranges, column)| {
+                                        track.marker_quads_for_ranges(
+                                            row_ranges.iter().cloned(),
+                                            Some(*column),
+                                        )
+                                    })
+                                    .collect()
+                            };
+                            (marker_quads(scrollbar_track), marker_quads(minimap_track))
                         })
                         .await;
 
                     editor.update(&mut cx, |editor, cx| {
                         editor.scrollbar_marker_state.markers = scrollbar_markers;
+                        editor.scrollbar_marker_state.minimap_markers = minimap_markers;
                         editor.scrollbar_marker_state.scrollbar_size = scrollbar_size;
+                        editor.scrollbar_marker_state.minimap_size = minimap_size;
                         editor.scrollbar_marker_state.pending_refresh = None;
                         cx.notify();
                     })?;
@@ -4853,6 +5315,21 @@ impl EditorElement {
         });
     }
 
+    /// The width of the minimap, if one is shown in this editor.
+    fn minimap_width(
+        &self,
+        mode: EditorMode,
+        text_width: Pixels,
+        cx: &WindowContext,
+    ) -> Option<Pixels> {
+        let settings = EditorSettings::get_global(cx).minimap;
+        if mode != EditorMode::Full || settings.show == ShowMinimap::Never {
+            return None;
+        }
+        let width = px(settings.width).min(text_width / 2.);
+        (width > MinimapLayout::MARKER_WIDTH).then_some(width)
+    }
+
     fn scrollbar_left(&self, bounds: &Bounds<Pixels>) -> Pixels {
         bounds.top_right().x - self.style.scrollbar_width
     }
@@ -5833,7 +6310,14 @@ impl Element for EditorElement {
                     );
                     let text_width = bounds.size.width - gutter_dimensions.width;
 
-                    let editor_width = text_width - gutter_dimensions.margin - em_width;
+                    let minimap_width = self.minimap_width(snapshot.mode, text_width, cx);
+                    let reserved_minimap_width = minimap_width
+                        .filter(|_| {
+                            EditorSettings::get_global(cx).minimap.show == ShowMinimap::Always
+                        })
+                        .unwrap_or_default();
+                    let editor_width =
+                        text_width - gutter_dimensions.margin - em_width - reserved_minimap_width;
 
                     snapshot = self.editor.update(cx, |editor, cx| {
                         editor.last_bounds = Some(bounds);
@@ -6055,6 +6539,7 @@ impl Element for EditorElement {
                         letter_size,
                         &snapshot,
                         longest_line_width,
+                        reserved_minimap_width,
                         &style,
                         cx,
                     );
@@ -6216,6 +6701,17 @@ impl Element for EditorElement {
                         cx,
                     );
 
+                    let sticky_headers = self.layout_sticky_headers(
+                        &snapshot,
+                        scroll_position,
+                        scroll_pixel_position,
+                        editor_width,
+                        &text_hitbox,
+                        content_origin,
+                        line_height,
+                        cx,
+                    );
+
                     let mut block_start_rows = HashSet::default();
                     cx.with_element_namespace("blocks", |cx| {
                         self.layout_blocks(
@@ -6258,6 +6754,19 @@ impl Element for EditorElement {
                         cx,
                     );
 
+                    let minimap = minimap_width.map(|minimap_width| {
+                        self.layout_minimap(
+                            &snapshot,
+                            scrollbar_bounds,
+                            minimap_width,
+                            scroll_position,
+                            height_in_lines,
+                            max_scroll_top,
+                            letter_size,
+                            cx,
+                        )
+                    });
+
                     let gutter_settings = EditorSettings::get_global(cx).gutter;
 
                     let expanded_add_hunks_by_rows = self.editor.update(cx, |editor, _| {
@@ -6486,6 +6995,8 @@ impl Element for EditorElement {
                         display_hunks,
                         content_origin,
                         scrollbars_layout,
+                        minimap,
+                        sticky_headers,
                         active_rows,
                         highlighted_rows,
                         highlighted_ranges,
@@ -6588,6 +7099,10 @@ impl Element for EditorElement {
                         });
                     }
 
+                    self.paint_sticky_headers(layout, cx);
+                    // Refresh slow scrollbar markers in the background. Below, we paint whatever markers have already been computed.
+                    self.refresh_slow_scrollbar_markers(layout, cx);
+                    self.paint_minimap(layout, cx);
                     self.paint_scrollbars(layout, cx);
                     self.paint_inline_completion_popover(layout, cx);
                     self.paint_mouse_context_menu(layout, cx);
@@ -6619,6 +7134,7 @@ impl ScrollbarRangeData {
         letter_size: Size<Pixels>,
         snapshot: &EditorSnapshot,
         longest_line_width: Pixels,
+        minimap_width: Pixels,
         style: &EditorStyle,
         cx: &WindowContext,
     ) -> ScrollbarRangeData {
@@ -6636,7 +7152,7 @@ impl ScrollbarRangeData {
         };
 
         let overscroll = size(
-            scrollbar_width + (letter_size.width / 2.0),
+            scrollbar_width + minimap_width + (letter_size.width / 2.0),
             letter_size.height * scroll_beyond_last_line,
         );
 
@@ -6669,6 +7185,8 @@ pub struct EditorLayout {
     gutter_dimensions: GutterDimensions,
     content_origin: gpui::Point<Pixels>,
     scrollbars_layout: AxisPair<Option<ScrollbarLayout>>,
+    minimap: Option<MinimapLayout>,
+    sticky_headers: Vec<StickyHeaderLayout>,
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
     indent_guides: Option<Vec<IndentGuideLayout>>,
@@ -6703,6 +7221,7 @@ impl EditorLayout {
     }
 }
 
+#[derive(Clone)]
 struct ColoredRange<T> {
     start: T,
     end: T,
@@ -6751,6 +7270,22 @@ impl ScrollbarLayout {
         self.hitbox.top() + row * self.text_unit_size
     }
 
+    fn marker_track(&self) -> MarkerTrack {
+        MarkerTrack {
+            width: self.hitbox.size.width,
+            text_unit_size: self.text_unit_size,
+        }
+    }
+}
+
+/// A vertical strip on which markers are painted, one row being `text_unit_size` tall.
+#[derive(Clone, Copy)]
+struct MarkerTrack {
+    width: Pixels,
+    text_unit_size: Pixels,
+}
+
+impl MarkerTrack {
     fn marker_quads_for_ranges(
         &self,
         row_ranges: impl IntoIterator<Item = ColoredRange<DisplayRow>>,
@@ -6761,25 +7296,25 @@ impl ScrollbarLayout {
             max: Pixels,
         }
         let (x_range, height_limit) = if let Some(column) = column {
-            let column_width = px(((self.hitbox.size.width - Self::BORDER_WIDTH).0 / 3.0).floor());
-            let start = Self::BORDER_WIDTH + (column as f32 * column_width);
+            let column_width = px(((self.width - ScrollbarLayout::BORDER_WIDTH).0 / 3.0).floor());
+            let start = ScrollbarLayout::BORDER_WIDTH + (column as f32 * column_width);
             let end = start + column_width;
             (
                 Range { start, end },
                 MinMax {
-                    min: Self::MIN_MARKER_HEIGHT,
+                    min: ScrollbarLayout::MIN_MARKER_HEIGHT,
                     max: px(f32::MAX),
                 },
             )
         } else {
             (
                 Range {
-                    start: Self::BORDER_WIDTH,
-                    end: self.hitbox.size.width,
+                    start: ScrollbarLayout::BORDER_WIDTH,
+                    end: self.width,
                 },
                 MinMax {
-                    min: Self::LINE_MARKER_HEIGHT,
-                    max: Self::LINE_MARKER_HEIGHT,
+                    min: ScrollbarLayout::LINE_MARKER_HEIGHT,
+                    max: ScrollbarLayout::LINE_MARKER_HEIGHT,
                 },
             )
         };
@@ -6832,6 +7367,58 @@ impl ScrollbarLayout {
     }
 }
 
+/// The first line of an outline item containing the top of the editor, pinned above the text.
+struct StickyHeaderLayout {
+    line: LineWithInvisibles,
+    origin: gpui::Point<Pixels>,
+    hitbox: Hitbox,
+    target: Anchor,
+}
+
+struct MinimapLayout {
+    hitbox: Hitbox,
+    show: ShowMinimap,
+    /// The height of a display row in the minimap.
+    line_height: Pixels,
+    /// The display row at the top of the minimap, which scrolls along with the editor when the
+    /// buffer is too long to fit.
+    scroll_top: f32,
+    /// How far the thumb moves for each row the editor scrolls.
+    thumb_unit_size: Pixels,
+    visible_range: Range<f32>,
+    text_quads: Vec<PaintQuad>,
+}
+
+impl MinimapLayout {
+    const MARKER_WIDTH: Pixels = px(7.0);
+
+    fn y_for_row(&self, row: f32) -> Pixels {
+        self.hitbox.top() + (row - self.scroll_top) * self.line_height
+    }
+
+    fn thumb_bounds(&self) -> Bounds<Pixels> {
+        Bounds::from_corners(
+            point(self.hitbox.left(), self.y_for_row(self.visible_range.start)),
+            point(self.hitbox.right(), self.y_for_row(self.visible_range.end)),
+        )
+    }
+
+    fn marker_track(&self) -> MarkerTrack {
+        MarkerTrack {
+            width: Self::MARKER_WIDTH,
+            text_unit_size: self.line_height,
+        }
+    }
+
+    /// Where the markers of the [`MarkerTrack`] are painted, along the right edge of the minimap.
+    fn marker_origin(&self) -> gpui::Point<Pixels> {
+        point(
+            self.hitbox.right() - Self::MARKER_WIDTH,
+            self.hitbox.top() - self.scroll_top * self.line_height,
+        )
+    }
+}
+
 struct CreaseTrailerLayout {
     element: AnyElement,
     bounds: Bounds<Pixels>,
@@ -6944,6 +7531,9 @@ pub struct IndentGuideLayout {
     single_indent_width: Pixels,
     depth: u32,
     active: bool,
+    /// The nesting depth of the brackets opening the scope, when the guide is active and its
+    /// brackets are colored by depth.
+    bracket_depth: Option<usize>,
     settings: IndentGuideSettings,
 }
 
@@ -7222,6 +7812,7 @@ pub fn register_action<T: Action>(
         let action = action.downcast_ref().unwrap();
         if phase == DispatchPhase::Bubble {
             view.update(cx, |editor, cx| {
+                editor.record_macro_action(action);
                 listener(editor, action, cx);
             })
         }
@@ -7295,12 +7886,15 @@ mod tests {
     use super::*;
     use crate::{
         display_map::{BlockPlacement, BlockProperties},
+        editor_settings::{MinimapContent, StickyScrollContent},
         editor_tests::{init_test, update_test_language_settings},
         Editor, MultiBuffer,
     };
     use gpui::{TestAppContext, VisualTestContext};
-    use language::language_settings;
+    use indoc::indoc;
+    use language::{language_settings, Buffer, Language, LanguageConfig};
     use log::info;
+    use settings::SettingsStore;
     use std::num::NonZeroU32;
     use util::test::sample_text;
 
@@ -7577,6 +8171,123 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_sticky_headers(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.sticky_scroll = Some(StickyScrollContent {
+                        enabled: Some(true),
+                        max_depth: None,
+                    });
+                });
+            });
+        });
+
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig::default(),
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_outline_query(
+                r#"
+                (mod_item "mod" @context name: (_) @name) @item
+                (function_item "fn" @context name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        );
+        let text = indoc! {"
+            mod outer {
+                fn inner() {
+                    let a = 1;
+                    let b = 2;
+                    let c = 3;
+                    let d = 4;
+                    let e = 5;
+                }
+            }
+        "};
+        let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(language, cx));
+        buffer.update(cx, |buffer, _| buffer.parsing_idle()).await;
+        let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+        let window = cx.add_window(|cx| Editor::new(EditorMode::Full, buffer, None, true, cx));
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|cx| editor.read(cx).style().unwrap().clone());
+
+        let sticky_header_rows = |scroll_top: f32, cx: &mut VisualTestContext| {
+            editor.update(cx, |editor, cx| {
+                editor.set_scroll_position(point(0., scroll_top), cx)
+            });
+            let (_, state) = cx.draw(point(px(500.), px(500.)), size(px(500.), px(500.)), |_| {
+                EditorElement::new(&editor, style.clone())
+            });
+            let snapshot = &state.position_map.snapshot;
+            state
+                .sticky_headers
+                .iter()
+                .map(|header| header.target.to_display_point(snapshot).row().0)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(sticky_header_rows(0., cx), Vec::<u32>::new());
+        assert_eq!(sticky_header_rows(1., cx), vec![0, 1]);
+        assert_eq!(sticky_header_rows(3., cx), vec![0, 1]);
+        // The header of `inner` would cover its last line.
+        assert_eq!(sticky_header_rows(6., cx), vec![0]);
+    }
+
+    #[gpui::test]
+    fn test_minimap_layout(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.minimap = Some(MinimapContent {
+                        show: Some(ShowMinimap::Always),
+                        width: Some(60.),
+                        max_columns: Some(10),
+                    });
+                });
+            });
+        });
+
+        let window = cx.add_window(|cx| {
+            let buffer = MultiBuffer::build_simple("one two\n\n  three\nfourteen letters", cx);
+            Editor::new(EditorMode::Full, buffer, None, true, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|cx| editor.read(cx).style().unwrap().clone());
+
+        let (_, state) = cx.draw(point(px(500.), px(500.)), size(px(500.), px(500.)), |_| {
+            EditorElement::new(&editor, style)
+        });
+
+        let minimap = state.minimap.as_ref().unwrap();
+        assert_eq!(minimap.hitbox.size.width, px(60.));
+        assert_eq!(minimap.scroll_top, 0.);
+        let column_width = (px(60.) - MinimapLayout::MARKER_WIDTH) / 10.;
+        let runs = minimap
+            .text_quads
+            .iter()
+            .map(|quad| {
+                let row = ((quad.bounds.top() - minimap.hitbox.top()) / minimap.line_height).round()
+                    as u32;
+                let start = ((quad.bounds.left() - minimap.hitbox.left()) / column_width).round();
+                let len = (quad.bounds.size.width / column_width).round();
+                (row, start as u32, len as u32)
+            })
+            .collect::<Vec<_>>();
+        // Whitespace is left out and lines are cut off after `max_columns`.
+        assert_eq!(
+            runs,
+            &[(0, 0, 3), (0, 4, 3), (2, 2, 5), (3, 0, 8), (3, 9, 1)]
+        );
+    }
+
     #[gpui::test]
     fn test_all_invisibles_drawing(cx: &mut TestAppContext) {
         const TAB_SIZE: u32 = 4;
@@ -1318,6 +1318,14 @@ impl SearchableItem for Editor {
     ) {
         let text = self.buffer.read(cx);
         let text = text.snapshot(cx);
+        if query.is_structural() {
+            if let Some(replacement) = structural_replacement(&text, identifier, query) {
+                self.transact(cx, |this, cx| {
+                    this.edit([(identifier.clone(), Arc::from(replacement.as_str()))], cx);
+                });
+            }
+            return;
+        }
         let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
         let text: Cow<_> = if text.len() == 1 {
             text.first().cloned().unwrap().into()
@@ -1342,6 +1350,12 @@ impl SearchableItem for Editor {
         let text = text.snapshot(cx);
         let mut edits = vec![];
         for m in matches {
+            if query.is_structural() {
+                if let Some(replacement) = structural_replacement(&text, m, query) {
+                    edits.push((m.clone(), Arc::from(replacement.as_str())));
+                }
+                continue;
+            }
             let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
             let text: Cow<_> = if text.len() == 1 {
                 text.first().cloned().unwrap().into()
@@ -1500,6 +1514,18 @@ impl SearchableItem for Editor {
     }
 }
 
+/// Returns what a structural search match would be replaced with, which depends on the syntax
+/// tree of the buffer containing the match.
+pub(crate) fn structural_replacement(
+    buffer: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+    query: &SearchQuery,
+) -> Option<String> {
+    let range = range.to_offset(buffer);
+    let excerpt = buffer.excerpt_containing(range.clone())?;
+    query.structural_replacement(excerpt.buffer(), excerpt.map_range_to_buffer(range))
+}
+
 pub fn active_match_index(
     ranges: &[Range<Anchor>],
     cursor: &Anchor,
@@ -0,0 +1,519 @@
+use std::{ops::Range, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use gpui::{Action, AppContext, Keystroke, Subscription, ViewContext, WeakView, WindowContext};
+use language::Point;
+use settings::{update_settings_file, Settings};
+use util::ResultExt;
+use workspace::{notifications::NotificationId, Toast};
+
+use crate::{
+    editor_settings::KeyboardMacroStep, Editor, EditorSettings, ReplayLastMacro,
+    ReplayLastMacroToEndOfFile, ReplayMacro, SaveLastMacro, ToggleMacroRecording,
+};
+
+/// Replays are aborted after this many steps, e.g. when replaying to the end of the file never
+/// gets there.
+const MAX_REPLAYED_STEPS: usize = 10_000;
+
+pub(crate) enum MacroStep {
+    Action {
+        action: Box<dyn Action>,
+        /// The keystroke that triggered the action while recording, if any, used to save actions
+        /// that can't be rebuilt from their name alone.
+        keystroke: Option<Keystroke>,
+    },
+    Keystroke(Keystroke),
+    Input {
+        text: Arc<str>,
+        utf16_range_to_replace: Option<Range<isize>>,
+    },
+}
+
+impl MacroStep {
+    fn from_saved(step: &KeyboardMacroStep, cx: &AppContext) -> Result<Self> {
+        Ok(match step {
+            KeyboardMacroStep::Action { action } => Self::Action {
+                action: cx.build_action(action, None)?,
+                keystroke: None,
+            },
+            KeyboardMacroStep::Keystroke { keystroke } => {
+                Self::Keystroke(Keystroke::parse(keystroke)?)
+            }
+            KeyboardMacroStep::Text { text } => Self::Input {
+                text: text.as_str().into(),
+                utf16_range_to_replace: None,
+            },
+        })
+    }
+
+    fn to_saved(&self, cx: &AppContext) -> Result<KeyboardMacroStep> {
+        Ok(match self {
+            Self::Action { action, keystroke } => {
+                let rebuilds_from_name = cx
+                    .build_action(action.name(), None)
+                    .map_or(false, |rebuilt| rebuilt.partial_eq(action.as_ref()));
+                if rebuilds_from_name {
+                    KeyboardMacroStep::Action {
+                        action: action.name().to_string(),
+                    }
+                } else {
+                    let keystroke = keystroke.as_ref().ok_or_else(|| {
+                        anyhow!("cannot save action {} with its arguments", action.name())
+                    })?;
+                    KeyboardMacroStep::Keystroke {
+                        keystroke: keystroke.unparse(),
+                    }
+                }
+            }
+            Self::Keystroke(keystroke) => KeyboardMacroStep::Keystroke {
+                keystroke: keystroke.unparse(),
+            },
+            Self::Input { text, .. } => KeyboardMacroStep::Text {
+                text: text.to_string(),
+            },
+        })
+    }
+
+    fn is_macro_action(action: &dyn Action) -> bool {
+        let action = action.as_any();
+        action.is::<ToggleMacroRecording>()
+            || action.is::<ReplayLastMacro>()
+            || action.is::<ReplayLastMacroToEndOfFile>()
+            || action.is::<ReplayMacro>()
+            || action.is::<SaveLastMacro>()
+    }
+}
+
+struct Recording {
+    steps: Vec<MacroStep>,
+    _keystroke_subscription: Subscription,
+}
+
+enum ReplayCount {
+    Times(usize),
+    /// Replays until an iteration leaves the cursor on the last row, or doesn't move it down.
+    ToEndOfFile {
+        iteration_start_row: u32,
+    },
+}
+
+struct Replay {
+    steps: Arc<[MacroStep]>,
+    ix: usize,
+    count: ReplayCount,
+    replayed_steps: usize,
+}
+
+/// Keyboard macros recorded and replayed in a single editor.
+#[derive(Default)]
+pub(crate) struct KeyboardMacros {
+    recording: Option<Recording>,
+    last_macro: Option<Arc<[MacroStep]>>,
+    replay: Option<Replay>,
+}
+
+/// The part of a step that is dispatched outside of the editor.
+enum ReplayedStep {
+    Action(Box<dyn Action>),
+    Keystroke(Keystroke),
+    Input {
+        text: Arc<str>,
+        utf16_range_to_replace: Option<Range<isize>>,
+    },
+}
+
+impl Editor {
+    pub fn toggle_macro_recording(&mut self, _: &ToggleMacroRecording, cx: &mut ViewContext<Self>) {
+        if let Some(recording) = self.keyboard_macros.recording.take() {
+            if !recording.steps.is_empty() {
+                self.keyboard_macros.last_macro = Some(recording.steps.into());
+            }
+        } else {
+            let keystroke_subscription = cx.observe_keystrokes(|editor, event, _| {
+                if let Some(action) = event.action.as_ref() {
+                    editor.attach_macro_keystroke(action.as_ref(), &event.keystroke);
+                }
+            });
+            self.keyboard_macros.recording = Some(Recording {
+                steps: Vec::new(),
+                _keystroke_subscription: keystroke_subscription,
+            });
+        }
+        cx.notify();
+    }
+
+    /// Records an action dispatched to the editor, whether it was triggered by a keystroke, the
+    /// command palette, a menu or the mouse.
+    pub(crate) fn record_macro_action(&mut self, action: &dyn Action) {
+        if self.keyboard_macros.recording.is_some() {
+            self.record_macro_step(MacroStep::Action {
+                action: action.boxed_clone(),
+                keystroke: None,
+            });
+        }
+    }
+
+    /// Keystrokes are observed after the action they triggered was dispatched, so they are
+    /// attached to the last recorded step.
+    fn attach_macro_keystroke(&mut self, action: &dyn Action, keystroke: &Keystroke) {
+        let Some(recording) = self.keyboard_macros.recording.as_mut() else {
+            return;
+        };
+        if let Some(MacroStep::Action {
+            action: recorded_action,
+            keystroke: recorded_keystroke,
+        }) = recording.steps.last_mut()
+        {
+            if recorded_keystroke.is_none() && recorded_action.partial_eq(action) {
+                *recorded_keystroke = Some(keystroke.clone());
+            }
+        }
+    }
+
+    pub(crate) fn record_macro_input(
+        &mut self,
+        text: &str,
+        utf16_range_to_replace: Option<Range<isize>>,
+    ) {
+        self.record_macro_step(MacroStep::Input {
+            text: text.into(),
+            utf16_range_to_replace,
+        });
+    }
+
+    fn record_macro_step(&mut self, step: MacroStep) {
+        if self.keyboard_macros.replay.is_some() {
+            return;
+        }
+        if let MacroStep::Action { action, .. } = &step {
+            if MacroStep::is_macro_action(action.as_ref()) {
+                return;
+            }
+        }
+        if let Some(recording) = self.keyboard_macros.recording.as_mut() {
+            recording.steps.push(step);
+        }
+    }
+
+    pub fn replay_last_macro(&mut self, action: &ReplayLastMacro, cx: &mut ViewContext<Self>) {
+        let Some(steps) = self.keyboard_macros.last_macro.clone() else {
+            return;
+        };
+        self.start_macro_replay(steps, ReplayCount::Times(action.times.unwrap_or(1)), cx);
+    }
+
+    pub fn replay_last_macro_to_end_of_file(
+        &mut self,
+        _: &ReplayLastMacroToEndOfFile,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(steps) = self.keyboard_macros.last_macro.clone() else {
+            return;
+        };
+        let iteration_start_row = self.selections.newest::<Point>(cx).head().row;
+        self.start_macro_replay(
+            steps,
+            ReplayCount::ToEndOfFile {
+                iteration_start_row,
+            },
+            cx,
+        );
+    }
+
+    pub fn replay_macro(&mut self, action: &ReplayMacro, cx: &mut ViewContext<Self>) {
+        let Some(saved_steps) = EditorSettings::get_global(cx)
+            .keyboard_macros
+            .get(&action.name)
+        else {
+            log::error!("no keyboard macro named {:?}", action.name);
+            return;
+        };
+        let steps = saved_steps
+            .iter()
+            .filter_map(|step| MacroStep::from_saved(step, cx).log_err())
+            .collect::<Arc<[_]>>();
+        self.start_macro_replay(steps, ReplayCount::Times(action.times.unwrap_or(1)), cx);
+    }
+
+    pub fn save_last_macro(&mut self, action: &SaveLastMacro, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let Some(steps) = self.keyboard_macros.last_macro.clone() else {
+            return;
+        };
+        let saved_steps = match steps
+            .iter()
+            .map(|step| step.to_saved(cx))
+            .collect::<Result<Vec<_>>>()
+        {
+            Ok(saved_steps) => saved_steps,
+            Err(error) => {
+                show_macro_toast(&workspace, format!("Failed to save macro: {error}"), cx);
+                return;
+            }
+        };
+
+        let existing_macros = &EditorSettings::get_global(cx).keyboard_macros;
+        let name = action.name.clone().unwrap_or_else(|| {
+            (1..)
+                .map(|ix| format!("macro-{ix}"))
+                .find(|name| !existing_macros.contains_key(name))
+                .unwrap()
+        });
+        let fs = workspace.read(cx).app_state().fs.clone();
+        update_settings_file::<EditorSettings>(fs, cx, {
+            let name = name.clone();
+            move |settings, _| {
+                settings
+                    .keyboard_macros
+                    .get_or_insert_with(Default::default)
+                    .insert(name, saved_steps);
+            }
+        });
+        show_macro_toast(&workspace, format!("Saved macro as {name:?}"), cx);
+    }
+
+    fn start_macro_replay(
+        &mut self,
+        steps: Arc<[MacroStep]>,
+        count: ReplayCount,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if steps.is_empty()
+            || self.keyboard_macros.replay.is_some()
+            || matches!(count, ReplayCount::Times(0))
+        {
+            return;
+        }
+        self.keyboard_macros.replay = Some(Replay {
+            steps,
+            ix: 0,
+            count,
+            replayed_steps: 0,
+        });
+        // Replayed actions are dispatched to the focused element.
+        cx.focus(&self.focus_handle);
+        let editor = cx.view().downgrade();
+        cx.window_context()
+            .defer(move |cx| replay_next_macro_step(editor, cx));
+    }
+
+    /// Advances the replay, returning the next step to dispatch, if any.
+    fn next_replayed_macro_step(&mut self, cx: &mut ViewContext<Self>) -> Option<ReplayedStep> {
+        let head_row = self.selections.newest::<Point>(cx).head().row;
+        let max_row = self.buffer.read(cx).snapshot(cx).max_point().row;
+        let replay = self.keyboard_macros.replay.as_mut()?;
+        if replay.ix == replay.steps.len() {
+            let replay_again = match &mut replay.count {
+                ReplayCount::Times(times) => {
+                    *times -= 1;
+                    *times > 0
+                }
+                ReplayCount::ToEndOfFile {
+                    iteration_start_row,
+                } => {
+                    let moved_down = head_row > *iteration_start_row;
+                    *iteration_start_row = head_row;
+                    moved_down && head_row < max_row
+                }
+            };
+            if !replay_again {
+                self.keyboard_macros.replay = None;
+                return None;
+            }
+            replay.ix = 0;
+        }
+
+        if replay.replayed_steps == MAX_REPLAYED_STEPS {
+            log::error!("aborting keyboard macro replay after {MAX_REPLAYED_STEPS} steps");
+            self.keyboard_macros.replay = None;
+            return None;
+        }
+        replay.replayed_steps += 1;
+
+        let step = &replay.steps[replay.ix];
+        replay.ix += 1;
+        Some(match step {
+            MacroStep::Action { action, .. } => ReplayedStep::Action(action.boxed_clone()),
+            MacroStep::Keystroke(keystroke) => ReplayedStep::Keystroke(keystroke.clone()),
+            MacroStep::Input {
+                text,
+                utf16_range_to_replace,
+            } => ReplayedStep::Input {
+                text: text.clone(),
+                utf16_range_to_replace: utf16_range_to_replace.clone(),
+            },
+        })
+    }
+}
+
+fn replay_next_macro_step(editor: WeakView<Editor>, cx: &mut WindowContext) {
+    let Ok(Some(step)) = editor.update(cx, |editor, cx| editor.next_replayed_macro_step(cx)) else {
+        return;
+    };
+    match step {
+        ReplayedStep::Action(action) => cx.dispatch_action(action),
+        ReplayedStep::Keystroke(keystroke) => {
+            cx.dispatch_keystroke(keystroke);
+        }
+        ReplayedStep::Input {
+            text,
+            utf16_range_to_replace,
+        } => {
+            editor
+                .update(cx, |editor, cx| {
+                    editor.replay_insert_event(&text, utf16_range_to_replace, cx)
+                })
+                .ok();
+        }
+    }
+    // Dispatched actions are handled in a deferred effect, so the next step has to wait for it.
+    cx.defer(move |cx| replay_next_macro_step(editor, cx));
+}
+
+fn show_macro_toast(
+    workspace: &gpui::View<workspace::Workspace>,
+    message: String,
+    cx: &mut ViewContext<Editor>,
+) {
+    struct KeyboardMacroToast;
+
+    workspace.update(cx, |workspace, cx| {
+        workspace.show_toast(
+            Toast::new(NotificationId::unique::<KeyboardMacroToast>(), message),
+            cx,
+        )
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        editor_tests::init_test, test::editor_test_context::EditorTestContext,
+        MoveToBeginningOfLine, MoveToEndOfLine,
+    };
+    use gpui::KeyBinding;
+    use indoc::indoc;
+
+    #[gpui::test]
+    async fn test_keyboard_macros(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.update(|cx| {
+            cx.bind_keys([
+                KeyBinding::new(
+                    "home",
+                    MoveToBeginningOfLine {
+                        stop_at_soft_wraps: true,
+                    },
+                    None,
+                ),
+                KeyBinding::new(
+                    "end",
+                    MoveToEndOfLine {
+                        stop_at_soft_wraps: true,
+                    },
+                    None,
+                ),
+                KeyBinding::new("down", crate::MoveDown, None),
+            ])
+        });
+        cx.set_state(indoc! {"
+            ˇone
+            two
+            three
+            four
+        "});
+
+        cx.dispatch_action(ToggleMacroRecording);
+        cx.simulate_keystrokes("end");
+        cx.simulate_input(";");
+        cx.simulate_keystrokes("down home");
+        cx.dispatch_action(ToggleMacroRecording);
+        cx.assert_editor_state(indoc! {"
+            one;
+            ˇtwo
+            three
+            four
+        "});
+        let saved_steps = cx.update_editor(|editor, cx| {
+            editor
+                .keyboard_macros
+                .last_macro
+                .as_ref()
+                .unwrap()
+                .iter()
+                .map(|step| step.to_saved(cx).unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            saved_steps,
+            [
+                KeyboardMacroStep::Action {
+                    action: "editor::MoveToEndOfLine".into()
+                },
+                KeyboardMacroStep::Text { text: ";".into() },
+                KeyboardMacroStep::Action {
+                    action: "editor::MoveDown".into()
+                },
+                KeyboardMacroStep::Action {
+                    action: "editor::MoveToBeginningOfLine".into()
+                },
+            ]
+        );
+
+        cx.dispatch_action(ReplayLastMacro { times: Some(2) });
+        cx.run_until_parked();
+        cx.assert_editor_state(indoc! {"
+            one;
+            two;
+            three;
+            ˇfour
+        "});
+
+        cx.dispatch_action(ReplayLastMacroToEndOfFile);
+        cx.run_until_parked();
+        cx.assert_editor_state(indoc! {"
+            one;
+            two;
+            three;
+            four;
+            ˇ
+        "});
+        cx.update_editor(|editor, _| assert!(editor.keyboard_macros.replay.is_none()));
+    }
+
+    #[gpui::test]
+    async fn test_keyboard_macros_record_actions_without_keystrokes(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.set_state(indoc! {"
+            ˇone
+            two
+        "});
+
+        // Actions dispatched from the command palette or a menu have no keystroke.
+        cx.dispatch_action(ToggleMacroRecording);
+        cx.dispatch_action(MoveToEndOfLine {
+            stop_at_soft_wraps: true,
+        });
+        cx.simulate_input("!");
+        cx.dispatch_action(crate::MoveDown);
+        cx.dispatch_action(ToggleMacroRecording);
+        cx.assert_editor_state(indoc! {"
+            one!
+            twoˇ
+        "});
+
+        cx.dispatch_action(ReplayLastMacro { times: None });
+        cx.run_until_parked();
+        cx.assert_editor_state(indoc! {"
+            one!
+            two!
+            ˇ
+        "});
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{Editor, EditorEvent, ExitLargeFileMode};
+use gpui::{EventEmitter, FocusableView, Subscription, View};
+use project::project_settings::ProjectSettings;
+use settings::Settings;
+use ui::{prelude::*, KeyBinding};
+use workspace::{ItemHandle, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView};
+
+/// Explains why the features of an editor are disabled for a file opened in large file mode,
+/// and offers to enable them again.
+pub struct LargeFileBanner {
+    current_editor: Option<View<Editor>>,
+    _subscription: Option<Subscription>,
+}
+
+impl Default for LargeFileBanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LargeFileBanner {
+    pub fn new() -> Self {
+        Self {
+            current_editor: None,
+            _subscription: None,
+        }
+    }
+
+    fn is_large_file(editor: &View<Editor>, cx: &AppContext) -> bool {
+        editor
+            .read(cx)
+            .buffer()
+            .read(cx)
+            .as_singleton()
+            .map_or(false, |buffer| buffer.read(cx).is_large_file())
+    }
+
+    fn toolbar_item_location(&self, cx: &AppContext) -> ToolbarItemLocation {
+        match &self.current_editor {
+            Some(editor) if Self::is_large_file(editor, cx) => ToolbarItemLocation::Secondary,
+            _ => ToolbarItemLocation::Hidden,
+        }
+    }
+}
+
+impl Render for LargeFileBanner {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let Some(editor) = self.current_editor.as_ref() else {
+            return div().into_any_element();
+        };
+        let focus_handle = editor.focus_handle(cx);
+        let threshold_mb = ProjectSettings::get_global(cx).large_files.threshold_mb;
+        // Only the host of a remote project can take its buffers out of large file mode.
+        let can_exit = editor
+            .read(cx)
+            .project
+            .as_ref()
+            .map_or(false, |project| project.read(cx).is_local());
+
+        h_flex()
+            .px_2()
+            .gap_2()
+            .justify_between()
+            .bg(cx.theme().status().info_background)
+            .rounded_md()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Icon::new(IconName::Info).size(IconSize::Small))
+                    .child(Label::new(format!(
+                        "This file is larger than {threshold_mb} MB, so syntax highlighting, \
+                         language servers, git diffs and soft wrap are disabled."
+                    ))),
+            )
+            .when(can_exit, |banner| {
+                banner.child(
+                    Button::new("exit-large-file-mode", "Enable All Features")
+                        .key_binding(KeyBinding::for_action_in(
+                            &ExitLargeFileMode,
+                            &focus_handle,
+                            cx,
+                        ))
+                        .on_click(move |_, cx| {
+                            focus_handle.dispatch_action(&ExitLargeFileMode, cx)
+                        }),
+                )
+            })
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<ToolbarItemEvent> for LargeFileBanner {}
+
+impl ToolbarItemView for LargeFileBanner {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) -> ToolbarItemLocation {
+        self.current_editor = active_pane_item.and_then(|item| item.act_as::<Editor>(cx));
+        self._subscription = self.current_editor.as_ref().map(|editor| {
+            cx.subscribe(editor, |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::Reparsed(_) = event {
+                    let location = this.toolbar_item_location(cx);
+                    cx.emit(ToolbarItemEvent::ChangeLocation(location));
+                    cx.notify();
+                }
+            })
+        });
+        self.toolbar_item_location(cx)
+    }
+}
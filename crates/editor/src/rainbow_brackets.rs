@@ -0,0 +1,260 @@
+use std::{cmp::Reverse, ops::Range, time::Duration};
+
+use collections::{BTreeMap, HashSet};
+use gpui::{HighlightStyle, Task};
+use language::language_settings::language_settings;
+use multi_buffer::{Anchor, ExcerptId, MultiBufferRow, MultiBufferSnapshot, ToOffset as _};
+use text::Point;
+use theme::ActiveTheme;
+use ui::ViewContext;
+use util::ResultExt;
+
+use crate::Editor;
+
+enum RainbowBracketHighlight {}
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(30);
+
+#[derive(Default)]
+pub(super) struct RainbowBracketsState {
+    /// The buffer rows whose brackets are colored or being colored, if they are still valid.
+    colored_rows: Option<Range<u32>>,
+    refresh_task: Option<Task<()>>,
+}
+
+impl RainbowBracketsState {
+    /// Recolors the brackets on the next refresh, e.g. after an edit or a reparse.
+    pub(super) fn invalidate(&mut self) {
+        self.colored_rows = None;
+    }
+}
+
+/// Colors the brackets around the visible rows by their nesting depth, using the accent
+/// colors of the theme. Brackets are colored a page above and below the viewport so that
+/// scrolling doesn't reveal uncolored brackets, and are only recomputed once the viewport
+/// leaves those rows or the buffer changes.
+pub(super) fn refresh_rainbow_brackets(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let Some(visible_line_count) = editor.visible_line_count() else {
+        return;
+    };
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let visible_line_count = visible_line_count.ceil() as u32;
+    let top_row = editor
+        .scroll_manager
+        .anchor()
+        .anchor
+        .to_point(&snapshot)
+        .row;
+    let visible_rows = top_row..top_row + visible_line_count;
+    if editor
+        .rainbow_brackets
+        .colored_rows
+        .as_ref()
+        .map_or(false, |rows| {
+            rows.start <= visible_rows.start && visible_rows.end <= rows.end
+        })
+    {
+        return;
+    }
+
+    let max_row = snapshot.max_point().row;
+    let rows = top_row.saturating_sub(visible_line_count)
+        ..(visible_rows.end + visible_line_count).min(max_row);
+    let range = Point::new(rows.start, 0)
+        ..Point::new(rows.end, snapshot.line_len(MultiBufferRow(rows.end)));
+    let enabled_excerpts = snapshot
+        .excerpts_for_range(range.clone())
+        .filter(|excerpt| {
+            let buffer = excerpt.buffer();
+            language_settings(buffer.language().map(|l| l.name()), buffer.file(), cx)
+                .colorize_brackets
+        })
+        .map(|excerpt| excerpt.id())
+        .collect::<HashSet<_>>();
+    editor.rainbow_brackets.colored_rows = Some(rows);
+    if enabled_excerpts.is_empty() {
+        editor.rainbow_brackets.refresh_task = None;
+        editor.clear_highlights::<RainbowBracketHighlight>(cx);
+        return;
+    }
+
+    let color_count = cx.theme().accents().0.len();
+    editor.rainbow_brackets.refresh_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+        let highlights = cx
+            .background_executor()
+            .spawn(
+                async move { bracket_highlights(&snapshot, range, &enabled_excerpts, color_count) },
+            )
+            .await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                editor.clear_highlights::<RainbowBracketHighlight>(cx);
+                for (color_ix, ranges) in highlights {
+                    let color = cx.theme().accents().color_for_index(color_ix as u32);
+                    editor.highlight_text_key::<RainbowBracketHighlight>(
+                        color_ix,
+                        ranges,
+                        HighlightStyle {
+                            color: Some(color),
+                            ..HighlightStyle::default()
+                        },
+                        cx,
+                    );
+                }
+            })
+            .log_err();
+    }));
+}
+
+/// Groups the brackets in `range` by the index of the accent color for their depth.
+fn bracket_highlights(
+    snapshot: &MultiBufferSnapshot,
+    range: Range<Point>,
+    enabled_excerpts: &HashSet<ExcerptId>,
+    color_count: usize,
+) -> BTreeMap<usize, Vec<Range<Anchor>>> {
+    let range = range.start.to_offset(snapshot)..range.end.to_offset(snapshot);
+    let mut brackets = BTreeMap::<usize, Vec<Range<usize>>>::new();
+    for excerpt in snapshot.excerpts_for_range(range.clone()) {
+        if !enabled_excerpts.contains(&excerpt.id()) {
+            continue;
+        }
+
+        // Pairs enclosing the range overlap it too, so depths are relative to the whole buffer,
+        // not just the queried rows.
+        let buffer_range = excerpt.map_range_to_buffer(range.clone());
+        let mut pairs = excerpt
+            .buffer()
+            .bracket_ranges(buffer_range.clone())
+            .collect::<Vec<_>>();
+        pairs.sort_by_key(|(open, close)| (open.start, Reverse(close.end)));
+        pairs.dedup();
+
+        let mut enclosing_ends = Vec::new();
+        for (open, close) in pairs {
+            while enclosing_ends
+                .last()
+                .map_or(false, |&end| end <= open.start)
+            {
+                enclosing_ends.pop();
+            }
+            let depth = enclosing_ends.len();
+            enclosing_ends.push(close.end);
+
+            for bracket in [open, close] {
+                if bracket.end > buffer_range.start
+                    && bracket.start < buffer_range.end
+                    && excerpt.contains_buffer_range(bracket.clone())
+                {
+                    brackets
+                        .entry(depth % color_count)
+                        .or_default()
+                        .push(excerpt.map_range_from_buffer(bracket));
+                }
+            }
+        }
+    }
+
+    brackets
+        .into_iter()
+        .map(|(color_ix, mut ranges)| {
+            ranges.sort_by_key(|range| range.start);
+            let ranges = ranges
+                .into_iter()
+                .map(|range| snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end))
+                .collect();
+            (color_ix, ranges)
+        })
+        .collect()
+}
+
+/// Returns the depth that the innermost bracket pair enclosing the offset is colored by, which
+/// is the number of pairs enclosing it in turn.
+pub(super) fn enclosing_bracket_depth(
+    snapshot: &MultiBufferSnapshot,
+    offset: usize,
+) -> Option<usize> {
+    let mut pairs = snapshot
+        .enclosing_bracket_ranges(offset..offset)?
+        .filter(|(open, close)| open.end <= offset && offset <= close.start)
+        .collect::<Vec<_>>();
+    pairs.sort_by_key(|(open, close)| (open.start, Reverse(close.end)));
+    pairs.dedup();
+    pairs.len().checked_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{Context as _, TestAppContext};
+    use indoc::indoc;
+    use language::{Buffer, Language, LanguageConfig};
+    use multi_buffer::MultiBuffer;
+    use std::sync::Arc;
+
+    #[gpui::test]
+    async fn test_bracket_highlights(cx: &mut TestAppContext) {
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_brackets_query(indoc! {r#"
+                ("{" @open "}" @close)
+                ("(" @open ")" @close)
+                ("[" @open "]" @close)
+            "#})
+            .unwrap(),
+        );
+        let text = indoc! {"
+            fn main() {
+                let v = [(1, 2)];
+            }
+        "};
+        let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(language, cx));
+        cx.condition(&buffer, |buffer, _| !buffer.is_parsing())
+            .await;
+        let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+        let snapshot = buffer.update(cx, |buffer, cx| buffer.snapshot(cx));
+        let excerpts = snapshot
+            .excerpts_for_range(0..snapshot.len())
+            .map(|excerpt| excerpt.id())
+            .collect::<HashSet<_>>();
+
+        let bracket_offsets = |range: Range<Point>| {
+            bracket_highlights(&snapshot, range, &excerpts, 2)
+                .into_iter()
+                .map(|(color_ix, ranges)| {
+                    let offsets = ranges
+                        .iter()
+                        .map(|range| range.start.to_offset(&snapshot))
+                        .collect::<Vec<_>>();
+                    (color_ix, offsets)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // The colors cycle once the nesting is deeper than the palette.
+        assert_eq!(
+            bracket_offsets(Point::new(0, 0)..Point::new(3, 0)),
+            [(0, vec![7, 8, 10, 25, 30, 34]), (1, vec![24, 31])]
+        );
+        // Brackets outside of the range still count towards the depth.
+        assert_eq!(
+            bracket_offsets(Point::new(1, 0)..Point::new(1, 21)),
+            [(0, vec![25, 30]), (1, vec![24, 31])]
+        );
+
+        // Offsets are in the scope of the innermost brackets around them, colored by their depth.
+        let depth_at =
+            |point: Point| enclosing_bracket_depth(&snapshot, point.to_offset(&snapshot));
+        assert_eq!(depth_at(Point::new(0, 0)), None);
+        assert_eq!(depth_at(Point::new(1, 0)), Some(0));
+        assert_eq!(depth_at(Point::new(1, 13)), Some(1));
+        assert_eq!(depth_at(Point::new(1, 14)), Some(2));
+    }
+}
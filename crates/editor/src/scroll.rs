@@ -7,6 +7,7 @@ use crate::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
     hover_popover::hide_hover,
     persistence::DB,
+    rainbow_brackets::refresh_rainbow_brackets,
     Anchor, DisplayPoint, DisplayRow, Editor, EditorEvent, EditorMode, EditorSettings,
     InlayHintRefreshReason, MultiBufferSnapshot, RowExt, ToPoint,
 };
@@ -183,6 +184,7 @@ pub struct ScrollManager {
     show_scrollbars: bool,
     hide_scrollbar_task: Option<Task<()>>,
     dragging_scrollbar: AxisPair<bool>,
+    dragging_minimap: bool,
     visible_line_count: Option<f32>,
     forbid_vertical_scroll: bool,
 }
@@ -197,6 +199,7 @@ impl ScrollManager {
             show_scrollbars: true,
             hide_scrollbar_task: None,
             dragging_scrollbar: axis_pair(false, false),
+            dragging_minimap: false,
             last_autoscroll: None,
             visible_line_count: None,
             forbid_vertical_scroll: false,
@@ -371,6 +374,15 @@ impl ScrollManager {
         cx.notify();
     }
 
+    pub fn is_dragging_minimap(&self) -> bool {
+        self.dragging_minimap
+    }
+
+    pub fn set_is_dragging_minimap(&mut self, dragging: bool, cx: &mut ViewContext<Editor>) {
+        self.dragging_minimap = dragging;
+        cx.notify();
+    }
+
     pub fn clamp_scroll_left(&mut self, max: f32) -> bool {
         if max < self.anchor.offset.x {
             self.anchor.offset.x = max;
@@ -411,6 +423,7 @@ impl Editor {
     pub(crate) fn set_visible_line_count(&mut self, lines: f32, cx: &mut ViewContext<Self>) {
         let opened_first_time = self.scroll_manager.visible_line_count.is_none();
         self.scroll_manager.visible_line_count = Some(lines);
+        refresh_rainbow_brackets(self, cx);
         if opened_first_time {
             cx.spawn(|editor, mut cx| async move {
                 editor
@@ -479,6 +492,7 @@ impl Editor {
         );
 
         self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+        refresh_rainbow_brackets(self, cx);
     }
 
     pub fn scroll_position(&self, cx: &mut ViewContext<Self>) -> gpui::Point<f32> {
@@ -0,0 +1,382 @@
+use std::ops::Range;
+
+use gpui::ViewContext;
+use language::{Selection, SelectionGoal, TextObject, TreeSitterOptions};
+use multi_buffer::MultiBufferExcerpt;
+use util::RangeExt;
+
+use crate::{
+    scroll::Autoscroll, Editor, MoveToNextTextObject, MoveToPreviousTextObject, SelectTextObject,
+    SwapWithNextTextObject, SwapWithPreviousTextObject,
+};
+
+impl Editor {
+    /// Selects the smallest text object of the given kind that contains each selection. When a
+    /// selection already covers such an object, the next larger one is selected instead.
+    pub fn select_text_object(&mut self, action: &SelectTextObject, cx: &mut ViewContext<Self>) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let old_selections = self.selections.all::<usize>(cx).into_boxed_slice();
+
+        let mut selected_text_object = false;
+        let new_selections = old_selections
+            .iter()
+            .map(|selection| {
+                let old_range = selection.range();
+                let new_range = buffer
+                    .excerpt_containing(old_range.clone())
+                    .and_then(|excerpt| {
+                        let buffer_range = excerpt.map_range_to_buffer(old_range.clone());
+                        let range =
+                            text_object_ranges(&excerpt, buffer_range.clone(), action.object)
+                                .filter(|range| {
+                                    range.contains_inclusive(&buffer_range)
+                                        && *range != buffer_range
+                                })
+                                .min_by_key(|range| range.len())?;
+                        Some(excerpt.map_range_from_buffer(range))
+                    })
+                    .unwrap_or(old_range.clone());
+
+                selected_text_object |= new_range != old_range;
+                Selection {
+                    id: selection.id,
+                    start: new_range.start,
+                    end: new_range.end,
+                    goal: SelectionGoal::None,
+                    reversed: selection.reversed,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if selected_text_object {
+            self.select_larger_syntax_node_stack.push(old_selections);
+            self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select(new_selections);
+            });
+        }
+    }
+
+    /// Moves each cursor to the start of the next text object of the given kind.
+    pub fn move_to_next_text_object(
+        &mut self,
+        action: &MoveToNextTextObject,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.move_to_text_object(action.object, true, cx);
+    }
+
+    /// Moves each cursor to the start of the previous text object of the given kind, or of the
+    /// one it is in.
+    pub fn move_to_previous_text_object(
+        &mut self,
+        action: &MoveToPreviousTextObject,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.move_to_text_object(action.object, false, cx);
+    }
+
+    /// Swaps the text object of the given kind around each selection with its next sibling,
+    /// for example moving an argument after the one that follows it.
+    pub fn swap_with_next_text_object(
+        &mut self,
+        action: &SwapWithNextTextObject,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.swap_text_object(action.object, true, cx);
+    }
+
+    /// Swaps the text object of the given kind around each selection with its previous sibling.
+    pub fn swap_with_previous_text_object(
+        &mut self,
+        action: &SwapWithPreviousTextObject,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.swap_text_object(action.object, false, cx);
+    }
+
+    fn move_to_text_object(
+        &mut self,
+        object: TextObject,
+        forward: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let mut moved = false;
+        let new_selections = self
+            .selections
+            .all::<usize>(cx)
+            .into_iter()
+            .map(|mut selection| {
+                let head = selection.head();
+                let target = buffer.excerpt_containing(head..head).and_then(|excerpt| {
+                    let buffer_head = excerpt.map_offset_to_buffer(head);
+                    let start = if forward {
+                        let search_range = buffer_head..excerpt.buffer().len();
+                        text_object_ranges(&excerpt, search_range, object)
+                            .map(|range| range.start)
+                            .filter(|start| *start > buffer_head)
+                            .min()
+                    } else {
+                        text_object_ranges(&excerpt, 0..buffer_head, object)
+                            .map(|range| range.start)
+                            .filter(|start| *start < buffer_head)
+                            .max()
+                    }?;
+                    Some(excerpt.map_offset_from_buffer(start))
+                });
+                if let Some(target) = target {
+                    moved = true;
+                    selection.collapse_to(target, SelectionGoal::None);
+                }
+                selection
+            })
+            .collect::<Vec<_>>();
+
+        if moved {
+            self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select(new_selections);
+            });
+        }
+    }
+
+    fn swap_text_object(&mut self, object: TextObject, forward: bool, cx: &mut ViewContext<Self>) {
+        // Swapping the "around" ranges would move separators such as commas along with the
+        // objects, so siblings are always swapped by their "inside" ranges.
+        let object = object.inside().unwrap_or(object);
+        let buffer = self.buffer.read(cx).snapshot(cx);
+
+        let mut edits = Vec::new();
+        let mut swapped_spans = Vec::<Range<usize>>::new();
+        let mut new_selections = Vec::new();
+        for selection in self.selections.all::<usize>(cx) {
+            let range = selection.range();
+            let Some((current, sibling)) = buffer
+                .excerpt_containing(range.clone())
+                .and_then(|excerpt| sibling_text_objects(&excerpt, range.clone(), object, forward))
+            else {
+                continue;
+            };
+            let span = current.start.min(sibling.start)..current.end.max(sibling.end);
+            if swapped_spans.iter().any(|swapped| swapped.overlaps(&span)) {
+                continue;
+            }
+            swapped_spans.push(span);
+
+            // Both ranges of a pair are replaced by text of the other's length, so the offsets
+            // outside of each pair are unaffected by the swap.
+            let current_text = buffer.text_for_range(current.clone()).collect::<String>();
+            let sibling_text = buffer.text_for_range(sibling.clone()).collect::<String>();
+            let new_start = if forward {
+                sibling.end - current.len()
+            } else {
+                sibling.start
+            };
+            new_selections.push(Selection {
+                id: selection.id,
+                start: new_start + (selection.start - current.start),
+                end: new_start + (selection.end - current.start),
+                goal: SelectionGoal::None,
+                reversed: selection.reversed,
+            });
+            edits.push((current, sibling_text));
+            edits.push((sibling, current_text));
+        }
+        if edits.is_empty() {
+            return;
+        }
+        edits.sort_by_key(|(range, _)| range.start);
+
+        self.transact(cx, |this, cx| {
+            this.edit(edits, cx);
+            this.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select(new_selections);
+            });
+        });
+    }
+}
+
+/// Returns the buffer ranges of the text objects of the given kind that overlap the buffer range
+/// and lie within the excerpt.
+fn text_object_ranges<'a>(
+    excerpt: &'a MultiBufferExcerpt<'a>,
+    buffer_range: Range<usize>,
+    object: TextObject,
+) -> impl Iterator<Item = Range<usize>> + 'a {
+    excerpt
+        .buffer()
+        .text_object_ranges(buffer_range, TreeSitterOptions::default())
+        .filter(move |(range, text_object)| {
+            *text_object == object && excerpt.contains_buffer_range(range.clone())
+        })
+        .map(|(range, _)| range)
+}
+
+/// Finds the smallest text object of the given kind containing the range, and the closest text
+/// object of the same kind after or before it that shares its parent syntax node. Both are
+/// returned as multi-buffer ranges.
+fn sibling_text_objects(
+    excerpt: &MultiBufferExcerpt,
+    range: Range<usize>,
+    object: TextObject,
+    forward: bool,
+) -> Option<(Range<usize>, Range<usize>)> {
+    let buffer = excerpt.buffer();
+    let buffer_range = excerpt.map_range_to_buffer(range);
+    let current = text_object_ranges(excerpt, buffer_range.clone(), object)
+        .filter(|range| range.contains_inclusive(&buffer_range))
+        .min_by_key(|range| range.len())?;
+    let parent = buffer.range_for_syntax_ancestor(current.clone())?;
+    let siblings = text_object_ranges(excerpt, parent.clone(), object).filter(|range| {
+        parent.contains_inclusive(range)
+            && buffer.range_for_syntax_ancestor(range.clone()).as_ref() == Some(&parent)
+    });
+    let sibling = if forward {
+        siblings
+            .filter(|range| range.start >= current.end)
+            .min_by_key(|range| range.start)
+    } else {
+        siblings
+            .filter(|range| range.end <= current.start)
+            .max_by_key(|range| range.end)
+    }?;
+    Some((
+        excerpt.map_range_from_buffer(current),
+        excerpt.map_range_from_buffer(sibling),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        editor_tests::init_test, test::editor_test_context::EditorTestContext,
+        SelectSmallerSyntaxNode,
+    };
+    use indoc::indoc;
+    use language::{Language, LanguageConfig};
+    use std::sync::Arc;
+
+    fn rust_lang() -> Arc<Language> {
+        Arc::new(
+            Language::new(
+                LanguageConfig::default(),
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_text_object_query(
+                r#"
+                (function_item
+                    body: (_
+                        "{"
+                        (_)* @function.inside
+                        "}" )) @function.around
+
+                (arguments
+                    (_) @parameter.inside @parameter.around
+                    .
+                    ","? @parameter.around)
+                "#,
+            )
+            .unwrap(),
+        )
+    }
+
+    #[gpui::test]
+    async fn test_select_text_object(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.update_buffer(|buffer, cx| buffer.set_language(Some(rust_lang()), cx));
+
+        cx.set_state("fn a() { call(aˇa, bb); }");
+        cx.run_until_parked();
+        cx.dispatch_action(SelectTextObject {
+            object: TextObject::InsideParameter,
+        });
+        cx.assert_editor_state("fn a() { call(«aaˇ», bb); }");
+
+        cx.dispatch_action(SelectTextObject {
+            object: TextObject::AroundParameter,
+        });
+        cx.assert_editor_state("fn a() { call(«aa,ˇ» bb); }");
+
+        cx.dispatch_action(SelectTextObject {
+            object: TextObject::InsideFunction,
+        });
+        cx.assert_editor_state("fn a() { «call(aa, bb);ˇ» }");
+
+        cx.dispatch_action(SelectSmallerSyntaxNode);
+        cx.assert_editor_state("fn a() { call(«aa,ˇ» bb); }");
+    }
+
+    #[gpui::test]
+    async fn test_move_to_text_object(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.update_buffer(|buffer, cx| buffer.set_language(Some(rust_lang()), cx));
+
+        cx.set_state(indoc! {"
+            fn a() {}
+            fn ˇb() {}
+            fn c() {}
+        "});
+        cx.run_until_parked();
+        cx.dispatch_action(MoveToNextTextObject {
+            object: TextObject::AroundFunction,
+        });
+        cx.assert_editor_state(indoc! {"
+            fn a() {}
+            fn b() {}
+            ˇfn c() {}
+        "});
+
+        cx.dispatch_action(MoveToNextTextObject {
+            object: TextObject::AroundFunction,
+        });
+        cx.assert_editor_state(indoc! {"
+            fn a() {}
+            fn b() {}
+            ˇfn c() {}
+        "});
+
+        cx.dispatch_action(MoveToPreviousTextObject {
+            object: TextObject::AroundFunction,
+        });
+        cx.assert_editor_state(indoc! {"
+            fn a() {}
+            ˇfn b() {}
+            fn c() {}
+        "});
+    }
+
+    #[gpui::test]
+    async fn test_swap_text_objects(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.update_buffer(|buffer, cx| buffer.set_language(Some(rust_lang()), cx));
+
+        cx.set_state("fn a() { call(aˇa, bb, x(c, d)); }");
+        cx.run_until_parked();
+        cx.dispatch_action(SwapWithNextTextObject {
+            object: TextObject::InsideParameter,
+        });
+        cx.assert_editor_state("fn a() { call(bb, aˇa, x(c, d)); }");
+
+        cx.dispatch_action(SwapWithNextTextObject {
+            object: TextObject::AroundParameter,
+        });
+        cx.assert_editor_state("fn a() { call(bb, x(c, d), aˇa); }");
+
+        // The last argument has no next sibling, and the arguments of the nested call aren't
+        // siblings of the outer ones.
+        cx.dispatch_action(SwapWithNextTextObject {
+            object: TextObject::InsideParameter,
+        });
+        cx.assert_editor_state("fn a() { call(bb, x(c, d), aˇa); }");
+
+        cx.set_state("fn a() { call(bb, x(c, ˇd), aa); }");
+        cx.run_until_parked();
+        cx.dispatch_action(SwapWithPreviousTextObject {
+            object: TextObject::InsideParameter,
+        });
+        cx.assert_editor_state("fn a() { call(bb, x(ˇd, c), aa); }");
+    }
+}
@@ -9,6 +9,7 @@ use futures::future::join_all;
 pub use open_path_prompt::OpenPathDelegate;
 
 use collections::HashMap;
+use db::frecency::{Frecencies, FrecencyKind, FrecencyScope, FRECENCY};
 use editor::{scroll::Autoscroll, Bias, Editor};
 use file_finder_settings::{FileFinderSettings, FileFinderWidth};
 use file_icons::FileIcons;
@@ -20,7 +21,7 @@ use gpui::{
 };
 use new_path_prompt::NewPathPrompt;
 use open_path_prompt::OpenPathPrompt;
-use picker::{Picker, PickerDelegate};
+use picker::{Picker, PickerDelegate, PickerPreview};
 use project::{PathMatchCandidateSet, Project, ProjectPath, WorktreeId};
 use settings::Settings;
 use std::{
@@ -94,6 +95,14 @@ impl FileFinder {
     ) -> Task<()> {
         let project = workspace.project().read(cx);
         let fs = project.fs();
+        let frecency_scope = workspace
+            .database_id()
+            .map(|id| FrecencyScope::Workspace(id.into()));
+        let frecencies = cx.background_executor().spawn(async move {
+            frecency_scope
+                .and_then(|scope| FRECENCY.frecencies(FrecencyKind::File, scope).log_err())
+                .unwrap_or_default()
+        });
 
         let currently_opened_path = workspace
             .active_item(cx)
@@ -132,6 +141,7 @@ impl FileFinder {
             .collect::<Vec<_>>();
         cx.spawn(move |workspace, mut cx| async move {
             let history_items = join_all(history_items).await.into_iter().flatten();
+            let frecencies = frecencies.await;
 
             workspace
                 .update(&mut cx, |workspace, cx| {
@@ -145,6 +155,8 @@ impl FileFinder {
                             currently_opened_path,
                             history_items.collect(),
                             separate_history,
+                            frecency_scope,
+                            frecencies,
                             cx,
                         );
 
@@ -279,9 +291,12 @@ impl Render for FileFinder {
         let file_finder_settings = FileFinderSettings::get_global(cx);
         let modal_max_width = Self::modal_max_width(file_finder_settings.modal_max_width, cx);
 
+        let preview_width = self.picker.read(cx).preview_width(cx).unwrap_or_default();
+        let modal_width = (modal_max_width + preview_width).min(cx.viewport_size().width);
+
         v_flex()
             .key_context(key_context)
-            .w(modal_max_width)
+            .w(modal_width)
             .on_modifiers_changed(cx.listener(Self::handle_modifiers_changed))
             .on_action(cx.listener(Self::handle_select_prev))
             .on_action(cx.listener(Self::handle_toggle_menu))
@@ -311,6 +326,8 @@ pub struct FileFinderDelegate {
     first_update: bool,
     popover_menu_handle: PopoverMenuHandle<ContextMenu>,
     focus_handle: FocusHandle,
+    frecency_scope: Option<FrecencyScope>,
+    frecencies: Frecencies,
 }
 
 /// Use a custom ordering for file finder: the regular one
@@ -418,6 +435,7 @@ impl Matches {
         history_items: impl IntoIterator<Item = &'a FoundPath> + Clone,
         currently_opened: Option<&'a FoundPath>,
         query: Option<&FileSearchQuery>,
+        frecencies: &Frecencies,
         new_search_matches: impl Iterator<Item = ProjectPanelOrdMatch>,
         extend_old_matches: bool,
     ) {
@@ -440,7 +458,8 @@ impl Matches {
             return;
         };
 
-        let new_history_matches = matching_history_items(history_items, currently_opened, query);
+        let new_history_matches =
+            matching_history_items(history_items, currently_opened, query, frecencies);
         let new_search_matches: Vec<Match> = new_search_matches
             .filter(|path_match| !new_history_matches.contains_key(&path_match.0.path))
             .map(Match::Search)
@@ -504,10 +523,15 @@ impl Matches {
     }
 }
 
+fn frecency_key(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
 fn matching_history_items<'a>(
     history_items: impl IntoIterator<Item = &'a FoundPath>,
     currently_opened: Option<&'a FoundPath>,
     query: &FileSearchQuery,
+    frecencies: &Frecencies,
 ) -> HashMap<Arc<Path>, Match> {
     let mut candidates_paths = HashMap::default();
 
@@ -556,7 +580,9 @@ fn matching_history_items<'a>(
                 max_results,
             )
             .into_iter()
-            .filter_map(|path_match| {
+            .filter_map(|mut path_match| {
+                path_match.score =
+                    frecencies.bias(&frecency_key(&path_match.path), path_match.score);
                 candidates_paths
                     .remove_entry(&ProjectPath {
                         worktree_id: WorktreeId::from_usize(path_match.worktree_id),
@@ -620,6 +646,8 @@ impl FileFinderDelegate {
         currently_opened_path: Option<FoundPath>,
         history_items: Vec<FoundPath>,
         separate_history: bool,
+        frecency_scope: Option<FrecencyScope>,
+        frecencies: Frecencies,
         cx: &mut ViewContext<FileFinder>,
     ) -> Self {
         Self::subscribe_to_updates(&project, cx);
@@ -641,6 +669,8 @@ impl FileFinderDelegate {
             first_update: true,
             popover_menu_handle: PopoverMenuHandle::default(),
             focus_handle: cx.focus_handle(),
+            frecency_scope,
+            frecencies,
         }
     }
 
@@ -739,11 +769,17 @@ impl FileFinderDelegate {
                 self.matches.get(self.selected_index).cloned()
             };
 
+            let frecencies = &self.frecencies;
             self.matches.push_new_matches(
                 &self.history_items,
                 self.currently_opened_path.as_ref(),
                 Some(&query),
-                matches.into_iter(),
+                frecencies,
+                matches.into_iter().map(|mut path_match| {
+                    path_match.0.score =
+                        frecencies.bias(&frecency_key(&path_match.0.path), path_match.0.score);
+                    path_match
+                }),
                 extend_old_matches,
             );
 
@@ -1033,6 +1069,7 @@ impl PickerDelegate for FileFinderDelegate {
                     }),
                     self.currently_opened_path.as_ref(),
                     None,
+                    &self.frecencies,
                     None.into_iter(),
                     false,
                 );
@@ -1066,6 +1103,12 @@ impl PickerDelegate for FileFinderDelegate {
 
     fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<FileFinderDelegate>>) {
         if let Some(m) = self.matches.get(self.selected_index()) {
+            if let Some(scope) = self.frecency_scope {
+                let key = frecency_key(m.path());
+                db::write_and_log(cx, move || {
+                    FRECENCY.record_use(FrecencyKind::File, scope, key)
+                });
+            }
             if let Some(workspace) = self.workspace.upgrade() {
                 let open_task = workspace.update(cx, move |workspace, cx| {
                     let split_or_open =
@@ -1182,6 +1225,44 @@ impl PickerDelegate for FileFinderDelegate {
         }
     }
 
+    fn supports_preview(&self) -> bool {
+        true
+    }
+
+    fn preview(
+        &self,
+        ix: usize,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Task<anyhow::Result<PickerPreview>>> {
+        let project_path = match self.matches.get(ix)? {
+            Match::History { path, .. } => {
+                self.project
+                    .read(cx)
+                    .worktree_for_id(path.project.worktree_id, cx)?;
+                path.project.clone()
+            }
+            Match::Search(m) => ProjectPath {
+                worktree_id: WorktreeId::from_usize(m.0.worktree_id),
+                path: m.0.path.clone(),
+            },
+        };
+        let row = self
+            .latest_search_query
+            .as_ref()
+            .and_then(|query| query.path_position.row)
+            .map(|row| row.saturating_sub(1));
+        let open_buffer = self
+            .project
+            .update(cx, |project, cx| project.open_buffer(project_path, cx));
+        Some(cx.spawn(|_, _| async move {
+            let buffer = open_buffer.await?;
+            Ok(PickerPreview {
+                buffer,
+                range: row.map(|row| Point::new(row, 0)..Point::new(row, 0)),
+            })
+        }))
+    }
+
     fn dismissed(&mut self, cx: &mut ViewContext<Picker<FileFinderDelegate>>) {
         self.file_finder
             .update(cx, |_, cx| cx.emit(DismissEvent))
@@ -1952,6 +1952,55 @@ async fn test_repeat_toggle_action(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_preview_selected_match(cx: &mut gpui::TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(
+            "/test",
+            json!({
+                "first.rs": "fn first() {}",
+                "second.rs": "fn second() {}",
+            }),
+        )
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), ["/test".as_ref()], cx).await;
+    let (picker, _, cx) = build_find_picker(project, cx);
+
+    cx.simulate_input("first");
+    cx.run_until_parked();
+    picker.update(cx, |picker, cx| {
+        assert_eq!(picker.preview_width(cx), None);
+        assert_eq!(picker.preview_text(cx), None);
+    });
+
+    cx.dispatch_action(picker::TogglePreview);
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+    picker.update(cx, |picker, cx| {
+        assert!(picker.preview_width(cx).is_some());
+        assert_eq!(picker.preview_text(cx).as_deref(), Some("fn first() {}"));
+    });
+
+    // The preview follows the query, once the selection settles.
+    picker.update(cx, |picker, cx| picker.set_query("second", cx));
+    cx.run_until_parked();
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+    picker.update(cx, |picker, cx| {
+        assert_eq!(picker.preview_text(cx).as_deref(), Some("fn second() {}"));
+    });
+
+    cx.dispatch_action(picker::TogglePreview);
+    picker.update(cx, |picker, cx| {
+        assert_eq!(picker.preview_width(cx), None);
+        assert_eq!(picker.preview_text(cx), None);
+    });
+}
+
 async fn open_close_queried_buffer(
     input: &str,
     expected_matches: usize,
@@ -0,0 +1,159 @@
+use std::ops::Range;
+
+/// A sequence of bytes to search for in a binary file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BytePattern {
+    /// The bytes to match, where `None` matches any byte.
+    bytes: Vec<Option<u8>>,
+    ignore_ascii_case: bool,
+}
+
+impl BytePattern {
+    /// Parses a search query into a pattern.
+    ///
+    /// Queries made of hex byte pairs and `??` wildcards, optionally separated by whitespace
+    /// (e.g. `7f 45 4c 46` or `cafe??be`), match those bytes. Queries wrapped in double quotes,
+    /// and all other queries, match their UTF-8 encoding.
+    pub fn parse(query: &str, case_sensitive: bool) -> Option<Self> {
+        if query.is_empty() {
+            return None;
+        }
+        if let Some(bytes) = parse_hex(query) {
+            return Some(Self {
+                bytes,
+                ignore_ascii_case: false,
+            });
+        }
+
+        let text = query
+            .strip_prefix('"')
+            .and_then(|query| query.strip_suffix('"'))
+            .unwrap_or(query);
+        if text.is_empty() {
+            return None;
+        }
+        Some(Self {
+            bytes: text.bytes().map(Some).collect(),
+            ignore_ascii_case: !case_sensitive,
+        })
+    }
+
+    /// Returns the non-overlapping ranges of the haystack that match the pattern.
+    pub fn find_all(&self, haystack: &[u8]) -> Vec<Range<usize>> {
+        let mut matches = Vec::new();
+        let mut start = 0;
+        while start + self.bytes.len() <= haystack.len() {
+            let end = start + self.bytes.len();
+            if self.matches(&haystack[start..end]) {
+                matches.push(start..end);
+                start = end;
+            } else {
+                start += 1;
+            }
+        }
+        matches
+    }
+
+    fn matches(&self, candidate: &[u8]) -> bool {
+        self.bytes
+            .iter()
+            .zip(candidate)
+            .all(|(pattern_byte, byte)| match pattern_byte {
+                None => true,
+                Some(pattern_byte) if self.ignore_ascii_case => {
+                    pattern_byte.eq_ignore_ascii_case(byte)
+                }
+                Some(pattern_byte) => pattern_byte == byte,
+            })
+    }
+}
+
+fn parse_hex(query: &str) -> Option<Vec<Option<u8>>> {
+    let digits = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+    if digits.is_empty() || digits.len() % 2 != 0 {
+        return None;
+    }
+    digits
+        .chunks(2)
+        .map(|pair| match pair {
+            ['?', '?'] => Some(None),
+            [high, low] => {
+                let high = high.to_digit(16)?;
+                let low = low.to_digit(16)?;
+                Some(Some(((high << 4) | low) as u8))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            BytePattern::parse("7f 45 4C46", true),
+            Some(BytePattern {
+                bytes: vec![Some(0x7f), Some(0x45), Some(0x4c), Some(0x46)],
+                ignore_ascii_case: false,
+            })
+        );
+        assert_eq!(
+            BytePattern::parse("ca ?? fe", false),
+            Some(BytePattern {
+                bytes: vec![Some(0xca), None, Some(0xfe)],
+                ignore_ascii_case: false,
+            })
+        );
+        assert_eq!(
+            BytePattern::parse("\"cafe\"", true),
+            Some(BytePattern {
+                bytes: b"cafe".iter().copied().map(Some).collect(),
+                ignore_ascii_case: false,
+            })
+        );
+        assert_eq!(
+            BytePattern::parse("ELF!", false),
+            Some(BytePattern {
+                bytes: b"ELF!".iter().copied().map(Some).collect(),
+                ignore_ascii_case: true,
+            })
+        );
+        assert_eq!(BytePattern::parse("", true), None);
+        assert_eq!(BytePattern::parse("\"\"", true), None);
+    }
+
+    #[test]
+    fn test_find_all() {
+        let haystack = b"\x7fELF\x00\x7fELF\x7felf";
+        assert_eq!(
+            BytePattern::parse("7f??4c", true)
+                .unwrap()
+                .find_all(haystack),
+            vec![0..3, 5..8]
+        );
+        assert_eq!(
+            BytePattern::parse("\x7fElf", true)
+                .unwrap()
+                .find_all(haystack),
+            Vec::<Range<usize>>::new()
+        );
+        assert_eq!(
+            BytePattern::parse("\x7fElf", false)
+                .unwrap()
+                .find_all(haystack),
+            vec![0..4, 5..9, 9..13]
+        );
+        assert_eq!(
+            BytePattern::parse("aaaa", true)
+                .unwrap()
+                .find_all(b"\xaa\xaa\xaa"),
+            vec![0..2]
+        );
+    }
+}
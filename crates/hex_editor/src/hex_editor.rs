@@ -0,0 +1,851 @@
+mod byte_pattern;
+
+use std::{fmt::Write as _, ops::Range, path::PathBuf, sync::Arc};
+
+use anyhow::anyhow;
+use byte_pattern::BytePattern;
+use editor::{
+    actions::{
+        Copy, MoveDown, MoveLeft, MovePageDown, MovePageUp, MoveRight, MoveToBeginning,
+        MoveToBeginningOfLine, MoveToEnd, MoveToEndOfLine, MoveUp, SelectAll, SelectDown,
+        SelectLeft, SelectPageDown, SelectPageUp, SelectRight, SelectToBeginning, SelectToEnd,
+        SelectUp,
+    },
+    items::entry_git_aware_label_color,
+};
+use file_icons::FileIcons;
+use gpui::{
+    actions, uniform_list, AnyElement, AppContext, ClipboardItem, EventEmitter, FocusHandle,
+    FocusableView, HighlightStyle, InteractiveText, KeyDownEvent, Model, MouseButton,
+    ScrollStrategy, StyledText, Subscription, Task, TextStyle, UniformListScrollHandle, View,
+    ViewContext, WindowContext,
+};
+use language::DiskState;
+use project::{
+    binary_store::BinaryItemEvent, search::SearchQuery, BinaryItem, Project, ProjectPath,
+};
+use settings::Settings;
+use theme::{Theme, ThemeSettings};
+use ui::prelude::*;
+use util::paths::PathExt;
+use workspace::{
+    item::{BreadcrumbText, Item, ItemEvent, ProjectItem, TabContentParams},
+    searchable::{SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
+    ItemSettings, ToolbarItemLocation, WorkspaceId,
+};
+
+actions!(hex_editor, [ToggleColumn]);
+
+const BYTES_PER_ROW: usize = 16;
+/// The number of rows moved by page up and page down before the editor has been laid out.
+const DEFAULT_ROWS_PER_PAGE: usize = 32;
+
+pub fn init(cx: &mut AppContext) {
+    workspace::register_project_item_fallback::<HexEditor>(cx);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Column {
+    Hex,
+    Ascii,
+}
+
+/// Shows the bytes of a file that can't be opened as text as rows of hex and ASCII columns,
+/// and lets them be overwritten in place.
+pub struct HexEditor {
+    binary: Model<BinaryItem>,
+    project: Model<Project>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    /// The offset of the byte under the cursor.
+    cursor: usize,
+    /// The offset of the byte at the other end of the selection, if bytes are selected.
+    selection_tail: Option<usize>,
+    /// Whether the next hex digit typed replaces the low nibble of the byte under the cursor.
+    editing_low_nibble: bool,
+    column: Column,
+    search_matches: Vec<Range<usize>>,
+    _subscription: Subscription,
+}
+
+pub enum HexEditorEvent {
+    Edited,
+    TitleChanged,
+    SelectionChanged,
+}
+
+impl HexEditor {
+    pub fn new(
+        binary: Model<BinaryItem>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let subscription = cx.subscribe(&binary, Self::on_binary_event);
+        Self {
+            binary,
+            project,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            cursor: 0,
+            selection_tail: None,
+            editing_low_nibble: false,
+            column: Column::Hex,
+            search_matches: Vec::new(),
+            _subscription: subscription,
+        }
+    }
+
+    fn on_binary_event(
+        &mut self,
+        _: Model<BinaryItem>,
+        event: &BinaryItemEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match event {
+            BinaryItemEvent::Edited => {
+                cx.emit(HexEditorEvent::Edited);
+                cx.emit(SearchEvent::MatchesInvalidated);
+            }
+            BinaryItemEvent::Reloaded => {
+                let max_offset = self.max_offset(cx);
+                self.cursor = self.cursor.min(max_offset);
+                self.selection_tail = self.selection_tail.map(|tail| tail.min(max_offset));
+                self.editing_low_nibble = false;
+                cx.emit(HexEditorEvent::TitleChanged);
+                cx.emit(SearchEvent::MatchesInvalidated);
+            }
+            BinaryItemEvent::Saved | BinaryItemEvent::FileHandleChanged => {
+                cx.emit(HexEditorEvent::TitleChanged);
+            }
+            BinaryItemEvent::ReloadNeeded => {}
+        }
+        cx.notify();
+    }
+
+    fn max_offset(&self, cx: &AppContext) -> usize {
+        self.binary.read(cx).len().saturating_sub(1)
+    }
+
+    fn row_count(&self, cx: &AppContext) -> usize {
+        self.binary.read(cx).len().div_ceil(BYTES_PER_ROW).max(1)
+    }
+
+    fn rows_per_page(&self) -> usize {
+        let state = self.scroll_handle.0.borrow();
+        let Some(item_size) = state.last_item_size else {
+            return DEFAULT_ROWS_PER_PAGE;
+        };
+        let viewport_height = state.base_handle.bounds().size.height;
+        ((viewport_height.0 / item_size.item.height.0) as usize).max(1)
+    }
+
+    /// The range of bytes that are selected, or the byte under the cursor.
+    fn selection(&self, cx: &AppContext) -> Range<usize> {
+        let tail = self.selection_tail.unwrap_or(self.cursor);
+        let range = self.cursor.min(tail)..self.cursor.max(tail) + 1;
+        self.binary.read(cx).clip_range(range)
+    }
+
+    fn is_read_only(&self, cx: &AppContext) -> bool {
+        self.project.read(cx).is_read_only(cx)
+    }
+
+    fn move_cursor_to(&mut self, offset: usize, select: bool, cx: &mut ViewContext<Self>) {
+        if select {
+            self.selection_tail.get_or_insert(self.cursor);
+        } else {
+            self.selection_tail = None;
+        }
+        self.cursor = offset.min(self.max_offset(cx));
+        self.editing_low_nibble = false;
+        self.scroll_handle
+            .scroll_to_item(self.cursor / BYTES_PER_ROW, ScrollStrategy::Top);
+        cx.emit(HexEditorEvent::SelectionChanged);
+        cx.emit(SearchEvent::ActiveMatchChanged);
+        cx.notify();
+    }
+
+    fn move_cursor_by(&mut self, delta: isize, select: bool, cx: &mut ViewContext<Self>) {
+        let offset = self.cursor.saturating_add_signed(delta);
+        self.move_cursor_to(offset, select, cx);
+    }
+
+    fn page_delta(&self) -> isize {
+        (self.rows_per_page() * BYTES_PER_ROW) as isize
+    }
+
+    fn move_left(&mut self, _: &MoveLeft, cx: &mut ViewContext<Self>) {
+        self.move_cursor_by(-1, false, cx);
+    }
+
+    fn move_right(&mut self, _: &MoveRight, cx: &mut ViewContext<Self>) {
+        self.move_cursor_by(1, false, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, cx: &mut ViewContext<Self>) {
+        self.move_cursor_by(-(BYTES_PER_ROW as isize), false, cx);
+    }
+
+    fn move_down(&mut self, _: &MoveDown, cx: &mut ViewContext<Self>) {
+        self.move_cursor_by(BYTES_PER_ROW as isize, false, cx);
+    }
+
+    fn move_page_up(&mut self, _: &MovePageUp, cx: &mut ViewContext<Self>) {
+        self.move_cursor_by(-self.page_delta(), false, cx);
+    }
+
+    fn move_page_down(&mut self, _: &MovePageDown, cx: &mut ViewContext<Self>) {
+        self.move_cursor_by(self.page_delta(), false, cx);
+    }
+
+    fn move_to_beginning_of_line(&mut self, _: &MoveToBeginningOfLine, cx: &mut ViewContext<Self>) {
+        let offset = self.cursor - self.cursor % BYTES_PER_ROW;
+        self.move_cursor_to(offset, false, cx);
+    }
+
+    fn move_to_end_of_line(&mut self, _: &MoveToEndOfLine, cx: &mut ViewContext<Self>) {
+        let offset = self.cursor - self.cursor % BYTES_PER_ROW + BYTES_PER_ROW - 1;
+        self.move_cursor_to(offset, false, cx);
+    }
+
+    fn move_to_beginning(&mut self, _: &MoveToBeginning, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(0, false, cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(usize::MAX, false, cx);
+    }
+
+    fn select_left(&mut self, _: &SelectLeft, cx: &mut ViewContext<Self>) {
+        self.move_cursor_by(-1, true, cx);
+    }
+
+    fn select_right(&mut self, _: &SelectRight, cx: &mut ViewContext<Self>) {
+        self.move_cursor_by(1, true, cx);
+    }
+
+    fn select_up(&mut self, _: &SelectUp, cx: &mut ViewContext<Self>) {
+        self.move_cursor_by(-(BYTES_PER_ROW as isize), true, cx);
+    }
+
+    fn select_down(&mut self, _: &SelectDown, cx: &mut ViewContext<Self>) {
+        self.move_cursor_by(BYTES_PER_ROW as isize, true, cx);
+    }
+
+    fn select_page_up(&mut self, _: &SelectPageUp, cx: &mut ViewContext<Self>) {
+        self.move_cursor_by(-self.page_delta(), true, cx);
+    }
+
+    fn select_page_down(&mut self, _: &SelectPageDown, cx: &mut ViewContext<Self>) {
+        self.move_cursor_by(self.page_delta(), true, cx);
+    }
+
+    fn select_to_beginning(&mut self, _: &SelectToBeginning, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(0, true, cx);
+    }
+
+    fn select_to_end(&mut self, _: &SelectToEnd, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(usize::MAX, true, cx);
+    }
+
+    fn select_all(&mut self, _: &SelectAll, cx: &mut ViewContext<Self>) {
+        self.selection_tail = None;
+        self.move_cursor_to(0, false, cx);
+        self.move_cursor_to(usize::MAX, true, cx);
+    }
+
+    fn select_range(&mut self, range: Range<usize>, cx: &mut ViewContext<Self>) {
+        if range.is_empty() {
+            return;
+        }
+        self.selection_tail = None;
+        self.move_cursor_to(range.start, false, cx);
+        self.move_cursor_to(range.end - 1, true, cx);
+        self.scroll_handle
+            .scroll_to_item(range.start / BYTES_PER_ROW, ScrollStrategy::Center);
+    }
+
+    fn toggle_column(&mut self, _: &ToggleColumn, cx: &mut ViewContext<Self>) {
+        self.column = match self.column {
+            Column::Hex => Column::Ascii,
+            Column::Ascii => Column::Hex,
+        };
+        self.editing_low_nibble = false;
+        cx.notify();
+    }
+
+    /// Copies the selected bytes as hex pairs from the hex column, or as text from the ASCII
+    /// column.
+    fn copy(&mut self, _: &Copy, cx: &mut ViewContext<Self>) {
+        let selection = self.selection(cx);
+        let bytes = &self.binary.read(cx).bytes()[selection];
+        let text = match self.column {
+            Column::Hex => hex_string(bytes),
+            Column::Ascii => bytes.iter().copied().map(ascii_char).collect(),
+        };
+        cx.write_to_clipboard(ClipboardItem::new_string(text));
+    }
+
+    fn key_down(&mut self, event: &KeyDownEvent, cx: &mut ViewContext<Self>) {
+        let modifiers = event.keystroke.modifiers;
+        if modifiers.control || modifiers.platform || modifiers.function {
+            return;
+        }
+        let Some(key_char) = event.keystroke.key_char.as_deref() else {
+            return;
+        };
+        let mut chars = key_char.chars();
+        if let (Some(input), None) = (chars.next(), chars.next()) {
+            if self.overwrite_at_cursor(input, cx) {
+                cx.stop_propagation();
+            }
+        }
+    }
+
+    /// Overwrites the byte under the cursor with a typed hex digit or ASCII character,
+    /// depending on the active column. Returns whether the input was handled.
+    fn overwrite_at_cursor(&mut self, input: char, cx: &mut ViewContext<Self>) -> bool {
+        if self.is_read_only(cx) || self.binary.read(cx).is_empty() {
+            return false;
+        }
+
+        let offset = self.cursor;
+        let byte = self.binary.read(cx).bytes()[offset];
+        let (new_byte, advance) = match self.column {
+            Column::Hex => {
+                let Some(digit) = input.to_digit(16) else {
+                    return false;
+                };
+                let digit = digit as u8;
+                if self.editing_low_nibble {
+                    ((byte & 0xf0) | digit, true)
+                } else {
+                    ((digit << 4) | (byte & 0x0f), false)
+                }
+            }
+            Column::Ascii => {
+                if !input.is_ascii_graphic() && input != ' ' {
+                    return false;
+                }
+                (input as u8, true)
+            }
+        };
+
+        self.selection_tail = None;
+        self.binary
+            .update(cx, |binary, cx| binary.overwrite(offset, &[new_byte], cx));
+        if advance {
+            self.move_cursor_to(offset + 1, false, cx);
+        } else {
+            self.editing_low_nibble = true;
+            cx.notify();
+        }
+        true
+    }
+
+    fn click_byte(&mut self, offset: usize, column: Column, cx: &mut ViewContext<Self>) {
+        self.column = column;
+        cx.focus(&self.focus_handle);
+        self.move_cursor_to(offset, cx.modifiers().shift, cx);
+    }
+
+    fn text_style(&self, cx: &AppContext) -> TextStyle {
+        let settings = ThemeSettings::get_global(cx);
+        TextStyle {
+            color: cx.theme().colors().editor_foreground,
+            font_family: settings.buffer_font.family.clone(),
+            font_features: settings.buffer_font.features.clone(),
+            font_fallbacks: settings.buffer_font.fallbacks.clone(),
+            font_size: settings.buffer_font_size(cx).into(),
+            font_weight: settings.buffer_font.weight,
+            line_height: relative(settings.buffer_line_height.value()),
+            ..Default::default()
+        }
+    }
+
+    fn byte_highlight(
+        &self,
+        offset: usize,
+        column: Column,
+        selection: &Range<usize>,
+        cx: &WindowContext,
+    ) -> Option<HighlightStyle> {
+        let player = cx.theme().players().local();
+        if offset == self.cursor && column == self.column && self.focus_handle.is_focused(cx) {
+            return Some(HighlightStyle {
+                color: Some(cx.theme().colors().editor_background),
+                background_color: Some(player.cursor),
+                ..HighlightStyle::default()
+            });
+        }
+
+        let background_color = if selection.contains(&offset) {
+            player.selection
+        } else if self
+            .search_matches
+            .iter()
+            .any(|search_match| search_match.contains(&offset))
+        {
+            cx.theme().colors().search_match_background
+        } else {
+            return None;
+        };
+        Some(HighlightStyle {
+            background_color: Some(background_color),
+            ..HighlightStyle::default()
+        })
+    }
+
+    fn render_column(
+        &self,
+        row: usize,
+        column: Column,
+        text_style: &TextStyle,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let row_start = row * BYTES_PER_ROW;
+        let selection = self.selection(cx);
+        let bytes = self.binary.read(cx).bytes();
+        let row_bytes =
+            &bytes[bytes.len().min(row_start)..bytes.len().min(row_start + BYTES_PER_ROW)];
+
+        let mut text = String::new();
+        let mut byte_ranges = Vec::with_capacity(row_bytes.len());
+        for (ix, byte) in row_bytes.iter().enumerate() {
+            let start = text.len();
+            match column {
+                Column::Hex => {
+                    if ix > 0 {
+                        text.push(' ');
+                    }
+                    if ix == BYTES_PER_ROW / 2 {
+                        text.push(' ');
+                    }
+                    let start = text.len();
+                    write!(text, "{byte:02x}").ok();
+                    byte_ranges.push(start..text.len());
+                    continue;
+                }
+                Column::Ascii => text.push(ascii_char(*byte)),
+            }
+            byte_ranges.push(start..text.len());
+        }
+
+        // Pad the last row so that the ASCII column stays aligned.
+        let column_width = match column {
+            Column::Hex => BYTES_PER_ROW * 3,
+            Column::Ascii => BYTES_PER_ROW,
+        };
+        while text.chars().count() < column_width {
+            text.push(' ');
+        }
+
+        let mut highlights = Vec::new();
+        for (ix, range) in byte_ranges.iter().enumerate() {
+            let offset = row_start + ix;
+            let Some(highlight) = self.byte_highlight(offset, column, &selection, cx) else {
+                continue;
+            };
+            let is_cursor = offset == self.cursor && column == self.column;
+            if is_cursor && self.editing_low_nibble && column == Column::Hex {
+                highlights.push((
+                    range.start..range.start + 1,
+                    HighlightStyle {
+                        background_color: Some(cx.theme().players().local().selection),
+                        ..HighlightStyle::default()
+                    },
+                ));
+                highlights.push((range.start + 1..range.end, highlight));
+            } else {
+                highlights.push((range.clone(), highlight));
+            }
+        }
+
+        let id = match column {
+            Column::Hex => ("hex-row", row),
+            Column::Ascii => ("ascii-row", row),
+        };
+        let view = cx.view().downgrade();
+        InteractiveText::new(
+            id,
+            StyledText::new(text).with_highlights(text_style, highlights),
+        )
+        .on_click(byte_ranges, move |ix, cx| {
+            view.update(cx, |this, cx| this.click_byte(row_start + ix, column, cx))
+                .ok();
+        })
+    }
+
+    fn render_row(
+        &self,
+        row: usize,
+        offset_digits: usize,
+        text_style: &TextStyle,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let offset_style = TextStyle {
+            color: cx.theme().colors().editor_line_number,
+            ..text_style.clone()
+        };
+        let offset = format!("{:0width$x}", row * BYTES_PER_ROW, width = offset_digits);
+        h_flex()
+            .px_2()
+            .gap_4()
+            .child(StyledText::new(offset).with_highlights(&offset_style, []))
+            .child(self.render_column(row, Column::Hex, text_style, cx))
+            .child(self.render_column(row, Column::Ascii, text_style, cx))
+    }
+}
+
+fn ascii_char(byte: u8) -> char {
+    if byte.is_ascii_graphic() || byte == b' ' {
+        byte as char
+    } else {
+        '.'
+    }
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len() * 3);
+    for (ix, byte) in bytes.iter().enumerate() {
+        if ix > 0 {
+            text.push(' ');
+        }
+        write!(text, "{byte:02x}").ok();
+    }
+    text
+}
+
+impl EventEmitter<HexEditorEvent> for HexEditor {}
+impl EventEmitter<SearchEvent> for HexEditor {}
+
+impl FocusableView for HexEditor {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for HexEditor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let row_count = self.row_count(cx);
+        let max_row_offset = (row_count - 1) * BYTES_PER_ROW;
+        let offset_digits = (format!("{max_row_offset:x}").len()).max(8);
+
+        div()
+            .key_context("HexEditor")
+            .track_focus(&self.focus_handle(cx))
+            .on_action(cx.listener(Self::move_left))
+            .on_action(cx.listener(Self::move_right))
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::move_page_up))
+            .on_action(cx.listener(Self::move_page_down))
+            .on_action(cx.listener(Self::move_to_beginning_of_line))
+            .on_action(cx.listener(Self::move_to_end_of_line))
+            .on_action(cx.listener(Self::move_to_beginning))
+            .on_action(cx.listener(Self::move_to_end))
+            .on_action(cx.listener(Self::select_left))
+            .on_action(cx.listener(Self::select_right))
+            .on_action(cx.listener(Self::select_up))
+            .on_action(cx.listener(Self::select_down))
+            .on_action(cx.listener(Self::select_page_up))
+            .on_action(cx.listener(Self::select_page_down))
+            .on_action(cx.listener(Self::select_to_beginning))
+            .on_action(cx.listener(Self::select_to_end))
+            .on_action(cx.listener(Self::select_all))
+            .on_action(cx.listener(Self::toggle_column))
+            .on_action(cx.listener(Self::copy))
+            .on_key_down(cx.listener(Self::key_down))
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|this, _, cx| cx.focus(&this.focus_handle)),
+            )
+            .size_full()
+            .py_1()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                uniform_list(
+                    cx.view().clone(),
+                    "hex-editor-rows",
+                    row_count,
+                    move |this, range, cx| {
+                        let text_style = this.text_style(cx);
+                        range
+                            .map(|row| this.render_row(row, offset_digits, &text_style, cx))
+                            .collect()
+                    },
+                )
+                .size_full()
+                .track_scroll(self.scroll_handle.clone()),
+            )
+    }
+}
+
+impl Item for HexEditor {
+    type Event = HexEditorEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            HexEditorEvent::Edited => {
+                f(ItemEvent::Edit);
+                f(ItemEvent::UpdateTab);
+            }
+            HexEditorEvent::TitleChanged => {
+                f(ItemEvent::UpdateTab);
+                f(ItemEvent::UpdateBreadcrumbs);
+            }
+            HexEditorEvent::SelectionChanged => f(ItemEvent::UpdateBreadcrumbs),
+        }
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.binary.entity_id(), self.binary.read(cx))
+    }
+
+    fn is_singleton(&self, _cx: &AppContext) -> bool {
+        true
+    }
+
+    fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
+        let abs_path = self.binary.read(cx).file.as_local()?.abs_path(cx);
+        let file_path = abs_path.compact().to_string_lossy().to_string();
+        Some(file_path.into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, cx: &WindowContext) -> AnyElement {
+        let project_path = self.binary.read(cx).project_path(cx);
+        let label_color = if ItemSettings::get_global(cx).git_status {
+            self.project
+                .read(cx)
+                .entry_for_path(&project_path, cx)
+                .map(|entry| {
+                    entry_git_aware_label_color(entry.git_status, entry.is_ignored, params.selected)
+                })
+                .unwrap_or_else(|| params.text_color())
+        } else {
+            params.text_color()
+        };
+
+        let title = self
+            .binary
+            .read(cx)
+            .file
+            .file_name(cx)
+            .to_string_lossy()
+            .to_string();
+        Label::new(title)
+            .single_line()
+            .color(label_color)
+            .italic(params.preview)
+            .into_any_element()
+    }
+
+    fn tab_icon(&self, cx: &WindowContext) -> Option<Icon> {
+        let path = self.binary.read(cx).path();
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.binary.read(cx).is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.binary.read(cx).has_conflict()
+    }
+
+    fn has_deleted_file(&self, cx: &AppContext) -> bool {
+        self.binary.read(cx).file.disk_state() == DiskState::Deleted
+    }
+
+    fn can_save(&self, cx: &AppContext) -> bool {
+        self.binary.read(cx).file.as_local().is_some()
+    }
+
+    fn save(
+        &mut self,
+        _format: bool,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        let binary = self.binary.clone();
+        project.update(cx, |project, cx| project.save_binary(binary, cx))
+    }
+
+    fn save_as(
+        &mut self,
+        _project: Model<Project>,
+        _path: ProjectPath,
+        _cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        Task::ready(Err(anyhow!(
+            "saving binary files under a different path is not supported"
+        )))
+    }
+
+    fn reload(
+        &mut self,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        let binary = self.binary.clone();
+        project.update(cx, |project, cx| project.reload_binary(binary, cx))
+    }
+
+    fn as_searchable(&self, handle: &View<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(handle.clone()))
+    }
+
+    fn breadcrumb_location(&self, _: &AppContext) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, _theme: &Theme, cx: &AppContext) -> Option<Vec<BreadcrumbText>> {
+        let binary = self.binary.read(cx);
+        let mut text = breadcrumbs_text_for_binary(self.project.read(cx), binary, cx);
+        if !binary.is_empty() {
+            let selection = self.selection(cx);
+            write!(text, " — 0x{:x}", self.cursor).ok();
+            if selection.len() > 1 {
+                write!(text, " ({} bytes selected)", selection.len()).ok();
+            }
+        }
+        Some(vec![BreadcrumbText {
+            text,
+            highlights: None,
+            font: None,
+        }])
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>>
+    where
+        Self: Sized,
+    {
+        Some(cx.new_view(|cx| Self::new(self.binary.clone(), self.project.clone(), cx)))
+    }
+}
+
+fn breadcrumbs_text_for_binary(project: &Project, binary: &BinaryItem, cx: &AppContext) -> String {
+    let path = binary.file.file_name(cx);
+    if project.visible_worktrees(cx).count() <= 1 {
+        return path.to_string_lossy().to_string();
+    }
+
+    project
+        .worktree_for_id(binary.project_path(cx).worktree_id, cx)
+        .map(|worktree| {
+            PathBuf::from(worktree.read(cx).root_name())
+                .join(path)
+                .to_string_lossy()
+                .to_string()
+        })
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+impl SearchableItem for HexEditor {
+    type Match = Range<usize>;
+
+    fn supported_options() -> SearchOptions {
+        SearchOptions {
+            case: true,
+            word: false,
+            regex: false,
+            replacement: false,
+            selection: false,
+        }
+    }
+
+    fn clear_matches(&mut self, cx: &mut ViewContext<Self>) {
+        self.search_matches.clear();
+        cx.notify();
+    }
+
+    fn update_matches(&mut self, matches: &[Self::Match], cx: &mut ViewContext<Self>) {
+        self.search_matches = matches.to_vec();
+        cx.notify();
+    }
+
+    fn query_suggestion(&mut self, cx: &mut ViewContext<Self>) -> String {
+        if self.selection_tail.is_none() {
+            return String::new();
+        }
+        let selection = self.selection(cx);
+        hex_string(&self.binary.read(cx).bytes()[selection])
+    }
+
+    fn activate_match(
+        &mut self,
+        index: usize,
+        matches: &[Self::Match],
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(search_match) = matches.get(index) {
+            self.select_range(search_match.clone(), cx);
+        }
+    }
+
+    fn select_matches(&mut self, matches: &[Self::Match], cx: &mut ViewContext<Self>) {
+        // There's a single selection, so select the span covering all matches.
+        if let Some((first, last)) = matches.first().zip(matches.last()) {
+            self.select_range(first.start..last.end, cx);
+        }
+    }
+
+    fn replace(&mut self, _: &Self::Match, _: &SearchQuery, _: &mut ViewContext<Self>) {
+        // Replacement is not supported in the hex editor, so this is a no-op.
+    }
+
+    fn find_matches(
+        &mut self,
+        query: Arc<SearchQuery>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Vec<Self::Match>> {
+        let Some(pattern) = BytePattern::parse(query.as_str(), query.case_sensitive()) else {
+            return Task::ready(Vec::new());
+        };
+        let bytes = self.binary.read(cx).bytes().to_vec();
+        cx.background_executor()
+            .spawn(async move { pattern.find_all(&bytes) })
+    }
+
+    fn active_match_index(
+        &mut self,
+        matches: &[Self::Match],
+        _: &mut ViewContext<Self>,
+    ) -> Option<usize> {
+        if matches.is_empty() {
+            return None;
+        }
+        // The match under the cursor, or else the first one after it, or else the last one.
+        matches
+            .iter()
+            .position(|search_match| search_match.end > self.cursor)
+            .or(Some(matches.len() - 1))
+    }
+}
+
+impl ProjectItem for HexEditor {
+    type Item = BinaryItem;
+
+    fn for_project_item(
+        project: Model<Project>,
+        item: Model<Self::Item>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, project, cx)
+    }
+}
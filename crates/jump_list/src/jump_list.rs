@@ -0,0 +1,486 @@
+mod jump_list_picker;
+mod named_selections;
+mod named_selections_picker;
+mod persistence;
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use collections::HashMap;
+use editor::{scroll::Autoscroll, Anchor, Bias, Editor, EditorEvent, JumpKind};
+use gpui::{
+    actions, impl_actions, AppContext, EntityId, Model, ModelContext, Subscription, Task, View,
+    ViewContext, WeakModel, WeakView,
+};
+use language::{Buffer, Point, ToPoint as _};
+use project::{Project, ProjectItem as _, ProjectPath};
+use serde::Deserialize;
+use util::ResultExt;
+use workspace::{Workspace, WorkspaceId};
+
+pub use jump_list_picker::JumpListPicker;
+pub use named_selections::{NamedSelection, NamedSelections};
+pub use named_selections_picker::NamedSelectionsPicker;
+use persistence::{SerializedJump, DB};
+
+/// Moves back to the previous position in the jump list.
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct Back {
+    /// Only stop at positions recorded for this kind of jump.
+    #[serde(default)]
+    pub kind: Option<JumpKind>,
+}
+
+/// Moves forward to the next position in the jump list.
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct Forward {
+    /// Only stop at positions recorded for this kind of jump.
+    #[serde(default)]
+    pub kind: Option<JumpKind>,
+}
+
+/// Lists the positions in the jump list.
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct Toggle {
+    /// Only list positions recorded for this kind of jump.
+    #[serde(default)]
+    pub kind: Option<JumpKind>,
+}
+
+impl_actions!(jump_list, [Back, Forward, Toggle]);
+actions!(jump_list, [ToggleNamedSelections]);
+
+/// The oldest jumps are forgotten once the list grows past this length.
+const MAX_JUMPS: usize = 100;
+
+pub(crate) const SERIALIZATION_DEBOUNCE: Duration = Duration::from_millis(500);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(register).detach();
+}
+
+fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let workspace_handle = cx.view().clone();
+    let project = workspace.project().clone();
+    let workspace_id = workspace.database_id();
+    let named_selections =
+        cx.new_model(|cx| NamedSelections::new(project.clone(), workspace_id, cx));
+    let jump_list = cx.new_model(|cx| JumpList::new(&workspace_handle, project, workspace_id, cx));
+
+    workspace.register_action({
+        let jump_list = jump_list.clone();
+        move |workspace, action: &Back, cx| {
+            let current = workspace.active_item_as::<Editor>(cx).and_then(|editor| {
+                let head = editor.read(cx).selections.newest_anchor().head();
+                Jump::new(&editor, head, JumpKind::Navigation, workspace.project(), cx)
+            });
+            let jump =
+                jump_list.update(cx, |jump_list, cx| jump_list.back(action.kind, current, cx));
+            if let Some(jump) = jump {
+                open_jump(workspace, &jump_list, jump, cx);
+            }
+        }
+    });
+    workspace.register_action({
+        let jump_list = jump_list.clone();
+        move |workspace, action: &Forward, cx| {
+            let jump = jump_list.update(cx, |jump_list, _| jump_list.forward(action.kind));
+            if let Some(jump) = jump {
+                open_jump(workspace, &jump_list, jump, cx);
+            }
+        }
+    });
+    workspace.register_action(move |workspace, _: &ToggleNamedSelections, cx| {
+        let named_selections = named_selections.clone();
+        let workspace_handle = cx.view().downgrade();
+        let project = workspace.project().clone();
+        let editor = workspace.active_item_as::<Editor>(cx);
+        workspace.toggle_modal(cx, |cx| {
+            NamedSelectionsPicker::new(workspace_handle, project, named_selections, editor, cx)
+        });
+    });
+    workspace.register_action(move |workspace, action: &Toggle, cx| {
+        let jump_list = jump_list.clone();
+        let workspace_handle = cx.view().downgrade();
+        let project = workspace.project().clone();
+        let kind = action.kind;
+        workspace.toggle_modal(cx, |cx| {
+            JumpListPicker::new(workspace_handle, project, jump_list, kind, cx)
+        });
+    });
+}
+
+/// A position the cursor jumped away from.
+#[derive(Clone)]
+pub struct Jump {
+    pub kind: JumpKind,
+    pub abs_path: Arc<Path>,
+    /// The position when the jump was recorded, used once the buffer has been closed.
+    point: Point,
+    anchor: Option<(WeakModel<Buffer>, language::Anchor)>,
+}
+
+impl Jump {
+    fn new(
+        editor: &View<Editor>,
+        position: Anchor,
+        kind: JumpKind,
+        project: &Model<Project>,
+        cx: &AppContext,
+    ) -> Option<Self> {
+        let (buffer, anchor) = editor
+            .read(cx)
+            .buffer()
+            .read(cx)
+            .text_anchor_for_position(position, cx)?;
+        let project_path = buffer.read(cx).project_path(cx)?;
+        let abs_path = project.read(cx).absolute_path(&project_path, cx)?;
+        Some(Self {
+            kind,
+            abs_path: abs_path.into(),
+            point: anchor.to_point(buffer.read(cx)),
+            anchor: Some((buffer.downgrade(), anchor)),
+        })
+    }
+
+    fn from_serialized(jump: SerializedJump) -> Self {
+        Self {
+            kind: jump.kind,
+            abs_path: jump.abs_path.into(),
+            point: jump.point,
+            anchor: None,
+        }
+    }
+
+    /// Returns the current position of the jump, following the edits made since it was recorded
+    /// while its buffer is open.
+    pub fn point(&self, cx: &AppContext) -> Point {
+        self.anchor
+            .as_ref()
+            .and_then(|(buffer, anchor)| Some(anchor.to_point(buffer.upgrade()?.read(cx))))
+            .unwrap_or(self.point)
+    }
+
+    pub fn project_path(&self, project: &Model<Project>, cx: &AppContext) -> Option<ProjectPath> {
+        project.read(cx).find_project_path(&self.abs_path, cx)
+    }
+
+    fn is_near(&self, other: &Self, cx: &AppContext) -> bool {
+        self.abs_path == other.abs_path && self.point(cx).row.abs_diff(other.point(cx).row) <= 1
+    }
+}
+
+/// The positions the cursor jumped away from in any editor of a workspace, oldest first.
+pub struct JumpList {
+    project: Model<Project>,
+    workspace_id: Option<WorkspaceId>,
+    jumps: Vec<Jump>,
+    /// The jump that was navigated to with [`Back`] or [`Forward`], or the length of the list
+    /// when the cursor moved since.
+    index: usize,
+    /// Whether the list is opening a jump, during which the cursor moves aren't recorded.
+    navigating: bool,
+    editors: HashMap<EntityId, (WeakView<Editor>, Subscription)>,
+    pending_serialization: Task<()>,
+    _subscription: Subscription,
+}
+
+impl JumpList {
+    fn new(
+        workspace: &View<Workspace>,
+        project: Model<Project>,
+        workspace_id: Option<WorkspaceId>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        if let Some(workspace_id) = workspace_id {
+            cx.spawn(|this, mut cx| async move {
+                let jumps = cx
+                    .background_executor()
+                    .spawn(async move { DB.get_jumps(workspace_id) })
+                    .await?;
+                this.update(&mut cx, |this, cx| {
+                    let restored = jumps.into_iter().map(Jump::from_serialized);
+                    this.jumps.splice(0..0, restored);
+                    let excess = this.jumps.len().saturating_sub(MAX_JUMPS);
+                    this.jumps.drain(..excess);
+                    this.index = this.jumps.len();
+                    cx.notify();
+                })
+            })
+            .detach_and_log_err(cx);
+        }
+
+        Self {
+            project,
+            workspace_id,
+            jumps: Vec::new(),
+            index: 0,
+            navigating: false,
+            editors: HashMap::default(),
+            pending_serialization: Task::ready(()),
+            _subscription: cx.subscribe(workspace, |this, _, event, cx| {
+                if let workspace::Event::ItemAdded { item } = event {
+                    if let Some(editor) = item.act_as::<Editor>(cx) {
+                        this.watch_editor(editor, cx);
+                    }
+                }
+            }),
+        }
+    }
+
+    pub fn jumps(&self) -> &[Jump] {
+        &self.jumps
+    }
+
+    fn watch_editor(&mut self, editor: View<Editor>, cx: &mut ModelContext<Self>) {
+        self.editors
+            .retain(|_, (watched_editor, _)| watched_editor.upgrade().is_some());
+        self.editors.entry(editor.entity_id()).or_insert_with(|| {
+            let subscription = cx.subscribe(&editor, Self::handle_editor_event);
+            (editor.downgrade(), subscription)
+        });
+    }
+
+    fn handle_editor_event(
+        &mut self,
+        editor: View<Editor>,
+        event: &EditorEvent,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let (position, kind) = match event {
+            EditorEvent::Jumped { from, kind } => (*from, *kind),
+            EditorEvent::Edited { .. } => (
+                editor.read(cx).selections.newest_anchor().head(),
+                JumpKind::Edit,
+            ),
+            _ => return,
+        };
+        if let Some(jump) = Jump::new(&editor, position, kind, &self.project, cx) {
+            self.push(jump, cx);
+        }
+    }
+
+    /// Records a jump, forgetting the jumps after the one that was navigated to. A jump next to the
+    /// latest one replaces it, keeping the more specific of their kinds.
+    fn push(&mut self, jump: Jump, cx: &mut ModelContext<Self>) {
+        if self.navigating {
+            return;
+        }
+
+        self.jumps.truncate(self.index + 1);
+        match self.jumps.last_mut() {
+            Some(last) if last.is_near(&jump, cx) => {
+                let kind = if jump.kind == JumpKind::Navigation {
+                    last.kind
+                } else {
+                    jump.kind
+                };
+                *last = Jump { kind, ..jump };
+            }
+            _ => self.jumps.push(jump),
+        }
+        let excess = self.jumps.len().saturating_sub(MAX_JUMPS);
+        self.jumps.drain(..excess);
+        self.index = self.jumps.len();
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    /// Returns the previous jump of the given kind. When starting to navigate the list, the
+    /// current position is recorded first so that [`Forward`] can return to it.
+    fn back(
+        &mut self,
+        kind: Option<JumpKind>,
+        current: Option<Jump>,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<Jump> {
+        if self.index >= self.jumps.len() && !self.navigating {
+            if let Some(current) = current {
+                self.push(current, cx);
+                self.index = self.jumps.len().saturating_sub(1);
+            }
+        }
+        let ix = (0..self.index)
+            .rev()
+            .find(|&ix| kind.map_or(true, |kind| self.jumps[ix].kind == kind))?;
+        self.index = ix;
+        Some(self.jumps[ix].clone())
+    }
+
+    fn forward(&mut self, kind: Option<JumpKind>) -> Option<Jump> {
+        let ix = (self.index + 1..self.jumps.len())
+            .find(|&ix| kind.map_or(true, |kind| self.jumps[ix].kind == kind))?;
+        self.index = ix;
+        Some(self.jumps[ix].clone())
+    }
+
+    /// Marks the given jump as the one that was navigated to.
+    fn select(&mut self, ix: usize) {
+        if ix < self.jumps.len() {
+            self.index = ix;
+        }
+    }
+
+    fn serialize(&mut self, cx: &mut ModelContext<Self>) {
+        let Some(workspace_id) = self.workspace_id else {
+            return;
+        };
+        let jumps = self
+            .jumps
+            .iter()
+            .map(|jump| SerializedJump {
+                kind: jump.kind,
+                abs_path: PathBuf::from(jump.abs_path.as_ref()),
+                point: jump.point(cx),
+            })
+            .collect();
+        self.pending_serialization = cx.spawn(|_, cx| async move {
+            cx.background_executor().timer(SERIALIZATION_DEBOUNCE).await;
+            DB.save_jumps(workspace_id, jumps).await.log_err();
+        });
+    }
+}
+
+fn open_jump(
+    workspace: &mut Workspace,
+    jump_list: &Model<JumpList>,
+    jump: Jump,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let open_task = match jump.project_path(workspace.project(), cx) {
+        Some(project_path) => workspace.open_path(project_path, None, true, cx),
+        None => workspace.open_abs_path(PathBuf::from(jump.abs_path.as_ref()), true, cx),
+    };
+    jump_list.update(cx, |jump_list, _| jump_list.navigating = true);
+
+    let jump_list = jump_list.clone();
+    cx.spawn(|_, mut cx| async move {
+        let result = async {
+            let item = open_task.await?;
+            if let Some(editor) = item.downcast::<Editor>() {
+                editor.update(&mut cx, |editor, cx| {
+                    let point = jump.point(cx);
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let point = snapshot.clip_point(point, Bias::Left);
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([point..point])
+                    });
+                })?;
+            }
+            anyhow::Ok(())
+        }
+        .await;
+        jump_list.update(&mut cx, |jump_list, _| jump_list.navigating = false)?;
+        result
+    })
+    .detach_and_log_err(cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_jump_list_navigation(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.txt": "a\n".repeat(50),
+                "b.txt": "b\n".repeat(50),
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+
+        let editor_a = open_editor(&workspace, (worktree_id, "a.txt"), cx).await;
+        move_cursor(&editor_a, Point::new(30, 0), cx);
+        let editor_b = open_editor(&workspace, (worktree_id, "b.txt"), cx).await;
+        move_cursor(&editor_b, Point::new(10, 0), cx);
+        editor_b.update(cx, |editor, cx| editor.insert("x", cx));
+        move_cursor(&editor_b, Point::new(40, 0), cx);
+
+        cx.dispatch_action(Back::default());
+        assert_cursor(&workspace, "b.txt", Point::new(10, 1), cx);
+        cx.dispatch_action(Back::default());
+        assert_cursor(&workspace, "b.txt", Point::new(0, 0), cx);
+        cx.dispatch_action(Back::default());
+        assert_cursor(&workspace, "a.txt", Point::new(30, 0), cx);
+
+        cx.dispatch_action(Forward {
+            kind: Some(JumpKind::Edit),
+        });
+        assert_cursor(&workspace, "b.txt", Point::new(10, 1), cx);
+        cx.dispatch_action(Forward::default());
+        assert_cursor(&workspace, "b.txt", Point::new(40, 0), cx);
+
+        cx.dispatch_action(Back {
+            kind: Some(JumpKind::Navigation),
+        });
+        assert_cursor(&workspace, "b.txt", Point::new(0, 0), cx);
+    }
+
+    async fn open_editor(
+        workspace: &View<Workspace>,
+        path: impl Into<ProjectPath>,
+        cx: &mut VisualTestContext,
+    ) -> View<Editor> {
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path(path, None, true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap()
+    }
+
+    fn move_cursor(editor: &View<Editor>, point: Point, cx: &mut VisualTestContext) {
+        editor.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |s| s.select_ranges([point..point]))
+        });
+    }
+
+    #[track_caller]
+    fn assert_cursor(
+        workspace: &View<Workspace>,
+        file_name: &str,
+        point: Point,
+        cx: &mut VisualTestContext,
+    ) {
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            let editor = editor.read(cx);
+            let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+            let path = buffer.read(cx).file().unwrap().path().clone();
+            assert_eq!(path.as_ref(), Path::new(file_name));
+            let head = editor.selections.newest::<Point>(cx).head();
+            assert_eq!(head, point);
+        });
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+            state
+        })
+    }
+}
@@ -0,0 +1,345 @@
+use std::sync::Arc;
+
+use collections::HashMap;
+use editor::JumpKind;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render, Task, View,
+    ViewContext, WeakView,
+};
+use language::{Buffer, Point};
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::{open_jump, Jump, JumpList};
+
+/// Lists the jumps of a workspace, most recent first, along with the line each one is on.
+pub struct JumpListPicker {
+    picker: View<Picker<JumpListPickerDelegate>>,
+}
+
+impl JumpListPicker {
+    pub(crate) fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        jump_list: Model<JumpList>,
+        kind: Option<JumpKind>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = JumpListPickerDelegate::new(
+            cx.view().downgrade(),
+            workspace,
+            project,
+            jump_list,
+            kind,
+            cx,
+        );
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self::load_previews(&picker, cx);
+        Self { picker }
+    }
+
+    /// Opens the buffers of the jumps whose files aren't open, to show the lines they are on.
+    fn load_previews(picker: &View<Picker<JumpListPickerDelegate>>, cx: &mut ViewContext<Self>) {
+        let delegate = &picker.read(cx).delegate;
+        let mut paths = delegate
+            .entries
+            .iter()
+            .filter(|entry| entry.preview.is_none())
+            .map(|entry| entry.jump.abs_path.clone())
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+        let project = delegate.project.clone();
+        let buffer_tasks = paths
+            .into_iter()
+            .filter_map(|abs_path| {
+                let project_path = project.read(cx).find_project_path(&abs_path, cx)?;
+                let task = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+                Some((abs_path, task))
+            })
+            .collect::<Vec<_>>();
+        if buffer_tasks.is_empty() {
+            return;
+        }
+
+        let picker = picker.downgrade();
+        cx.spawn(|_, mut cx| async move {
+            let mut buffers = HashMap::default();
+            for (abs_path, task) in buffer_tasks {
+                if let Some(buffer) = task.await.log_err() {
+                    buffers.insert(abs_path, buffer);
+                }
+            }
+            picker.update(&mut cx, |picker, cx| {
+                for entry in &mut picker.delegate.entries {
+                    if entry.preview.is_none() {
+                        if let Some(buffer) = buffers.get(&entry.jump.abs_path) {
+                            entry.preview = Some(line_preview(buffer.read(cx), entry.point));
+                        }
+                    }
+                }
+                picker.delegate.buffers.extend(buffers.into_values());
+                picker.delegate.candidates = picker.delegate.candidates();
+                picker.refresh(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+impl Render for JumpListPicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for JumpListPicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for JumpListPicker {}
+impl ModalView for JumpListPicker {}
+
+struct JumpEntry {
+    jump_ix: usize,
+    jump: Jump,
+    point: Point,
+    location: String,
+    preview: Option<String>,
+}
+
+pub struct JumpListPickerDelegate {
+    jump_list_picker: WeakView<JumpListPicker>,
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    jump_list: Model<JumpList>,
+    entries: Vec<JumpEntry>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    /// Keeps the buffers opened for previews alive while the picker is open.
+    buffers: Vec<Model<Buffer>>,
+}
+
+impl JumpListPickerDelegate {
+    fn new(
+        jump_list_picker: WeakView<JumpListPicker>,
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        jump_list: Model<JumpList>,
+        kind: Option<JumpKind>,
+        cx: &AppContext,
+    ) -> Self {
+        let entries = jump_list
+            .read(cx)
+            .jumps()
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, jump)| kind.map_or(true, |kind| jump.kind == kind))
+            .map(|(jump_ix, jump)| {
+                let point = jump.point(cx);
+                let path = jump.project_path(&project, cx).map_or_else(
+                    || jump.abs_path.to_string_lossy().into_owned(),
+                    |project_path| project_path.path.to_string_lossy().into_owned(),
+                );
+                let preview = jump
+                    .anchor
+                    .as_ref()
+                    .and_then(|(buffer, _)| buffer.upgrade())
+                    .map(|buffer| line_preview(buffer.read(cx), point));
+                JumpEntry {
+                    jump_ix,
+                    jump: jump.clone(),
+                    point,
+                    location: format!("{path}:{}", point.row + 1),
+                    preview,
+                }
+            })
+            .collect();
+
+        let mut this = Self {
+            jump_list_picker,
+            workspace,
+            project,
+            jump_list,
+            entries,
+            candidates: Vec::new(),
+            matches: Vec::new(),
+            selected_index: 0,
+            buffers: Vec::new(),
+        };
+        this.candidates = this.candidates();
+        this
+    }
+
+    fn candidates(&self) -> Vec<StringMatchCandidate> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| {
+                let preview = entry.preview.as_deref().unwrap_or_default();
+                StringMatchCandidate::new(id, &format!("{} {preview}", entry.location))
+            })
+            .collect()
+    }
+}
+
+/// Returns the trimmed text of the line the point is on.
+fn line_preview(buffer: &Buffer, point: Point) -> String {
+    let row = point.row.min(buffer.max_point().row);
+    buffer
+        .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn kind_icon(kind: JumpKind) -> IconName {
+    match kind {
+        JumpKind::Navigation => IconName::CursorIBeam,
+        JumpKind::Definition => IconName::Code,
+        JumpKind::Edit => IconName::Pencil,
+    }
+}
+
+impl PickerDelegate for JumpListPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search jumps…".into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        "No jumps recorded".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                let mut matches = match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await;
+                matches.sort_unstable_by_key(|mat| mat.candidate_id);
+                matches
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.entries.get(mat.candidate_id))
+        else {
+            return;
+        };
+        let jump_ix = entry.jump_ix;
+        let jump = entry.jump.clone();
+        let jump_list = self.jump_list.clone();
+        jump_list.update(cx, |jump_list, _| jump_list.select(jump_ix));
+        self.workspace
+            .update(cx, |workspace, cx| {
+                open_jump(workspace, &jump_list, jump, cx);
+            })
+            .log_err();
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.jump_list_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let entry = self.entries.get(mat.candidate_id)?;
+        let preview_start = entry.location.len() + 1;
+        let (location_positions, preview_positions): (Vec<_>, Vec<_>) = mat
+            .positions
+            .iter()
+            .copied()
+            .partition(|position| *position < preview_start);
+        let preview_positions = preview_positions
+            .into_iter()
+            .map(|position| position - preview_start)
+            .collect();
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(
+                    Icon::new(kind_icon(entry.jump.kind))
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(HighlightedLabel::new(
+                            entry.location.clone(),
+                            location_positions,
+                        ))
+                        .children(entry.preview.clone().map(|preview| {
+                            HighlightedLabel::new(preview, preview_positions)
+                                .color(Color::Muted)
+                                .single_line()
+                        })),
+                ),
+        )
+    }
+}
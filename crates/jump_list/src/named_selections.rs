@@ -0,0 +1,333 @@
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use collections::HashMap;
+use editor::{scroll::Autoscroll, Bias, Editor};
+use gpui::{
+    AppContext, EntityId, Model, ModelContext, Subscription, Task, View, ViewContext, WeakModel,
+};
+use language::{Buffer, BufferEvent, Point, ToPoint as _};
+use project::{Project, ProjectItem as _, ProjectPath};
+use util::ResultExt;
+use workspace::{Workspace, WorkspaceId};
+
+use crate::persistence::{SerializedNamedSelection, DB};
+use crate::SERIALIZATION_DEBOUNCE;
+
+/// A selection range saved under a name, to be selected again later.
+#[derive(Clone)]
+pub struct NamedSelection {
+    pub name: String,
+    pub abs_path: Arc<Path>,
+    /// The range when the selection was saved, used once the buffer has been closed.
+    range: Range<Point>,
+    anchors: Option<(WeakModel<Buffer>, Range<language::Anchor>)>,
+}
+
+impl NamedSelection {
+    /// Returns the current range of the selection, following the edits made since it was saved
+    /// while its buffer is open.
+    pub fn range(&self, cx: &AppContext) -> Range<Point> {
+        self.anchors
+            .as_ref()
+            .and_then(|(buffer, anchors)| {
+                let buffer = buffer.upgrade()?;
+                let buffer = buffer.read(cx);
+                Some(anchors.start.to_point(buffer)..anchors.end.to_point(buffer))
+            })
+            .unwrap_or_else(|| self.range.clone())
+    }
+
+    pub fn project_path(&self, project: &Model<Project>, cx: &AppContext) -> Option<ProjectPath> {
+        project.read(cx).find_project_path(&self.abs_path, cx)
+    }
+}
+
+/// The selection ranges saved by name in a workspace, ordered by name.
+pub struct NamedSelections {
+    project: Model<Project>,
+    workspace_id: Option<WorkspaceId>,
+    selections: Vec<NamedSelection>,
+    buffer_subscriptions: HashMap<EntityId, Subscription>,
+    pending_serialization: Task<()>,
+}
+
+impl NamedSelections {
+    pub(crate) fn new(
+        project: Model<Project>,
+        workspace_id: Option<WorkspaceId>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        if let Some(workspace_id) = workspace_id {
+            cx.spawn(|this, mut cx| async move {
+                let selections = cx
+                    .background_executor()
+                    .spawn(async move { DB.get_named_selections(workspace_id) })
+                    .await?;
+                this.update(&mut cx, |this, cx| {
+                    for selection in selections {
+                        let selection = NamedSelection {
+                            name: selection.name,
+                            abs_path: selection.abs_path.into(),
+                            range: selection.range,
+                            anchors: None,
+                        };
+                        // Selections saved before the list was restored take precedence.
+                        if let Err(ix) = this.position(&selection.name) {
+                            this.selections.insert(ix, selection);
+                        }
+                    }
+                    cx.notify();
+                })
+            })
+            .detach_and_log_err(cx);
+        }
+
+        Self {
+            project,
+            workspace_id,
+            selections: Vec::new(),
+            buffer_subscriptions: HashMap::default(),
+            pending_serialization: Task::ready(()),
+        }
+    }
+
+    pub fn selections(&self) -> &[NamedSelection] {
+        &self.selections
+    }
+
+    fn position(&self, name: &str) -> Result<usize, usize> {
+        self.selections
+            .binary_search_by(|selection| selection.name.as_str().cmp(name))
+    }
+
+    /// Saves the newest selection of the editor under the given name, replacing the selection
+    /// previously saved under it.
+    pub(crate) fn save(
+        &mut self,
+        name: String,
+        editor: &View<Editor>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let editor = editor.read(cx);
+        let selection = editor.selections.newest_anchor();
+        let multi_buffer = editor.buffer().read(cx);
+        let Some((buffer, start)) = multi_buffer.text_anchor_for_position(selection.start, cx)
+        else {
+            return;
+        };
+        let end = match multi_buffer.text_anchor_for_position(selection.end, cx) {
+            Some((end_buffer, end)) if end_buffer == buffer => end,
+            _ => start,
+        };
+        let Some(abs_path) = buffer
+            .read(cx)
+            .project_path(cx)
+            .and_then(|project_path| self.project.read(cx).absolute_path(&project_path, cx))
+        else {
+            return;
+        };
+
+        let snapshot = buffer.read(cx);
+        let selection = NamedSelection {
+            name,
+            abs_path: abs_path.into(),
+            range: start.to_point(snapshot)..end.to_point(snapshot),
+            anchors: Some((buffer.downgrade(), start..end)),
+        };
+        match self.position(&selection.name) {
+            Ok(ix) => self.selections[ix] = selection,
+            Err(ix) => self.selections.insert(ix, selection),
+        }
+        self.track_buffer(&buffer, cx);
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    pub(crate) fn remove(&mut self, name: &str, cx: &mut ModelContext<Self>) {
+        if let Ok(ix) = self.position(name) {
+            self.selections.remove(ix);
+            self.serialize(cx);
+            cx.notify();
+        }
+    }
+
+    /// Makes the selection follow the edits of the buffer it has been opened in.
+    fn attach_buffer(&mut self, name: &str, buffer: Model<Buffer>, cx: &mut ModelContext<Self>) {
+        let Ok(ix) = self.position(name) else {
+            return;
+        };
+        let selection = &mut self.selections[ix];
+        if selection
+            .anchors
+            .as_ref()
+            .map_or(false, |(anchored, _)| anchored.upgrade().is_some())
+        {
+            return;
+        }
+        let snapshot = buffer.read(cx);
+        let range = snapshot.clip_point(selection.range.start, Bias::Left)
+            ..snapshot.clip_point(selection.range.end, Bias::Right);
+        let anchors = snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end);
+        selection.anchors = Some((buffer.downgrade(), anchors));
+        self.track_buffer(&buffer, cx);
+    }
+
+    /// Saves the selections again when the buffer of one of them is edited, as their ranges
+    /// may have moved.
+    fn track_buffer(&mut self, buffer: &Model<Buffer>, cx: &mut ModelContext<Self>) {
+        self.buffer_subscriptions
+            .entry(buffer.entity_id())
+            .or_insert_with(|| {
+                cx.subscribe(buffer, |this, _, event, cx| {
+                    if let BufferEvent::Edited = event {
+                        this.serialize(cx);
+                    }
+                })
+            });
+    }
+
+    fn serialize(&mut self, cx: &mut ModelContext<Self>) {
+        let Some(workspace_id) = self.workspace_id else {
+            return;
+        };
+        let selections = self
+            .selections
+            .iter()
+            .map(|selection| SerializedNamedSelection {
+                name: selection.name.clone(),
+                abs_path: PathBuf::from(selection.abs_path.as_ref()),
+                range: selection.range(cx),
+            })
+            .collect();
+        self.pending_serialization = cx.spawn(|_, cx| async move {
+            cx.background_executor().timer(SERIALIZATION_DEBOUNCE).await;
+            DB.save_named_selections(workspace_id, selections)
+                .await
+                .log_err();
+        });
+    }
+}
+
+pub(crate) fn open_named_selection(
+    workspace: &mut Workspace,
+    named_selections: &Model<NamedSelections>,
+    selection: NamedSelection,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let open_task = match selection.project_path(workspace.project(), cx) {
+        Some(project_path) => workspace.open_path(project_path, None, true, cx),
+        None => workspace.open_abs_path(PathBuf::from(selection.abs_path.as_ref()), true, cx),
+    };
+
+    let named_selections = named_selections.clone();
+    cx.spawn(|_, mut cx| async move {
+        let item = open_task.await?;
+        let Some(editor) = item.downcast::<Editor>() else {
+            return Ok(());
+        };
+        editor.update(&mut cx, |editor, cx| {
+            if let Some(buffer) = editor.buffer().read(cx).as_singleton() {
+                named_selections.update(cx, |named_selections, cx| {
+                    named_selections.attach_buffer(&selection.name, buffer, cx)
+                });
+            }
+            let range = selection.range(cx);
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let range = snapshot.clip_point(range.start, Bias::Left)
+                ..snapshot.clip_point(range.end, Bias::Right);
+            editor.change_selections(Some(Autoscroll::center()), cx, |s| s.select_ranges([range]));
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToggleNamedSelections;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_save_and_open_named_selection(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.txt": "one\ntwo\nthree\nfour\n",
+                "b.txt": "b\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+
+        let editor_a = open_editor(&workspace, (worktree_id, "a.txt"), cx).await;
+        editor_a.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |s| {
+                s.select_ranges([Point::new(1, 0)..Point::new(2, 5)])
+            })
+        });
+        cx.dispatch_action(ToggleNamedSelections);
+        cx.simulate_input("middle");
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+
+        // The selection follows the edits made above it.
+        editor_a.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |s| {
+                s.select_ranges([Point::new(0, 0)..Point::new(0, 0)])
+            });
+            editor.insert("zero\n", cx);
+        });
+        open_editor(&workspace, (worktree_id, "b.txt"), cx).await;
+
+        cx.dispatch_action(ToggleNamedSelections);
+        cx.simulate_input("mid");
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            assert_eq!(editor.entity_id(), editor_a.entity_id());
+            let selection = editor.read(cx).selections.newest::<Point>(cx);
+            assert_eq!(selection.range(), Point::new(2, 0)..Point::new(3, 5));
+        });
+    }
+
+    async fn open_editor(
+        workspace: &View<Workspace>,
+        path: impl Into<ProjectPath>,
+        cx: &mut VisualTestContext,
+    ) -> View<Editor> {
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path(path, None, true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap()
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+            state
+        })
+    }
+}
@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use editor::Editor;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render, Task, View,
+    ViewContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::named_selections::{open_named_selection, NamedSelection, NamedSelections};
+
+/// Lists the named selections of a workspace, and saves the selection of the active editor
+/// under the name typed in the query.
+pub struct NamedSelectionsPicker {
+    picker: View<Picker<NamedSelectionsPickerDelegate>>,
+}
+
+impl NamedSelectionsPicker {
+    pub(crate) fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        named_selections: Model<NamedSelections>,
+        editor: Option<View<Editor>>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = NamedSelectionsPickerDelegate {
+            named_selections_picker: cx.view().downgrade(),
+            workspace,
+            project,
+            named_selections,
+            editor,
+            matches: Vec::new(),
+            new_name: None,
+            selected_index: 0,
+        };
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for NamedSelectionsPicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for NamedSelectionsPicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for NamedSelectionsPicker {}
+impl ModalView for NamedSelectionsPicker {}
+
+pub struct NamedSelectionsPickerDelegate {
+    named_selections_picker: WeakView<NamedSelectionsPicker>,
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    named_selections: Model<NamedSelections>,
+    /// The editor whose selection is saved, if one was active.
+    editor: Option<View<Editor>>,
+    matches: Vec<(NamedSelection, StringMatch)>,
+    /// The query, when an editor is active and no selection is named like it. It is listed
+    /// after the matches, to save the selection under it.
+    new_name: Option<String>,
+    selected_index: usize,
+}
+
+impl NamedSelectionsPickerDelegate {
+    fn remove_at(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some((selection, _)) = self.matches.get(ix) else {
+            return;
+        };
+        let name = selection.name.clone();
+        self.named_selections.update(cx, |named_selections, cx| {
+            named_selections.remove(&name, cx)
+        });
+        self.matches.remove(ix);
+        self.selected_index = self
+            .selected_index
+            .min(self.match_count().saturating_sub(1));
+        cx.notify();
+    }
+}
+
+impl PickerDelegate for NamedSelectionsPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        if self.editor.is_some() {
+            "Search or name the selection…".into()
+        } else {
+            "Search named selections…".into()
+        }
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        "No named selections".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len() + self.new_name.is_some() as usize
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let selections = self.named_selections.read(cx).selections().to_vec();
+        let candidates = selections
+            .iter()
+            .enumerate()
+            .map(|(id, selection)| StringMatchCandidate::new(id, &selection.name))
+            .collect::<Vec<_>>();
+        cx.spawn(|this, mut cx| async move {
+            let query = query.trim().to_string();
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                let exists = selections.iter().any(|selection| selection.name == query);
+                delegate.new_name =
+                    (delegate.editor.is_some() && !query.is_empty() && !exists).then_some(query);
+                delegate.matches = matches
+                    .into_iter()
+                    .map(|mat| (selections[mat.candidate_id].clone(), mat))
+                    .collect();
+                delegate.selected_index = 0;
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some((selection, _)) = self.matches.get(self.selected_index) {
+            let selection = selection.clone();
+            let named_selections = self.named_selections.clone();
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    open_named_selection(workspace, &named_selections, selection, cx);
+                })
+                .log_err();
+        } else if let Some((new_name, editor)) = self.new_name.clone().zip(self.editor.as_ref()) {
+            self.named_selections.update(cx, |named_selections, cx| {
+                named_selections.save(new_name, editor, cx)
+            });
+        } else {
+            return;
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.named_selections_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let Some((selection, mat)) = self.matches.get(ix) else {
+            let new_name = self.new_name.as_ref()?;
+            return Some(
+                ListItem::new(ix)
+                    .inset(true)
+                    .spacing(ListItemSpacing::Sparse)
+                    .toggle_state(selected)
+                    .start_slot(
+                        Icon::new(IconName::Plus)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(Label::new(format!("Save Selection as “{new_name}”"))),
+            );
+        };
+        let range = selection.range(cx);
+        let path = selection.project_path(&self.project, cx).map_or_else(
+            || selection.abs_path.to_string_lossy().into_owned(),
+            |project_path| project_path.path.to_string_lossy().into_owned(),
+        );
+        let location = if range.start.row == range.end.row {
+            format!("{path}:{}", range.start.row + 1)
+        } else {
+            format!("{path}:{}-{}", range.start.row + 1, range.end.row + 1)
+        };
+        let remove_button = IconButton::new("remove_named_selection", IconName::Close)
+            .icon_size(IconSize::Small)
+            .on_click(cx.listener(move |picker, _, cx| {
+                cx.stop_propagation();
+                picker.delegate.remove_at(ix, cx);
+            }))
+            .tooltip(|cx| Tooltip::text("Remove", cx));
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(
+                    Icon::new(IconName::SelectAll)
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(HighlightedLabel::new(
+                            selection.name.clone(),
+                            mat.positions.clone(),
+                        ))
+                        .child(Label::new(location).color(Color::Muted).single_line()),
+                )
+                .end_hover_slot(remove_button),
+        )
+    }
+}
@@ -0,0 +1,282 @@
+use std::{ops::Range, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use db::sqlez::bindable::{Bind, Column, StaticColumnCount};
+use db::sqlez::statement::Statement;
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+use editor::JumpKind;
+use language::Point;
+use workspace::{WorkspaceDb, WorkspaceId};
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SerializedJump {
+    pub(crate) kind: JumpKind,
+    pub(crate) abs_path: PathBuf,
+    pub(crate) point: Point,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SerializedNamedSelection {
+    pub(crate) name: String,
+    pub(crate) abs_path: PathBuf,
+    pub(crate) range: Range<Point>,
+}
+
+fn kind_to_str(kind: JumpKind) -> &'static str {
+    match kind {
+        JumpKind::Navigation => "navigation",
+        JumpKind::Definition => "definition",
+        JumpKind::Edit => "edit",
+    }
+}
+
+fn kind_from_str(kind: &str) -> Result<JumpKind> {
+    match kind {
+        "navigation" => Ok(JumpKind::Navigation),
+        "definition" => Ok(JumpKind::Definition),
+        "edit" => Ok(JumpKind::Edit),
+        _ => Err(anyhow!("unknown jump kind {kind:?}")),
+    }
+}
+
+impl StaticColumnCount for SerializedJump {
+    fn column_count() -> usize {
+        4
+    }
+}
+
+impl Bind for SerializedJump {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        let start_index = statement.bind(&kind_to_str(self.kind), start_index)?;
+        let start_index = statement.bind(&self.abs_path, start_index)?;
+        let start_index = statement.bind(&self.point.row, start_index)?;
+        statement.bind(&self.point.column, start_index)
+    }
+}
+
+impl Column for SerializedJump {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (kind, start_index): (String, i32) = Column::column(statement, start_index)?;
+        let (abs_path, start_index): (PathBuf, i32) = Column::column(statement, start_index)?;
+        let (row, start_index): (u32, i32) = Column::column(statement, start_index)?;
+        let (column, start_index): (u32, i32) = Column::column(statement, start_index)?;
+        let jump = Self {
+            kind: kind_from_str(&kind)?,
+            abs_path,
+            point: Point::new(row, column),
+        };
+        Ok((jump, start_index))
+    }
+}
+
+impl StaticColumnCount for SerializedNamedSelection {
+    fn column_count() -> usize {
+        6
+    }
+}
+
+impl Bind for SerializedNamedSelection {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        let start_index = statement.bind(&self.name, start_index)?;
+        let start_index = statement.bind(&self.abs_path, start_index)?;
+        let start_index = statement.bind(&self.range.start.row, start_index)?;
+        let start_index = statement.bind(&self.range.start.column, start_index)?;
+        let start_index = statement.bind(&self.range.end.row, start_index)?;
+        statement.bind(&self.range.end.column, start_index)
+    }
+}
+
+impl Column for SerializedNamedSelection {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (name, start_index): (String, i32) = Column::column(statement, start_index)?;
+        let (abs_path, start_index): (PathBuf, i32) = Column::column(statement, start_index)?;
+        let (start_row, start_index): (u32, i32) = Column::column(statement, start_index)?;
+        let (start_column, start_index): (u32, i32) = Column::column(statement, start_index)?;
+        let (end_row, start_index): (u32, i32) = Column::column(statement, start_index)?;
+        let (end_column, start_index): (u32, i32) = Column::column(statement, start_index)?;
+        let selection = Self {
+            name,
+            abs_path,
+            range: Point::new(start_row, start_column)..Point::new(end_row, end_column),
+        };
+        Ok((selection, start_index))
+    }
+}
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // jumps(
+    //   workspace_id: usize,
+    //   position: usize,
+    //   kind: String,
+    //   path: PathBuf,
+    //   cursor_row: u32,
+    //   cursor_column: u32,
+    // )
+    // named_selections(
+    //   workspace_id: usize,
+    //   name: String,
+    //   path: PathBuf,
+    //   start_row: u32,
+    //   start_column: u32,
+    //   end_row: u32,
+    //   end_column: u32,
+    // )
+    pub static ref DB: JumpListDb<WorkspaceDb> =
+        &[sql! (
+            CREATE TABLE jumps(
+                workspace_id INTEGER NOT NULL,
+                position INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                path BLOB NOT NULL,
+                cursor_row INTEGER NOT NULL,
+                cursor_column INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, position),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        ),
+        sql! (
+            CREATE TABLE named_selections(
+                workspace_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                path BLOB NOT NULL,
+                start_row INTEGER NOT NULL,
+                start_column INTEGER NOT NULL,
+                end_row INTEGER NOT NULL,
+                end_column INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, name),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        )];
+);
+
+impl JumpListDb {
+    query! {
+        pub(crate) fn get_jumps(workspace_id: WorkspaceId) -> Result<Vec<SerializedJump>> {
+            SELECT kind, path, cursor_row, cursor_column FROM jumps
+            WHERE workspace_id = ?
+            ORDER BY position
+        }
+    }
+
+    pub(crate) async fn save_jumps(
+        &self,
+        workspace_id: WorkspaceId,
+        jumps: Vec<SerializedJump>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_jumps", || {
+                conn.exec_bound(sql!(DELETE FROM jumps WHERE workspace_id = ?))?(workspace_id)?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT INTO jumps
+                        (workspace_id, position, kind, path, cursor_row, cursor_column)
+                    VALUES
+                        (?, ?, ?, ?, ?, ?)
+                ))?;
+                for (position, jump) in jumps.into_iter().enumerate() {
+                    insert((workspace_id, position, jump))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub(crate) fn get_named_selections(workspace_id: WorkspaceId) -> Result<Vec<SerializedNamedSelection>> {
+            SELECT name, path, start_row, start_column, end_row, end_column FROM named_selections
+            WHERE workspace_id = ?
+            ORDER BY name
+        }
+    }
+
+    pub(crate) async fn save_named_selections(
+        &self,
+        workspace_id: WorkspaceId,
+        selections: Vec<SerializedNamedSelection>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_named_selections", || {
+                conn.exec_bound(sql!(DELETE FROM named_selections WHERE workspace_id = ?))?(
+                    workspace_id,
+                )?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT INTO named_selections
+                        (workspace_id, name, path, start_row, start_column, end_row, end_column)
+                    VALUES
+                        (?, ?, ?, ?, ?, ?, ?)
+                ))?;
+                for selection in selections {
+                    insert((workspace_id, selection))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_save_and_get_jumps() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let jumps = vec![
+            SerializedJump {
+                kind: JumpKind::Navigation,
+                abs_path: PathBuf::from("/root/a.rs"),
+                point: Point::new(3, 4),
+            },
+            SerializedJump {
+                kind: JumpKind::Edit,
+                abs_path: PathBuf::from("/root/b.rs"),
+                point: Point::new(10, 0),
+            },
+        ];
+
+        DB.save_jumps(workspace_id, jumps.clone()).await.unwrap();
+        assert_eq!(DB.get_jumps(workspace_id).unwrap(), jumps);
+
+        DB.save_jumps(workspace_id, jumps[1..].to_vec())
+            .await
+            .unwrap();
+        assert_eq!(DB.get_jumps(workspace_id).unwrap(), &jumps[1..]);
+    }
+
+    #[gpui::test]
+    async fn test_save_and_get_named_selections() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let selections = vec![
+            SerializedNamedSelection {
+                name: "handler".into(),
+                abs_path: PathBuf::from("/root/b.rs"),
+                range: Point::new(1, 0)..Point::new(8, 1),
+            },
+            SerializedNamedSelection {
+                name: "config".into(),
+                abs_path: PathBuf::from("/root/a.rs"),
+                range: Point::new(3, 4)..Point::new(3, 10),
+            },
+        ];
+
+        DB.save_named_selections(workspace_id, selections.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_named_selections(workspace_id).unwrap(),
+            [selections[1].clone(), selections[0].clone()]
+        );
+
+        DB.save_named_selections(workspace_id, Vec::new())
+            .await
+            .unwrap();
+        assert!(DB.get_named_selections(workspace_id).unwrap().is_empty());
+    }
+}
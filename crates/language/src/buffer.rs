@@ -123,6 +123,9 @@ pub struct Buffer {
     completion_triggers_timestamp: clock::Lamport,
     deferred_ops: OperationQueue<Operation>,
     capability: Capability,
+    /// Whether the buffer was opened in large file mode, in which it isn't parsed, isn't
+    /// registered with language servers and isn't diffed against git.
+    large_file: bool,
     has_conflict: bool,
     /// Memoize calls to has_changes_since(saved_version).
     /// The contents of a cell are (self.version, has_changes) at the time of a last call.
@@ -345,6 +348,8 @@ pub enum BufferEvent {
     DiagnosticsUpdated,
     /// The buffer gained or lost editing capabilities.
     CapabilityChanged,
+    /// The buffer entered or left large file mode.
+    LargeFileModeChanged,
     /// The buffer was explicitly requested to close.
     Closed,
     /// The buffer was discarded when closing.
@@ -663,6 +668,7 @@ impl Buffer {
         ));
         this.saved_version = proto::deserialize_version(&message.saved_version);
         this.saved_mtime = message.saved_mtime.map(|time| time.into());
+        this.large_file = message.large_file;
         Ok(this)
     }
 
@@ -675,6 +681,7 @@ impl Buffer {
             line_ending: proto::serialize_line_ending(self.line_ending()) as i32,
             saved_version: proto::serialize_version(&self.saved_version),
             saved_mtime: self.saved_mtime.map(|time| time.into()),
+            large_file: self.large_file,
         }
     }
 
@@ -744,6 +751,11 @@ impl Buffer {
         self.capability == Capability::ReadOnly
     }
 
+    /// Whether this buffer is in large file mode.
+    pub fn is_large_file(&self) -> bool {
+        self.large_file
+    }
+
     /// Builds a [`Buffer`] with the given underlying [`TextBuffer`], diff base, [`File`] and [`Capability`].
     pub fn build(buffer: TextBuffer, file: Option<Arc<dyn File>>, capability: Capability) -> Self {
         let saved_mtime = file.as_ref().and_then(|file| file.disk_state().mtime());
@@ -761,6 +773,7 @@ impl Buffer {
             branch_state: None,
             file,
             capability,
+            large_file: false,
             syntax_map,
             parsing_in_background: false,
             non_text_state_update_count: 0,
@@ -781,6 +794,34 @@ impl Buffer {
         }
     }
 
+    /// Builds a snapshot of a buffer with the given text, parsed with the given language, without
+    /// creating the buffer. The returned future does the parsing, and can run on a background
+    /// thread.
+    pub fn build_snapshot(
+        text: String,
+        language: Option<Arc<Language>>,
+        language_registry: Option<Arc<LanguageRegistry>>,
+        cx: &mut AppContext,
+    ) -> impl Future<Output = BufferSnapshot> {
+        let buffer_id = BufferId::from(cx.reserve_model::<Self>().entity_id().as_non_zero_u64());
+        async move {
+            let text = TextBuffer::new(0, buffer_id, text).snapshot();
+            let mut syntax = SyntaxMap::new(&text).snapshot();
+            if let Some(language) = language.clone() {
+                syntax.reparse(&text, language_registry, language);
+            }
+            BufferSnapshot {
+                text,
+                syntax,
+                file: None,
+                diagnostics: Default::default(),
+                remote_selections: Default::default(),
+                language,
+                non_text_state_update_count: 0,
+            }
+        }
+    }
+
     /// Retrieve a snapshot of the buffer's current state. This is computationally
     /// cheap, and allows reading from the buffer on a background thread.
     pub fn snapshot(&self) -> BufferSnapshot {
@@ -966,6 +1007,20 @@ impl Buffer {
         cx.emit(BufferEvent::CapabilityChanged)
     }
 
+    /// Puts the buffer in or out of large file mode. Entering it drops the buffer's language,
+    /// and it's up to the owner of the buffer to detect it again when leaving it.
+    pub fn set_large_file(&mut self, large_file: bool, cx: &mut ModelContext<Self>) {
+        if self.large_file == large_file {
+            return;
+        }
+        self.large_file = large_file;
+        if large_file {
+            self.set_language(None, cx);
+        }
+        cx.emit(BufferEvent::LargeFileModeChanged);
+        cx.notify();
+    }
+
     /// This method is called to signal that the buffer has been saved.
     pub fn did_save(
         &mut self,
@@ -1227,6 +1282,18 @@ impl Buffer {
         self.parse_status.1.clone()
     }
 
+    /// Waits until the buffer has finished parsing its latest changes.
+    pub fn parsing_idle(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut parse_status = self.parse_status();
+        async move {
+            while *parse_status.borrow() != ParseStatus::Idle {
+                if parse_status.changed().await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
     /// Assign to the buffer a set of diagnostics created by a given language server.
     pub fn update_diagnostics(
         &mut self,
@@ -2762,6 +2829,48 @@ impl BufferSnapshot {
         (captures, highlight_maps)
     }
 
+    /// Returns the ranges within `range` that are captured by the highlights queries with a
+    /// name satisfying `predicate`, e.g. all comments and strings. Overlapping captures are
+    /// merged.
+    pub fn highlight_capture_ranges(
+        &self,
+        range: Range<usize>,
+        mut predicate: impl FnMut(&str) -> bool,
+    ) -> Vec<Range<usize>> {
+        let mut captures = self.syntax.captures(range.clone(), &self.text, |grammar| {
+            grammar.highlights_query.as_ref()
+        });
+        let capture_names = captures
+            .grammars()
+            .iter()
+            .map(|grammar| {
+                grammar
+                    .highlights_query
+                    .as_ref()
+                    .map(|query| query.capture_names())
+            })
+            .collect::<Vec<_>>();
+
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        while let Some(capture) = captures.peek() {
+            let name = capture_names[capture.grammar_index]
+                .and_then(|names| names.get(capture.index as usize));
+            if name.map_or(false, |name| predicate(name)) {
+                let capture_range = capture.node.byte_range();
+                let capture_range =
+                    capture_range.start.max(range.start)..capture_range.end.min(range.end);
+                match ranges.last_mut() {
+                    Some(last) if capture_range.start <= last.end => {
+                        last.end = last.end.max(capture_range.end);
+                    }
+                    _ => ranges.push(capture_range),
+                }
+            }
+            captures.advance();
+        }
+        ranges
+    }
+
     /// Iterates over chunks of text in the given range of the buffer. Text is chunked
     /// in an arbitrary way due to being stored in a [`Rope`](text::Rope). The text is also
     /// returned in chunks where each chunk has a single syntax highlighting style and
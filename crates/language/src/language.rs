@@ -874,7 +874,8 @@ pub struct OutlineConfig {
     pub annotation_capture_ix: Option<u32>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TextObject {
     InsideFunction,
     AroundFunction,
@@ -882,6 +883,16 @@ pub enum TextObject {
     AroundClass,
     InsideComment,
     AroundComment,
+    InsideParameter,
+    AroundParameter,
+    InsideCall,
+    AroundCall,
+    InsideTest,
+    AroundTest,
+    InsideConditional,
+    AroundConditional,
+    InsideLoop,
+    AroundLoop,
 }
 
 impl TextObject {
@@ -893,6 +904,16 @@ impl TextObject {
             "class.around" => Some(TextObject::AroundClass),
             "comment.inside" => Some(TextObject::InsideComment),
             "comment.around" => Some(TextObject::AroundComment),
+            "parameter.inside" => Some(TextObject::InsideParameter),
+            "parameter.around" => Some(TextObject::AroundParameter),
+            "call.inside" => Some(TextObject::InsideCall),
+            "call.around" => Some(TextObject::AroundCall),
+            "test.inside" => Some(TextObject::InsideTest),
+            "test.around" => Some(TextObject::AroundTest),
+            "conditional.inside" => Some(TextObject::InsideConditional),
+            "conditional.around" => Some(TextObject::AroundConditional),
+            "loop.inside" => Some(TextObject::InsideLoop),
+            "loop.around" => Some(TextObject::AroundLoop),
             _ => None,
         }
     }
@@ -902,6 +923,25 @@ impl TextObject {
             TextObject::InsideFunction => Some(TextObject::AroundFunction),
             TextObject::InsideClass => Some(TextObject::AroundClass),
             TextObject::InsideComment => Some(TextObject::AroundComment),
+            TextObject::InsideParameter => Some(TextObject::AroundParameter),
+            TextObject::InsideCall => Some(TextObject::AroundCall),
+            TextObject::InsideTest => Some(TextObject::AroundTest),
+            TextObject::InsideConditional => Some(TextObject::AroundConditional),
+            TextObject::InsideLoop => Some(TextObject::AroundLoop),
+            _ => None,
+        }
+    }
+
+    pub fn inside(&self) -> Option<Self> {
+        match self {
+            TextObject::AroundFunction => Some(TextObject::InsideFunction),
+            TextObject::AroundClass => Some(TextObject::InsideClass),
+            TextObject::AroundComment => Some(TextObject::InsideComment),
+            TextObject::AroundParameter => Some(TextObject::InsideParameter),
+            TextObject::AroundCall => Some(TextObject::InsideCall),
+            TextObject::AroundTest => Some(TextObject::InsideTest),
+            TextObject::AroundConditional => Some(TextObject::InsideConditional),
+            TextObject::AroundLoop => Some(TextObject::InsideLoop),
             _ => None,
         }
     }
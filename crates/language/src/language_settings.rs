@@ -132,6 +132,8 @@ pub struct LanguageSettings {
     pub auto_indent_on_paste: bool,
     // Controls how the editor handles the autoclosed characters.
     pub always_treat_brackets_as_autoclosed: bool,
+    /// Whether to color brackets by their nesting depth.
+    pub colorize_brackets: bool,
     /// Which code actions to run on save
     pub code_actions_on_format: HashMap<String, bool>,
     /// Whether to perform linked edits
@@ -365,6 +367,11 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: false
     pub always_treat_brackets_as_autoclosed: Option<bool>,
+    /// Whether to color brackets by their nesting depth, using the accent colors of the theme.
+    /// Indent guides in "indent_aware" coloring mode use the same colors.
+    ///
+    /// Default: false
+    pub colorize_brackets: Option<bool>,
     /// Whether to use additional LSP queries to format (and amend) the code after
     /// every "trigger" symbol input, defined by LSP server capabilities.
     ///
@@ -1158,6 +1165,7 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         &mut settings.always_treat_brackets_as_autoclosed,
         src.always_treat_brackets_as_autoclosed,
     );
+    merge(&mut settings.colorize_brackets, src.colorize_brackets);
     merge(&mut settings.show_wrap_guides, src.show_wrap_guides);
     merge(&mut settings.wrap_guides, src.wrap_guides.clone());
     merge(&mut settings.indent_guides, src.indent_guides);
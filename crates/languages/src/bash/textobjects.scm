@@ -5,3 +5,30 @@
     "}" )) @function.around
 
 (comment) @comment.around
+
+(command
+    argument: (_) @parameter.inside @parameter.around)
+
+(command) @call.around
+
+(if_statement) @conditional.around
+
+(case_statement) @conditional.around
+
+(for_statement
+    body: (do_group
+        "do"
+        (_)* @loop.inside
+        "done")) @loop.around
+
+(c_style_for_statement
+    body: (do_group
+        "do"
+        (_)* @loop.inside
+        "done")) @loop.around
+
+(while_statement
+    body: (do_group
+        "do"
+        (_)* @loop.inside
+        "done")) @loop.around
@@ -23,3 +23,51 @@
         "{"
         [(_) ","?]* @class.inside
         "}")) @class.around
+
+(parameter_list
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+(argument_list
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+(call_expression
+    arguments: (argument_list
+        "("
+        (_)* @call.inside
+        ")")) @call.around
+
+(if_statement
+    consequence: (compound_statement
+        "{"
+        (_)* @conditional.inside
+        "}")) @conditional.around
+
+(switch_statement
+    body: (compound_statement
+        "{"
+        (_)* @conditional.inside
+        "}")) @conditional.around
+
+(conditional_expression) @conditional.around
+
+(for_statement
+    body: (compound_statement
+        "{"
+        (_)* @loop.inside
+        "}")) @loop.around
+
+(while_statement
+    body: (compound_statement
+        "{"
+        (_)* @loop.inside
+        "}")) @loop.around
+
+(do_statement
+    body: (compound_statement
+        "{"
+        (_)* @loop.inside
+        "}")) @loop.around
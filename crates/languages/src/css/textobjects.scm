@@ -28,3 +28,14 @@
         "{"
         (_)* @class.inside
         "}" ))) @class.around
+
+(arguments
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+(call_expression
+    (arguments
+        "("
+        (_)* @call.inside
+        ")")) @call.around
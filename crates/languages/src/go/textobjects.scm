@@ -23,3 +23,45 @@
 (type_declaration) @class.around
 
 (comment)+ @comment.around
+
+(parameter_list
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+(argument_list
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+(call_expression
+    arguments: (argument_list
+        "("
+        (_)* @call.inside
+        ")")) @call.around
+
+(function_declaration
+    name: (_) @_name
+    (#match? @_name "^(Test|Benchmark|Fuzz|Example)")
+    body: (_
+        "{"
+        (_)* @test.inside
+        "}")) @test.around
+
+(if_statement
+    consequence: (_
+        "{"
+        (_)* @conditional.inside
+        "}")) @conditional.around
+
+(expression_switch_statement) @conditional.around
+
+(type_switch_statement) @conditional.around
+
+(select_statement) @conditional.around
+
+(for_statement
+    body: (_
+        "{"
+        (_)* @loop.inside
+        "}")) @loop.around
@@ -5,3 +5,47 @@
 
 (class_definition
   body: (_) @class.inside) @class.around
+
+(parameters
+  (_) @parameter.inside @parameter.around
+  .
+  ","? @parameter.around)
+
+(lambda_parameters
+  (_) @parameter.inside @parameter.around
+  .
+  ","? @parameter.around)
+
+(argument_list
+  (_) @parameter.inside @parameter.around
+  .
+  ","? @parameter.around)
+
+(call
+  arguments: (argument_list
+    "("
+    (_)* @call.inside
+    ")")) @call.around
+
+(function_definition
+  name: (_) @_name
+  (#match? @_name "^test")
+  body: (_) @test.inside) @test.around
+
+(class_definition
+  name: (_) @_name
+  (#match? @_name "^Test")
+  body: (_) @test.inside) @test.around
+
+(if_statement
+  consequence: (_) @conditional.inside) @conditional.around
+
+(match_statement) @conditional.around
+
+(conditional_expression) @conditional.around
+
+(for_statement
+  body: (_) @loop.inside) @loop.around
+
+(while_statement
+  body: (_) @loop.inside) @loop.around
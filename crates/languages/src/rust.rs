@@ -815,6 +815,7 @@ mod tests {
     use lsp::CompletionItemLabelDetails;
     use settings::SettingsStore;
     use theme::SyntaxTheme;
+    use unindent::Unindent;
 
     #[gpui::test]
     async fn test_process_rust_diagnostics() {
@@ -1096,6 +1097,42 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_rust_text_objects(cx: &mut TestAppContext) {
+        let language = crate::language("rust", tree_sitter_rust::LANGUAGE.into());
+        let text = r#"
+            #[test]
+            fn test_a() {
+                for x in xs {
+                    if a(x, 2) {}
+                }
+            }
+        "#
+        .unindent();
+
+        let buffer = cx.new_model(|cx| Buffer::local(text.clone(), cx).with_language(language, cx));
+        let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+        let offset = text.find('2').unwrap();
+        let text_objects = snapshot
+            .text_object_ranges(offset..offset, TreeSitterOptions::default())
+            .map(|(range, text_object)| (&text[range], text_object))
+            .collect::<Vec<_>>();
+
+        for expected in [
+            ("2", TextObject::InsideParameter),
+            ("x, 2", TextObject::InsideCall),
+            ("a(x, 2)", TextObject::AroundCall),
+            ("if a(x, 2) {}", TextObject::AroundConditional),
+            ("if a(x, 2) {}", TextObject::InsideLoop),
+            (text.trim_end(), TextObject::AroundTest),
+        ] {
+            assert!(
+                text_objects.contains(&expected),
+                "{expected:?} not found in {text_objects:?}"
+            );
+        }
+    }
+
     #[test]
     fn test_package_name_from_pkgid() {
         for (input, expected) in [
@@ -49,3 +49,96 @@
 (line_comment)+ @comment.around
 
 (block_comment) @comment.around
+
+; parameters
+(parameters
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+(closure_parameters
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+(type_parameters
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+(arguments
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+(type_arguments
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+; calls
+(call_expression
+    arguments: (arguments
+        "("
+        (_)* @call.inside
+        ")" )) @call.around
+
+(macro_invocation) @call.around
+
+; tests
+(
+    (attribute_item (attribute
+        [((identifier) @_attribute)
+            (scoped_identifier (identifier) @_attribute)
+            ])
+        (#match? @_attribute "test")
+    ) @test.around
+    .
+    (attribute_item)*
+    .
+    (function_item
+        body: (_
+            "{"
+            (_)* @test.inside
+            "}" )) @test.around
+)
+
+(mod_item
+    name: (_) @_name
+    (#eq? @_name "tests")
+    body: (_
+        "{"
+        (_)* @test.inside
+        "}" )) @test.around
+
+; conditionals
+(if_expression
+    consequence: (_
+        "{"
+        (_)* @conditional.inside
+        "}" )) @conditional.around
+
+(match_expression
+    body: (_
+        "{"
+        (_)* @conditional.inside
+        "}" )) @conditional.around
+
+; loops
+(loop_expression
+    body: (_
+        "{"
+        (_)* @loop.inside
+        "}" )) @loop.around
+
+(while_expression
+    body: (_
+        "{"
+        (_)* @loop.inside
+        "}" )) @loop.around
+
+(for_expression
+    body: (_
+        "{"
+        (_)* @loop.inside
+        "}" )) @loop.around
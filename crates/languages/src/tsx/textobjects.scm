@@ -77,3 +77,95 @@
         "}" )) @class.around
 
 (type_alias_declaration) @class.around
+
+(formal_parameters
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+(type_parameters
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+(type_arguments
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+(arguments
+    (_) @parameter.inside @parameter.around
+    .
+    ","? @parameter.around)
+
+(call_expression
+    arguments: (arguments
+        "("
+        (_)* @call.inside
+        ")")) @call.around
+
+(new_expression
+    arguments: (arguments
+        "("
+        (_)* @call.inside
+        ")")) @call.around
+
+(call_expression
+    function: [
+        (identifier) @_name
+        (member_expression
+            object: (identifier) @_name)
+    ]
+    (#match? @_name "^(describe|it|test)$")
+    arguments: (arguments
+        (_)
+        [
+            (arrow_function
+                body: (statement_block
+                    "{"
+                    (_)* @test.inside
+                    "}"))
+            (function_expression
+                body: (statement_block
+                    "{"
+                    (_)* @test.inside
+                    "}"))
+        ])) @test.around
+
+(if_statement
+    consequence: (statement_block
+        "{"
+        (_)* @conditional.inside
+        "}")) @conditional.around
+
+(switch_statement
+    body: (switch_body
+        "{"
+        (_)* @conditional.inside
+        "}")) @conditional.around
+
+(ternary_expression) @conditional.around
+
+(for_statement
+    body: (statement_block
+        "{"
+        (_)* @loop.inside
+        "}")) @loop.around
+
+(for_in_statement
+    body: (statement_block
+        "{"
+        (_)* @loop.inside
+        "}")) @loop.around
+
+(while_statement
+    body: (statement_block
+        "{"
+        (_)* @loop.inside
+        "}")) @loop.around
+
+(do_statement
+    body: (statement_block
+        "{"
+        (_)* @loop.inside
+        "}")) @loop.around
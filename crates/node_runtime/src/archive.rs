@@ -1,6 +1,6 @@
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_zip::base::read::stream::ZipFileReader;
 use futures::{io::BufReader, AsyncRead};
 
@@ -14,13 +14,14 @@ pub async fn extract_zip<R: AsyncRead + Unpin>(destination: &Path, reader: R) ->
     while let Some(mut item) = reader.next_with_entry().await? {
         let entry_reader = item.reader_mut();
         let entry = entry_reader.entry();
-        let path = destination.join(entry.filename().as_str().unwrap());
+        let path = destination.join(entry_path(entry.filename().as_str()?)?);
 
-        if entry.dir().unwrap() {
+        if entry.dir()? {
             std::fs::create_dir_all(&path)?;
         } else {
-            let parent_dir = path.parent().expect("failed to get parent directory");
-            std::fs::create_dir_all(parent_dir)?;
+            if let Some(parent_dir) = path.parent() {
+                std::fs::create_dir_all(parent_dir)?;
+            }
             let mut file = smol::fs::File::create(&path).await?;
             futures::io::copy(entry_reader, &mut file).await?;
         }
@@ -31,6 +32,22 @@ pub async fn extract_zip<R: AsyncRead + Unpin>(destination: &Path, reader: R) ->
     Ok(())
 }
 
+/// The path of a zip entry relative to the destination, rejecting the entries that would be
+/// extracted outside of it.
+fn entry_path(filename: &str) -> Result<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in Path::new(filename).components() {
+        match component {
+            Component::Normal(component) => path.push(component),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(anyhow!("invalid zip entry path {filename:?}"));
+            }
+        }
+    }
+    Ok(path)
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -115,4 +132,23 @@ mod tests {
             assert_file_content(&dst.join("foo/bar/dar你好.txt"), "你好世界");
         });
     }
+
+    #[test]
+    fn test_extract_zip_rejects_entries_outside_destination() {
+        smol::block_on(async {
+            for filename in ["../escaped.txt", "foo/../../escaped.txt", "/escaped.txt"] {
+                let mut data = Vec::new();
+                let mut writer = ZipFileWriter::new(&mut data);
+                let builder = ZipEntryBuilder::new(filename.into(), async_zip::Compression::Stored);
+                writer.write_entry_whole(builder, b"escaped").await.unwrap();
+                writer.close().await.unwrap();
+
+                let dir = tempfile::tempdir().unwrap();
+                let dst = dir.path().join("dst");
+                std::fs::create_dir(&dst).unwrap();
+                assert!(extract_zip(&dst, Cursor::new(data)).await.is_err());
+                assert!(!dir.path().join("escaped.txt").exists());
+            }
+        });
+    }
 }
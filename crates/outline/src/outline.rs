@@ -15,7 +15,7 @@ use gpui::{
 };
 use language::{Outline, OutlineItem};
 use ordered_float::OrderedFloat;
-use picker::{Picker, PickerDelegate};
+use picker::{Picker, PickerDelegate, PickerPreview};
 use settings::Settings;
 use theme::{color_alpha, ActiveTheme, ThemeSettings};
 use ui::{prelude::*, ListItem, ListItemSpacing};
@@ -61,8 +61,12 @@ impl ModalView for OutlineView {
 }
 
 impl Render for OutlineView {
-    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
-        v_flex().w(rems(34.)).child(self.picker.clone())
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let preview_width = self.picker.read(cx).preview_width(cx).unwrap_or_default();
+
+        v_flex()
+            .w(rems(34.).to_pixels(cx.rem_size()) + preview_width)
+            .child(self.picker.clone())
     }
 }
 
@@ -267,6 +271,25 @@ impl PickerDelegate for OutlineViewDelegate {
         self.restore_active_editor(cx);
     }
 
+    fn supports_preview(&self) -> bool {
+        true
+    }
+
+    fn preview(
+        &self,
+        ix: usize,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Task<anyhow::Result<PickerPreview>>> {
+        let outline_item = &self.outline.items[self.matches.get(ix)?.candidate_id];
+        let multi_buffer = self.active_editor.read(cx).buffer().read(cx);
+        let buffer = multi_buffer.as_singleton()?;
+        let range = outline_item.range.to_point(&multi_buffer.snapshot(cx));
+        Some(Task::ready(Ok(PickerPreview {
+            buffer,
+            range: Some(range),
+        })))
+    }
+
     fn render_match(
         &self,
         ix: usize,
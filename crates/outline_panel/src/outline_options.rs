@@ -0,0 +1,466 @@
+use std::ops::Range;
+
+use collections::{HashMap, HashSet};
+use language::{Anchor, BufferSnapshot, OffsetRangeExt, OutlineItem, ToOffset};
+use lsp::SymbolKind;
+use project::DocumentSymbol;
+
+use crate::Outline;
+
+/// The groups of outline items that can be hidden from the panel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum OutlineKind {
+    Function,
+    Type,
+    Field,
+    Test,
+    Other,
+}
+
+impl OutlineKind {
+    pub(crate) const FILTERABLE: [Self; 4] = [Self::Function, Self::Type, Self::Field, Self::Test];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Function => "Functions",
+            Self::Type => "Types",
+            Self::Field => "Fields",
+            Self::Test => "Tests",
+            Self::Other => "Other",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Visibility {
+    Public,
+    Private,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum OutlineSort {
+    #[default]
+    Position,
+    Name,
+}
+
+/// How the outline items of each excerpt are shown.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct OutlineOptions {
+    pub(crate) hidden_kinds: HashSet<OutlineKind>,
+    pub(crate) sort: OutlineSort,
+    pub(crate) show_visibility: bool,
+}
+
+/// An outline item placed by [`OutlineOptions::arrange`].
+pub(crate) struct ArrangedOutline<'a> {
+    pub(crate) outline: &'a Outline,
+    pub(crate) depth: usize,
+    pub(crate) visibility: Option<Visibility>,
+}
+
+struct OutlineNode<'a> {
+    outline: &'a Outline,
+    kind: OutlineKind,
+    visibility: Option<Visibility>,
+    children: Vec<OutlineNode<'a>>,
+}
+
+impl OutlineOptions {
+    pub(crate) fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub(crate) fn toggle_kind(&mut self, kind: OutlineKind) {
+        if !self.hidden_kinds.remove(&kind) {
+            self.hidden_kinds.insert(kind);
+        }
+    }
+
+    /// Arranges the outline items of an excerpt, given in document order: hides the filtered out
+    /// kinds, moving their children up to take their place, and sorts the siblings.
+    ///
+    /// The kinds reported by a language server for the items, by range, take precedence over the
+    /// ones guessed from their keywords.
+    pub(crate) fn arrange<'a>(
+        &self,
+        outlines: impl IntoIterator<Item = &'a Outline>,
+        symbol_kinds: &HashMap<Range<Anchor>, OutlineKind>,
+    ) -> Vec<ArrangedOutline<'a>> {
+        let outlines = outlines.into_iter().collect::<Vec<_>>();
+        // Languages marking public items with a keyword make the unmarked ones private.
+        let marks_public = outlines
+            .iter()
+            .any(|outline| explicit_visibility(outline) == Some(Visibility::Public));
+
+        let mut roots = Vec::new();
+        let mut parents = Vec::<OutlineNode>::new();
+        for outline in outlines {
+            while let Some(parent) = parents.pop() {
+                if parent.outline.depth < outline.depth {
+                    parents.push(parent);
+                    break;
+                }
+                attach(parent, &mut parents, &mut roots);
+            }
+            let parent = parents.last();
+            let visibility = explicit_visibility(outline).or_else(|| {
+                let inherited = parent.map_or(false, |parent| inherits_visibility(parent.outline));
+                (marks_public && !inherited && !is_impl(outline)).then_some(Visibility::Private)
+            });
+            let kind = symbol_kinds
+                .get(&outline.range)
+                .copied()
+                .unwrap_or_else(|| outline_kind(outline, parent.map(|parent| parent.kind)));
+            parents.push(OutlineNode {
+                outline,
+                kind,
+                visibility,
+                children: Vec::new(),
+            });
+        }
+        while let Some(node) = parents.pop() {
+            attach(node, &mut parents, &mut roots);
+        }
+
+        let mut arranged = Vec::new();
+        self.flatten(roots, 0, &mut arranged);
+        arranged
+    }
+
+    fn flatten<'a>(
+        &self,
+        nodes: Vec<OutlineNode<'a>>,
+        depth: usize,
+        arranged: &mut Vec<ArrangedOutline<'a>>,
+    ) {
+        let mut visible_nodes = Vec::new();
+        let mut pending = nodes;
+        pending.reverse();
+        while let Some(node) = pending.pop() {
+            if self.hidden_kinds.contains(&node.kind) {
+                pending.extend(node.children.into_iter().rev());
+            } else {
+                visible_nodes.push(node);
+            }
+        }
+        if self.sort == OutlineSort::Name {
+            visible_nodes.sort_by_cached_key(|node| outline_name(node.outline).to_lowercase());
+        }
+
+        for node in visible_nodes {
+            arranged.push(ArrangedOutline {
+                outline: node.outline,
+                depth,
+                visibility: node.visibility,
+            });
+            self.flatten(node.children, depth + 1, arranged);
+        }
+    }
+}
+
+fn attach<'a>(
+    node: OutlineNode<'a>,
+    parents: &mut [OutlineNode<'a>],
+    roots: &mut Vec<OutlineNode<'a>>,
+) {
+    match parents.last_mut() {
+        Some(parent) => parent.children.push(node),
+        None => roots.push(node),
+    }
+}
+
+fn outline_name(outline: &Outline) -> String {
+    let name = outline
+        .name_ranges
+        .iter()
+        .filter_map(|range| outline.text.get(range.clone()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if name.is_empty() {
+        outline.text.clone()
+    } else {
+        name
+    }
+}
+
+/// The words before the item's name, which outline queries capture as context: keywords and
+/// visibility modifiers.
+fn context_words(outline: &Outline) -> impl Iterator<Item = &str> {
+    let name_start = outline.name_ranges.first().map_or(0, |range| range.start);
+    outline
+        .text
+        .get(..name_start)
+        .unwrap_or_default()
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+}
+
+fn outline_kind(outline: &Outline, parent_kind: Option<OutlineKind>) -> OutlineKind {
+    for word in context_words(outline) {
+        match word {
+            "fn" | "func" | "function" | "def" | "method" => {
+                return if outline_name(outline).starts_with("test") {
+                    OutlineKind::Test
+                } else {
+                    OutlineKind::Function
+                };
+            }
+            "struct" | "class" | "enum" | "trait" | "interface" | "type" | "union" => {
+                return OutlineKind::Type
+            }
+            "impl" | "mod" | "module" | "namespace" | "const" | "static" | "let" | "var" => {
+                return OutlineKind::Other
+            }
+            _ => {}
+        }
+    }
+    if parent_kind == Some(OutlineKind::Type) {
+        OutlineKind::Field
+    } else {
+        OutlineKind::Other
+    }
+}
+
+/// The kind of an item, from the symbol kind reported for it by a language server.
+fn symbol_outline_kind(kind: SymbolKind, name: &str) -> OutlineKind {
+    match kind {
+        SymbolKind::FUNCTION | SymbolKind::METHOD | SymbolKind::CONSTRUCTOR => {
+            if name.starts_with("test") {
+                OutlineKind::Test
+            } else {
+                OutlineKind::Function
+            }
+        }
+        SymbolKind::CLASS
+        | SymbolKind::STRUCT
+        | SymbolKind::ENUM
+        | SymbolKind::INTERFACE
+        | SymbolKind::TYPE_PARAMETER => OutlineKind::Type,
+        SymbolKind::FIELD | SymbolKind::PROPERTY | SymbolKind::ENUM_MEMBER => OutlineKind::Field,
+        _ => OutlineKind::Other,
+    }
+}
+
+/// Implementation blocks have no visibility of their own, unlike the items they contain.
+fn is_impl(outline: &Outline) -> bool {
+    context_words(outline).any(|word| word == "impl")
+}
+
+fn explicit_visibility(outline: &Outline) -> Option<Visibility> {
+    for word in context_words(outline) {
+        match word {
+            "pub" | "public" | "export" => return Some(Visibility::Public),
+            "private" | "protected" => return Some(Visibility::Private),
+            _ => {}
+        }
+    }
+    let name = outline_name(outline);
+    (name.starts_with('_') || name.starts_with('#')).then_some(Visibility::Private)
+}
+
+/// Whether the children of the item are as visible as the item itself, without a modifier of
+/// their own: enum variants, trait items and trait implementations.
+fn inherits_visibility(parent: &Outline) -> bool {
+    let words = context_words(parent).collect::<Vec<_>>();
+    words
+        .iter()
+        .any(|word| matches!(*word, "enum" | "trait" | "interface"))
+        || (words.contains(&"impl") && parent.text.contains(" for "))
+}
+
+/// Converts the document symbols of a language server intersecting the range into outline items,
+/// nested like the symbols are, along with the kinds of the symbols by outline range.
+pub(crate) fn outlines_from_document_symbols(
+    symbols: &[DocumentSymbol],
+    range: Range<Anchor>,
+    snapshot: &BufferSnapshot,
+) -> (Vec<Outline>, HashMap<Range<Anchor>, OutlineKind>) {
+    let range = range.to_offset(snapshot);
+    let mut outlines = Vec::new();
+    let mut kinds = HashMap::default();
+    push_document_symbols(symbols, 0, &range, snapshot, &mut outlines, &mut kinds);
+    (outlines, kinds)
+}
+
+fn push_document_symbols(
+    symbols: &[DocumentSymbol],
+    depth: usize,
+    range: &Range<usize>,
+    snapshot: &BufferSnapshot,
+    outlines: &mut Vec<Outline>,
+    kinds: &mut HashMap<Range<Anchor>, OutlineKind>,
+) {
+    for symbol in symbols {
+        let symbol_range = symbol.range.to_offset(snapshot);
+        if symbol_range.end < range.start || symbol_range.start > range.end {
+            continue;
+        }
+
+        // Show the symbol like outline queries do: its name, preceded by the keywords on its line.
+        let name_start = symbol.selection_range.start.to_offset(snapshot);
+        let line_start = snapshot.point_to_offset(language::Point::new(
+            snapshot.offset_to_point(name_start).row,
+            0,
+        ));
+        let line = snapshot
+            .text_for_range(line_start.max(symbol_range.start)..name_start)
+            .collect::<String>();
+        let prefix = line.trim_start();
+        let text = format!("{prefix}{}", symbol.name);
+        kinds.insert(
+            symbol.range.clone(),
+            symbol_outline_kind(symbol.kind, &symbol.name),
+        );
+        outlines.push(OutlineItem {
+            depth,
+            range: symbol.range.clone(),
+            name_ranges: vec![prefix.len()..text.len()],
+            text,
+            highlight_ranges: Vec::new(),
+            body_range: None,
+            annotation_range: None,
+        });
+        push_document_symbols(
+            &symbol.children,
+            depth + 1,
+            range,
+            snapshot,
+            outlines,
+            kinds,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline(depth: usize, text: &str, name: &str) -> Outline {
+        let name_start = text.find(name).unwrap();
+        OutlineItem {
+            depth,
+            range: Anchor::MIN..Anchor::MAX,
+            text: text.to_string(),
+            highlight_ranges: Vec::new(),
+            name_ranges: vec![name_start..name_start + name.len()],
+            body_range: None,
+            annotation_range: None,
+        }
+    }
+
+    fn display(arranged: &[ArrangedOutline]) -> Vec<String> {
+        arranged
+            .iter()
+            .map(|arranged| {
+                let marker = match arranged.visibility {
+                    Some(Visibility::Public) => "+",
+                    Some(Visibility::Private) => "-",
+                    None => " ",
+                };
+                format!(
+                    "{}{marker}{}",
+                    "  ".repeat(arranged.depth),
+                    arranged.outline.text
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_arrange_outlines() {
+        let outlines = [
+            outline(0, "pub struct Point", "Point"),
+            outline(1, "pub y", "y"),
+            outline(1, "x", "x"),
+            outline(0, "impl Point", "Point"),
+            outline(1, "fn origin", "origin"),
+            outline(1, "pub fn distance", "distance"),
+            outline(0, "enum Axis", "Axis"),
+            outline(1, "Y", "Y"),
+            outline(1, "X", "X"),
+            outline(0, "fn test_distance", "test_distance"),
+        ];
+
+        let mut options = OutlineOptions::default();
+        assert_eq!(
+            display(&options.arrange(&outlines, &HashMap::default())),
+            [
+                "+pub struct Point",
+                "  +pub y",
+                "  -x",
+                " impl Point",
+                "  -fn origin",
+                "  +pub fn distance",
+                "-enum Axis",
+                "   Y",
+                "   X",
+                "-fn test_distance",
+            ]
+        );
+
+        options.sort = OutlineSort::Name;
+        options.hidden_kinds = HashSet::from_iter([OutlineKind::Field, OutlineKind::Test]);
+        assert_eq!(
+            display(&options.arrange(&outlines, &HashMap::default())),
+            [
+                "-enum Axis",
+                "+pub struct Point",
+                " impl Point",
+                "  +pub fn distance",
+                "  -fn origin",
+            ]
+        );
+
+        // The children of hidden items take their place.
+        options.sort = OutlineSort::Position;
+        options.hidden_kinds = HashSet::from_iter([OutlineKind::Other]);
+        assert_eq!(
+            display(&options.arrange(&outlines, &HashMap::default())),
+            [
+                "+pub struct Point",
+                "  +pub y",
+                "  -x",
+                "-fn origin",
+                "+pub fn distance",
+                "-enum Axis",
+                "   Y",
+                "   X",
+                "-fn test_distance",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arrange_outlines_with_symbol_kinds() {
+        // Without keywords to go by, the kinds reported by the language server are used.
+        let outlines = [outline(0, "Point", "Point")];
+        let symbol_kinds = HashMap::from_iter([(
+            Anchor::MIN..Anchor::MAX,
+            symbol_outline_kind(SymbolKind::STRUCT, "Point"),
+        )]);
+        let mut options = OutlineOptions::default();
+        options.hidden_kinds = HashSet::from_iter([OutlineKind::Other]);
+        assert_eq!(
+            display(&options.arrange(&outlines, &HashMap::default())),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            display(&options.arrange(&outlines, &symbol_kinds)),
+            [" Point"]
+        );
+
+        assert_eq!(
+            symbol_outline_kind(SymbolKind::METHOD, "test_distance"),
+            OutlineKind::Test
+        );
+        assert_eq!(
+            symbol_outline_kind(SymbolKind::ENUM_MEMBER, "X"),
+            OutlineKind::Field
+        );
+        assert_eq!(
+            symbol_outline_kind(SymbolKind::MODULE, "geometry"),
+            OutlineKind::Other
+        );
+    }
+}
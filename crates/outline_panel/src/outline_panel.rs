@@ -1,3 +1,4 @@
+mod outline_options;
 mod outline_panel_settings;
 
 use std::{
@@ -21,6 +22,7 @@ use editor::{
     ExcerptRange, MultiBufferSnapshot, RangeToAnchorExt, ShowScrollbar,
 };
 use file_icons::FileIcons;
+use futures::{future::Shared, FutureExt};
 use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
 use gpui::{
     actions, anchored, deferred, div, point, px, size, uniform_list, Action, AnyElement,
@@ -35,8 +37,9 @@ use itertools::Itertools;
 use language::{BufferId, BufferSnapshot, OffsetRangeExt, OutlineItem};
 use menu::{Cancel, SelectFirst, SelectLast, SelectNext, SelectPrev};
 
+use outline_options::{OutlineKind, OutlineOptions, OutlineSort, Visibility};
 use outline_panel_settings::{OutlinePanelDockPosition, OutlinePanelSettings, ShowIndentGuides};
-use project::{File, Fs, Project, ProjectItem};
+use project::{DocumentSymbol, File, Fs, Project, ProjectItem};
 use search::{BufferSearchBar, ProjectSearchView};
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
@@ -50,9 +53,9 @@ use workspace::{
     searchable::{SearchEvent, SearchableItem},
     ui::{
         h_flex, v_flex, ActiveTheme, ButtonCommon, Clickable, Color, ContextMenu, FluentBuilder,
-        HighlightedLabel, Icon, IconButton, IconButtonShape, IconName, IconSize, Label,
-        LabelCommon, ListItem, Scrollbar, ScrollbarState, StyledExt, StyledTypography, Toggleable,
-        Tooltip,
+        HighlightedLabel, Icon, IconButton, IconButtonShape, IconName, IconPosition, IconSize,
+        Indicator, Label, LabelCommon, ListItem, Scrollbar, ScrollbarState, StyledExt,
+        StyledTypography, Toggleable, Tooltip,
     },
     OpenInTerminal, WeakItemHandle, Workspace,
 };
@@ -118,6 +121,7 @@ pub struct OutlinePanel {
     hide_scrollbar_task: Option<Task<()>>,
     max_width_item_index: Option<usize>,
     preserve_selection_on_buffer_fold_toggles: HashSet<BufferId>,
+    outline_options: OutlineOptions,
 }
 
 #[derive(Debug)]
@@ -301,6 +305,7 @@ struct CachedEntry {
     depth: usize,
     string_match: Option<StringMatch>,
     entry: PanelEntry,
+    visibility: Option<Visibility>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -315,6 +320,8 @@ enum CollapsedEntry {
 struct Excerpt {
     range: ExcerptRange<language::Anchor>,
     outlines: ExcerptOutlines,
+    /// The kinds reported by the language server for the outline items, by range.
+    symbol_kinds: HashMap<Range<language::Anchor>, OutlineKind>,
 }
 
 impl Excerpt {
@@ -720,6 +727,7 @@ impl OutlinePanel {
                 updating_fs_entries: false,
                 new_entries_for_fs_update: HashSet::default(),
                 preserve_selection_on_buffer_fold_toggles: HashSet::default(),
+                outline_options: OutlineOptions::default(),
                 fs_entries_update_task: Task::ready(()),
                 cached_entries_update_task: Task::ready(()),
                 reveal_selection_task: Task::ready(Ok(())),
@@ -1226,6 +1234,64 @@ impl OutlinePanel {
         cx.notify();
     }
 
+    fn deploy_options_menu(&mut self, position: Point<Pixels>, cx: &mut ViewContext<Self>) {
+        let outline_panel = cx.view().downgrade();
+        let options = self.outline_options.clone();
+        let context_menu = ContextMenu::build(cx, |menu, _| {
+            let menu =
+                OutlineKind::FILTERABLE
+                    .into_iter()
+                    .fold(menu.header("Show"), |menu, kind| {
+                        menu.toggleable_entry(
+                            kind.label(),
+                            !options.hidden_kinds.contains(&kind),
+                            IconPosition::Start,
+                            None,
+                            update_outline_options(outline_panel.clone(), move |options| {
+                                options.toggle_kind(kind)
+                            }),
+                        )
+                    });
+            menu.separator()
+                .header("Sort")
+                .toggleable_entry(
+                    "By Position",
+                    options.sort == OutlineSort::Position,
+                    IconPosition::Start,
+                    None,
+                    update_outline_options(outline_panel.clone(), |options| {
+                        options.sort = OutlineSort::Position
+                    }),
+                )
+                .toggleable_entry(
+                    "By Name",
+                    options.sort == OutlineSort::Name,
+                    IconPosition::Start,
+                    None,
+                    update_outline_options(outline_panel.clone(), |options| {
+                        options.sort = OutlineSort::Name
+                    }),
+                )
+                .separator()
+                .toggleable_entry(
+                    "Visibility Markers",
+                    options.show_visibility,
+                    IconPosition::Start,
+                    None,
+                    update_outline_options(outline_panel, |options| {
+                        options.show_visibility = !options.show_visibility
+                    }),
+                )
+        });
+        cx.focus_view(&context_menu);
+        let subscription = cx.subscribe(&context_menu, |outline_panel, _, _: &DismissEvent, cx| {
+            outline_panel.context_menu.take();
+            cx.notify();
+        });
+        self.context_menu = Some((context_menu, position, subscription));
+        cx.notify();
+    }
+
     fn is_unfoldable(&self, entry: &PanelEntry) -> bool {
         matches!(entry, PanelEntry::FoldedDirs(..))
     }
@@ -1876,6 +1942,7 @@ impl OutlinePanel {
         ))
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn render_outline(
         &self,
         buffer_id: BufferId,
@@ -1883,6 +1950,7 @@ impl OutlinePanel {
         rendered_outline: &Outline,
         depth: usize,
         string_match: Option<&StringMatch>,
+        visibility: Option<Visibility>,
         cx: &mut ViewContext<Self>,
     ) -> Stateful<Div> {
         let (item_id, label_element) = (
@@ -1899,6 +1967,17 @@ impl OutlinePanel {
             )
             .into_any_element(),
         );
+        let label_element = match visibility {
+            Some(visibility) => h_flex()
+                .gap_1()
+                .child(Indicator::dot().color(match visibility {
+                    Visibility::Public => Color::Created,
+                    Visibility::Private => Color::Muted,
+                }))
+                .child(label_element)
+                .into_any_element(),
+            None => label_element,
+        };
         let is_active = match self.selected_entry() {
             Some(PanelEntry::Outline(OutlineEntry::Outline(
                 selected_buffer_id,
@@ -2327,25 +2406,31 @@ impl OutlinePanel {
                             .2
                             .push(excerpt_id);
 
-                        let outlines = match outline_panel
+                        let (outlines, symbol_kinds) = match outline_panel
                             .excerpts
                             .get(&buffer_id)
                             .and_then(|excerpts| excerpts.get(&excerpt_id))
                         {
                             Some(old_excerpt) => match &old_excerpt.outlines {
-                                ExcerptOutlines::Outlines(outlines) => {
-                                    ExcerptOutlines::Outlines(outlines.clone())
+                                ExcerptOutlines::Outlines(outlines) => (
+                                    ExcerptOutlines::Outlines(outlines.clone()),
+                                    old_excerpt.symbol_kinds.clone(),
+                                ),
+                                ExcerptOutlines::Invalidated(_) => {
+                                    (ExcerptOutlines::NotFetched, HashMap::default())
+                                }
+                                ExcerptOutlines::NotFetched => {
+                                    (ExcerptOutlines::NotFetched, HashMap::default())
                                 }
-                                ExcerptOutlines::Invalidated(_) => ExcerptOutlines::NotFetched,
-                                ExcerptOutlines::NotFetched => ExcerptOutlines::NotFetched,
                             },
-                            None => ExcerptOutlines::NotFetched,
+                            None => (ExcerptOutlines::NotFetched, HashMap::default()),
                         };
                         new_excerpts.entry(buffer_id).or_default().insert(
                             excerpt_id,
                             Excerpt {
                                 range: excerpt_range,
                                 outlines,
+                                symbol_kinds,
                             },
                         );
                         buffer_excerpts
@@ -2912,22 +2997,37 @@ impl OutlinePanel {
 
         let syntax_theme = cx.theme().syntax().clone();
         for (buffer_id, (buffer_snapshot, excerpt_ranges)) in excerpt_fetch_ranges {
+            let document_symbols = self.document_symbols(buffer_id, cx);
             for (excerpt_id, excerpt_range) in excerpt_ranges {
                 let syntax_theme = syntax_theme.clone();
                 let buffer_snapshot = buffer_snapshot.clone();
+                let document_symbols = document_symbols.clone();
                 self.outline_fetch_tasks.insert(
                     (buffer_id, excerpt_id),
                     cx.spawn(|outline_panel, mut cx| async move {
-                        let fetched_outlines = cx
+                        let document_symbols = document_symbols.await;
+                        let (fetched_outlines, symbol_kinds) = cx
                             .background_executor()
                             .spawn(async move {
-                                buffer_snapshot
+                                // Prefer the symbol hierarchy of the language server, when it
+                                // reports one, to the outline query of the language.
+                                let (outlines, symbol_kinds) =
+                                    outline_options::outlines_from_document_symbols(
+                                        &document_symbols,
+                                        excerpt_range.context.clone(),
+                                        &buffer_snapshot,
+                                    );
+                                if !outlines.is_empty() {
+                                    return (outlines, symbol_kinds);
+                                }
+                                let outlines = buffer_snapshot
                                     .outline_items_containing(
                                         excerpt_range.context,
                                         false,
                                         Some(&syntax_theme),
                                     )
-                                    .unwrap_or_default()
+                                    .unwrap_or_default();
+                                (outlines, HashMap::default())
                             })
                             .await;
                         outline_panel
@@ -2939,6 +3039,7 @@ impl OutlinePanel {
                                     .get_mut(&excerpt_id)
                                 {
                                     excerpt.outlines = ExcerptOutlines::Outlines(fetched_outlines);
+                                    excerpt.symbol_kinds = symbol_kinds;
                                 }
                                 outline_panel.update_cached_entries(Some(UPDATE_DEBOUNCE), cx);
                             })
@@ -2949,6 +3050,24 @@ impl OutlinePanel {
         }
     }
 
+    fn document_symbols(
+        &self,
+        buffer_id: BufferId,
+        cx: &mut ViewContext<Self>,
+    ) -> Shared<Task<Arc<Vec<DocumentSymbol>>>> {
+        let Some(buffer) = self
+            .active_editor()
+            .and_then(|editor| editor.read(cx).buffer().read(cx).buffer(buffer_id))
+        else {
+            return Task::ready(Arc::default()).shared();
+        };
+        let symbols = self
+            .project
+            .update(cx, |project, cx| project.document_symbols(&buffer, cx));
+        cx.spawn(|_, _| async move { Arc::new(symbols.await.log_err().unwrap_or_default()) })
+            .shared()
+    }
+
     fn is_singleton_active(&self, cx: &AppContext) -> bool {
         self.active_editor().map_or(false, |active_editor| {
             active_editor.read(cx).buffer().read(cx).is_singleton()
@@ -3553,6 +3672,7 @@ impl OutlinePanel {
             depth,
             entry,
             string_match: None,
+            visibility: None,
         });
     }
 
@@ -3729,18 +3849,26 @@ impl OutlinePanel {
                     continue;
                 }
 
-                for outline in excerpt.iter_outlines() {
+                for arranged in self
+                    .outline_options
+                    .arrange(excerpt.iter_outlines(), &excerpt.symbol_kinds)
+                {
                     self.push_entry(
                         state,
                         track_matches,
                         PanelEntry::Outline(OutlineEntry::Outline(
                             buffer_id,
                             excerpt_id,
-                            outline.clone(),
+                            arranged.outline.clone(),
                         )),
-                        outline_base_depth + outline.depth,
+                        outline_base_depth + arranged.depth,
                         cx,
                     );
+                    if self.outline_options.show_visibility {
+                        if let Some(entry) = state.entries.last_mut() {
+                            entry.visibility = arranged.visibility;
+                        }
+                    }
                 }
             }
         }
@@ -4166,6 +4294,7 @@ impl OutlinePanel {
                                     &outline,
                                     cached_entry.depth,
                                     cached_entry.string_match.as_ref(),
+                                    cached_entry.visibility,
                                     cx,
                                 )),
                                 PanelEntry::Search(SearchEntry {
@@ -4272,6 +4401,19 @@ impl OutlinePanel {
                 .p_2()
                 .w_full()
                 .child(self.filter_editor.clone())
+                .child(
+                    IconButton::new("outline-panel-options", IconName::Filter)
+                        .icon_color(if self.outline_options.is_default() {
+                            Color::Default
+                        } else {
+                            Color::Accent
+                        })
+                        .tooltip(|cx| Tooltip::text("Outline Options", cx))
+                        .shape(IconButtonShape::Square)
+                        .on_click(cx.listener(|outline_panel, event: &gpui::ClickEvent, cx| {
+                            outline_panel.deploy_options_menu(event.down.position, cx);
+                        })),
+                )
                 .child(
                     div().child(
                         IconButton::new(
@@ -4712,6 +4854,20 @@ fn subscribe_for_editor_events(
     )
 }
 
+fn update_outline_options(
+    outline_panel: WeakView<OutlinePanel>,
+    update: impl Fn(&mut OutlineOptions) + 'static,
+) -> impl Fn(&mut WindowContext) + 'static {
+    move |cx| {
+        outline_panel
+            .update(cx, |outline_panel, cx| {
+                update(&mut outline_panel.outline_options);
+                outline_panel.update_cached_entries(None, cx);
+            })
+            .ok();
+    }
+}
+
 fn empty_icon() -> AnyElement {
     h_flex()
         .size(IconSize::default().rems())
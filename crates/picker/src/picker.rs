@@ -3,14 +3,17 @@ use editor::{scroll::Autoscroll, Editor};
 use gpui::{
     actions, div, impl_actions, list, prelude::*, uniform_list, AnyElement, AppContext, ClickEvent,
     DismissEvent, EventEmitter, FocusHandle, FocusableView, Length, ListSizingBehavior, ListState,
-    MouseButton, MouseUpEvent, Render, ScrollStrategy, Task, UniformListScrollHandle, View,
-    ViewContext, WindowContext,
+    Model, MouseButton, MouseUpEvent, Pixels, Render, ScrollStrategy, Task,
+    UniformListScrollHandle, View, ViewContext, WindowContext,
 };
 use head::Head;
+use language::{Bias, Buffer, Point};
 use serde::Deserialize;
-use std::{sync::Arc, time::Duration};
+use settings::Settings;
+use std::{ops::Range, sync::Arc, time::Duration};
 use ui::{prelude::*, v_flex, Color, Divider, Label, ListItem, ListItemSpacing};
-use workspace::ModalView;
+use util::ResultExt;
+use workspace::{ModalView, WorkspaceSettings};
 
 mod head;
 pub mod highlighted_match_with_paths;
@@ -20,7 +23,13 @@ enum ElementContainer {
     UniformList(UniformListScrollHandle),
 }
 
-actions!(picker, [ConfirmCompletion]);
+actions!(picker, [ConfirmCompletion, TogglePreview]);
+
+const PREVIEW_WIDTH_REMS: f32 = 36.;
+const PREVIEW_MIN_HEIGHT_REMS: f32 = 20.;
+/// How long the selection has to rest on a match before it is previewed, so that moving
+/// through the list doesn't load every match on the way.
+const PREVIEW_DEBOUNCE: Duration = Duration::from_millis(50);
 
 /// ConfirmInput is an alternative editor action which - instead of selecting active picker entry - treats pickers editor input literally,
 /// performing some kind of action on it.
@@ -31,6 +40,13 @@ pub struct ConfirmInput {
 
 impl_actions!(picker, [ConfirmInput]);
 
+/// A location shown in the preview pane of a picker.
+pub struct PickerPreview {
+    pub buffer: Model<Buffer>,
+    /// The rows to highlight and scroll to. The start of the buffer is shown when this is `None`.
+    pub range: Option<Range<Point>>,
+}
+
 struct PendingUpdateMatches {
     delegate_update_matches: Option<Task<()>>,
     _task: Task<Result<()>>,
@@ -49,6 +65,11 @@ pub struct Picker<D: PickerDelegate> {
     ///
     /// Set this to `false` when rendering the `Picker` as part of a larger modal.
     is_modal: bool,
+
+    /// Whether the preview pane is shown, for delegates that support previews.
+    show_preview: bool,
+    preview: Option<View<Editor>>,
+    pending_preview: Option<Task<()>>,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
@@ -152,6 +173,19 @@ pub trait PickerDelegate: Sized + 'static {
     fn render_footer(&self, _: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
         None
     }
+
+    /// Whether the matches of this picker can be shown in the preview pane.
+    fn supports_preview(&self) -> bool {
+        false
+    }
+    /// Loads the location of the match at `ix` to show in the preview pane.
+    fn preview(
+        &self,
+        _ix: usize,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Task<Result<PickerPreview>>> {
+        None
+    }
 }
 
 impl<D: PickerDelegate> FocusableView for Picker<D> {
@@ -205,6 +239,8 @@ impl<D: PickerDelegate> Picker<D> {
     }
 
     fn new(delegate: D, container: ContainerKind, head: Head, cx: &mut ViewContext<Self>) -> Self {
+        let show_preview =
+            delegate.supports_preview() && WorkspaceSettings::get_global(cx).show_picker_preview;
         let mut this = Self {
             delegate,
             head,
@@ -214,6 +250,9 @@ impl<D: PickerDelegate> Picker<D> {
             width: None,
             max_height: Some(rems(18.).into()),
             is_modal: true,
+            show_preview,
+            preview: None,
+            pending_preview: None,
         };
         this.update_matches("".to_string(), cx);
         // give the delegate 4ms to render the first set of suggestions.
@@ -290,7 +329,101 @@ impl<D: PickerDelegate> Picker<D> {
             if scroll_to_index {
                 self.scroll_to_item_index(ix);
             }
+            self.update_preview(cx);
+        }
+    }
+
+    /// The width the preview pane adds to the picker, if it is shown.
+    ///
+    /// Views wrapping the picker in a container of a fixed width should widen it by this much.
+    pub fn preview_width(&self, cx: &WindowContext) -> Option<Pixels> {
+        self.show_preview
+            .then(|| rems(PREVIEW_WIDTH_REMS).to_pixels(cx.rem_size()))
+    }
+
+    fn toggle_preview(&mut self, _: &TogglePreview, cx: &mut ViewContext<Self>) {
+        if !self.delegate.supports_preview() {
+            cx.propagate();
+            return;
+        }
+        self.show_preview = !self.show_preview;
+        self.update_preview(cx);
+        cx.notify();
+    }
+
+    fn update_preview(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.show_preview || self.delegate.match_count() == 0 {
+            self.preview = None;
+            self.pending_preview = None;
+            return;
         }
+
+        let ix = self.delegate.selected_index();
+        self.pending_preview = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(PREVIEW_DEBOUNCE).await;
+            let Ok(load_preview) = this.update(&mut cx, |this, cx| {
+                let load_preview = this.delegate.preview(ix, cx);
+                if load_preview.is_none() {
+                    this.preview = None;
+                    cx.notify();
+                }
+                load_preview
+            }) else {
+                return;
+            };
+            let Some(load_preview) = load_preview else {
+                return;
+            };
+            let Some(preview) = load_preview.await.log_err() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.set_preview(preview, cx);
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn set_preview(&mut self, preview: PickerPreview, cx: &mut ViewContext<Self>) {
+        let editor = match &self.preview {
+            Some(editor)
+                if editor.read(cx).buffer().read(cx).as_singleton().as_ref()
+                    == Some(&preview.buffer) =>
+            {
+                editor.clone()
+            }
+            _ => {
+                let editor = cx.new_view(|cx| {
+                    let mut editor = Editor::for_buffer(preview.buffer, None, cx);
+                    editor.set_read_only(true);
+                    editor.set_searchable(false);
+                    editor
+                });
+                self.preview = Some(editor.clone());
+                editor
+            }
+        };
+
+        editor.update(cx, |editor, cx| {
+            editor.clear_row_highlights::<PreviewRowHighlights>();
+            match preview.range {
+                Some(range) => {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let start = snapshot.clip_point(range.start, Bias::Left);
+                    let end = snapshot.clip_point(range.end, Bias::Right);
+                    let range = snapshot.anchor_before(start)..snapshot.anchor_after(end);
+                    editor.highlight_rows::<PreviewRowHighlights>(
+                        range,
+                        cx.theme().colors().editor_highlighted_line_background,
+                        true,
+                        cx,
+                    );
+                    editor.request_autoscroll(Autoscroll::center(), cx);
+                }
+                None => editor.set_scroll_position(gpui::Point::default(), cx),
+            }
+        });
     }
 
     pub fn select_next(&mut self, _: &menu::SelectNext, cx: &mut ViewContext<Self>) {
@@ -479,6 +612,7 @@ impl<D: PickerDelegate> Picker<D> {
 
         let index = self.delegate.selected_index();
         self.scroll_to_item_index(index);
+        self.update_preview(cx);
         self.pending_update_matches = None;
         if let Some(secondary) = self.confirm_on_update.take() {
             self.do_confirm(secondary, cx);
@@ -578,6 +712,11 @@ impl<D: PickerDelegate> Picker<D> {
         }
     }
 
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn preview_text(&self, cx: &AppContext) -> Option<String> {
+        Some(self.preview.as_ref()?.read(cx).text(cx))
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn logical_scroll_top_index(&self) -> usize {
         match &self.element_container {
@@ -589,17 +728,23 @@ impl<D: PickerDelegate> Picker<D> {
     }
 }
 
+enum PreviewRowHighlights {}
+
 impl<D: PickerDelegate> EventEmitter<DismissEvent> for Picker<D> {}
 impl<D: PickerDelegate> ModalView for Picker<D> {}
 
 impl<D: PickerDelegate> Render for Picker<D> {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         let editor_position = self.delegate.editor_position();
+        let preview = self.show_preview.then(|| self.preview.clone());
 
-        v_flex()
+        div()
+            .flex()
             .key_context("Picker")
             .size_full()
-            .when_some(self.width, |el, width| el.w(width))
+            .when(preview.is_none(), |el| {
+                el.when_some(self.width, |el, width| el.w(width))
+            })
             .overflow_hidden()
             // This is a bit of a hack to remove the modal styling when we're rendering the `Picker`
             // as a part of a modal rather than the entire modal.
@@ -615,49 +760,72 @@ impl<D: PickerDelegate> Render for Picker<D> {
             .on_action(cx.listener(Self::secondary_confirm))
             .on_action(cx.listener(Self::confirm_completion))
             .on_action(cx.listener(Self::confirm_input))
-            .children(match &self.head {
-                Head::Editor(editor) => {
-                    if editor_position == PickerEditorPosition::Start {
-                        Some(self.delegate.render_editor(&editor.clone(), cx))
-                    } else {
-                        None
-                    }
-                }
-                Head::Empty(empty_head) => Some(div().child(empty_head.clone())),
-            })
-            .when(self.delegate.match_count() > 0, |el| {
+            .on_action(cx.listener(Self::toggle_preview))
+            .child(
+                v_flex()
+                    .min_w_0()
+                    .map(|el| match self.width {
+                        Some(width) if preview.is_some() => el.flex_none().w(width),
+                        _ => el.flex_1(),
+                    })
+                    .children(match &self.head {
+                        Head::Editor(editor) => {
+                            if editor_position == PickerEditorPosition::Start {
+                                Some(self.delegate.render_editor(&editor.clone(), cx))
+                            } else {
+                                None
+                            }
+                        }
+                        Head::Empty(empty_head) => Some(div().child(empty_head.clone())),
+                    })
+                    .when(self.delegate.match_count() > 0, |el| {
+                        el.child(
+                            v_flex()
+                                .flex_grow()
+                                .when_some(self.max_height, |div, max_h| div.max_h(max_h))
+                                .overflow_hidden()
+                                .children(self.delegate.render_header(cx))
+                                .child(self.render_element_container(cx)),
+                        )
+                    })
+                    .when(self.delegate.match_count() == 0, |el| {
+                        el.child(
+                            v_flex().flex_grow().py_2().child(
+                                ListItem::new("empty_state")
+                                    .inset(true)
+                                    .spacing(ListItemSpacing::Sparse)
+                                    .disabled(true)
+                                    .child(
+                                        Label::new(self.delegate.no_matches_text(cx))
+                                            .color(Color::Muted),
+                                    ),
+                            ),
+                        )
+                    })
+                    .children(self.delegate.render_footer(cx))
+                    .children(match &self.head {
+                        Head::Editor(editor) => {
+                            if editor_position == PickerEditorPosition::End {
+                                Some(self.delegate.render_editor(&editor.clone(), cx))
+                            } else {
+                                None
+                            }
+                        }
+                        Head::Empty(empty_head) => Some(div().child(empty_head.clone())),
+                    }),
+            )
+            .when_some(preview, |el, preview| {
                 el.child(
-                    v_flex()
-                        .flex_grow()
-                        .when_some(self.max_height, |div, max_h| div.max_h(max_h))
+                    div()
+                        .flex_none()
+                        .w(rems(PREVIEW_WIDTH_REMS))
+                        .min_h(rems(PREVIEW_MIN_HEIGHT_REMS))
+                        .border_l_1()
+                        .border_color(cx.theme().colors().border_variant)
+                        .bg(cx.theme().colors().editor_background)
                         .overflow_hidden()
-                        .children(self.delegate.render_header(cx))
-                        .child(self.render_element_container(cx)),
+                        .children(preview),
                 )
             })
-            .when(self.delegate.match_count() == 0, |el| {
-                el.child(
-                    v_flex().flex_grow().py_2().child(
-                        ListItem::new("empty_state")
-                            .inset(true)
-                            .spacing(ListItemSpacing::Sparse)
-                            .disabled(true)
-                            .child(
-                                Label::new(self.delegate.no_matches_text(cx)).color(Color::Muted),
-                            ),
-                    ),
-                )
-            })
-            .children(self.delegate.render_footer(cx))
-            .children(match &self.head {
-                Head::Editor(editor) => {
-                    if editor_position == PickerEditorPosition::End {
-                        Some(self.delegate.render_editor(&editor.clone(), cx))
-                    } else {
-                        None
-                    }
-                }
-                Head::Empty(empty_head) => Some(div().child(empty_head.clone())),
-            })
     }
 }
@@ -0,0 +1,464 @@
+use crate::{
+    worktree_store::{WorktreeStore, WorktreeStoreEvent},
+    Project, ProjectEntryId, ProjectItem, ProjectPath,
+};
+use anyhow::{anyhow, Context as _, Result};
+use collections::HashMap;
+use fs::MTime;
+use futures::{future::Shared, FutureExt as _};
+use gpui::{AppContext, EventEmitter, Model, ModelContext, Task, WeakModel};
+use language::{DiskState, File};
+use std::{ops::Range, path::Path, sync::Arc};
+use worktree::{LoadedBinaryFile, PathChange, Worktree};
+
+pub enum BinaryItemEvent {
+    Edited,
+    Saved,
+    Reloaded,
+    ReloadNeeded,
+    FileHandleChanged,
+}
+
+impl EventEmitter<BinaryItemEvent> for BinaryItem {}
+
+/// The raw contents of a file that can't be opened as a text buffer.
+///
+/// Edits overwrite bytes in place, so the length of the contents never changes.
+pub struct BinaryItem {
+    pub file: Arc<dyn File>,
+    bytes: Vec<u8>,
+    version: usize,
+    saved_version: usize,
+    saved_mtime: Option<MTime>,
+    reload_task: Option<Task<()>>,
+}
+
+impl BinaryItem {
+    fn new(file: Arc<dyn File>, bytes: Vec<u8>) -> Self {
+        Self {
+            saved_mtime: file.disk_state().mtime(),
+            file,
+            bytes,
+            version: 0,
+            saved_version: 0,
+            reload_task: None,
+        }
+    }
+
+    pub fn project_path(&self, cx: &AppContext) -> ProjectPath {
+        ProjectPath {
+            worktree_id: self.file.worktree_id(cx),
+            path: self.file.path().clone(),
+        }
+    }
+
+    pub fn path(&self) -> &Arc<Path> {
+        self.file.path()
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Incremented on every edit, save and reload.
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.version != self.saved_version
+    }
+
+    /// Whether the file changed on disk while there were unsaved edits.
+    pub fn has_conflict(&self) -> bool {
+        self.is_dirty()
+            && match self.file.disk_state() {
+                DiskState::Present { mtime } => Some(mtime) != self.saved_mtime,
+                DiskState::New | DiskState::Deleted => false,
+            }
+    }
+
+    /// Overwrites the bytes starting at `offset`, dropping any bytes that would extend past the
+    /// end of the contents.
+    pub fn overwrite(&mut self, offset: usize, bytes: &[u8], cx: &mut ModelContext<Self>) {
+        let range = self.clip_range(offset..offset.saturating_add(bytes.len()));
+        if range.is_empty() || self.bytes[range.clone()] == bytes[..range.len()] {
+            return;
+        }
+        let len = range.len();
+        self.bytes[range].copy_from_slice(&bytes[..len]);
+        self.version += 1;
+        cx.emit(BinaryItemEvent::Edited);
+        cx.notify();
+    }
+
+    pub fn clip_range(&self, range: Range<usize>) -> Range<usize> {
+        range.start.min(self.bytes.len())..range.end.min(self.bytes.len())
+    }
+
+    fn did_save(&mut self, version: usize, file: Arc<dyn File>, cx: &mut ModelContext<Self>) {
+        self.saved_version = version;
+        self.saved_mtime = file.disk_state().mtime();
+        self.file = file;
+        cx.emit(BinaryItemEvent::Saved);
+        cx.notify();
+    }
+
+    fn file_updated(&mut self, new_file: Arc<dyn File>, cx: &mut ModelContext<Self>) {
+        let mut file_changed = false;
+
+        let old_file = self.file.as_ref();
+        if new_file.path() != old_file.path() {
+            file_changed = true;
+        }
+
+        let old_state = old_file.disk_state();
+        let new_state = new_file.disk_state();
+        if old_state != new_state {
+            file_changed = true;
+            if !self.is_dirty() && new_state.mtime() != self.saved_mtime {
+                if let DiskState::Present { .. } = new_state {
+                    cx.emit(BinaryItemEvent::ReloadNeeded)
+                }
+            }
+        }
+
+        self.file = new_file;
+        if file_changed {
+            cx.emit(BinaryItemEvent::FileHandleChanged);
+            cx.notify();
+        }
+    }
+
+    fn reload(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let Some(local_file) = self.file.as_local() else {
+            return Task::ready(Err(anyhow!(
+                "reloading remote binary files is not supported"
+            )));
+        };
+        let content = local_file.load_bytes(cx);
+        let (tx, rx) = futures::channel::oneshot::channel();
+        self.reload_task = Some(cx.spawn(|this, mut cx| async move {
+            let result = match content.await.context("reloading binary file") {
+                Ok(bytes) => this.update(&mut cx, |this, cx| {
+                    this.bytes = bytes;
+                    this.version += 1;
+                    this.saved_version = this.version;
+                    this.saved_mtime = this.file.disk_state().mtime();
+                    cx.emit(BinaryItemEvent::Reloaded);
+                    cx.notify();
+                }),
+                Err(error) => Err(error),
+            };
+            tx.send(result).ok();
+        }));
+        cx.background_executor()
+            .spawn(async move { rx.await.context("reload was cancelled")? })
+    }
+}
+
+impl ProjectItem for BinaryItem {
+    fn try_open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Model<Self>>>> {
+        Some(project.update(cx, |project, cx| project.open_binary(path.clone(), cx)))
+    }
+
+    fn entry_id(&self, _: &AppContext) -> Option<ProjectEntryId> {
+        worktree::File::from_dyn(Some(&self.file))?.entry_id
+    }
+
+    fn project_path(&self, cx: &AppContext) -> Option<ProjectPath> {
+        Some(self.project_path(cx))
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.is_dirty()
+    }
+}
+
+type LoadingBinary = Shared<Task<Result<Model<BinaryItem>, Arc<anyhow::Error>>>>;
+
+/// Opens the files of local worktrees as raw bytes, and keeps the opened ones up to date with
+/// the changes on disk.
+pub struct BinaryStore {
+    worktree_store: Model<WorktreeStore>,
+    opened_binaries: HashMap<ProjectPath, WeakModel<BinaryItem>>,
+    loading_binaries: HashMap<ProjectPath, LoadingBinary>,
+}
+
+impl BinaryStore {
+    pub fn new(worktree_store: Model<WorktreeStore>, cx: &mut ModelContext<Self>) -> Self {
+        cx.subscribe(&worktree_store, |this, _, event, cx| {
+            if let WorktreeStoreEvent::WorktreeAdded(worktree) = event {
+                this.subscribe_to_worktree(worktree, cx);
+            }
+        })
+        .detach();
+        Self {
+            worktree_store,
+            opened_binaries: HashMap::default(),
+            loading_binaries: HashMap::default(),
+        }
+    }
+
+    pub fn binaries(&self) -> impl '_ + Iterator<Item = Model<BinaryItem>> {
+        self.opened_binaries
+            .values()
+            .filter_map(|binary| binary.upgrade())
+    }
+
+    pub fn open_binary(
+        &mut self,
+        project_path: ProjectPath,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<BinaryItem>>> {
+        if let Some(binary) = self
+            .opened_binaries
+            .get(&project_path)
+            .and_then(|binary| binary.upgrade())
+        {
+            return Task::ready(Ok(binary));
+        }
+
+        let Some(worktree) = self
+            .worktree_store
+            .read(cx)
+            .worktree_for_id(project_path.worktree_id, cx)
+        else {
+            return Task::ready(Err(anyhow!("no such worktree")));
+        };
+
+        let loading = self
+            .loading_binaries
+            .entry(project_path.clone())
+            .or_insert_with(|| {
+                let load_file = worktree.update(cx, |worktree, cx| {
+                    worktree.load_binary_file(project_path.path.as_ref(), cx)
+                });
+                let project_path = project_path.clone();
+                cx.spawn(move |this, mut cx| async move {
+                    let loaded = load_file.await;
+                    this.update(&mut cx, |this, cx| {
+                        this.loading_binaries.remove(&project_path);
+                        let LoadedBinaryFile { file, content } = loaded.map_err(Arc::new)?;
+                        let binary = cx.new_model(|_| BinaryItem::new(file, content));
+                        cx.subscribe(&binary, Self::on_binary_event).detach();
+                        this.opened_binaries
+                            .insert(project_path, binary.downgrade());
+                        Ok(binary)
+                    })
+                    .map_err(Arc::new)?
+                })
+                .shared()
+            })
+            .clone();
+        cx.background_executor()
+            .spawn(async move { loading.await.map_err(|error| anyhow!("{error:#}")) })
+    }
+
+    pub fn save_binary(
+        &mut self,
+        binary: Model<BinaryItem>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let (path, version, bytes, worktree) = {
+            let binary = binary.read(cx);
+            let Some(file) = worktree::File::from_dyn(Some(&binary.file)) else {
+                return Task::ready(Err(anyhow!("binary file has no worktree")));
+            };
+            (
+                file.path.clone(),
+                binary.version,
+                binary.bytes.clone(),
+                file.worktree.clone(),
+            )
+        };
+        let write = worktree.update(cx, |worktree, cx| {
+            worktree.write_binary_file(&path, bytes, cx)
+        });
+        cx.spawn(move |_, mut cx| async move {
+            let file = write.await?;
+            binary.update(&mut cx, |binary, cx| binary.did_save(version, file, cx))
+        })
+    }
+
+    pub fn reload_binary(
+        &mut self,
+        binary: Model<BinaryItem>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        binary.update(cx, |binary, cx| binary.reload(cx))
+    }
+
+    fn on_binary_event(
+        &mut self,
+        binary: Model<BinaryItem>,
+        event: &BinaryItemEvent,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let BinaryItemEvent::ReloadNeeded = event {
+            binary
+                .update(cx, |binary, cx| binary.reload(cx))
+                .detach_and_log_err(cx);
+        }
+    }
+
+    fn subscribe_to_worktree(&mut self, worktree: &Model<Worktree>, cx: &mut ModelContext<Self>) {
+        cx.subscribe(worktree, |this, worktree, event, cx| {
+            if let worktree::Event::UpdatedEntries(changes) = event {
+                if worktree.read(cx).is_local() {
+                    this.local_worktree_entries_changed(&worktree, changes, cx);
+                }
+            }
+        })
+        .detach();
+    }
+
+    fn local_worktree_entries_changed(
+        &mut self,
+        worktree: &Model<Worktree>,
+        changes: &[(Arc<Path>, ProjectEntryId, PathChange)],
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.opened_binaries
+            .retain(|_, binary| binary.upgrade().is_some());
+        if self.opened_binaries.is_empty() {
+            return;
+        }
+
+        let snapshot = worktree.read(cx).snapshot();
+        for binary in self.binaries().collect::<Vec<_>>() {
+            let Some(old_file) = worktree::File::from_dyn(Some(&binary.read(cx).file)).cloned()
+            else {
+                continue;
+            };
+            if old_file.worktree != *worktree
+                || !changes.iter().any(|(path, entry_id, _)| {
+                    *path == old_file.path || Some(*entry_id) == old_file.entry_id
+                })
+            {
+                continue;
+            }
+
+            let new_file = match old_file
+                .entry_id
+                .and_then(|entry_id| snapshot.entry_for_id(entry_id))
+                .or_else(|| snapshot.entry_for_path(old_file.path.as_ref()))
+            {
+                Some(entry) => worktree::File {
+                    disk_state: match entry.mtime {
+                        Some(mtime) => DiskState::Present { mtime },
+                        None => old_file.disk_state,
+                    },
+                    entry_id: Some(entry.id),
+                    path: entry.path.clone(),
+                    is_private: entry.is_private,
+                    ..old_file.clone()
+                },
+                None => worktree::File {
+                    disk_state: DiskState::Deleted,
+                    ..old_file.clone()
+                },
+            };
+            if new_file == old_file {
+                continue;
+            }
+
+            if new_file.path != old_file.path {
+                self.opened_binaries.remove(&ProjectPath {
+                    worktree_id: snapshot.id(),
+                    path: old_file.path.clone(),
+                });
+                self.opened_binaries.insert(
+                    ProjectPath {
+                        worktree_id: snapshot.id(),
+                        path: new_file.path.clone(),
+                    },
+                    binary.downgrade(),
+                );
+            }
+            binary.update(cx, |binary, cx| {
+                binary.file_updated(Arc::new(new_file), cx);
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::PathBuf;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_edit_and_save_binary(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({})).await;
+        fs.insert_file("/root/data.bin", vec![0x00, 0xff, 0x10, 0x20])
+            .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let worktree_id =
+            cx.update(|cx| project.read(cx).worktrees(cx).next().unwrap().read(cx).id());
+        let project_path = ProjectPath {
+            worktree_id,
+            path: PathBuf::from("data.bin").into(),
+        };
+
+        let (task1, task2) = project.update(cx, |project, cx| {
+            (
+                project.open_binary(project_path.clone(), cx),
+                project.open_binary(project_path.clone(), cx),
+            )
+        });
+        let binary = task1.await.unwrap();
+        assert_eq!(binary, task2.await.unwrap());
+
+        binary.update(cx, |binary, cx| {
+            binary.overwrite(2, &[0xca, 0xfe, 0xba, 0xbe], cx);
+            assert_eq!(binary.bytes(), &[0x00, 0xff, 0xca, 0xfe]);
+            assert!(binary.is_dirty());
+        });
+
+        project
+            .update(cx, |project, cx| project.save_binary(binary.clone(), cx))
+            .await
+            .unwrap();
+        binary.update(cx, |binary, _| assert!(!binary.is_dirty()));
+        assert_eq!(
+            fs.load_bytes(Path::new("/root/data.bin")).await.unwrap(),
+            vec![0x00, 0xff, 0xca, 0xfe]
+        );
+
+        fs.insert_file("/root/data.bin", vec![0x01, 0x02]).await;
+        cx.run_until_parked();
+        binary.update(cx, |binary, _| {
+            assert_eq!(binary.bytes(), &[0x01, 0x02]);
+            assert!(!binary.is_dirty());
+        });
+    }
+}
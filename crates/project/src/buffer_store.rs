@@ -1,5 +1,6 @@
 use crate::{
     lsp_store::OpenLspBufferHandle,
+    project_settings::ProjectSettings,
     search::SearchQuery,
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
     ProjectItem as _, ProjectPath,
@@ -24,6 +25,7 @@ use language::{
     Buffer, BufferEvent, Capability, DiskState, File as _, Language, Operation,
 };
 use rpc::{proto, AnyProtoClient, ErrorExt as _, TypedEnvelope};
+use settings::{Settings as _, SettingsLocation};
 use smol::channel::Receiver;
 use std::{io, ops::Range, path::Path, str::FromStr as _, sync::Arc, time::Instant};
 use text::{BufferId, LineEnding, Rope};
@@ -785,17 +787,63 @@ impl LocalBufferStore {
         cx: &mut ModelContext<BufferStore>,
     ) -> Task<Result<Model<Buffer>>> {
         let load_buffer = worktree.update(cx, |worktree, cx| {
-            let load_file = worktree.load_file(path.as_ref(), cx);
+            let file_size = worktree.file_size(path.as_ref(), cx);
+            let large_files = ProjectSettings::get(
+                Some(SettingsLocation {
+                    worktree_id: worktree.id(),
+                    path: path.as_ref(),
+                }),
+                cx,
+            )
+            .large_files;
             let reservation = cx.reserve_model();
             let buffer_id = BufferId::from(reservation.entity_id().as_non_zero_u64());
-            cx.spawn(move |_, mut cx| async move {
-                let loaded = load_file.await?;
-                let text_buffer = cx
-                    .background_executor()
-                    .spawn(async move { text::Buffer::new(0, buffer_id, loaded.text) })
-                    .await;
-                cx.insert_model(reservation, |_| {
-                    Buffer::build(text_buffer, Some(loaded.file), Capability::ReadWrite)
+            let path = path.clone();
+            cx.spawn(move |worktree, mut cx| async move {
+                // Check the size before loading, so that large files are read into a rope in
+                // chunks instead of in full into a string first.
+                let large_file = file_size
+                    .await?
+                    .map_or(false, |len| large_files.is_large_file(len as usize));
+                let (file, text_buffer) = if large_file {
+                    let loaded = worktree
+                        .update(&mut cx, |worktree, cx| {
+                            worktree.load_large_file(path.as_ref(), cx)
+                        })?
+                        .await?;
+                    let text_buffer = cx
+                        .background_executor()
+                        .spawn(async move {
+                            text::Buffer::new_normalized(
+                                0,
+                                buffer_id,
+                                loaded.line_ending,
+                                loaded.text,
+                            )
+                        })
+                        .await;
+                    (loaded.file, text_buffer)
+                } else {
+                    let loaded = worktree
+                        .update(&mut cx, |worktree, cx| {
+                            worktree.load_file(path.as_ref(), cx)
+                        })?
+                        .await?;
+                    let text_buffer = cx
+                        .background_executor()
+                        .spawn(async move { text::Buffer::new(0, buffer_id, loaded.text) })
+                        .await;
+                    (loaded.file, text_buffer)
+                };
+                cx.insert_model(reservation, |cx| {
+                    let capability = if large_file && large_files.read_only {
+                        Capability::ReadOnly
+                    } else {
+                        Capability::ReadWrite
+                    };
+                    let mut buffer = Buffer::build(text_buffer, Some(file), capability);
+                    buffer.set_large_file(large_file, cx);
+                    buffer
                 })
             })
         });
@@ -2,9 +2,9 @@ mod signature_help;
 
 use crate::{
     lsp_store::{LocalLspStore, LspStore},
-    CodeAction, CoreCompletion, DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint,
-    InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location,
-    LocationLink, MarkupContent, ProjectTransaction, ResolveState,
+    CodeAction, CoreCompletion, DocumentHighlight, DocumentSymbol, Hover, HoverBlock,
+    HoverBlockKind, InlayHint, InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip,
+    InlayHintTooltip, Location, LocationLink, MarkupContent, ProjectTransaction, ResolveState,
 };
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -145,6 +145,9 @@ pub(crate) struct GetDocumentHighlights {
     pub position: PointUtf16,
 }
 
+#[derive(Debug)]
+pub(crate) struct GetDocumentSymbols;
+
 #[derive(Clone, Debug)]
 pub(crate) struct GetSignatureHelp {
     pub position: PointUtf16,
@@ -1372,6 +1375,230 @@ impl LspCommand for GetDocumentHighlights {
     }
 }
 
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentSymbols {
+    type Response = Vec<DocumentSymbol>;
+    type LspRequest = lsp::request::DocumentSymbolRequest;
+    type ProtoRequest = proto::GetDocumentSymbols;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match capabilities.server_capabilities.document_symbol_provider {
+            Some(OneOf::Left(supported)) => supported,
+            Some(OneOf::Right(_)) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentSymbolParams {
+        lsp::DocumentSymbolParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        response: Option<lsp::DocumentSymbolResponse>,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentSymbol>> {
+        let lsp_symbols = match response {
+            Some(lsp::DocumentSymbolResponse::Nested(symbols)) => symbols,
+            Some(lsp::DocumentSymbolResponse::Flat(symbols)) => nest_flat_symbols(symbols),
+            None => Vec::new(),
+        };
+        buffer.update(&mut cx, |buffer, _| {
+            lsp_symbols
+                .into_iter()
+                .map(|symbol| document_symbol_from_lsp(symbol, buffer))
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentSymbols {
+        proto::GetDocumentSymbols {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentSymbols,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Vec<DocumentSymbol>,
+        _: &mut LspStore,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentSymbolsResponse {
+        proto::GetDocumentSymbolsResponse {
+            symbols: response
+                .into_iter()
+                .map(serialize_document_symbol)
+                .collect(),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentSymbolsResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentSymbol>> {
+        let mut anchors = Vec::new();
+        let symbols = message
+            .symbols
+            .into_iter()
+            .map(|symbol| deserialize_document_symbol(symbol, &mut anchors))
+            .collect::<Result<Vec<_>>>()?;
+        buffer
+            .update(&mut cx, |buffer, _| buffer.wait_for_anchors(anchors))?
+            .await?;
+        Ok(symbols)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentSymbols) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+/// Nests the symbols of a flat response by their ranges, as servers without hierarchical
+/// document symbol support don't report which symbol contains which.
+fn nest_flat_symbols(mut symbols: Vec<lsp::SymbolInformation>) -> Vec<lsp::DocumentSymbol> {
+    symbols.sort_by_key(|symbol| {
+        (
+            symbol.location.range.start,
+            Reverse(symbol.location.range.end),
+        )
+    });
+
+    fn attach(
+        symbol: lsp::DocumentSymbol,
+        parents: &mut [lsp::DocumentSymbol],
+        roots: &mut Vec<lsp::DocumentSymbol>,
+    ) {
+        match parents.last_mut() {
+            Some(parent) => parent.children.get_or_insert_with(Vec::new).push(symbol),
+            None => roots.push(symbol),
+        }
+    }
+
+    let mut roots = Vec::new();
+    let mut parents = Vec::<lsp::DocumentSymbol>::new();
+    for symbol in symbols {
+        let range = symbol.location.range;
+        while let Some(parent) = parents.pop() {
+            if parent.range.end >= range.end {
+                parents.push(parent);
+                break;
+            }
+            attach(parent, &mut parents, &mut roots);
+        }
+        #[allow(deprecated)]
+        parents.push(lsp::DocumentSymbol {
+            name: symbol.name,
+            detail: None,
+            kind: symbol.kind,
+            tags: symbol.tags,
+            deprecated: symbol.deprecated,
+            range,
+            selection_range: range,
+            children: None,
+        });
+    }
+    while let Some(symbol) = parents.pop() {
+        attach(symbol, &mut parents, &mut roots);
+    }
+    roots
+}
+
+fn document_symbol_from_lsp(symbol: lsp::DocumentSymbol, buffer: &Buffer) -> DocumentSymbol {
+    let anchor_range = |range: lsp::Range| {
+        let start = buffer.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
+        let end = buffer.clip_point_utf16(point_from_lsp(range.end), Bias::Left);
+        buffer.anchor_after(start)..buffer.anchor_before(end)
+    };
+    DocumentSymbol {
+        name: symbol.name,
+        kind: symbol.kind,
+        range: anchor_range(symbol.range),
+        selection_range: anchor_range(symbol.selection_range),
+        children: symbol
+            .children
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| document_symbol_from_lsp(child, buffer))
+            .collect(),
+    }
+}
+
+fn serialize_document_symbol(symbol: DocumentSymbol) -> proto::DocumentSymbol {
+    proto::DocumentSymbol {
+        name: symbol.name,
+        kind: unsafe { std::mem::transmute::<lsp::SymbolKind, i32>(symbol.kind) },
+        start: Some(serialize_anchor(&symbol.range.start)),
+        end: Some(serialize_anchor(&symbol.range.end)),
+        selection_start: Some(serialize_anchor(&symbol.selection_range.start)),
+        selection_end: Some(serialize_anchor(&symbol.selection_range.end)),
+        children: symbol
+            .children
+            .into_iter()
+            .map(serialize_document_symbol)
+            .collect(),
+    }
+}
+
+fn deserialize_document_symbol(
+    symbol: proto::DocumentSymbol,
+    anchors: &mut Vec<Anchor>,
+) -> Result<DocumentSymbol> {
+    let mut anchor = |anchor: Option<proto::Anchor>| {
+        let anchor = anchor
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid document symbol anchor"))?;
+        anchors.push(anchor);
+        anyhow::Ok(anchor)
+    };
+    let range = anchor(symbol.start)?..anchor(symbol.end)?;
+    let selection_range = anchor(symbol.selection_start)?..anchor(symbol.selection_end)?;
+    Ok(DocumentSymbol {
+        name: symbol.name,
+        kind: unsafe { std::mem::transmute::<i32, lsp::SymbolKind>(symbol.kind) },
+        range,
+        selection_range,
+        children: symbol
+            .children
+            .into_iter()
+            .map(|child| deserialize_document_symbol(child, anchors))
+            .collect::<Result<_>>()?,
+    })
+}
+
 #[async_trait(?Send)]
 impl LspCommand for GetSignatureHelp {
     type Response = Option<SignatureHelp>;
@@ -3070,3 +3297,96 @@ impl LspCommand for LinkedEditingRange {
         BufferId::new(message.buffer_id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{Context as _, TestAppContext};
+    use lsp::SymbolKind;
+
+    #[gpui::test]
+    fn test_document_symbols_from_flat_response(cx: &mut TestAppContext) {
+        let buffer = cx.new_model(|cx| {
+            Buffer::local(
+                "struct Point {\n    x: f32,\n    y: f32,\n}\nfn origin() -> Point {}\n",
+                cx,
+            )
+        });
+        // Servers without hierarchical symbols report them flat, in any order.
+        let symbols = nest_flat_symbols(vec![
+            flat_symbol("origin", SymbolKind::FUNCTION, (4, 0), (4, 23)),
+            flat_symbol("y", SymbolKind::FIELD, (2, 4), (2, 10)),
+            flat_symbol("Point", SymbolKind::STRUCT, (0, 0), (3, 1)),
+            flat_symbol("x", SymbolKind::FIELD, (1, 4), (1, 10)),
+        ]);
+
+        buffer.update(cx, |buffer, _| {
+            let symbols = symbols
+                .into_iter()
+                .map(|symbol| document_symbol_from_lsp(symbol, buffer))
+                .collect::<Vec<_>>();
+            let expected = [
+                (0, "Point", SymbolKind::STRUCT, (0, 0), (3, 1)),
+                (1, "x", SymbolKind::FIELD, (1, 4), (1, 10)),
+                (1, "y", SymbolKind::FIELD, (2, 4), (2, 10)),
+                (0, "origin", SymbolKind::FUNCTION, (4, 0), (4, 23)),
+            ];
+            assert_eq!(flatten(&symbols, 0, buffer), expected);
+
+            // The symbols keep their kinds and ranges when sent to remote clients.
+            let mut anchors = Vec::new();
+            let deserialized = symbols
+                .into_iter()
+                .map(|symbol| {
+                    deserialize_document_symbol(serialize_document_symbol(symbol), &mut anchors)
+                })
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(anchors.len(), 16);
+            assert_eq!(flatten(&deserialized, 0, buffer), expected);
+        });
+    }
+
+    fn flat_symbol(
+        name: &str,
+        kind: SymbolKind,
+        start: (u32, u32),
+        end: (u32, u32),
+    ) -> lsp::SymbolInformation {
+        #[allow(deprecated)]
+        lsp::SymbolInformation {
+            name: name.to_string(),
+            kind,
+            tags: None,
+            deprecated: None,
+            location: lsp::Location {
+                uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+                range: lsp::Range::new(
+                    lsp::Position::new(start.0, start.1),
+                    lsp::Position::new(end.0, end.1),
+                ),
+            },
+            container_name: None,
+        }
+    }
+
+    fn flatten<'a>(
+        symbols: &'a [DocumentSymbol],
+        depth: usize,
+        buffer: &Buffer,
+    ) -> Vec<(usize, &'a str, SymbolKind, (u32, u32), (u32, u32))> {
+        let mut flattened = Vec::new();
+        for symbol in symbols {
+            let Range { start, end } = symbol.range.to_point(buffer);
+            flattened.push((
+                depth,
+                symbol.name.as_str(),
+                symbol.kind,
+                (start.row, start.column),
+                (end.row, end.column),
+            ));
+            flattened.extend(flatten(&symbol.children, depth + 1, buffer));
+        }
+        flattened
+    }
+}
@@ -1876,6 +1876,9 @@ impl LocalLspStore {
     ) {
         let buffer = buffer_handle.read(cx);
         let buffer_id = buffer.remote_id();
+        if buffer.is_large_file() {
+            return;
+        }
 
         let Some(file) = File::from_dyn(buffer.file()) else {
             return;
@@ -2841,6 +2844,7 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_lsp_command::<GetDeclaration>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetTypeDefinition>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentHighlights>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentSymbols>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetReferences>);
         client.add_model_request_handler(Self::handle_lsp_command::<PrepareRename>);
         client.add_model_request_handler(Self::handle_lsp_command::<PerformRename>);
@@ -3162,6 +3166,21 @@ impl LspStore {
                 self.on_buffer_saved(buffer, cx);
             }
 
+            language::BufferEvent::LargeFileModeChanged => {
+                if !buffer.read(cx).is_large_file() {
+                    self.detect_language_for_buffer(&buffer, cx);
+                    if let Some(local) = self.as_local_mut() {
+                        local.initialize_buffer(&buffer, cx);
+                        if local
+                            .registered_buffers
+                            .contains_key(&buffer.read(cx).remote_id())
+                        {
+                            local.register_buffer_with_language_servers(&buffer, cx);
+                        }
+                    }
+                }
+            }
+
             _ => {}
         }
     }
@@ -3325,6 +3344,9 @@ impl LspStore {
     ) -> Option<language::AvailableLanguage> {
         // If the buffer has a language, set it and start the language server if we haven't already.
         let buffer = buffer_handle.read(cx);
+        if buffer.is_large_file() {
+            return None;
+        }
         let file = buffer.file()?;
 
         let content = buffer.as_rope();
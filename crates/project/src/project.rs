@@ -1,3 +1,4 @@
+pub mod binary_store;
 pub mod buffer_store;
 mod color_extractor;
 pub mod connection_manager;
@@ -25,6 +26,7 @@ pub mod search_history;
 mod yarn;
 
 use anyhow::{anyhow, Context as _, Result};
+pub use binary_store::{BinaryItem, BinaryStore};
 use buffer_store::{BufferChangeSet, BufferStore, BufferStoreEvent};
 use client::{proto, Client, Collaborator, PendingEntitySubscription, TypedEnvelope, UserStore};
 use clock::ReplicaId;
@@ -65,7 +67,7 @@ use rpc::{
     proto::{LanguageServerPromptResponse, SSH_PROJECT_ID},
     AnyProtoClient, ErrorCode,
 };
-use search::{SearchInputKind, SearchQuery, SearchResult};
+use search::{ExternalSearchRoot, SearchInputKind, SearchQuery, SearchResult};
 use search_history::SearchHistory;
 use settings::{InvalidSettingsError, Settings, SettingsLocation, SettingsStore};
 use smol::channel::Receiver;
@@ -154,6 +156,7 @@ pub struct Project {
     worktree_store: Model<WorktreeStore>,
     buffer_store: Model<BufferStore>,
     image_store: Model<ImageStore>,
+    binary_store: Model<BinaryStore>,
     lsp_store: Model<LspStore>,
     _subscriptions: Vec<gpui::Subscription>,
     buffers_needing_diff: HashSet<WeakModel<Buffer>>,
@@ -164,6 +167,9 @@ pub struct Project {
     search_history: SearchHistory,
     search_included_history: SearchHistory,
     search_excluded_history: SearchHistory,
+    /// The invisible worktrees created to open the files found by external searches, with the
+    /// number of searches whose results are in each of them.
+    external_search_worktrees: HashMap<WorktreeId, usize>,
     snippets: Model<SnippetProvider>,
     environment: Model<ProjectEnvironment>,
     settings_observer: Model<SettingsObserver>,
@@ -452,6 +458,17 @@ pub struct DocumentHighlight {
     pub kind: DocumentHighlightKind,
 }
 
+/// A symbol of a single buffer, nested in the symbols containing it.
+#[derive(Clone, Debug)]
+pub struct DocumentSymbol {
+    pub name: String,
+    pub kind: lsp::SymbolKind,
+    pub range: Range<language::Anchor>,
+    /// The range to select when navigating to the symbol, e.g. its name.
+    pub selection_range: Range<language::Anchor>,
+    pub children: Vec<DocumentSymbol>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Symbol {
     pub language_server_name: LanguageServerName,
@@ -620,6 +637,7 @@ impl Project {
             let image_store = cx.new_model(|cx| ImageStore::local(worktree_store.clone(), cx));
             cx.subscribe(&image_store, Self::on_image_store_event)
                 .detach();
+            let binary_store = cx.new_model(|cx| BinaryStore::new(worktree_store.clone(), cx));
 
             let prettier_store = cx.new_model(|cx| {
                 PrettierStore::new(
@@ -683,6 +701,7 @@ impl Project {
                 worktree_store,
                 buffer_store,
                 image_store,
+                binary_store,
                 lsp_store,
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
@@ -709,6 +728,7 @@ impl Project {
 
                 search_included_history: Self::new_search_history(),
                 search_excluded_history: Self::new_search_history(),
+                external_search_worktrees: HashMap::default(),
 
                 toolchain_store: Some(toolchain_store),
             }
@@ -754,6 +774,7 @@ impl Project {
                     cx,
                 )
             });
+            let binary_store = cx.new_model(|cx| BinaryStore::new(worktree_store.clone(), cx));
             cx.subscribe(&buffer_store, Self::on_buffer_store_event)
                 .detach();
             let toolchain_store = cx.new_model(|cx| {
@@ -801,6 +822,7 @@ impl Project {
                 worktree_store,
                 buffer_store,
                 image_store,
+                binary_store,
                 lsp_store,
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
@@ -842,6 +864,7 @@ impl Project {
 
                 search_included_history: Self::new_search_history(),
                 search_excluded_history: Self::new_search_history(),
+                external_search_worktrees: HashMap::default(),
 
                 toolchain_store: Some(toolchain_store),
             };
@@ -950,6 +973,7 @@ impl Project {
         let image_store = cx.new_model(|cx| {
             ImageStore::remote(worktree_store.clone(), client.clone().into(), remote_id, cx)
         })?;
+        let binary_store = cx.new_model(|cx| BinaryStore::new(worktree_store.clone(), cx))?;
 
         let lsp_store = cx.new_model(|cx| {
             let mut lsp_store = LspStore::new_remote(
@@ -1014,6 +1038,7 @@ impl Project {
                 buffer_ordered_messages_tx: tx,
                 buffer_store: buffer_store.clone(),
                 image_store,
+                binary_store,
                 worktree_store: worktree_store.clone(),
                 lsp_store: lsp_store.clone(),
                 active_entry: None,
@@ -1044,6 +1069,7 @@ impl Project {
                 search_history: Self::new_search_history(),
                 search_included_history: Self::new_search_history(),
                 search_excluded_history: Self::new_search_history(),
+                external_search_worktrees: HashMap::default(),
                 environment: ProjectEnvironment::new(&worktree_store, None, cx),
                 remotely_created_models: Arc::new(Mutex::new(RemotelyCreatedModels::default())),
                 toolchain_store: None,
@@ -1907,6 +1933,26 @@ impl Project {
         })
     }
 
+    /// Takes a buffer out of large file mode, so that it gets parsed, registered with language
+    /// servers and diffed against the git index like any other buffer.
+    ///
+    /// Only local projects can do this, as the host of a remote project keeps the buffer in
+    /// large file mode.
+    pub fn exit_large_file_mode(&mut self, buffer: &Model<Buffer>, cx: &mut ModelContext<Self>) {
+        if !self.is_local() {
+            return;
+        }
+        buffer.update(cx, |buffer, cx| {
+            if !buffer.is_large_file() {
+                return;
+            }
+            if buffer.read_only() {
+                buffer.set_capability(Capability::ReadWrite, cx);
+            }
+            buffer.set_large_file(false, cx);
+        });
+    }
+
     pub fn open_buffer_by_id(
         &mut self,
         id: BufferId,
@@ -2008,6 +2054,39 @@ impl Project {
         })
     }
 
+    pub fn open_binary(
+        &mut self,
+        path: impl Into<ProjectPath>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<BinaryItem>>> {
+        if self.is_disconnected(cx) {
+            return Task::ready(Err(anyhow!(ErrorCode::Disconnected)));
+        }
+
+        self.binary_store.update(cx, |binary_store, cx| {
+            binary_store.open_binary(path.into(), cx)
+        })
+    }
+
+    pub fn save_binary(
+        &mut self,
+        binary: Model<BinaryItem>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        self.binary_store
+            .update(cx, |binary_store, cx| binary_store.save_binary(binary, cx))
+    }
+
+    pub fn reload_binary(
+        &mut self,
+        binary: Model<BinaryItem>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        self.binary_store.update(cx, |binary_store, cx| {
+            binary_store.reload_binary(binary, cx)
+        })
+    }
+
     async fn send_buffer_ordered_messages(
         this: WeakModel<Self>,
         rx: UnboundedReceiver<BufferOrderedMessage>,
@@ -2763,6 +2842,21 @@ impl Project {
         self.document_highlights_impl(buffer, position, cx)
     }
 
+    /// Returns the symbols of the buffer reported by its primary language server, or none when the
+    /// server doesn't support document symbols.
+    pub fn document_symbols(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<DocumentSymbol>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetDocumentSymbols,
+            cx,
+        )
+    }
+
     pub fn symbols(&self, query: &str, cx: &mut ModelContext<Self>) -> Task<Result<Vec<Symbol>>> {
         self.lsp_store
             .update(cx, |lsp_store, cx| lsp_store.symbols(query, cx))
@@ -3023,15 +3117,136 @@ impl Project {
         query: SearchQuery,
         cx: &mut ModelContext<Self>,
     ) -> Receiver<SearchResult> {
-        let (result_tx, result_rx) = smol::channel::unbounded();
-
         let matching_buffers_rx = if query.is_opened_only() {
             self.sort_search_candidates(&query, cx)
         } else {
             self.find_search_candidate_buffers(&query, MAX_SEARCH_RESULT_FILES + 1, cx)
         };
+        self.search_candidate_buffers(query, matching_buffers_rx, cx)
+    }
+
+    /// Searches files outside of the project's worktrees, opening the ones that match in
+    /// invisible worktrees. Only supported in local projects.
+    pub fn search_external(
+        &mut self,
+        root: ExternalSearchRoot,
+        query: SearchQuery,
+        cx: &mut ModelContext<Self>,
+    ) -> Receiver<SearchResult> {
+        let (buffers_tx, buffers_rx) = smol::channel::unbounded();
+        if self.is_local() {
+            let fs = self.fs.clone();
+            let worktree_roots = self
+                .visible_worktrees(cx)
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+                .collect::<Vec<_>>();
+            let cargo_home = std::env::var_os("CARGO_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| util::paths::home_dir().join(".cargo"));
+            let query = query.clone();
+            cx.spawn(|this, mut cx| async move {
+                let directories = root.directories(&worktree_roots, &cargo_home, &fs).await?;
+                let (paths_tx, paths_rx) = smol::channel::unbounded();
+                let find_paths = cx.background_executor().spawn(async move {
+                    search::find_candidate_paths(
+                        directories,
+                        &query,
+                        MAX_SEARCH_RESULT_FILES + 1,
+                        fs,
+                        paths_tx,
+                    )
+                    .await
+                });
+
+                const MAX_CONCURRENT_BUFFER_OPENS: usize = 64;
+                let mut paths = paths_rx.ready_chunks(MAX_CONCURRENT_BUFFER_OPENS);
+                while let Some(paths) = paths.next().await {
+                    let buffers = this.update(&mut cx, |this, cx| {
+                        paths
+                            .into_iter()
+                            .map(|(root, abs_path)| this.open_external_buffer(root, abs_path, cx))
+                            .collect::<Vec<_>>()
+                    })?;
+                    for buffer in futures::future::join_all(buffers).await {
+                        if let Some(buffer) = buffer.log_err() {
+                            buffers_tx.send(buffer).await?;
+                        }
+                    }
+                }
+                find_paths.await
+            })
+            .detach_and_log_err(cx);
+        }
+        self.search_candidate_buffers(query, buffers_rx, cx)
+    }
+
+    /// Opens a file outside of the project's worktrees. The files found in the same search root
+    /// share one invisible worktree, which is removed once no search retains it.
+    fn open_external_buffer(
+        &mut self,
+        root: PathBuf,
+        abs_path: PathBuf,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<Buffer>>> {
+        let created = self.find_worktree(&root, cx).is_none();
+        let worktree = self.find_or_create_worktree(&root, false, cx);
+        cx.spawn(|this, mut cx| async move {
+            let (worktree, _) = worktree.await?;
+            let (worktree_id, worktree_path) =
+                worktree.read_with(&cx, |worktree, _| (worktree.id(), worktree.abs_path()))?;
+            let project_path = ProjectPath {
+                worktree_id,
+                path: abs_path.strip_prefix(&worktree_path)?.into(),
+            };
+            this.update(&mut cx, |this, cx| {
+                if created {
+                    this.external_search_worktrees
+                        .entry(worktree_id)
+                        .or_insert(0);
+                }
+                this.open_buffer(project_path, cx)
+            })?
+            .await
+        })
+    }
+
+    /// Keeps the worktree alive while a search has results in it, if it was created by an
+    /// external search. Returns whether the worktree was retained, and should be released.
+    pub fn retain_external_search_worktree(&mut self, worktree_id: WorktreeId) -> bool {
+        match self.external_search_worktrees.get_mut(&worktree_id) {
+            Some(count) => {
+                *count += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the worktree created by external searches once no search retains it anymore.
+    pub fn release_external_search_worktree(
+        &mut self,
+        worktree_id: WorktreeId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(count) = self.external_search_worktrees.get_mut(&worktree_id) else {
+            return;
+        };
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            self.external_search_worktrees.remove(&worktree_id);
+            self.remove_worktree(worktree_id, cx);
+        }
+    }
+
+    fn search_candidate_buffers(
+        &self,
+        query: SearchQuery,
+        matching_buffers_rx: Receiver<Model<Buffer>>,
+        cx: &mut ModelContext<Self>,
+    ) -> Receiver<SearchResult> {
+        let (result_tx, result_rx) = smol::channel::unbounded();
 
-        cx.spawn(|_, cx| async move {
+        cx.spawn(|this, mut cx| async move {
             let mut range_count = 0;
             let mut buffer_count = 0;
             let mut limit_reached = false;
@@ -3043,6 +3258,16 @@ impl Project {
             // opened buffer, we will spawn a background task that retrieves all the
             // ranges in the buffer matched by the query.
             'outer: while let Some(matching_buffer_chunk) = chunks.next().await {
+                if query.needs_syntax_tree() {
+                    let syntax_trees = this.update(&mut cx, |this, cx| {
+                        matching_buffer_chunk
+                            .iter()
+                            .map(|buffer| this.wait_for_syntax_tree(buffer, cx))
+                            .collect::<Vec<_>>()
+                    })?;
+                    futures::future::join_all(syntax_trees).await;
+                }
+
                 let mut chunk_results = Vec::new();
                 for buffer in matching_buffer_chunk {
                     let buffer = buffer.clone();
@@ -3091,6 +3316,43 @@ impl Project {
         result_rx
     }
 
+    /// Waits for a newly opened buffer to be assigned its language, which may still be loading,
+    /// and to be parsed.
+    fn wait_for_syntax_tree(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<()> {
+        let available_language = {
+            let buffer = buffer.read(cx);
+            if buffer.language().is_some() || buffer.is_large_file() {
+                None
+            } else {
+                buffer.file().and_then(|file| {
+                    self.languages
+                        .language_for_file(file, Some(buffer.as_rope()), cx)
+                })
+            }
+        };
+        let languages = self.languages.clone();
+        let buffer = buffer.clone();
+        cx.spawn(|this, mut cx| async move {
+            if let Some(available_language) = available_language {
+                if let Ok(Ok(language)) = languages.load_language(&available_language).await {
+                    this.update(&mut cx, |this, cx| {
+                        if buffer.read(cx).language().is_none() {
+                            this.set_language_for_buffer(&buffer, language, cx);
+                        }
+                    })
+                    .ok();
+                }
+            }
+            if let Ok(parsing_idle) = buffer.read_with(&cx, |buffer, _| buffer.parsing_idle()) {
+                parsing_idle.await;
+            }
+        })
+    }
+
     fn find_search_candidate_buffers(
         &mut self,
         query: &SearchQuery,
@@ -56,6 +56,10 @@ pub struct ProjectSettings {
     /// Configuration for session-related features
     #[serde(default)]
     pub session: SessionSettings,
+
+    /// Configuration for opening large files
+    #[serde(default)]
+    pub large_files: LargeFileSettings,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -200,6 +204,36 @@ impl Default for SessionSettings {
     }
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct LargeFileSettings {
+    /// The size in megabytes from which files are opened in large file mode,
+    /// in which they aren't parsed, registered with language servers, diffed
+    /// against git or soft wrapped.
+    ///
+    /// Default: 32
+    pub threshold_mb: u64,
+    /// Whether files opened in large file mode are read-only.
+    ///
+    /// Default: false
+    pub read_only: bool,
+}
+
+impl LargeFileSettings {
+    /// Whether a file of the given size in bytes is opened in large file mode.
+    pub fn is_large_file(&self, len: usize) -> bool {
+        len as u64 >= self.threshold_mb.saturating_mul(1024 * 1024)
+    }
+}
+
+impl Default for LargeFileSettings {
+    fn default() -> Self {
+        Self {
+            threshold_mb: 32,
+            read_only: false,
+        }
+    }
+}
+
 impl Settings for ProjectSettings {
     const KEY: Option<&'static str> = None;
 
@@ -3644,6 +3644,86 @@ async fn test_buffer_file_changes_on_disk(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_large_file_mode(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.large_files = project_settings::LargeFileSettings {
+                    threshold_mb: 1,
+                    read_only: true,
+                };
+            });
+        })
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            // Just below the threshold.
+            "medium.rs": "a".repeat(1024 * 1024 - 1),
+            "large.rs": "// generated\n".repeat(100_000),
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp("Rust", FakeLspAdapter::default());
+
+    let medium_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/medium.rs", cx)
+        })
+        .await
+        .unwrap();
+    medium_buffer.update(cx, |buffer, _| {
+        assert!(!buffer.is_large_file());
+        assert!(!buffer.read_only());
+    });
+
+    let (large_buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/large.rs", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    large_buffer.update(cx, |buffer, _| {
+        assert!(buffer.is_large_file());
+        assert_eq!(buffer.text(), "// generated\n".repeat(100_000));
+        assert_eq!(buffer.capability(), Capability::ReadOnly);
+        assert_eq!(buffer.language().map(|language| language.name()), None);
+    });
+    // No language server was started for the large file.
+    assert!(fake_servers.try_next().is_err());
+
+    project.update(cx, |project, cx| {
+        project.exit_large_file_mode(&large_buffer, cx)
+    });
+    cx.executor().run_until_parked();
+    large_buffer.update(cx, |buffer, _| {
+        assert!(!buffer.is_large_file());
+        assert_eq!(buffer.capability(), Capability::ReadWrite);
+        assert_eq!(
+            buffer.language().map(|language| language.name()),
+            Some("Rust".into())
+        );
+    });
+    let mut fake_server = fake_servers.next().await.unwrap();
+    assert_eq!(
+        fake_server
+            .receive_notification::<lsp::notification::DidOpenTextDocument>()
+            .await
+            .text_document
+            .uri,
+        lsp::Url::from_file_path("/dir/large.rs").unwrap()
+    );
+}
+
 #[gpui::test]
 async fn test_buffer_line_endings(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4836,6 +4916,118 @@ async fn test_search_in_gitignored_dirs(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_search_external_directory(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "main.rs": "fn main() { needle(); }",
+        }),
+    )
+    .await;
+    fs.insert_tree(
+        "/registry",
+        json!({
+            "haystack-1.0.0": {
+                ".git": { "HEAD": "needle" },
+                "src": {
+                    "lib.rs": "pub fn needle() {}",
+                    "needle.rs": "// needle",
+                    "other.rs": "pub fn other() {}",
+                },
+                "README.md": "Finds the needle",
+            },
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let query = SearchQuery::text(
+        "needle",
+        false,
+        false,
+        false,
+        PathMatcher::new(&["*.rs".to_owned()]).unwrap(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
+    let mut search_rx = project.update(cx, |project, cx| {
+        project.search_external(
+            ExternalSearchRoot::Directory(PathBuf::from("/registry")),
+            query,
+            cx,
+        )
+    });
+    let mut buffers = Vec::new();
+    let mut results = Vec::new();
+    while let Some(search_result) = search_rx.next().await {
+        if let SearchResult::Buffer { buffer, ranges } = search_result {
+            buffers.push(buffer.clone());
+            results.push(buffer.read_with(cx, |buffer, cx| {
+                let abs_path = buffer.file().unwrap().as_local().unwrap().abs_path(cx);
+                let ranges = ranges
+                    .into_iter()
+                    .map(|range| range.to_offset(buffer))
+                    .collect::<Vec<_>>();
+                (abs_path, ranges)
+            }));
+        }
+    }
+    assert_eq!(
+        results,
+        [
+            (
+                PathBuf::from("/registry/haystack-1.0.0/src/lib.rs"),
+                vec![7..13]
+            ),
+            (
+                PathBuf::from("/registry/haystack-1.0.0/src/needle.rs"),
+                vec![3..9]
+            ),
+        ],
+        "should only search the files matching the filters"
+    );
+    let external_worktree_id = project.read_with(cx, |project, cx| {
+        assert_eq!(project.visible_worktrees(cx).count(), 1);
+        assert_eq!(
+            project
+                .worktrees(cx)
+                .filter(|worktree| !worktree.read(cx).is_visible())
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+                .collect::<Vec<_>>(),
+            [PathBuf::from("/registry")],
+            "the files found in the same directory should share one worktree"
+        );
+        project
+            .worktrees(cx)
+            .find(|worktree| !worktree.read(cx).is_visible())
+            .unwrap()
+            .read(cx)
+            .id()
+    });
+
+    let visible_worktree_id = project.read_with(cx, |project, cx| {
+        project.visible_worktrees(cx).next().unwrap().read(cx).id()
+    });
+    project.update(cx, |project, cx| {
+        assert!(!project.retain_external_search_worktree(visible_worktree_id));
+        assert!(project.retain_external_search_worktree(external_worktree_id));
+        assert!(project.retain_external_search_worktree(external_worktree_id));
+        project.release_external_search_worktree(external_worktree_id, cx);
+        assert!(project.worktree_for_id(external_worktree_id, cx).is_some());
+        project.release_external_search_worktree(external_worktree_id, cx);
+        assert!(
+            project.worktree_for_id(external_worktree_id, cx).is_none(),
+            "the worktree should be removed once no search retains it"
+        );
+    });
+    drop(buffers);
+}
+
 #[gpui::test]
 async fn test_create_entry(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -1,3 +1,7 @@
+mod external;
+mod structural;
+mod syntax_kinds;
+
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use anyhow::Result;
 use client::proto;
@@ -15,6 +19,11 @@ use std::{
 use text::Anchor;
 use util::paths::PathMatcher;
 
+pub(crate) use external::find_candidate_paths;
+pub use external::ExternalSearchRoot;
+pub use structural::StructuralPattern;
+pub use syntax_kinds::SyntaxKinds;
+
 pub enum SearchResult {
     Buffer {
         buffer: Model<Buffer>,
@@ -36,6 +45,7 @@ pub struct SearchInputs {
     files_to_include: PathMatcher,
     files_to_exclude: PathMatcher,
     buffers: Option<Vec<Model<Buffer>>>,
+    syntax_kinds: SyntaxKinds,
 }
 
 impl SearchInputs {
@@ -51,6 +61,9 @@ impl SearchInputs {
     pub fn buffers(&self) -> &Option<Vec<Model<Buffer>>> {
         &self.buffers
     }
+    pub fn syntax_kinds(&self) -> SyntaxKinds {
+        self.syntax_kinds
+    }
 }
 #[derive(Clone, Debug)]
 pub enum SearchQuery {
@@ -72,6 +85,13 @@ pub enum SearchQuery {
         include_ignored: bool,
         inner: SearchInputs,
     },
+
+    Structural {
+        pattern: Arc<StructuralPattern>,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 static WORD_MATCH_TEST: LazyLock<Regex> = LazyLock::new(|| {
@@ -99,6 +119,7 @@ impl SearchQuery {
             files_to_exclude,
             files_to_include,
             buffers,
+            syntax_kinds: SyntaxKinds::empty(),
         };
         Ok(Self::Text {
             search: Arc::new(search),
@@ -146,6 +167,7 @@ impl SearchQuery {
             files_to_exclude,
             files_to_include,
             buffers,
+            syntax_kinds: SyntaxKinds::empty(),
         };
         Ok(Self::Regex {
             regex,
@@ -158,8 +180,43 @@ impl SearchQuery {
         })
     }
 
+    /// Creates a query that matches syntax nodes against a pattern with metavariables, such as
+    /// `$A.unwrap()`. See [`StructuralPattern`] for the pattern syntax.
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        buffers: Option<Vec<Model<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let pattern = StructuralPattern::new(&query)?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            buffers,
+            syntax_kinds: SyntaxKinds::empty(),
+        };
+        Ok(Self::Structural {
+            pattern: Arc::new(pattern),
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     pub fn from_proto(message: proto::SearchQuery) -> Result<Self> {
-        if message.regex {
+        let syntax_kinds = SyntaxKinds::from_bits_truncate(message.syntax_kinds as u8);
+        let query = if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                deserialize_path_matches(&message.files_to_include)?,
+                deserialize_path_matches(&message.files_to_exclude)?,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -179,7 +236,8 @@ impl SearchQuery {
                 deserialize_path_matches(&message.files_to_exclude)?,
                 None, // search opened only don't need search remote
             )
-        }
+        }?;
+        Ok(query.with_syntax_kinds(syntax_kinds))
     }
 
     pub fn with_replacement(mut self, new_replacement: String) -> Self {
@@ -191,6 +249,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -198,15 +260,29 @@ impl SearchQuery {
         }
     }
 
+    /// Restricts the matches to the given kinds of syntax, such as comments or strings.
+    pub fn with_syntax_kinds(mut self, syntax_kinds: SyntaxKinds) -> Self {
+        match self {
+            Self::Text { ref mut inner, .. }
+            | Self::Regex { ref mut inner, .. }
+            | Self::Structural { ref mut inner, .. } => {
+                inner.syntax_kinds = syntax_kinds;
+                self
+            }
+        }
+    }
+
     pub fn to_proto(&self) -> proto::SearchQuery {
         proto::SearchQuery {
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
             files_to_include: self.files_to_include().sources().join(","),
             files_to_exclude: self.files_to_exclude().sources().join(","),
+            syntax_kinds: self.syntax_kinds().bits() as u32,
         }
     }
 
@@ -245,19 +321,22 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            Self::Structural { pattern, .. } => pattern.may_match(stream),
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
+    /// Structural queries need the syntax tree around the match, see [`Self::structural_replacement`].
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
+            SearchQuery::Structural { .. } => None,
             SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
             SearchQuery::Regex {
                 regex, replacement, ..
@@ -282,6 +361,23 @@ impl SearchQuery {
         }
     }
 
+    /// Returns the replacement for the structural match at the given range of the buffer, with
+    /// the text matched by the pattern's metavariables substituted into the replacement.
+    pub fn structural_replacement(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        match self {
+            SearchQuery::Structural {
+                pattern,
+                replacement: Some(replacement),
+                ..
+            } => pattern.replacement(replacement, buffer, range),
+            _ => None,
+        }
+    }
+
     pub async fn search(
         &self,
         buffer: &BufferSnapshot,
@@ -374,8 +470,21 @@ impl SearchQuery {
                     }
                 }
             }
+
+            Self::Structural { pattern, .. } => {
+                let range = range_offset..range_offset + rope.len();
+                matches = pattern
+                    .search(buffer, range)
+                    .into_iter()
+                    .map(|range| range.start - range_offset..range.end - range_offset)
+                    .collect();
+            }
         }
 
+        let syntax_kinds = self.syntax_kinds();
+        if !syntax_kinds.is_empty() {
+            syntax_kinds.retain_matches(buffer, range_offset, &mut matches);
+        }
         matches
     }
 
@@ -391,6 +500,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -398,6 +508,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => true,
         }
     }
 
@@ -409,6 +520,9 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
@@ -416,6 +530,20 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
+    pub fn syntax_kinds(&self) -> SyntaxKinds {
+        self.as_inner().syntax_kinds()
+    }
+
+    /// Whether matching depends on the syntax trees of buffers, which may still be parsing
+    /// when they are searched.
+    pub fn needs_syntax_tree(&self) -> bool {
+        self.is_structural() || !self.syntax_kinds().is_empty()
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -453,7 +581,9 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
         }
     }
 }
@@ -0,0 +1,441 @@
+//! Searching files that don't belong to any worktree of the project: arbitrary directories,
+//! archives, and the sources of the project's dependencies.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use async_compression::futures::bufread::GzipDecoder;
+use async_tar::Archive;
+use fs::Fs;
+use futures::{io::BufReader, StreamExt as _};
+use sha2::{Digest as _, Sha256};
+use smol::channel::Sender;
+use util::{paths::PathExt as _, ResultExt as _};
+
+use super::SearchQuery;
+
+/// Files larger than this are assumed to be generated or binary, and are skipped.
+const MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Archives larger than this are not extracted, as they are loaded in memory first.
+const MAX_ARCHIVE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// How many extracted archives are kept in the temporary directory. The least recently
+/// extracted ones are removed when extracting another archive.
+const MAX_EXTRACTED_ARCHIVES: usize = 4;
+
+/// Directories that never contain sources worth searching.
+const SKIPPED_DIRECTORIES: &[&str] = &[".git", ".hg", ".svn"];
+
+/// Where to search, when searching outside of the project's worktrees.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ExternalSearchRoot {
+    /// A directory anywhere on disk, searched recursively.
+    Directory(PathBuf),
+    /// A `.zip`, `.tar.gz` or `.tgz` archive, extracted to a temporary directory first.
+    Archive(PathBuf),
+    /// The sources of the Cargo crates and npm packages the project depends on.
+    Dependencies,
+}
+
+impl ExternalSearchRoot {
+    /// The root for a path picked by the user, which is an archive if it has the extension of one.
+    pub fn for_path(path: PathBuf) -> Self {
+        if archive_kind(&path).is_some() {
+            Self::Archive(path)
+        } else {
+            Self::Directory(path)
+        }
+    }
+
+    pub fn display_name(&self) -> String {
+        match self {
+            Self::Directory(path) | Self::Archive(path) => path.compact().to_string_lossy().into(),
+            Self::Dependencies => "dependencies".to_string(),
+        }
+    }
+
+    /// The directories to walk when searching this root. `worktree_roots` are the paths of the
+    /// project's visible worktrees, where the dependency manifests are looked up.
+    pub(crate) async fn directories(
+        &self,
+        worktree_roots: &[PathBuf],
+        cargo_home: &Path,
+        fs: &Arc<dyn Fs>,
+    ) -> Result<Vec<PathBuf>> {
+        match self {
+            Self::Directory(path) => {
+                if fs.is_dir(path).await {
+                    Ok(vec![path.clone()])
+                } else {
+                    Err(anyhow!("{path:?} is not a directory"))
+                }
+            }
+            Self::Archive(path) => Ok(vec![extract_archive(path, fs).await?]),
+            Self::Dependencies => {
+                let mut directories = Vec::new();
+                for worktree_root in worktree_roots {
+                    for directory in dependency_directories(worktree_root, cargo_home, fs).await {
+                        if !directories.contains(&directory) {
+                            directories.push(directory);
+                        }
+                    }
+                }
+                Ok(directories)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    TarGz,
+}
+
+fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    let file_name = path.file_name()?.to_str()?.to_lowercase();
+    if file_name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else {
+        None
+    }
+}
+
+/// Extracts the archive into a temporary directory named after its path and modification time,
+/// so that searching the same archive again doesn't extract it again.
+async fn extract_archive(path: &Path, fs: &Arc<dyn Fs>) -> Result<PathBuf> {
+    let kind = archive_kind(path).ok_or_else(|| anyhow!("{path:?} is not an archive"))?;
+    let metadata = fs
+        .metadata(path)
+        .await?
+        .ok_or_else(|| anyhow!("{path:?} does not exist"))?;
+    if metadata.len > MAX_ARCHIVE_SIZE {
+        return Err(anyhow!(
+            "{path:?} is larger than {} MB",
+            MAX_ARCHIVE_SIZE / 1024 / 1024
+        ));
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(path.to_string_lossy().as_bytes());
+    if let Some((secs, nanos)) = metadata.mtime.to_seconds_and_nanos_for_persistence() {
+        hasher.update(secs.to_le_bytes());
+        hasher.update(nanos.to_le_bytes());
+    }
+    let extraction_directory = paths::temp_dir().join("archive_search");
+    let destination = extraction_directory.join(format!("{:x}", hasher.finalize()));
+    if fs.is_dir(&destination).await {
+        return Ok(destination);
+    }
+    remove_extracted_archives(&extraction_directory, MAX_EXTRACTED_ARCHIVES - 1, fs).await;
+
+    let bytes = fs.load_bytes(path).await?;
+    fs.create_dir(&destination).await?;
+    let extracted = match kind {
+        ArchiveKind::Zip => node_runtime::extract_zip(&destination, bytes.as_slice()).await,
+        ArchiveKind::TarGz => {
+            let body = GzipDecoder::new(BufReader::new(bytes.as_slice()));
+            futures::pin_mut!(body);
+            fs.extract_tar_file(&destination, Archive::new(body)).await
+        }
+    };
+    if let Err(error) = extracted {
+        fs.remove_dir(
+            &destination,
+            fs::RemoveOptions {
+                recursive: true,
+                ignore_if_not_exists: true,
+            },
+        )
+        .await
+        .log_err();
+        return Err(error).with_context(|| format!("failed to extract {path:?}"));
+    }
+    Ok(destination)
+}
+
+/// Removes the archives extracted in `directory`, except for the `keep` most recent ones.
+async fn remove_extracted_archives(directory: &Path, keep: usize, fs: &Arc<dyn Fs>) {
+    let mut extracted = Vec::new();
+    if let Ok(mut entries) = fs.read_dir(directory).await {
+        while let Some(entry) = entries.next().await {
+            let Ok(path) = entry else {
+                continue;
+            };
+            if let Ok(Some(metadata)) = fs.metadata(&path).await {
+                extracted.push((metadata.mtime.timestamp_for_user(), path));
+            }
+        }
+    }
+    extracted.sort_by(|(a, _), (b, _)| b.cmp(a));
+    for (_, path) in extracted.into_iter().skip(keep) {
+        fs.remove_dir(
+            &path,
+            fs::RemoveOptions {
+                recursive: true,
+                ignore_if_not_exists: true,
+            },
+        )
+        .await
+        .log_err();
+    }
+}
+
+/// The directories containing the sources of the dependencies of the project at
+/// `worktree_root`: the registry sources of the crates locked in its `Cargo.lock`, and its
+/// `node_modules`.
+async fn dependency_directories(
+    worktree_root: &Path,
+    cargo_home: &Path,
+    fs: &Arc<dyn Fs>,
+) -> Vec<PathBuf> {
+    let mut directories = Vec::new();
+
+    if let Ok(lockfile) = fs.load(&worktree_root.join("Cargo.lock")).await {
+        let packages = locked_registry_packages(&lockfile);
+        let registry_sources = cargo_home.join("registry").join("src");
+        let mut registries = Vec::new();
+        if let Ok(mut entries) = fs.read_dir(&registry_sources).await {
+            while let Some(entry) = entries.next().await {
+                if let Ok(entry) = entry {
+                    registries.push(entry);
+                }
+            }
+        }
+        registries.sort();
+        for (name, version) in packages {
+            for registry in &registries {
+                let directory = registry.join(format!("{name}-{version}"));
+                if fs.is_dir(&directory).await {
+                    directories.push(directory);
+                    break;
+                }
+            }
+        }
+    }
+
+    let node_modules = worktree_root.join("node_modules");
+    if fs.is_dir(&node_modules).await {
+        directories.push(node_modules);
+    }
+
+    directories
+}
+
+/// The names and versions of the packages in a `Cargo.lock` that come from a registry, rather
+/// than from the workspace itself or from git.
+fn locked_registry_packages(lockfile: &str) -> Vec<(String, String)> {
+    fn value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+        let value = line
+            .strip_prefix(key)?
+            .trim_start()
+            .strip_prefix('=')?
+            .trim();
+        value.strip_prefix('"')?.strip_suffix('"')
+    }
+
+    let mut packages = Vec::new();
+    for package in lockfile.split("[[package]]").skip(1) {
+        let mut name = None;
+        let mut version = None;
+        let mut from_registry = false;
+        for line in package.lines().map(str::trim) {
+            if line.starts_with('[') {
+                break;
+            } else if let Some(value) = value(line, "name") {
+                name = Some(value);
+            } else if let Some(value) = value(line, "version") {
+                version = Some(value);
+            } else if let Some(value) = value(line, "source") {
+                from_registry = value.starts_with("registry+") || value.starts_with("sparse+");
+            }
+        }
+        if let Some((name, version)) = name.zip(version).filter(|_| from_registry) {
+            packages.push((name.to_string(), version.to_string()));
+        }
+    }
+    packages
+}
+
+/// Walks the directories and sends the paths of the files matching the query, along with the
+/// directory they were found in, until `limit` files were found.
+pub(crate) async fn find_candidate_paths(
+    directories: Vec<PathBuf>,
+    query: &SearchQuery,
+    limit: usize,
+    fs: Arc<dyn Fs>,
+    tx: Sender<(PathBuf, PathBuf)>,
+) -> Result<()> {
+    let mut found = 0;
+    for root in directories {
+        let mut stack = vec![root.clone()];
+        while let Some(directory) = stack.pop() {
+            let Some(mut entries) = fs.read_dir(&directory).await.log_err() else {
+                continue;
+            };
+            let mut files = Vec::new();
+            let first_subdirectory = stack.len();
+            while let Some(entry) = entries.next().await {
+                let Ok(path) = entry else {
+                    continue;
+                };
+                let Ok(Some(metadata)) = fs.metadata(&path).await else {
+                    continue;
+                };
+                if metadata.is_dir {
+                    let skipped = path.file_name().map_or(false, |name| {
+                        SKIPPED_DIRECTORIES.iter().any(|skipped| name == *skipped)
+                    });
+                    // Symlinked directories aren't followed, to avoid cycles and searching the
+                    // packages that pnpm links within `node_modules` twice.
+                    if !skipped && !metadata.is_symlink {
+                        stack.push(path);
+                    }
+                } else if !metadata.is_fifo && metadata.len <= MAX_FILE_SIZE {
+                    files.push(path);
+                }
+            }
+            // Visit files and subdirectories in a stable order. The stack is popped from the end,
+            // so the subdirectories are pushed in reverse.
+            stack[first_subdirectory..].sort_by(|a, b| b.cmp(a));
+            files.sort();
+
+            for path in files {
+                let relative_path = path.strip_prefix(&root).unwrap_or(&path);
+                if !query.file_matches(relative_path) {
+                    continue;
+                }
+                let Some(file) = fs.open_sync(&path).await.log_err() else {
+                    continue;
+                };
+                if query.detect(file).unwrap_or(false) {
+                    tx.send((root.clone(), path)).await?;
+                    found += 1;
+                    if found >= limit {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_external_search_directories(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/cargo",
+            json!({
+                "registry": {
+                    "src": {
+                        "index.crates.io-6f17d22bba15001f": {
+                            "serde-1.0.0": { "lib.rs": "pub trait Serialize {}" },
+                            "log-0.4.0": { "lib.rs": "pub fn log() {}" },
+                        }
+                    }
+                }
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            "/project",
+            json!({
+                "Cargo.lock": r#"
+                    version = 3
+
+                    [[package]]
+                    name = "project"
+                    version = "0.1.0"
+                    dependencies = [
+                     "serde",
+                    ]
+
+                    [[package]]
+                    name = "serde"
+                    version = "1.0.0"
+                    source = "registry+https://github.com/rust-lang/crates.io-index"
+                    checksum = "0000"
+                "#,
+                "node_modules": { "left-pad": { "index.js": "module.exports = leftPad;" } },
+                "src": { "main.rs": "fn main() {}" },
+            }),
+        )
+        .await;
+        let fs: Arc<dyn Fs> = fs;
+
+        let dependencies = ExternalSearchRoot::Dependencies
+            .directories(&[PathBuf::from("/project")], Path::new("/cargo"), &fs)
+            .await
+            .unwrap();
+        assert_eq!(
+            dependencies,
+            [
+                PathBuf::from("/cargo/registry/src/index.crates.io-6f17d22bba15001f/serde-1.0.0"),
+                PathBuf::from("/project/node_modules"),
+            ]
+        );
+
+        assert_eq!(
+            ExternalSearchRoot::for_path(PathBuf::from("/downloads/sources.tar.gz")),
+            ExternalSearchRoot::Archive(PathBuf::from("/downloads/sources.tar.gz"))
+        );
+        assert_eq!(
+            ExternalSearchRoot::for_path(PathBuf::from("/cargo")),
+            ExternalSearchRoot::Directory(PathBuf::from("/cargo"))
+        );
+        assert!(ExternalSearchRoot::Directory(PathBuf::from("/missing"))
+            .directories(&[], Path::new("/cargo"), &fs)
+            .await
+            .is_err());
+
+        fs.create_dir(Path::new("/tmp/archive_search/old"))
+            .await
+            .unwrap();
+        fs.create_dir(Path::new("/tmp/archive_search/new"))
+            .await
+            .unwrap();
+        remove_extracted_archives(Path::new("/tmp/archive_search"), 1, &fs).await;
+        assert!(!fs.is_dir(Path::new("/tmp/archive_search/old")).await);
+        assert!(fs.is_dir(Path::new("/tmp/archive_search/new")).await);
+
+        let query = SearchQuery::text(
+            "pub",
+            false,
+            false,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .unwrap();
+        let (tx, rx) = smol::channel::unbounded();
+        find_candidate_paths(vec![PathBuf::from("/cargo")], &query, 10, fs.clone(), tx)
+            .await
+            .unwrap();
+        assert_eq!(
+            rx.map(|(_, path)| path).collect::<Vec<_>>().await,
+            [
+                PathBuf::from(
+                    "/cargo/registry/src/index.crates.io-6f17d22bba15001f/log-0.4.0/lib.rs"
+                ),
+                PathBuf::from(
+                    "/cargo/registry/src/index.crates.io-6f17d22bba15001f/serde-1.0.0/lib.rs"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,504 @@
+//! Syntax-aware search, where a pattern written in the language of a buffer is matched against
+//! the nodes of its syntax trees rather than against its text.
+//!
+//! Patterns can contain metavariables: `$NAME` matches any single node, `$$$NAME` matches any
+//! sequence of sibling nodes, and `$_` and `$$$` do the same without capturing what they matched.
+//! A metavariable used more than once must match the same text everywhere. Replacements can
+//! refer to the captured text with the same syntax, so that `$A.unwrap()` can be replaced with
+//! `$A?`.
+
+use std::{fmt::Write as _, ops::Range, sync::Arc};
+
+use aho_corasick::AhoCorasick;
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use language::{with_parser, BufferSnapshot, Grammar, GrammarId, Language, Node};
+use parking_lot::Mutex;
+
+/// Identifiers substituted for metavariables before a pattern is parsed, so that it remains
+/// valid code in most languages.
+const SINGLE_PLACEHOLDER_PREFIX: &str = "__zed_var_";
+const MULTIPLE_PLACEHOLDER_PREFIX: &str = "__zed_vars_";
+
+#[derive(Debug)]
+pub struct StructuralPattern {
+    source: String,
+    /// The pattern with its metavariables replaced by placeholder identifiers.
+    code: String,
+    /// A word that every match contains, used to skip files without parsing them.
+    required_word: Option<AhoCorasick>,
+    /// The pattern parsed with each grammar it was searched with, or `None` if it isn't valid in
+    /// that grammar.
+    compiled: Mutex<HashMap<GrammarId, Option<Arc<PatternNode>>>>,
+}
+
+#[derive(Debug, PartialEq)]
+enum PatternNode {
+    Metavariable {
+        name: Option<String>,
+        multiple: bool,
+    },
+    Leaf {
+        kind_id: u16,
+        text: String,
+    },
+    Branch {
+        kind_id: u16,
+        children: Vec<PatternNode>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+enum TemplatePart<'a> {
+    Text(&'a str),
+    Metavariable(&'a str),
+}
+
+type Bindings = Vec<(String, String)>;
+
+impl StructuralPattern {
+    pub fn new(source: &str) -> Result<Self> {
+        let mut code = String::with_capacity(source.len());
+        let mut words = Vec::new();
+        let mut has_code = false;
+        for part in parse_template(source) {
+            match part {
+                TemplatePart::Text(text) => {
+                    has_code |= !text.trim().is_empty();
+                    words.extend(
+                        text.split(|c: char| !c.is_alphanumeric() && c != '_')
+                            .filter(|word| !word.is_empty()),
+                    );
+                    code.push_str(text);
+                }
+                TemplatePart::Metavariable(metavariable) => {
+                    let (prefix, name) = match metavariable.strip_prefix("$$$") {
+                        Some(name) => (MULTIPLE_PLACEHOLDER_PREFIX, name),
+                        None => (SINGLE_PLACEHOLDER_PREFIX, &metavariable[1..]),
+                    };
+                    write!(code, "{prefix}{name}").ok();
+                }
+            }
+        }
+        if !has_code {
+            return Err(anyhow!(
+                "structural patterns must contain code besides metavariables"
+            ));
+        }
+
+        let required_word = words
+            .into_iter()
+            .max_by_key(|word| word.len())
+            .map(|word| AhoCorasick::new([word]))
+            .transpose()?;
+        Ok(Self {
+            source: source.to_string(),
+            code,
+            required_word,
+            compiled: Default::default(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns whether the text may contain a match, without parsing it.
+    pub fn may_match(&self, text: impl std::io::Read) -> Result<bool> {
+        match &self.required_word {
+            Some(word) => match word.stream_find_iter(text).next() {
+                Some(Ok(_)) => Ok(true),
+                Some(Err(error)) => Err(error.into()),
+                None => Ok(false),
+            },
+            None => Ok(true),
+        }
+    }
+
+    /// Returns the ranges of the buffer's syntax nodes that match the pattern, in order and
+    /// without overlaps. Only nodes within `range` are considered.
+    pub fn search(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<Range<usize>> {
+        let mut matches = Vec::new();
+        for layer in buffer.syntax_layers() {
+            let Some(pattern) = self.compiled(layer.language) else {
+                continue;
+            };
+
+            let mut cursor = layer.node().walk();
+            let mut visited_children = false;
+            loop {
+                if !visited_children {
+                    let node = cursor.node();
+                    let node_range = node.byte_range();
+                    let descend = if node_range.end <= range.start || node_range.start >= range.end
+                    {
+                        false
+                    } else if node_range.start >= range.start
+                        && node_range.end <= range.end
+                        && pattern.matches(node, buffer, &mut Vec::new())
+                    {
+                        matches.push(node_range);
+                        false
+                    } else {
+                        true
+                    };
+                    if descend && cursor.goto_first_child() {
+                        continue;
+                    }
+                    visited_children = true;
+                }
+
+                if cursor.goto_next_sibling() {
+                    visited_children = false;
+                } else if !cursor.goto_parent() {
+                    break;
+                }
+            }
+        }
+
+        // Matches from different layers may overlap when languages are injected into others.
+        matches.sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+        let mut end = 0;
+        matches.retain(|range| {
+            let keep = range.start >= end;
+            if keep {
+                end = range.end;
+            }
+            keep
+        });
+        matches
+    }
+
+    /// Returns the replacement for a match at the given range of the buffer, with the text
+    /// captured by the pattern's metavariables substituted into the template.
+    pub fn replacement(
+        &self,
+        template: &str,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        let bindings = buffer.syntax_layers().find_map(|layer| {
+            let pattern = self.compiled(layer.language)?;
+            let mut node = layer
+                .node()
+                .descendant_for_byte_range(range.start, range.end)?;
+            while node.byte_range() == range {
+                let mut bindings = Vec::new();
+                if pattern.matches(node, buffer, &mut bindings) {
+                    return Some(bindings);
+                }
+                node = node.parent()?;
+            }
+            None
+        })?;
+
+        let mut replacement = String::new();
+        for part in parse_template(template) {
+            match part {
+                TemplatePart::Text(text) => replacement.push_str(text),
+                TemplatePart::Metavariable(metavariable) => {
+                    let name = metavariable.trim_start_matches('$');
+                    match bindings.iter().find(|(bound_name, _)| bound_name == name) {
+                        Some((_, text)) => replacement.push_str(text),
+                        None => replacement.push_str(metavariable),
+                    }
+                }
+            }
+        }
+        Some(replacement)
+    }
+
+    fn compiled(&self, language: &Arc<Language>) -> Option<Arc<PatternNode>> {
+        let grammar = language.grammar()?;
+        self.compiled
+            .lock()
+            .entry(grammar.id())
+            .or_insert_with(|| PatternNode::parse(&self.code, grammar).map(Arc::new))
+            .clone()
+    }
+}
+
+impl PatternNode {
+    fn parse(code: &str, grammar: &Grammar) -> Option<Self> {
+        let tree = with_parser(|parser| {
+            parser.set_language(&grammar.ts_language).ok()?;
+            parser.parse(code, None)
+        })?;
+
+        let mut root = tree.root_node();
+        if contains_error(root) {
+            return None;
+        }
+        // Patterns are usually parsed as a whole program, so skip the nodes that only wrap the
+        // code the user wrote, such as the source file and expression statements.
+        loop {
+            let mut children = significant_children(root);
+            match (children.next(), children.next()) {
+                (Some(child), None) => root = child,
+                _ => break,
+            }
+        }
+
+        let pattern = Self::from_node(root, code);
+        match pattern {
+            PatternNode::Metavariable { .. } => None,
+            pattern => Some(pattern),
+        }
+    }
+
+    fn from_node(node: Node, code: &str) -> Self {
+        let text = &code[node.byte_range()];
+        if let Some(pattern) = Self::metavariable(text) {
+            pattern
+        } else if node.child_count() == 0 {
+            PatternNode::Leaf {
+                kind_id: node.kind_id(),
+                text: text.to_string(),
+            }
+        } else {
+            PatternNode::Branch {
+                kind_id: node.kind_id(),
+                children: significant_children(node)
+                    .map(|child| Self::from_node(child, code))
+                    .collect(),
+            }
+        }
+    }
+
+    fn metavariable(text: &str) -> Option<Self> {
+        let (name, multiple) = if let Some(name) = text.strip_prefix(MULTIPLE_PLACEHOLDER_PREFIX) {
+            (name, true)
+        } else {
+            (text.strip_prefix(SINGLE_PLACEHOLDER_PREFIX)?, false)
+        };
+        if !name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        {
+            return None;
+        }
+        let name = (!name.is_empty() && name != "_").then(|| name.to_string());
+        Some(PatternNode::Metavariable { name, multiple })
+    }
+
+    fn matches(&self, node: Node, buffer: &BufferSnapshot, bindings: &mut Bindings) -> bool {
+        match self {
+            PatternNode::Metavariable { name, .. } => {
+                bind(name.as_deref(), node.byte_range(), buffer, bindings)
+            }
+            PatternNode::Leaf { kind_id, text } => {
+                node.kind_id() == *kind_id
+                    && node.byte_range().len() == text.len()
+                    && buffer.text_for_range(node.byte_range()).collect::<String>() == *text
+            }
+            PatternNode::Branch { kind_id, children } => {
+                node.kind_id() == *kind_id
+                    && Self::matches_sequence(
+                        children,
+                        &significant_children(node).collect::<Vec<_>>(),
+                        buffer,
+                        bindings,
+                    )
+            }
+        }
+    }
+
+    fn matches_sequence(
+        patterns: &[PatternNode],
+        nodes: &[Node],
+        buffer: &BufferSnapshot,
+        bindings: &mut Bindings,
+    ) -> bool {
+        let Some((pattern, remaining_patterns)) = patterns.split_first() else {
+            return nodes.is_empty();
+        };
+
+        if let PatternNode::Metavariable {
+            name,
+            multiple: true,
+        } = pattern
+        {
+            for count in 0..=nodes.len() {
+                let range = match (nodes.first(), count.checked_sub(1).map(|ix| &nodes[ix])) {
+                    (Some(first), Some(last)) => first.start_byte()..last.end_byte(),
+                    _ => 0..0,
+                };
+                let mut candidate = bindings.clone();
+                if bind(name.as_deref(), range, buffer, &mut candidate)
+                    && Self::matches_sequence(
+                        remaining_patterns,
+                        &nodes[count..],
+                        buffer,
+                        &mut candidate,
+                    )
+                {
+                    *bindings = candidate;
+                    return true;
+                }
+            }
+            return false;
+        }
+
+        let Some((node, remaining_nodes)) = nodes.split_first() else {
+            return false;
+        };
+        let mut candidate = bindings.clone();
+        if pattern.matches(*node, buffer, &mut candidate)
+            && Self::matches_sequence(remaining_patterns, remaining_nodes, buffer, &mut candidate)
+        {
+            *bindings = candidate;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Records the text matched by a metavariable, or checks that it is the same as the text the
+/// metavariable matched before.
+fn bind(
+    name: Option<&str>,
+    range: Range<usize>,
+    buffer: &BufferSnapshot,
+    bindings: &mut Bindings,
+) -> bool {
+    let Some(name) = name else {
+        return true;
+    };
+    let text = buffer.text_for_range(range).collect::<String>();
+    match bindings.iter().find(|(bound_name, _)| bound_name == name) {
+        Some((_, bound_text)) => *bound_text == text,
+        None => {
+            bindings.push((name.to_string(), text));
+            true
+        }
+    }
+}
+
+/// Returns the children of the node that take part in matching, skipping comments and the
+/// nodes inserted by the parser to recover from missing tokens.
+fn significant_children<'a>(node: Node<'a>) -> impl Iterator<Item = Node<'a>> {
+    (0..node.child_count())
+        .filter_map(move |ix| node.child(ix))
+        .filter(|child| !child.is_extra() && !child.is_missing())
+}
+
+fn contains_error(node: Node) -> bool {
+    node.is_error() || (0..node.child_count()).any(|ix| node.child(ix).is_some_and(contains_error))
+}
+
+/// Splits a pattern or replacement into text and metavariables such as `$A`, `$$$ARGS` or `$_`.
+/// Dollar signs that aren't followed by an uppercase name are kept as text.
+fn parse_template(template: &str) -> Vec<TemplatePart> {
+    let mut parts = Vec::new();
+    let mut text_start = 0;
+    let mut ix = 0;
+    while let Some(offset) = template[ix..].find('$') {
+        let start = ix + offset;
+        let name_start = if template[start..].starts_with("$$$") {
+            start + 3
+        } else {
+            start + 1
+        };
+        let name_len = template[name_start..]
+            .find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+            .unwrap_or(template.len() - name_start);
+        let name = &template[name_start..name_start + name_len];
+        let is_metavariable = if name_start == start + 3 {
+            name.is_empty() || name.starts_with(|c: char| !c.is_ascii_digit())
+        } else {
+            !name.is_empty() && name.starts_with(|c: char| !c.is_ascii_digit())
+        };
+        if is_metavariable {
+            if text_start < start {
+                parts.push(TemplatePart::Text(&template[text_start..start]));
+            }
+            parts.push(TemplatePart::Metavariable(
+                &template[start..name_start + name_len],
+            ));
+            text_start = name_start + name_len;
+            ix = text_start;
+        } else {
+            ix = start + 1;
+        }
+    }
+    if text_start < template.len() {
+        parts.push(TemplatePart::Text(&template[text_start..]));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{Context as _, TestAppContext};
+    use language::{tree_sitter_rust, Buffer, LanguageConfig};
+
+    #[test]
+    fn test_parse_template() {
+        assert_eq!(
+            parse_template("$A.unwrap_or($$$ARGS, $_) + $$$ + $5 $a"),
+            vec![
+                TemplatePart::Metavariable("$A"),
+                TemplatePart::Text(".unwrap_or("),
+                TemplatePart::Metavariable("$$$ARGS"),
+                TemplatePart::Text(", "),
+                TemplatePart::Metavariable("$_"),
+                TemplatePart::Text(") + "),
+                TemplatePart::Metavariable("$$$"),
+                TemplatePart::Text(" + $5 $a"),
+            ]
+        );
+        assert!(StructuralPattern::new("$A").is_err());
+        assert!(StructuralPattern::new("$A + $B").is_ok());
+    }
+
+    #[gpui::test]
+    async fn test_structural_search_and_replacement(cx: &mut TestAppContext) {
+        let text = r#"
+            fn main() {
+                let a = parse(x).unwrap();
+                let b = items.get(0).unwrap();
+                // not_code.unwrap()
+                let c = unwrap();
+                let d = same(x, x) + same(x, y);
+                call(1, 2, 3);
+            }
+        "#;
+        let language = Arc::new(Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        ));
+        let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(language, cx));
+        cx.executor().run_until_parked();
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        let find = |pattern: &StructuralPattern| {
+            pattern
+                .search(&snapshot, 0..snapshot.len())
+                .into_iter()
+                .map(|range| snapshot.text_for_range(range).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+
+        let pattern = StructuralPattern::new("$A.unwrap()").unwrap();
+        assert_eq!(
+            find(&pattern),
+            ["parse(x).unwrap()", "items.get(0).unwrap()"]
+        );
+        let ranges = pattern.search(&snapshot, 0..snapshot.len());
+        assert_eq!(
+            pattern.replacement("$A?", &snapshot, ranges[1].clone()),
+            Some("items.get(0)?".to_string())
+        );
+
+        let pattern = StructuralPattern::new("same($X, $X)").unwrap();
+        assert_eq!(find(&pattern), ["same(x, x)"]);
+
+        let pattern = StructuralPattern::new("call($$$ARGS)").unwrap();
+        let ranges = pattern.search(&snapshot, 0..snapshot.len());
+        assert_eq!(
+            pattern.replacement("call_all([$$$ARGS])", &snapshot, ranges[0].clone()),
+            Some("call_all([1, 2, 3])".to_string())
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use std::ops::Range;
+
+use bitflags::bitflags;
+use language::BufferSnapshot;
+
+bitflags! {
+    /// The kinds of syntax a search match may be restricted to. A search with no kinds set
+    /// matches anywhere.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct SyntaxKinds: u8 {
+        const COMMENTS = 0b0001;
+        const STRINGS = 0b0010;
+        const IDENTIFIERS = 0b0100;
+        /// Everything outside of comments, including strings and identifiers.
+        const CODE = 0b1000;
+    }
+}
+
+impl SyntaxKinds {
+    /// Removes the matches that aren't within one of these kinds of syntax. `matches` are
+    /// sorted ranges relative to `offset` in the buffer.
+    pub(crate) fn retain_matches(
+        self,
+        buffer: &BufferSnapshot,
+        offset: usize,
+        matches: &mut Vec<Range<usize>>,
+    ) {
+        let (Some(first), Some(last)) = (matches.first(), matches.last()) else {
+            return;
+        };
+        let range = offset + first.start..offset + last.end.max(first.end);
+        let comments = buffer.highlight_capture_ranges(range.clone(), is_comment_capture);
+        let strings = if self.contains(SyntaxKinds::STRINGS) {
+            buffer.highlight_capture_ranges(range.clone(), is_string_capture)
+        } else {
+            Vec::new()
+        };
+        let identifiers = if self.contains(SyntaxKinds::IDENTIFIERS) {
+            buffer.highlight_capture_ranges(range, is_identifier_capture)
+        } else {
+            Vec::new()
+        };
+
+        matches.retain(|range| {
+            let range = offset + range.start..offset + range.end;
+            // Highlights queries don't always capture comments and strings in embedded or
+            // less common languages, so fall back to the scopes of the overrides queries.
+            let override_name = || {
+                buffer
+                    .language_scope_at(range.start)
+                    .and_then(|scope| scope.override_name().map(str::to_string))
+            };
+            let is_comment = contains(&comments, range.start)
+                || override_name().map_or(false, |name| name.starts_with("comment"));
+            (self.contains(SyntaxKinds::COMMENTS) && is_comment)
+                || (self.contains(SyntaxKinds::CODE) && !is_comment)
+                || (self.contains(SyntaxKinds::STRINGS)
+                    && (contains(&strings, range.start)
+                        || override_name().map_or(false, |name| name.starts_with("string"))))
+                || (self.contains(SyntaxKinds::IDENTIFIERS)
+                    && contains(&identifiers, range.start)
+                    && contains(&identifiers, range.end.saturating_sub(1).max(range.start)))
+        });
+    }
+}
+
+fn is_comment_capture(name: &str) -> bool {
+    name.starts_with("comment")
+}
+
+fn is_string_capture(name: &str) -> bool {
+    name.starts_with("string")
+}
+
+/// Whether a highlight capture names something, rather than being a keyword, literal or
+/// punctuation.
+fn is_identifier_capture(name: &str) -> bool {
+    [
+        "variable",
+        "function",
+        "property",
+        "type",
+        "constructor",
+        "constant",
+        "label",
+        "namespace",
+        "module",
+        "attribute",
+        "tag",
+    ]
+    .iter()
+    .any(|prefix| {
+        name.strip_prefix(prefix)
+            .map_or(false, |rest| rest.is_empty() || rest.starts_with('.'))
+    })
+}
+
+/// Whether one of the sorted, disjoint ranges contains the offset.
+fn contains(ranges: &[Range<usize>], offset: usize) -> bool {
+    let ix = ranges.partition_point(|range| range.end <= offset);
+    ranges.get(ix).map_or(false, |range| range.start <= offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::SearchQuery;
+    use gpui::{Context as _, TestAppContext};
+    use language::{tree_sitter_rust, Buffer, Language, LanguageConfig};
+    use std::sync::Arc;
+
+    #[gpui::test]
+    async fn test_search_within_syntax_kinds(cx: &mut TestAppContext) {
+        let text = r#"
+            // the value is computed below
+            fn value() -> &'static str {
+                let value = "value";
+                value
+            }
+        "#;
+        let language = Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_highlights_query(
+            r#"
+            (line_comment) @comment
+            (string_literal) @string
+            (identifier) @variable
+            (function_item name: (identifier) @function)
+            "#,
+        )
+        .unwrap();
+        let buffer =
+            cx.new_model(|cx| Buffer::local(text, cx).with_language(Arc::new(language), cx));
+        cx.executor().run_until_parked();
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+
+        let search = |syntax_kinds| {
+            let query = SearchQuery::text(
+                "value",
+                true,
+                true,
+                false,
+                Default::default(),
+                Default::default(),
+                None,
+            )
+            .unwrap()
+            .with_syntax_kinds(syntax_kinds);
+            let snapshot = snapshot.clone();
+            async move { query.search(&snapshot, None).await.len() }
+        };
+
+        assert_eq!(search(SyntaxKinds::empty()).await, 5);
+        assert_eq!(search(SyntaxKinds::COMMENTS).await, 1);
+        assert_eq!(search(SyntaxKinds::STRINGS).await, 1);
+        assert_eq!(search(SyntaxKinds::IDENTIFIERS).await, 3);
+        assert_eq!(search(SyntaxKinds::CODE).await, 4);
+        assert_eq!(
+            search(SyntaxKinds::COMMENTS | SyntaxKinds::STRINGS).await,
+            2
+        );
+    }
+}
@@ -4,6 +4,7 @@ use collections::HashMap;
 use gpui::{AnyWindowHandle, AppContext, Context, Entity, Model, ModelContext, Task, WeakModel};
 use itertools::Itertools;
 use language::LanguageName;
+use remote::ContainerEngine;
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
@@ -37,9 +38,20 @@ pub enum TerminalKind {
     Task(SpawnInTerminal),
 }
 
+/// How the remote server is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteConnectionKind {
+    Ssh,
+    /// The remote server runs in a local container.
+    Container(ContainerEngine),
+}
+
 /// SshCommand describes how to connect to a remote server
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SshCommand {
+    kind: RemoteConnectionKind,
+    /// `ssh`, or the container engine when the remote server runs in a local container.
+    program: String,
     arguments: Vec<String>,
 }
 
@@ -67,10 +79,19 @@ impl Project {
     fn ssh_details(&self, cx: &AppContext) -> Option<(String, SshCommand)> {
         if let Some(ssh_client) = &self.ssh_client {
             let ssh_client = ssh_client.read(cx);
-            if let Some(args) = ssh_client.ssh_args() {
+            if let Some((program, args)) = ssh_client.ssh_program().zip(ssh_client.ssh_args()) {
+                let connection_options = ssh_client.connection_options();
+                let kind = match connection_options.container() {
+                    Some((engine, _)) => RemoteConnectionKind::Container(engine),
+                    None => RemoteConnectionKind::Ssh,
+                };
                 return Some((
-                    ssh_client.connection_options().host.clone(),
-                    SshCommand { arguments: args },
+                    connection_options.host.clone(),
+                    SshCommand {
+                        kind,
+                        program: program.to_string(),
+                        arguments: args,
+                    },
                 ));
             }
         }
@@ -476,6 +497,11 @@ pub fn wrap_for_ssh(
             env_changes.push_str(&format!("PATH={}:$PATH ", str));
         }
     }
+    // Lets the remote host tell the terminal's processes apart, to detect the ports they open.
+    env_changes.push_str(&format!(
+        "{}=true ",
+        remote::port_forwarding::REMOTE_TERMINAL_ENV_VAR
+    ));
 
     let commands = if let Some(path) = path {
         let path_string = path.to_string_lossy().to_string();
@@ -495,13 +521,21 @@ pub fn wrap_for_ssh(
     } else {
         format!("cd; {env_changes} {to_run}")
     };
-    let shell_invocation = format!("sh -c {}", shlex::try_quote(&commands).unwrap());
-
-    let program = "ssh".to_string();
+    let program = ssh_command.program.clone();
     let mut args = ssh_command.arguments.clone();
 
-    args.push("-t".to_string());
-    args.push(shell_invocation);
+    match ssh_command.kind {
+        RemoteConnectionKind::Ssh => {
+            let shell_invocation = format!("sh -c {}", shlex::try_quote(&commands).unwrap());
+            args.push("-t".to_string());
+            args.push(shell_invocation);
+        }
+        // Container engines execute their arguments directly instead of passing them to a
+        // shell, and take their tty flag before the container, as part of the arguments.
+        RemoteConnectionKind::Container(_) => {
+            args.extend(["sh".to_string(), "-c".to_string(), commands]);
+        }
+    }
     (program, args)
 }
 
@@ -521,6 +555,35 @@ fn add_environment_path(env: &mut HashMap<String, String>, new_path: &Path) -> R
 #[cfg(test)]
 mod tests {
     use collections::HashMap;
+    use remote::ContainerEngine;
+
+    use super::{RemoteConnectionKind, SshCommand};
+
+    #[test]
+    fn test_wrap_for_ssh_by_connection_kind() {
+        // The program may be a full path, so only the kind of the connection decides how to wrap.
+        let ssh_command = SshCommand {
+            kind: RemoteConnectionKind::Ssh,
+            program: "/usr/bin/ssh".to_string(),
+            arguments: vec!["-p".to_string(), "2222".to_string(), "host".to_string()],
+        };
+        let (program, args) =
+            super::wrap_for_ssh(&ssh_command, None, None, HashMap::default(), None);
+        assert_eq!(program, "/usr/bin/ssh");
+        assert_eq!(args[..4], ["-p", "2222", "host", "-t"]);
+        assert!(args[4].starts_with("sh -c "));
+
+        let container_command = SshCommand {
+            kind: RemoteConnectionKind::Container(ContainerEngine::Docker),
+            program: "docker".to_string(),
+            arguments: vec!["exec".to_string(), "-it".to_string(), "dev".to_string()],
+        };
+        let (program, args) =
+            super::wrap_for_ssh(&container_command, None, None, HashMap::default(), None);
+        assert_eq!(program, "docker");
+        assert_eq!(args[..5], ["exec", "-it", "dev", "sh", "-c"]);
+        assert!(args[5].starts_with("cd; "));
+    }
 
     #[test]
     fn test_add_environment_path_with_existing_path() {
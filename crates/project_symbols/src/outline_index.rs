@@ -0,0 +1,364 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use futures::StreamExt as _;
+use gpui::{AsyncAppContext, Model, ModelContext, Task, WeakModel};
+use language::{
+    Anchor, Buffer, BufferSnapshot, CodeLabel, LanguageRegistry, OutlineItem, ToPointUtf16,
+    Unclipped,
+};
+use lsp::LanguageServerName;
+use project::{
+    project_settings::ProjectSettings, Fs, MTime, Project, ProjectPath, Symbol, WorktreeId,
+};
+use settings::Settings as _;
+use util::ResultExt;
+
+use crate::persistence::{IndexedSymbol, DB};
+
+/// The language server name given to the symbols found by the outline index.
+const OUTLINE_INDEX_SERVER_NAME: &str = "outline index";
+
+/// How many modified files are loaded and parsed at the same time.
+const MAX_CONCURRENT_FILES: usize = 16;
+
+/// Whether the symbol comes from the outline index, rather than from a language server.
+pub(crate) fn is_indexed(symbol: &Symbol) -> bool {
+    symbol.language_server_name.0 == OUTLINE_INDEX_SERVER_NAME
+}
+
+/// The symbols of the project's visible worktrees, found with the outline queries of their
+/// languages for searching projects without a running language server.
+///
+/// The symbols are kept in an on-disk index, so that only the files modified since the last
+/// search are parsed again. The symbols of the files that are up to date are available first,
+/// and the ones of the modified files are added as they are parsed.
+pub(crate) struct OutlineIndex {
+    symbols: Vec<Symbol>,
+    _index_worktrees: Task<()>,
+}
+
+impl OutlineIndex {
+    pub(crate) fn new(project: &Model<Project>, cx: &mut ModelContext<Self>) -> Self {
+        let project = project.read(cx);
+        if !project.is_local() {
+            return Self {
+                symbols: Vec::new(),
+                _index_worktrees: Task::ready(()),
+            };
+        }
+        let fs = project.fs().clone();
+        let languages = project.languages().clone();
+        let large_files = ProjectSettings::get_global(cx).large_files;
+        let worktrees = project
+            .visible_worktrees(cx)
+            .map(|worktree| {
+                let worktree = worktree.read(cx);
+                let files = worktree
+                    .snapshot()
+                    .files(false, 0)
+                    .filter(|entry| !large_files.is_large_file(entry.size as usize))
+                    .map(|entry| (entry.path.clone(), entry.mtime))
+                    .collect::<Vec<_>>();
+                (worktree.id(), worktree.abs_path(), files)
+            })
+            .collect::<Vec<_>>();
+
+        let index_worktrees = cx.spawn(|this, cx| async move {
+            for (worktree_id, worktree_path, files) in worktrees {
+                index_worktree(
+                    &this,
+                    worktree_id,
+                    &worktree_path,
+                    files,
+                    &fs,
+                    &languages,
+                    cx.clone(),
+                )
+                .await
+                .log_err();
+            }
+        });
+        Self {
+            symbols: Vec::new(),
+            _index_worktrees: index_worktrees,
+        }
+    }
+
+    /// The symbols found so far.
+    pub(crate) fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    fn add_symbols(
+        &mut self,
+        symbols: impl IntoIterator<Item = Symbol>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.symbols.extend(symbols);
+        cx.notify();
+    }
+}
+
+/// Adds the symbols of the files that weren't modified since they were indexed, then parses the
+/// other ones, and forgets the files that were deleted.
+async fn index_worktree(
+    this: &WeakModel<OutlineIndex>,
+    worktree_id: WorktreeId,
+    worktree_path: &Path,
+    files: Vec<(Arc<Path>, Option<MTime>)>,
+    fs: &Arc<dyn Fs>,
+    languages: &Arc<LanguageRegistry>,
+    mut cx: AsyncAppContext,
+) -> Result<()> {
+    let (indexed_files, indexed_symbols) = cx
+        .background_executor()
+        .spawn({
+            let worktree_path = worktree_path.to_path_buf();
+            async move {
+                anyhow::Ok((
+                    DB.indexed_files(worktree_path.clone())?,
+                    DB.indexed_symbols(worktree_path)?,
+                ))
+            }
+        })
+        .await?;
+    let mut indexed_files = indexed_files
+        .into_iter()
+        .map(|(path, seconds, nanos)| (path, (seconds, nanos)))
+        .collect::<HashMap<_, _>>();
+
+    let mut up_to_date = HashSet::new();
+    let mut modified = Vec::new();
+    for (path, mtime) in files {
+        let Some(mtime) = mtime
+            .and_then(|mtime| mtime.to_seconds_and_nanos_for_persistence())
+            .map(|(seconds, nanos)| (seconds as i64, nanos as i64))
+        else {
+            continue;
+        };
+        if indexed_files.remove(path.as_ref()) == Some(mtime) {
+            up_to_date.insert(path.to_path_buf());
+        } else {
+            modified.push((path, mtime));
+        }
+    }
+    this.update(&mut cx, |this, cx| {
+        this.add_symbols(
+            indexed_symbols
+                .into_iter()
+                .filter(|(path, _)| up_to_date.contains(path))
+                .map(|(path, symbol)| project_symbol(worktree_id, path, symbol)),
+            cx,
+        )
+    })?;
+
+    for path in indexed_files.into_keys() {
+        DB.delete_file(worktree_path.to_path_buf(), path)
+            .await
+            .log_err();
+    }
+
+    let outline_cx = cx.clone();
+    let mut outlines = futures::stream::iter(modified)
+        .map(|(path, mtime)| {
+            let cx = outline_cx.clone();
+            async move {
+                let symbols = outline_file(worktree_path, &path, fs, languages, cx)
+                    .await
+                    .log_err()?;
+                Some((path, mtime, symbols))
+            }
+        })
+        .buffer_unordered(MAX_CONCURRENT_FILES)
+        .ready_chunks(MAX_CONCURRENT_FILES);
+    while let Some(outlines) = outlines.next().await {
+        let mut symbols = Vec::new();
+        for (path, mtime, file_symbols) in outlines.into_iter().flatten() {
+            DB.save_file(
+                worktree_path.to_path_buf(),
+                path.to_path_buf(),
+                mtime,
+                file_symbols.clone(),
+            )
+            .await
+            .log_err();
+            symbols.extend(
+                file_symbols
+                    .into_iter()
+                    .map(|symbol| project_symbol(worktree_id, path.to_path_buf(), symbol)),
+            );
+        }
+        this.update(&mut cx, |this, cx| this.add_symbols(symbols, cx))?;
+    }
+    Ok(())
+}
+
+/// Parses a file on a background thread, returning no symbols when its language has no outline
+/// query, so that it is indexed anyway and isn't looked at until it is modified.
+async fn outline_file(
+    worktree_path: &Path,
+    path: &Path,
+    fs: &Arc<dyn Fs>,
+    languages: &Arc<LanguageRegistry>,
+    cx: AsyncAppContext,
+) -> Result<Vec<IndexedSymbol>> {
+    let Ok(language) = languages.language_for_file_path(path).await else {
+        return Ok(Vec::new());
+    };
+    if language
+        .grammar()
+        .map_or(true, |grammar| grammar.outline_config.is_none())
+    {
+        return Ok(Vec::new());
+    }
+
+    let text = fs.load(&worktree_path.join(path)).await?;
+    let snapshot =
+        cx.update(|cx| Buffer::build_snapshot(text, Some(language), Some(languages.clone()), cx))?;
+    Ok(cx
+        .background_executor()
+        .spawn(async move {
+            let snapshot = snapshot.await;
+            snapshot
+                .outline_items_containing(0..snapshot.len(), false, None)
+                .unwrap_or_default()
+                .iter()
+                .map(|item| indexed_symbol(item, &snapshot))
+                .collect::<Vec<_>>()
+        })
+        .await)
+}
+
+fn indexed_symbol(item: &OutlineItem<Anchor>, snapshot: &BufferSnapshot) -> IndexedSymbol {
+    let mut name = item
+        .name_ranges
+        .iter()
+        .map(|range| &item.text[range.clone()])
+        .collect::<Vec<_>>()
+        .join(" ");
+    if name.is_empty() {
+        name = item.text.clone();
+    }
+    // Outline queries capture the keyword introducing the item as context, before its name.
+    let keywords_end = item.name_ranges.first().map_or(0, |range| range.start);
+    let kind = item.text[..keywords_end]
+        .split_whitespace()
+        .find_map(kind_for_keyword)
+        .unwrap_or("other");
+    IndexedSymbol {
+        name,
+        text: item.text.clone(),
+        kind: kind.to_string(),
+        start: item.range.start.to_point_utf16(snapshot),
+        end: item.range.end.to_point_utf16(snapshot),
+    }
+}
+
+fn kind_for_keyword(keyword: &str) -> Option<&'static str> {
+    match keyword {
+        "fn" | "func" | "function" | "def" | "method" => Some("function"),
+        "struct" | "class" | "enum" | "trait" | "interface" | "type" | "union" => Some("type"),
+        "const" | "static" => Some("constant"),
+        "mod" | "module" | "namespace" | "package" => Some("module"),
+        _ => None,
+    }
+}
+
+fn project_symbol(worktree_id: WorktreeId, path: PathBuf, symbol: IndexedSymbol) -> Symbol {
+    let kind = match symbol.kind.as_str() {
+        "function" => lsp::SymbolKind::FUNCTION,
+        "type" => lsp::SymbolKind::STRUCT,
+        "constant" => lsp::SymbolKind::CONSTANT,
+        "module" => lsp::SymbolKind::MODULE,
+        _ => lsp::SymbolKind::NULL,
+    };
+    Symbol {
+        language_server_name: LanguageServerName::new_static(OUTLINE_INDEX_SERVER_NAME),
+        source_worktree_id: worktree_id,
+        path: ProjectPath {
+            worktree_id,
+            path: path.into(),
+        },
+        label: CodeLabel::plain(symbol.text, Some(&symbol.name)),
+        name: symbol.name,
+        kind,
+        range: Unclipped(symbol.start)..Unclipped(symbol.end),
+        signature: [0; 32],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{init_test, rust_lang};
+    use gpui::{Context as _, TestAppContext};
+    use project::FakeFs;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_outline_index_parses_modified_files(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/outline_index",
+            json!({
+                "one.rs": "fn one() {}\n",
+                "two.rs": "struct Two;\n",
+                "notes.txt": "fn not_a_symbol() {}\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/outline_index".as_ref()], cx).await;
+        project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+
+        let index = cx.new_model(|cx| OutlineIndex::new(&project, cx));
+        cx.run_until_parked();
+        assert_eq!(symbol_names(&index, cx), ["Two", "one"]);
+        assert_eq!(
+            indexed_paths(),
+            ["notes.txt", "one.rs", "two.rs"],
+            "files without outline queries should be indexed too, to skip them next time"
+        );
+
+        fs.insert_file("/outline_index/one.rs", "fn three() {}\n".into())
+            .await;
+        fs.remove_file("/outline_index/two.rs".as_ref(), Default::default())
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        let index = cx.new_model(|cx| OutlineIndex::new(&project, cx));
+        cx.run_until_parked();
+        assert_eq!(symbol_names(&index, cx), ["three"]);
+        assert_eq!(indexed_paths(), ["notes.txt", "one.rs"]);
+    }
+
+    fn symbol_names(index: &Model<OutlineIndex>, cx: &mut TestAppContext) -> Vec<String> {
+        index.read_with(cx, |index, _| {
+            let mut names = index
+                .symbols()
+                .iter()
+                .map(|symbol| symbol.name.clone())
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        })
+    }
+
+    fn indexed_paths() -> Vec<PathBuf> {
+        let mut paths = DB
+            .indexed_files(PathBuf::from("/outline_index"))
+            .unwrap()
+            .into_iter()
+            .map(|(path, _, _)| path)
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+}
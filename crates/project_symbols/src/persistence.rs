@@ -0,0 +1,234 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use db::sqlez::bindable::{Bind, Column, StaticColumnCount};
+use db::sqlez::statement::Statement;
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+use language::PointUtf16;
+
+/// A symbol found by the outline query of a file's language.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct IndexedSymbol {
+    pub(crate) name: String,
+    pub(crate) text: String,
+    pub(crate) kind: String,
+    pub(crate) start: PointUtf16,
+    pub(crate) end: PointUtf16,
+}
+
+impl StaticColumnCount for IndexedSymbol {
+    fn column_count() -> usize {
+        7
+    }
+}
+
+impl Bind for IndexedSymbol {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        let start_index = statement.bind(&self.name, start_index)?;
+        let start_index = statement.bind(&self.text, start_index)?;
+        let start_index = statement.bind(&self.kind, start_index)?;
+        let start_index = statement.bind(&self.start.row, start_index)?;
+        let start_index = statement.bind(&self.start.column, start_index)?;
+        let start_index = statement.bind(&self.end.row, start_index)?;
+        statement.bind(&self.end.column, start_index)
+    }
+}
+
+impl Column for IndexedSymbol {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (name, start_index) = Column::column(statement, start_index)?;
+        let (text, start_index) = Column::column(statement, start_index)?;
+        let (kind, start_index) = Column::column(statement, start_index)?;
+        let (start_row, start_index) = Column::column(statement, start_index)?;
+        let (start_column, start_index) = Column::column(statement, start_index)?;
+        let (end_row, start_index) = Column::column(statement, start_index)?;
+        let (end_column, start_index) = Column::column(statement, start_index)?;
+        let symbol = Self {
+            name,
+            text,
+            kind,
+            start: PointUtf16::new(start_row, start_column),
+            end: PointUtf16::new(end_row, end_column),
+        };
+        Ok((symbol, start_index))
+    }
+}
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // outline_index_files(
+    //   worktree_path: PathBuf,
+    //   path: PathBuf,
+    //   mtime_seconds: u64,
+    //   mtime_nanos: u32,
+    // )
+    //
+    // outline_index_symbols(
+    //   worktree_path: PathBuf,
+    //   path: PathBuf,
+    //   name: String,
+    //   text: String,
+    //   kind: String,
+    //   start_row: u32,
+    //   start_column: u32,
+    //   end_row: u32,
+    //   end_column: u32,
+    // )
+    pub static ref DB: OutlineIndexDb<()> =
+        &[sql!(
+            CREATE TABLE outline_index_files(
+                worktree_path BLOB NOT NULL,
+                path BLOB NOT NULL,
+                mtime_seconds INTEGER NOT NULL,
+                mtime_nanos INTEGER NOT NULL,
+                PRIMARY KEY(worktree_path, path)
+            ) STRICT;
+
+            CREATE TABLE outline_index_symbols(
+                worktree_path BLOB NOT NULL,
+                path BLOB NOT NULL,
+                name TEXT NOT NULL,
+                text TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                start_row INTEGER NOT NULL,
+                start_column INTEGER NOT NULL,
+                end_row INTEGER NOT NULL,
+                end_column INTEGER NOT NULL
+            ) STRICT;
+
+            CREATE INDEX outline_index_symbols_by_file
+            ON outline_index_symbols(worktree_path, path);
+        )];
+);
+
+impl OutlineIndexDb {
+    query! {
+        pub(crate) fn indexed_files(worktree_path: PathBuf) -> Result<Vec<(PathBuf, i64, i64)>> {
+            SELECT path, mtime_seconds, mtime_nanos
+            FROM outline_index_files
+            WHERE worktree_path = ?
+        }
+    }
+
+    query! {
+        pub(crate) fn indexed_symbols(worktree_path: PathBuf) -> Result<Vec<(PathBuf, IndexedSymbol)>> {
+            SELECT path, name, text, kind, start_row, start_column, end_row, end_column
+            FROM outline_index_symbols
+            WHERE worktree_path = ?
+            ORDER BY path, start_row, start_column
+        }
+    }
+
+    /// Replaces the symbols indexed for a file, along with the modification time they are from.
+    pub(crate) async fn save_file(
+        &self,
+        worktree_path: PathBuf,
+        path: PathBuf,
+        mtime: (i64, i64),
+        symbols: Vec<IndexedSymbol>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_outline_index_file", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM outline_index_symbols WHERE worktree_path = ? AND path = ?
+                ))?((worktree_path.as_path(), path.as_path()))?;
+                conn.exec_bound(sql!(
+                    INSERT OR REPLACE INTO outline_index_files
+                        (worktree_path, path, mtime_seconds, mtime_nanos)
+                    VALUES (?, ?, ?, ?)
+                ))?((worktree_path.as_path(), path.as_path(), mtime.0, mtime.1))?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT INTO outline_index_symbols
+                        (worktree_path, path, name, text, kind, start_row, start_column, end_row, end_column)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ))?;
+                for symbol in symbols {
+                    insert((worktree_path.as_path(), path.as_path(), symbol))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Forgets a file that was deleted since it was indexed.
+    pub(crate) async fn delete_file(&self, worktree_path: PathBuf, path: PathBuf) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("delete_outline_index_file", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM outline_index_symbols WHERE worktree_path = ? AND path = ?
+                ))?((worktree_path.as_path(), path.as_path()))?;
+                conn.exec_bound(sql!(
+                    DELETE FROM outline_index_files WHERE worktree_path = ? AND path = ?
+                ))?((worktree_path.as_path(), path.as_path()))
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_save_and_delete_indexed_files() {
+        let worktree_path = PathBuf::from("/persistence");
+        let symbol = |name: &str, row| IndexedSymbol {
+            name: name.to_string(),
+            text: format!("fn {name}"),
+            kind: "function".to_string(),
+            start: PointUtf16::new(row, 0),
+            end: PointUtf16::new(row, 10),
+        };
+
+        DB.save_file(
+            worktree_path.clone(),
+            PathBuf::from("a.rs"),
+            (1, 2),
+            vec![symbol("one", 0), symbol("two", 1)],
+        )
+        .await
+        .unwrap();
+        DB.save_file(worktree_path.clone(), PathBuf::from("b.rs"), (3, 4), vec![])
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.indexed_symbols(worktree_path.clone()).unwrap(),
+            [
+                (PathBuf::from("a.rs"), symbol("one", 0)),
+                (PathBuf::from("a.rs"), symbol("two", 1)),
+            ]
+        );
+
+        // Saving a file again replaces its symbols.
+        DB.save_file(
+            worktree_path.clone(),
+            PathBuf::from("a.rs"),
+            (5, 6),
+            vec![symbol("three", 2)],
+        )
+        .await
+        .unwrap();
+        let mut files = DB.indexed_files(worktree_path.clone()).unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            [(PathBuf::from("a.rs"), 5, 6), (PathBuf::from("b.rs"), 3, 4)]
+        );
+        assert_eq!(
+            DB.indexed_symbols(worktree_path.clone()).unwrap(),
+            [(PathBuf::from("a.rs"), symbol("three", 2))]
+        );
+
+        DB.delete_file(worktree_path.clone(), PathBuf::from("a.rs"))
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.indexed_files(worktree_path.clone()).unwrap(),
+            [(PathBuf::from("b.rs"), 3, 4)]
+        );
+        assert!(DB.indexed_symbols(worktree_path).unwrap().is_empty());
+    }
+}
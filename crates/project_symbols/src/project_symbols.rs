@@ -1,20 +1,37 @@
+mod outline_index;
+mod persistence;
+
 use editor::{scroll::Autoscroll, styled_runs_for_code_label, Bias, Editor};
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
-    rems, AppContext, DismissEvent, FontWeight, Model, ParentElement, StyledText, Task, View,
-    ViewContext, WeakView, WindowContext,
+    rems, AnyElement, AppContext, Context as _, DismissEvent, EntityId, FontWeight, Global, Model,
+    ParentElement, StyledText, Subscription, Task, View, ViewContext, WeakView, WindowContext,
 };
+use language::Buffer;
 use ordered_float::OrderedFloat;
-use picker::{Picker, PickerDelegate};
+use outline_index::OutlineIndex;
+use picker::{Picker, PickerDelegate, PickerPreview};
 use project::{Project, Symbol};
-use std::{borrow::Cow, cmp::Reverse, sync::Arc};
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use theme::ActiveTheme;
 use util::ResultExt;
 use workspace::{
-    ui::{v_flex, Color, Label, LabelCommon, LabelLike, ListItem, ListItemSpacing, Toggleable},
+    ui::{
+        h_flex, v_flex, Color, Label, LabelCommon, LabelLike, LabelSize, ListItem, ListItemSpacing,
+        Toggleable,
+    },
     Workspace,
 };
 
+/// How many server responses are kept, to show results right away when searching again.
+const CACHED_QUERIES: usize = 32;
+
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(
         |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
@@ -33,14 +50,151 @@ pub fn init(cx: &mut AppContext) {
 
 pub type ProjectSymbols = View<Picker<ProjectSymbolsDelegate>>;
 
+/// A group of symbol kinds to filter by, written as `kind:<name>` in the query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SymbolKindFilter {
+    Function,
+    Type,
+    Constant,
+    Module,
+}
+
+impl SymbolKindFilter {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "fn" | "function" | "method" => Some(Self::Function),
+            "type" | "struct" | "class" | "enum" | "interface" | "trait" => Some(Self::Type),
+            "const" | "constant" => Some(Self::Constant),
+            "mod" | "module" | "namespace" => Some(Self::Module),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Function => "functions",
+            Self::Type => "types",
+            Self::Constant => "constants",
+            Self::Module => "modules",
+        }
+    }
+
+    fn matches(self, kind: lsp::SymbolKind) -> bool {
+        match self {
+            Self::Function => matches!(
+                kind,
+                lsp::SymbolKind::FUNCTION | lsp::SymbolKind::METHOD | lsp::SymbolKind::CONSTRUCTOR
+            ),
+            Self::Type => matches!(
+                kind,
+                lsp::SymbolKind::CLASS
+                    | lsp::SymbolKind::STRUCT
+                    | lsp::SymbolKind::ENUM
+                    | lsp::SymbolKind::INTERFACE
+                    | lsp::SymbolKind::TYPE_PARAMETER
+            ),
+            Self::Constant => matches!(
+                kind,
+                lsp::SymbolKind::CONSTANT | lsp::SymbolKind::ENUM_MEMBER
+            ),
+            Self::Module => matches!(
+                kind,
+                lsp::SymbolKind::MODULE | lsp::SymbolKind::NAMESPACE | lsp::SymbolKind::PACKAGE
+            ),
+        }
+    }
+}
+
+/// A symbol search, where `kind:<kind>` filters the symbols by kind and `in:<path>` scopes them
+/// to a directory or worktree. The rest of the query is sent to the language servers.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct SymbolQuery {
+    text: String,
+    kinds: Vec<SymbolKindFilter>,
+    scope: Option<PathBuf>,
+}
+
+impl SymbolQuery {
+    fn parse(query: &str) -> Self {
+        let mut words = Vec::new();
+        let mut kinds = Vec::new();
+        let mut scope = None;
+        for word in query.split_whitespace() {
+            if let Some(kind) = word.strip_prefix("kind:").and_then(SymbolKindFilter::parse) {
+                if !kinds.contains(&kind) {
+                    kinds.push(kind);
+                }
+            } else if let Some(path) = word.strip_prefix("in:").filter(|path| !path.is_empty()) {
+                scope = Some(PathBuf::from(path.trim_end_matches('/')));
+            } else {
+                words.push(word);
+            }
+        }
+        Self {
+            text: words.join(" "),
+            kinds,
+            scope,
+        }
+    }
+
+    fn has_filters(&self) -> bool {
+        !self.kinds.is_empty() || self.scope.is_some()
+    }
+
+    /// Whether the symbol passes the filters, given the root name of its worktree.
+    fn matches(&self, symbol: &Symbol, root_name: Option<&str>) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.iter().any(|kind| kind.matches(symbol.kind)) {
+            return false;
+        }
+        let Some(scope) = &self.scope else {
+            return true;
+        };
+        symbol.path.path.starts_with(scope)
+            || root_name.map_or(false, |root_name| {
+                Path::new(root_name)
+                    .join(&symbol.path.path)
+                    .starts_with(scope)
+            })
+    }
+}
+
+/// The symbols the language servers last returned for a query, shown while they answer again.
+#[derive(Default)]
+struct SymbolCache {
+    entries: VecDeque<(EntityId, String, Arc<Vec<Symbol>>)>,
+}
+
+impl Global for SymbolCache {}
+
+impl SymbolCache {
+    fn get(&self, project_id: EntityId, query: &str) -> Option<Arc<Vec<Symbol>>> {
+        self.entries
+            .iter()
+            .find(|(id, cached_query, _)| *id == project_id && cached_query == query)
+            .map(|(_, _, symbols)| symbols.clone())
+    }
+
+    fn insert(&mut self, project_id: EntityId, query: String, symbols: Arc<Vec<Symbol>>) {
+        self.entries
+            .retain(|(id, cached_query, _)| *id != project_id || *cached_query != query);
+        if self.entries.len() >= CACHED_QUERIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((project_id, query, symbols));
+    }
+}
+
 pub struct ProjectSymbolsDelegate {
     workspace: WeakView<Workspace>,
     project: Model<Project>,
     selected_match_index: usize,
+    query: SymbolQuery,
     symbols: Vec<Symbol>,
+    outline_index: Option<(Model<OutlineIndex>, Subscription)>,
     visible_match_candidates: Vec<StringMatchCandidate>,
     external_match_candidates: Vec<StringMatchCandidate>,
     show_worktree_root_name: bool,
+    show_language_server_name: bool,
     matches: Vec<StringMatch>,
 }
 
@@ -50,11 +204,14 @@ impl ProjectSymbolsDelegate {
             workspace,
             project,
             selected_match_index: 0,
+            query: SymbolQuery::default(),
             symbols: Default::default(),
+            outline_index: None,
             visible_match_candidates: Default::default(),
             external_match_candidates: Default::default(),
             matches: Default::default(),
             show_worktree_root_name: false,
+            show_language_server_name: false,
         }
     }
 
@@ -83,6 +240,8 @@ impl ProjectSymbolsDelegate {
 
         visible_matches.sort_unstable_by_key(sort_key_for_match);
         external_matches.sort_unstable_by_key(sort_key_for_match);
+        self.group_by_language_server(&mut visible_matches);
+        self.group_by_language_server(&mut external_matches);
         let mut matches = visible_matches;
         matches.append(&mut external_matches);
 
@@ -97,6 +256,120 @@ impl ProjectSymbolsDelegate {
         self.matches = matches;
         self.set_selected_index(0, cx);
     }
+
+    /// Keeps the matches of each language server together, ordering the servers by their best match.
+    fn group_by_language_server(&self, matches: &mut [StringMatch]) {
+        let mut servers = Vec::new();
+        for mat in matches.iter() {
+            let server = &self.symbols[mat.candidate_id].language_server_name;
+            if !servers.contains(&server) {
+                servers.push(server);
+            }
+        }
+        matches.sort_by_key(|mat| {
+            let server = &self.symbols[mat.candidate_id].language_server_name;
+            servers.iter().position(|candidate| *candidate == server)
+        });
+    }
+
+    /// Replaces the symbols to match against with the ones passing the query's filters.
+    fn set_symbols(&mut self, mut symbols: Vec<Symbol>, cx: &mut ViewContext<Picker<Self>>) {
+        let project = self.project.read(cx);
+        symbols.retain(|symbol| {
+            let root_name = project
+                .worktree_for_id(symbol.path.worktree_id, cx)
+                .map(|worktree| worktree.read(cx).root_name());
+            self.query.matches(symbol, root_name)
+        });
+        let (visible_match_candidates, external_match_candidates) = symbols
+            .iter()
+            .enumerate()
+            .map(|(id, symbol)| StringMatchCandidate::new(id, &symbol.label.filter_text()))
+            .partition(|candidate| {
+                project
+                    .entry_for_path(&symbols[candidate.id].path, cx)
+                    .map_or(false, |e| !e.is_ignored)
+            });
+
+        self.show_language_server_name = symbols
+            .iter()
+            .any(|symbol| symbol.language_server_name != symbols[0].language_server_name);
+        self.visible_match_candidates = visible_match_candidates;
+        self.external_match_candidates = external_match_candidates;
+        self.symbols = symbols;
+    }
+
+    /// Searches the language servers, or the outline index when none of them is running.
+    fn search_symbols(
+        &mut self,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Task<anyhow::Result<Vec<Symbol>>> {
+        if self.has_language_servers(cx) {
+            let project_id = self.project.entity_id();
+            let text = self.query.text.clone();
+            let symbols = self
+                .project
+                .update(cx, |project, cx| project.symbols(&text, cx));
+            return cx.spawn(|_, mut cx| async move {
+                let symbols = symbols.await?;
+                cx.update(|cx| {
+                    cx.default_global::<SymbolCache>().insert(
+                        project_id,
+                        text,
+                        Arc::new(symbols.clone()),
+                    )
+                })?;
+                Ok(symbols)
+            });
+        }
+
+        let outline_index = self.outline_index(cx);
+        Task::ready(Ok(outline_index.read(cx).symbols().to_vec()))
+    }
+
+    fn has_language_servers(&self, cx: &AppContext) -> bool {
+        self.project
+            .read(cx)
+            .language_server_statuses(cx)
+            .next()
+            .is_some()
+    }
+
+    /// Returns the outline index, creating it on the first search without language servers.
+    /// The symbols are matched again whenever it finds more of them.
+    fn outline_index(&mut self, cx: &mut ViewContext<Picker<Self>>) -> Model<OutlineIndex> {
+        if let Some((outline_index, _)) = &self.outline_index {
+            return outline_index.clone();
+        }
+        let project = self.project.clone();
+        let outline_index = cx.new_model(|cx| OutlineIndex::new(&project, cx));
+        let subscription = cx.observe(&outline_index, |picker, outline_index, cx| {
+            let delegate = &mut picker.delegate;
+            if delegate.has_language_servers(cx) {
+                return;
+            }
+            delegate.set_symbols(outline_index.read(cx).symbols().to_vec(), cx);
+            let text = delegate.query.text.clone();
+            delegate.filter(&text, cx);
+            cx.notify();
+        });
+        self.outline_index = Some((outline_index.clone(), subscription));
+        outline_index
+    }
+
+    fn open_buffer_for_symbol(
+        &self,
+        symbol: &Symbol,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Task<anyhow::Result<Model<Buffer>>> {
+        self.project.update(cx, |project, cx| {
+            if outline_index::is_indexed(symbol) {
+                project.open_buffer(symbol.path.clone(), cx)
+            } else {
+                project.open_buffer_for_symbol(symbol, cx)
+            }
+        })
+    }
 }
 
 impl PickerDelegate for ProjectSymbolsDelegate {
@@ -111,10 +384,7 @@ impl PickerDelegate for ProjectSymbolsDelegate {
             .get(self.selected_match_index)
             .map(|mat| self.symbols[mat.candidate_id].clone())
         {
-            let buffer = self.project.update(cx, |project, cx| {
-                project.open_buffer_for_symbol(&symbol, cx)
-            });
-            let symbol = symbol.clone();
+            let buffer = self.open_buffer_for_symbol(&symbol, cx);
             let workspace = self.workspace.clone();
             cx.spawn(|_, mut cx| async move {
                 let buffer = buffer.await?;
@@ -146,6 +416,33 @@ impl PickerDelegate for ProjectSymbolsDelegate {
 
     fn dismissed(&mut self, _cx: &mut ViewContext<Picker<Self>>) {}
 
+    fn supports_preview(&self) -> bool {
+        true
+    }
+
+    fn preview(
+        &self,
+        ix: usize,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Task<anyhow::Result<PickerPreview>>> {
+        let symbol = self
+            .symbols
+            .get(self.matches.get(ix)?.candidate_id)?
+            .clone();
+        let buffer = self.open_buffer_for_symbol(&symbol, cx);
+        Some(cx.spawn(|_, mut cx| async move {
+            let buffer = buffer.await?;
+            let range = buffer.read_with(&cx, |buffer, _| {
+                buffer.unclipped_point_utf16_to_point(symbol.range.start)
+                    ..buffer.unclipped_point_utf16_to_point(symbol.range.end)
+            })?;
+            Ok(PickerPreview {
+                buffer,
+                range: Some(range),
+            })
+        }))
+    }
+
     fn match_count(&self) -> usize {
         self.matches.len()
     }
@@ -159,39 +456,62 @@ impl PickerDelegate for ProjectSymbolsDelegate {
     }
 
     fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
-        self.filter(&query, cx);
+        self.query = SymbolQuery::parse(&query);
+        let cached_symbols = cx
+            .try_global::<SymbolCache>()
+            .and_then(|cache| cache.get(self.project.entity_id(), &self.query.text));
+        if let Some(symbols) = cached_symbols {
+            self.set_symbols(symbols.as_ref().clone(), cx);
+        }
+        let text = self.query.text.clone();
+        self.filter(&text, cx);
         self.show_worktree_root_name = self.project.read(cx).visible_worktrees(cx).count() > 1;
-        let symbols = self
-            .project
-            .update(cx, |project, cx| project.symbols(&query, cx));
+        let symbols = self.search_symbols(cx);
         cx.spawn(|this, mut cx| async move {
             let symbols = symbols.await.log_err();
             if let Some(symbols) = symbols {
                 this.update(&mut cx, |this, cx| {
                     let delegate = &mut this.delegate;
-                    let project = delegate.project.read(cx);
-                    let (visible_match_candidates, external_match_candidates) = symbols
-                        .iter()
-                        .enumerate()
-                        .map(|(id, symbol)| {
-                            StringMatchCandidate::new(id, &symbol.label.filter_text())
-                        })
-                        .partition(|candidate| {
-                            project
-                                .entry_for_path(&symbols[candidate.id].path, cx)
-                                .map_or(false, |e| !e.is_ignored)
-                        });
-
-                    delegate.visible_match_candidates = visible_match_candidates;
-                    delegate.external_match_candidates = external_match_candidates;
-                    delegate.symbols = symbols;
-                    delegate.filter(&query, cx);
+                    delegate.set_symbols(symbols, cx);
+                    delegate.filter(&text, cx);
                 })
                 .log_err();
             }
         })
     }
 
+    fn render_header(&self, _: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        if !self.query.has_filters() {
+            return None;
+        }
+        let kinds = (!self.query.kinds.is_empty()).then(|| {
+            let kinds = self
+                .query
+                .kinds
+                .iter()
+                .map(|kind| kind.label())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Label::new(format!("Only {kinds}"))
+                .size(LabelSize::Small)
+                .color(Color::Muted)
+        });
+        let scope = self.query.scope.as_ref().map(|scope| {
+            Label::new(format!("In {}", scope.display()))
+                .size(LabelSize::Small)
+                .color(Color::Muted)
+        });
+        Some(
+            h_flex()
+                .mt_1()
+                .px_3()
+                .gap_2()
+                .children(kinds)
+                .children(scope)
+                .into_any_element(),
+        )
+    }
+
     fn render_match(
         &self,
         ix: usize,
@@ -230,11 +550,18 @@ impl PickerDelegate for ProjectSymbolsDelegate {
             }),
         );
 
+        let language_server_name = self.show_language_server_name.then(|| {
+            Label::new(symbol.language_server_name.0.clone())
+                .size(LabelSize::Small)
+                .color(Color::Muted)
+        });
+
         Some(
             ListItem::new(ix)
                 .inset(true)
                 .spacing(ListItemSpacing::Sparse)
                 .toggle_state(selected)
+                .end_slot::<Label>(language_server_name)
                 .child(
                     v_flex()
                         .child(
@@ -383,7 +710,87 @@ mod tests {
         });
     }
 
-    fn init_test(cx: &mut TestAppContext) {
+    #[gpui::test]
+    async fn test_outline_index_without_language_servers(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "src": {
+                    "lib.rs": "struct Point;\nconst ORIGIN: Point = Point;\nfn distance() {}\n",
+                },
+                "tests": {
+                    "distance.rs": "fn test_distance() {}\n",
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let symbols = cx.new_view(|cx| {
+            Picker::uniform_list(
+                ProjectSymbolsDelegate::new(workspace.downgrade(), project.clone()),
+                cx,
+            )
+        });
+        let mut search = |query: &str| {
+            symbols.update(cx, |p, cx| p.update_matches(query.to_string(), cx));
+            cx.run_until_parked();
+            symbols.update(cx, |symbols, _| {
+                let delegate = &symbols.delegate;
+                let mut names = delegate
+                    .matches
+                    .iter()
+                    .map(|mat| delegate.symbols[mat.candidate_id].name.clone())
+                    .collect::<Vec<_>>();
+                names.sort();
+                names
+            })
+        };
+
+        assert_eq!(search("dist"), ["distance", "test_distance"]);
+        assert_eq!(search("dist in:tests"), ["test_distance"]);
+        assert_eq!(search("dist in:dir/src"), ["distance"]);
+        assert_eq!(search("kind:type"), ["Point"]);
+        assert_eq!(search("kind:const kind:type"), ["ORIGIN", "Point"]);
+        assert_eq!(search("kind:fn in:src"), ["distance"]);
+    }
+
+    #[test]
+    fn test_symbol_query_parsing() {
+        assert_eq!(
+            SymbolQuery::parse("  point  "),
+            SymbolQuery {
+                text: "point".into(),
+                kinds: Vec::new(),
+                scope: None,
+            }
+        );
+        assert_eq!(
+            SymbolQuery::parse("kind:fn new kind:const in:crates/gpui/ kind:fn"),
+            SymbolQuery {
+                text: "new".into(),
+                kinds: vec![SymbolKindFilter::Function, SymbolKindFilter::Constant],
+                scope: Some(PathBuf::from("crates/gpui")),
+            }
+        );
+        // Unknown kinds and empty scopes are searched for as text.
+        assert_eq!(
+            SymbolQuery::parse("kind:banana in:"),
+            SymbolQuery {
+                text: "kind:banana in:".into(),
+                kinds: Vec::new(),
+                scope: None,
+            }
+        );
+    }
+
+    pub(crate) fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let store = SettingsStore::test(cx);
             cx.set_global(store);
@@ -396,6 +803,28 @@ mod tests {
         });
     }
 
+    pub(crate) fn rust_lang() -> Arc<Language> {
+        Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_outline_query(
+                r#"(struct_item "struct" @context name: (_) @name) @item
+                (const_item "const" @context name: (_) @name) @item
+                (function_item "fn" @context name: (_) @name) @item"#,
+            )
+            .unwrap(),
+        )
+    }
+
     fn symbol(name: &str, path: impl AsRef<Path>) -> lsp::SymbolInformation {
         #[allow(deprecated)]
         lsp::SymbolInformation {
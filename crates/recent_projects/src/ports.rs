@@ -0,0 +1,184 @@
+use editor::Editor;
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render,
+    Subscription, View, ViewContext,
+};
+use remote::{PortForwardSource, SshPortForward, SshRemoteClient};
+use ui::{prelude::*, ListItem, ListItemSpacing, Tooltip};
+use workspace::{notifications::DetachAndPromptErr, ModalView, Workspace};
+use zed_actions::OpenPorts;
+
+/// Lists the ports forwarded over the SSH connection of the project.
+pub struct PortsView {
+    ssh_client: Model<SshRemoteClient>,
+    port_editor: View<Editor>,
+    port_error: Option<SharedString>,
+    _subscription: Subscription,
+}
+
+impl ModalView for PortsView {}
+
+impl EventEmitter<DismissEvent> for PortsView {}
+
+impl FocusableView for PortsView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.port_editor.focus_handle(cx)
+    }
+}
+
+impl PortsView {
+    pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &OpenPorts, cx| {
+            let Some(ssh_client) = workspace.project().read(cx).ssh_client() else {
+                return;
+            };
+            workspace.toggle_modal(cx, |cx| Self::new(ssh_client, cx))
+        });
+    }
+
+    fn new(ssh_client: Model<SshRemoteClient>, cx: &mut ViewContext<Self>) -> Self {
+        let port_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Forward a port, e.g. 3000 or 8080:db:5432", cx);
+            editor
+        });
+        let _subscription = cx.observe(&ssh_client, |_, _, cx| cx.notify());
+        Self {
+            ssh_client,
+            port_editor,
+            port_error: None,
+            _subscription,
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let input = self.port_editor.read(cx).text(cx);
+        match SshPortForward::parse_local(&input) {
+            Ok(forward) => {
+                self.port_error = None;
+                self.port_editor.update(cx, |editor, cx| editor.clear(cx));
+                self.ssh_client
+                    .update(cx, |client, cx| {
+                        client.forward_port(forward, PortForwardSource::Manual, cx)
+                    })
+                    .detach_and_prompt_err("Failed to forward port", cx, |_, _| None);
+            }
+            Err(error) => self.port_error = Some(error.to_string().into()),
+        }
+        cx.notify();
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn stop_port_forward(&mut self, forward: SshPortForward, cx: &mut ViewContext<Self>) {
+        self.ssh_client
+            .update(cx, |client, cx| client.stop_port_forward(&forward, cx))
+            .detach_and_prompt_err("Failed to stop port forward", cx, |_, _| None);
+    }
+
+    fn render_port_forwards(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let port_forwards = self.ssh_client.read(cx).port_forwards().to_vec();
+        v_flex()
+            .py_1()
+            .when(port_forwards.is_empty(), |this| {
+                this.child(
+                    h_flex().px_2().py_1().child(
+                        Label::new("No ports are forwarded.")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+                )
+            })
+            .children(port_forwards.into_iter().enumerate().map(|(ix, active)| {
+                let source = match active.source {
+                    PortForwardSource::Configured => "Configured",
+                    PortForwardSource::Detected => "Detected",
+                    PortForwardSource::Manual => "Manual",
+                };
+                let url = active.forward.local_url();
+                let forward = active.forward.clone();
+                ListItem::new(ix)
+                    .inset(true)
+                    .spacing(ListItemSpacing::Sparse)
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(active.forward.to_string()))
+                            .child(
+                                Label::new(source)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .when_some(active.error, |this, error| {
+                                this.child(
+                                    Label::new(error)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Error)
+                                        .single_line(),
+                                )
+                            }),
+                    )
+                    .end_slot(
+                        h_flex()
+                            .gap_1()
+                            .when_some(url, |this, url| {
+                                this.child(
+                                    IconButton::new("open-in-browser", IconName::ExternalLink)
+                                        .icon_size(IconSize::Small)
+                                        .tooltip(|cx| Tooltip::text("Open in Browser", cx))
+                                        .on_click(move |_, cx| cx.open_url(&url)),
+                                )
+                            })
+                            .child(
+                                IconButton::new("stop", IconName::Close)
+                                    .icon_size(IconSize::Small)
+                                    .tooltip(|cx| Tooltip::text("Stop Forwarding", cx))
+                                    .on_click(cx.listener(move |this, _, cx| {
+                                        this.stop_port_forward(forward.clone(), cx)
+                                    })),
+                            ),
+                    )
+            }))
+    }
+}
+
+impl Render for PortsView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let connection_string = self.ssh_client.read(cx).connection_string();
+        v_flex()
+            .key_context("PortsView")
+            .elevation_3(cx)
+            .w(rems(34.))
+            .overflow_hidden()
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Headline::new("Forwarded Ports").size(HeadlineSize::XSmall))
+                    .child(
+                        Label::new(connection_string)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(self.render_port_forwards(cx))
+            .child(
+                v_flex()
+                    .p_2()
+                    .gap_1()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(self.port_editor.clone())
+                    .when_some(self.port_error.clone(), |this, error| {
+                        this.child(Label::new(error).size(LabelSize::Small).color(Color::Error))
+                    }),
+            )
+    }
+}
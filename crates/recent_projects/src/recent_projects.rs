@@ -1,8 +1,11 @@
 pub mod disconnected_overlay;
+mod ports;
 mod remote_servers;
 mod ssh_connections;
 pub use ssh_connections::{is_connecting_over_ssh, open_ssh_project};
 
+use anyhow::Context as _;
+use db::frecency::{Frecencies, FrecencyKind, FrecencyScope, FRECENCY};
 use disconnected_overlay::DisconnectedOverlay;
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
@@ -15,18 +18,24 @@ use picker::{
     highlighted_match_with_paths::{HighlightedMatchWithPaths, HighlightedText},
     Picker, PickerDelegate,
 };
+pub use ports::PortsView;
+use remote::{
+    devcontainer::{find_dev_container_config, DevContainer, DevContainerConfig},
+    ContainerEngine,
+};
 pub use remote_servers::RemoteServerProjects;
 use settings::Settings;
 pub use ssh_connections::SshSettings;
 use std::{
+    collections::HashSet,
     path::{Path, PathBuf},
     sync::Arc,
 };
 use ui::{prelude::*, tooltip_container, KeyBinding, ListItem, ListItemSpacing, Tooltip};
 use util::{paths::PathExt, ResultExt};
 use workspace::{
-    CloseIntent, ModalView, OpenOptions, SerializedWorkspaceLocation, Workspace, WorkspaceId,
-    WORKSPACE_DB,
+    notifications::DetachAndPromptErr, CloseIntent, ModalView, OpenOptions,
+    SerializedWorkspaceLocation, Workspace, WorkspaceId, WORKSPACE_DB,
 };
 use zed_actions::{OpenRecent, OpenRemote};
 
@@ -36,6 +45,7 @@ pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(RemoteServerProjects::register)
         .detach();
     cx.observe_new_views(DisconnectedOverlay::register).detach();
+    cx.observe_new_views(PortsView::register).detach();
 }
 
 pub struct RecentProjects {
@@ -65,9 +75,27 @@ impl RecentProjects {
                 .await
                 .log_err()
                 .unwrap_or_default();
+            let frecencies = cx
+                .background_executor()
+                .spawn(async move {
+                    FRECENCY
+                        .frecencies(FrecencyKind::Project, FrecencyScope::Global)
+                        .log_err()
+                        .unwrap_or_default()
+                })
+                .await;
+            let dev_container_workspaces = cx
+                .background_executor()
+                .spawn({
+                    let workspaces = workspaces.clone();
+                    async move { workspaces_with_dev_containers(&workspaces) }
+                })
+                .await;
             this.update(&mut cx, move |this, cx| {
                 this.picker.update(cx, move |picker, cx| {
                     picker.delegate.set_workspaces(workspaces);
+                    picker.delegate.frecencies = frecencies;
+                    picker.delegate.dev_container_workspaces = dev_container_workspaces;
                     picker.update_matches(picker.query(cx), cx)
                 })
             })
@@ -141,6 +169,9 @@ pub struct RecentProjectsDelegate {
     // Flag to reset index when there is a new query vs not reset index when user delete an item
     reset_selected_match_index: bool,
     has_any_non_local_projects: bool,
+    /// Local projects with a `devcontainer.json`, which can be opened in a container.
+    dev_container_workspaces: HashSet<WorkspaceId>,
+    frecencies: Frecencies,
 }
 
 impl RecentProjectsDelegate {
@@ -154,6 +185,8 @@ impl RecentProjectsDelegate {
             render_paths,
             reset_selected_match_index: true,
             has_any_non_local_projects: false,
+            dev_container_workspaces: HashSet::default(),
+            frecencies: Frecencies::default(),
         }
     }
 
@@ -239,6 +272,10 @@ impl PickerDelegate for RecentProjectsDelegate {
             &Default::default(),
             cx.background_executor().clone(),
         ));
+        for mat in &mut self.matches {
+            let (workspace_id, _) = self.workspaces[mat.candidate_id];
+            mat.score = self.frecencies.bias(&frecency_key(workspace_id), mat.score);
+        }
         self.matches.sort_unstable_by_key(|m| m.candidate_id);
 
         if self.reset_selected_match_index {
@@ -268,6 +305,10 @@ impl PickerDelegate for RecentProjectsDelegate {
             } else {
                 !secondary
             };
+            let key = frecency_key(*candidate_workspace_id);
+            db::write_and_log(cx, move || {
+                FRECENCY.record_use(FrecencyKind::Project, FrecencyScope::Global, key)
+            });
             workspace
                 .update(cx, |workspace, cx| {
                     if workspace.database_id() == Some(*candidate_workspace_id) {
@@ -423,7 +464,24 @@ impl PickerDelegate for RecentProjectsDelegate {
                         }),
                 )
                 .map(|el| {
-                    let delete_button = div()
+                    let workspace_id = self.workspaces[hit.candidate_id].0;
+                    let buttons = h_flex()
+                        .when(
+                            self.dev_container_workspaces.contains(&workspace_id),
+                            |this| {
+                                this.child(
+                                    IconButton::new("open-in-container", IconName::Blocks)
+                                        .icon_size(IconSize::Small)
+                                        .on_click(cx.listener(move |this, _event, cx| {
+                                            cx.stop_propagation();
+                                            cx.prevent_default();
+
+                                            this.delegate.open_in_dev_container(ix, cx)
+                                        }))
+                                        .tooltip(|cx| Tooltip::text("Open in Dev Container", cx)),
+                                )
+                            },
+                        )
                         .child(
                             IconButton::new("delete", IconName::Close)
                                 .icon_size(IconSize::Small)
@@ -438,9 +496,9 @@ impl PickerDelegate for RecentProjectsDelegate {
                         .into_any_element();
 
                     if self.selected_index() == ix {
-                        el.end_slot::<AnyElement>(delete_button)
+                        el.end_slot::<AnyElement>(buttons)
                     } else {
-                        el.end_hover_slot::<AnyElement>(delete_button)
+                        el.end_hover_slot::<AnyElement>(buttons)
                     }
                 })
                 .tooltip(move |cx| {
@@ -477,6 +535,27 @@ impl PickerDelegate for RecentProjectsDelegate {
     }
 }
 
+fn frecency_key(workspace_id: WorkspaceId) -> String {
+    i64::from(workspace_id).to_string()
+}
+
+fn workspaces_with_dev_containers(
+    workspaces: &[(WorkspaceId, SerializedWorkspaceLocation)],
+) -> HashSet<WorkspaceId> {
+    workspaces
+        .iter()
+        .filter_map(|(workspace_id, location)| match location {
+            SerializedWorkspaceLocation::Local(paths, _)
+                if paths.paths().len() == 1
+                    && find_dev_container_config(&paths.paths()[0]).is_some() =>
+            {
+                Some(*workspace_id)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 // Compute the highlighted text for the name and path
 fn highlights_for_path(
     path: &Path,
@@ -547,6 +626,46 @@ impl RecentProjectsDelegate {
         }
     }
 
+    /// Starts the dev container of a local project, and opens the project inside of it.
+    fn open_in_dev_container(&self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(selected_match) = self.matches.get(ix) else {
+            return;
+        };
+        let SerializedWorkspaceLocation::Local(paths, _) =
+            &self.workspaces[selected_match.candidate_id].1
+        else {
+            return;
+        };
+        let Some(project_path) = paths.paths().first().cloned() else {
+            return;
+        };
+        let Some(app_state) = self
+            .workspace
+            .update(cx, |workspace, _| workspace.app_state().clone())
+            .log_err()
+        else {
+            return;
+        };
+
+        cx.spawn(|_, mut cx| async move {
+            let engine = ContainerEngine::detect().context(
+                "Neither docker nor podman was found, which are required to run dev containers.",
+            )?;
+            let config = DevContainerConfig::load(&project_path).await?;
+            let container = DevContainer::up(engine, &project_path, &config).await?;
+            open_ssh_project(
+                container.connection_options(),
+                vec![container.workspace_folder],
+                app_state,
+                OpenOptions::default(),
+                &mut cx,
+            )
+            .await
+        })
+        .detach_and_prompt_err("Failed to open dev container", cx, |_, _| None);
+        cx.emit(DismissEvent);
+    }
+
     fn is_current_workspace(
         &self,
         workspace_id: WorkspaceId,
@@ -860,6 +860,8 @@ impl RemoteServerProjects {
                     nickname: None,
                     args: connection_options.args.unwrap_or_default(),
                     upload_binary_over_ssh: None,
+                    port_forwards: connection_options.port_forwards.unwrap_or_default(),
+                    auto_forward_ports: None,
                 })
         });
     }
@@ -17,7 +17,7 @@ use language::CursorShape;
 use markdown::{Markdown, MarkdownStyle};
 use release_channel::ReleaseChannel;
 use remote::ssh_session::ConnectionIdentifier;
-use remote::{SshConnectionOptions, SshPlatform, SshRemoteClient};
+use remote::{SshConnectionOptions, SshPlatform, SshPortForward, SshRemoteClient};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
@@ -54,6 +54,8 @@ impl SshSettings {
                     port,
                     username,
                     password: None,
+                    port_forwards: Some(conn.port_forwards),
+                    auto_forward_ports: conn.auto_forward_ports,
                 };
             }
         }
@@ -87,6 +89,15 @@ pub struct SshConnection {
     // limited outbound internet access.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub upload_binary_over_ssh: Option<bool>,
+    /// Ports to forward while connected to this server.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub port_forwards: Vec<SshPortForward>,
+    /// Whether to forward ports that the processes of terminals on this server start listening on.
+    ///
+    /// Default: true
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_forward_ports: Option<bool>,
 }
 
 impl From<SshConnection> for SshConnectionOptions {
@@ -99,6 +110,8 @@ impl From<SshConnection> for SshConnectionOptions {
             args: Some(val.args),
             nickname: val.nickname,
             upload_binary_over_ssh: val.upload_binary_over_ssh.unwrap_or_default(),
+            port_forwards: Some(val.port_forwards),
+            auto_forward_ports: val.auto_forward_ports,
         }
     }
 }
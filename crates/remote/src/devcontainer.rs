@@ -0,0 +1,536 @@
+use crate::{
+    docker_session::{run_command, ContainerEngine},
+    SshConnectionOptions,
+};
+use anyhow::{anyhow, Context as _, Result};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+/// Where dev container configurations are looked up, relative to the project root.
+pub const DEV_CONTAINER_CONFIG_PATHS: &[&str] =
+    &[".devcontainer/devcontainer.json", ".devcontainer.json"];
+
+/// Label recording which local folder a dev container was created for.
+const LOCAL_FOLDER_LABEL: &str = "dev.zed.devcontainer.local_folder";
+
+/// Returns the dev container configuration file of the project, if it has one.
+pub fn find_dev_container_config(project_path: &Path) -> Option<PathBuf> {
+    DEV_CONTAINER_CONFIG_PATHS
+        .iter()
+        .map(|path| project_path.join(path))
+        .find(|path| path.is_file())
+}
+
+/// The subset of `devcontainer.json` that is needed to run a project in a container.
+///
+/// See https://containers.dev/implementors/json_reference/
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevContainerConfig {
+    pub name: Option<String>,
+    pub image: Option<String>,
+    pub build: Option<serde_json::Value>,
+    pub docker_compose_file: Option<serde_json::Value>,
+    pub workspace_folder: Option<String>,
+    pub workspace_mount: Option<String>,
+    #[serde(default)]
+    pub mounts: Vec<DevContainerMount>,
+    #[serde(default)]
+    pub run_args: Vec<String>,
+    #[serde(default)]
+    pub container_env: BTreeMap<String, String>,
+    pub container_user: Option<String>,
+    pub remote_user: Option<String>,
+    #[serde(default)]
+    pub forward_ports: Vec<ForwardPort>,
+    pub post_create_command: Option<LifecycleCommand>,
+    pub override_command: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum DevContainerMount {
+    /// A mount in `docker run --mount` syntax, e.g. `source=foo,target=/foo,type=volume`.
+    Spec(String),
+    Object {
+        source: Option<String>,
+        target: String,
+        #[serde(rename = "type")]
+        kind: Option<String>,
+    },
+}
+
+/// A port in the container that should be reachable from the local machine.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "PortSpec")]
+pub struct ForwardPort {
+    /// The host the port is on, when it belongs to another service (`"db:5432"`).
+    pub host: Option<String>,
+    pub port: u16,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PortSpec {
+    Port(u16),
+    HostAndPort(String),
+}
+
+impl TryFrom<PortSpec> for ForwardPort {
+    type Error = anyhow::Error;
+
+    fn try_from(spec: PortSpec) -> Result<Self> {
+        match spec {
+            PortSpec::Port(port) => Ok(Self { host: None, port }),
+            PortSpec::HostAndPort(spec) => {
+                let (host, port) = match spec.rsplit_once(':') {
+                    Some((host, port)) => (Some(host.to_string()), port),
+                    None => (None, spec.as_str()),
+                };
+                let port = port
+                    .parse()
+                    .with_context(|| format!("invalid port {spec:?}"))?;
+                Ok(Self { host, port })
+            }
+        }
+    }
+}
+
+impl ForwardPort {
+    /// Whether the port belongs to the dev container itself, rather than another service.
+    pub fn is_local(&self) -> bool {
+        self.host
+            .as_deref()
+            .map_or(true, |host| host == "localhost" || host == "127.0.0.1")
+    }
+}
+
+/// A command run at some point of the container's lifecycle.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum LifecycleCommand {
+    /// A command run by a shell.
+    Shell(String),
+    /// A program and its arguments, run without a shell.
+    Exec(Vec<String>),
+    /// Named commands, which are run one after another.
+    Named(BTreeMap<String, LifecycleCommand>),
+}
+
+impl LifecycleCommand {
+    /// The command lines to run, in order.
+    pub fn command_lines(&self) -> Vec<Vec<String>> {
+        match self {
+            Self::Shell(command) => vec![vec!["/bin/sh".into(), "-c".into(), command.clone()]],
+            Self::Exec(args) if args.is_empty() => Vec::new(),
+            Self::Exec(args) => vec![args.clone()],
+            Self::Named(commands) => commands
+                .values()
+                .flat_map(|command| command.command_lines())
+                .collect(),
+        }
+    }
+}
+
+impl DevContainerConfig {
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(serde_json_lenient::from_str(content)?)
+    }
+
+    pub async fn load(project_path: &Path) -> Result<Self> {
+        let config_path = find_dev_container_config(project_path).ok_or_else(|| {
+            anyhow!(
+                "{} does not contain a devcontainer.json",
+                project_path.display()
+            )
+        })?;
+        let content = smol::fs::read_to_string(&config_path).await?;
+        Self::parse(&content).with_context(|| format!("failed to parse {}", config_path.display()))
+    }
+
+    /// Where the project is mounted inside the container.
+    pub fn workspace_folder(&self, project_path: &Path) -> String {
+        match &self.workspace_folder {
+            Some(folder) => Variables::new(project_path, "").substitute(folder),
+            None => format!("/workspaces/{}", basename(project_path)),
+        }
+    }
+
+    /// The user that the project is opened as, if not the container's default user.
+    pub fn user(&self) -> Option<&str> {
+        self.remote_user
+            .as_deref()
+            .or(self.container_user.as_deref())
+    }
+
+    /// The arguments to `docker run` that create the container for the project.
+    pub fn run_args(&self, project_path: &Path, container_name: &str) -> Result<Vec<String>> {
+        if self.docker_compose_file.is_some() {
+            anyhow::bail!("Docker Compose dev containers are not supported");
+        }
+        let Some(image) = &self.image else {
+            if self.build.is_some() {
+                anyhow::bail!(
+                    "building dev container images is not supported, set \"image\" instead"
+                );
+            }
+            anyhow::bail!("devcontainer.json does not specify an image");
+        };
+
+        let workspace_folder = self.workspace_folder(project_path);
+        let variables = Variables::new(project_path, &workspace_folder);
+        let workspace_mount = match &self.workspace_mount {
+            Some(mount) => variables.substitute(mount),
+            None => format!(
+                "type=bind,source={},target={workspace_folder}",
+                project_path.display()
+            ),
+        };
+
+        let mut args = vec![
+            "run".to_string(),
+            "--detach".to_string(),
+            "--name".to_string(),
+            container_name.to_string(),
+            "--label".to_string(),
+            format!("{LOCAL_FOLDER_LABEL}={}", project_path.display()),
+            "--mount".to_string(),
+            workspace_mount,
+        ];
+        for mount in &self.mounts {
+            args.push("--mount".to_string());
+            args.push(match mount {
+                DevContainerMount::Spec(spec) => variables.substitute(spec),
+                DevContainerMount::Object {
+                    source,
+                    target,
+                    kind,
+                } => {
+                    let mut spec = format!("type={}", kind.as_deref().unwrap_or("bind"));
+                    if let Some(source) = source {
+                        spec.push_str(&format!(",source={}", variables.substitute(source)));
+                    }
+                    spec.push_str(&format!(",target={}", variables.substitute(target)));
+                    spec
+                }
+            });
+        }
+        for port in self.forward_ports.iter().filter(|port| port.is_local()) {
+            args.push("--publish".to_string());
+            args.push(format!("127.0.0.1:{0}:{0}", port.port));
+        }
+        for (name, value) in &self.container_env {
+            args.push("--env".to_string());
+            args.push(format!("{name}={}", variables.substitute(value)));
+        }
+        if let Some(user) = &self.container_user {
+            args.push("--user".to_string());
+            args.push(user.clone());
+        }
+        args.extend(self.run_args.iter().map(|arg| variables.substitute(arg)));
+        args.push(image.clone());
+        if self.override_command.unwrap_or(true) {
+            // Keep the container running regardless of the image's entrypoint.
+            args.extend([
+                "/bin/sh".to_string(),
+                "-c".to_string(),
+                "while sleep 1000; do :; done".to_string(),
+            ]);
+        }
+        Ok(args)
+    }
+}
+
+/// A running container for a project with a `devcontainer.json`.
+#[derive(Debug, Clone)]
+pub struct DevContainer {
+    pub engine: ContainerEngine,
+    pub container_name: String,
+    pub workspace_folder: PathBuf,
+    pub user: Option<String>,
+}
+
+impl DevContainer {
+    /// Starts the dev container of the project, creating it if needed.
+    ///
+    /// `postCreateCommand` is only run when the container is created.
+    pub async fn up(
+        engine: ContainerEngine,
+        project_path: &Path,
+        config: &DevContainerConfig,
+    ) -> Result<Self> {
+        let existing = run_command(engine.command().args([
+            "ps",
+            "--all",
+            "--filter",
+            &format!("label={LOCAL_FOLDER_LABEL}={}", project_path.display()),
+            "--format",
+            "{{.Names}}",
+        ]))
+        .await?;
+
+        let this = Self {
+            engine,
+            container_name: existing
+                .lines()
+                .next()
+                .map(|name| name.trim().to_string())
+                .unwrap_or_else(|| container_name(project_path)),
+            workspace_folder: config.workspace_folder(project_path).into(),
+            user: config.user().map(ToString::to_string),
+        };
+
+        if !existing.trim().is_empty() {
+            run_command(engine.command().args(["start", &this.container_name]))
+                .await
+                .with_context(|| format!("failed to start {}", this.container_name))?;
+            return Ok(this);
+        }
+
+        log::info!(
+            "creating dev container {} for {}",
+            this.container_name,
+            project_path.display()
+        );
+        run_command(
+            engine
+                .command()
+                .args(config.run_args(project_path, &this.container_name)?),
+        )
+        .await
+        .context("failed to create dev container")?;
+
+        if let Some(post_create_command) = &config.post_create_command {
+            if let Err(error) = this.run_lifecycle_command(post_create_command).await {
+                // Remove the container, so that the command is run again on the next attempt.
+                run_command(
+                    engine
+                        .command()
+                        .args(["rm", "--force", &this.container_name]),
+                )
+                .await
+                .ok();
+                return Err(error.context("postCreateCommand failed"));
+            }
+        }
+
+        Ok(this)
+    }
+
+    pub fn connection_options(&self) -> SshConnectionOptions {
+        SshConnectionOptions::for_container(self.engine, &self.container_name, self.user.clone())
+    }
+
+    async fn run_lifecycle_command(&self, command: &LifecycleCommand) -> Result<()> {
+        for command_line in command.command_lines() {
+            let mut command = self.engine.command();
+            command
+                .arg("exec")
+                .arg("--workdir")
+                .arg(&self.workspace_folder);
+            if let Some(user) = &self.user {
+                command.args(["--user", user]);
+            }
+            command.arg(&self.container_name).args(&command_line);
+            run_command(&mut command).await?;
+        }
+        Ok(())
+    }
+}
+
+/// A container name that is stable for the project, and unique between projects with the
+/// same directory name.
+fn container_name(project_path: &Path) -> String {
+    let mut hasher = DefaultHasher::new();
+    project_path.hash(&mut hasher);
+    let name = basename(project_path)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    format!("zed-{}-{:08x}", name, hasher.finish() as u32)
+}
+
+fn basename(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// The `${...}` variables that may be used in `devcontainer.json`.
+struct Variables {
+    local_workspace_folder: String,
+    container_workspace_folder: String,
+}
+
+impl Variables {
+    fn new(project_path: &Path, container_workspace_folder: &str) -> Self {
+        Self {
+            local_workspace_folder: project_path.to_string_lossy().into_owned(),
+            container_workspace_folder: container_workspace_folder.to_string(),
+        }
+    }
+
+    fn substitute(&self, value: &str) -> String {
+        let mut result = String::new();
+        let mut rest = value;
+        while let Some(start) = rest.find("${") {
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+            result.push_str(&rest[..start]);
+            let name = &rest[start + 2..start + len];
+            match self.lookup(name) {
+                Some(value) => result.push_str(&value),
+                None => result.push_str(&rest[start..=start + len]),
+            }
+            rest = &rest[start + len + 1..];
+        }
+        result.push_str(rest);
+        result
+    }
+
+    fn lookup(&self, name: &str) -> Option<String> {
+        let basename = |path: &str| basename(Path::new(path));
+        match name {
+            "localWorkspaceFolder" => Some(self.local_workspace_folder.clone()),
+            "localWorkspaceFolderBasename" => Some(basename(&self.local_workspace_folder)),
+            "containerWorkspaceFolder" => Some(self.container_workspace_folder.clone()),
+            "containerWorkspaceFolderBasename" => Some(basename(&self.container_workspace_folder)),
+            _ => {
+                let env = name.strip_prefix("localEnv:")?;
+                let (variable, default) = env.split_once(':').unwrap_or((env, ""));
+                Some(std::env::var(variable).unwrap_or_else(|_| default.to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dev_container_config() {
+        let config = DevContainerConfig::parse(
+            r#"{
+                // Comments and trailing commas are allowed.
+                "name": "Rust",
+                "image": "mcr.microsoft.com/devcontainers/rust:1",
+                "mounts": [
+                    "source=cargo-cache,target=/usr/local/cargo/registry,type=volume",
+                    { "source": "${localWorkspaceFolder}/.cache", "target": "/cache", "type": "bind" },
+                ],
+                "forwardPorts": [3000, "db:5432", "localhost:8080"],
+                "postCreateCommand": "cargo fetch",
+                "remoteUser": "vscode",
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.name.as_deref(), Some("Rust"));
+        assert_eq!(config.user(), Some("vscode"));
+        assert_eq!(
+            config.forward_ports,
+            vec![
+                ForwardPort {
+                    host: None,
+                    port: 3000
+                },
+                ForwardPort {
+                    host: Some("db".into()),
+                    port: 5432
+                },
+                ForwardPort {
+                    host: Some("localhost".into()),
+                    port: 8080
+                },
+            ]
+        );
+        assert_eq!(
+            config.post_create_command.unwrap().command_lines(),
+            vec![vec![
+                "/bin/sh".to_string(),
+                "-c".to_string(),
+                "cargo fetch".to_string()
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_lifecycle_commands() {
+        let command: LifecycleCommand =
+            serde_json::from_str(r#"{ "b": ["npm", "install"], "a": "echo hello" }"#).unwrap();
+        assert_eq!(
+            command.command_lines(),
+            vec![
+                vec!["/bin/sh".to_string(), "-c".into(), "echo hello".into()],
+                vec!["npm".to_string(), "install".into()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_args() {
+        let config = DevContainerConfig::parse(
+            r#"{
+                "image": "ubuntu",
+                "mounts": [
+                    "source=${localWorkspaceFolderBasename}-home,target=/home,type=volume",
+                    { "source": "/tmp", "target": "${containerWorkspaceFolder}/tmp" }
+                ],
+                "forwardPorts": [3000, "db:5432"],
+                "containerEnv": { "WORKSPACE": "${containerWorkspaceFolder}" },
+                "runArgs": ["--cap-add=SYS_PTRACE"],
+                "overrideCommand": false
+            }"#,
+        )
+        .unwrap();
+
+        let project_path = Path::new("/home/user/my-project");
+        assert_eq!(
+            config.run_args(project_path, "container").unwrap(),
+            [
+                "run",
+                "--detach",
+                "--name",
+                "container",
+                "--label",
+                "dev.zed.devcontainer.local_folder=/home/user/my-project",
+                "--mount",
+                "type=bind,source=/home/user/my-project,target=/workspaces/my-project",
+                "--mount",
+                "source=my-project-home,target=/home,type=volume",
+                "--mount",
+                "type=bind,source=/tmp,target=/workspaces/my-project/tmp",
+                "--publish",
+                "127.0.0.1:3000:3000",
+                "--env",
+                "WORKSPACE=/workspaces/my-project",
+                "--cap-add=SYS_PTRACE",
+                "ubuntu",
+            ]
+        );
+
+        let config =
+            DevContainerConfig::parse(r#"{ "build": { "dockerfile": "Dockerfile" } }"#).unwrap();
+        assert!(config.run_args(project_path, "container").is_err());
+    }
+
+    #[test]
+    fn test_container_name() {
+        let name = container_name(Path::new("/home/user/My Project"));
+        assert!(name.starts_with("zed-my-project-"), "{name}");
+        assert_eq!(name, container_name(Path::new("/home/user/My Project")));
+        assert_ne!(name, container_name(Path::new("/tmp/My Project")));
+    }
+}
@@ -0,0 +1,388 @@
+use crate::{
+    port_forwarding::SshPortForward,
+    shell_script,
+    ssh_session::{
+        parse_platform, remote_server_binary_path, RemoteConnection, SshRemoteConnection,
+    },
+    SshClientDelegate, SshConnectionOptions,
+};
+use anyhow::{anyhow, Context as _, Result};
+use async_trait::async_trait;
+use futures::channel::mpsc::{Sender, UnboundedReceiver, UnboundedSender};
+use gpui::{AppContext, AsyncAppContext, SemanticVersion, Task};
+use itertools::Itertools;
+use release_channel::{AppCommitSha, AppVersion, ReleaseChannel};
+use rpc::proto::Envelope;
+use smol::process::{self, Stdio};
+use std::{
+    collections::BTreeSet,
+    iter,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering::SeqCst},
+        Arc,
+    },
+};
+
+/// A command line tool that can run containers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ContainerEngine {
+    Docker,
+    Podman,
+}
+
+impl ContainerEngine {
+    pub const ALL: [Self; 2] = [Self::Docker, Self::Podman];
+
+    /// Returns the first container engine found on the `PATH`, preferring Docker.
+    pub fn detect() -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|engine| which::which(engine.program()).is_ok())
+    }
+
+    pub fn program(&self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+        }
+    }
+
+    fn url_scheme(&self) -> &'static str {
+        match self {
+            Self::Docker => "docker://",
+            Self::Podman => "podman://",
+        }
+    }
+
+    pub fn container_url(&self, container: &str) -> String {
+        format!("{}{}", self.url_scheme(), container)
+    }
+
+    /// Parses a `docker://<container>` or `podman://<container>` url.
+    pub fn parse_container_url(url: &str) -> Option<(Self, &str)> {
+        Self::ALL.into_iter().find_map(|engine| {
+            let container = url.strip_prefix(engine.url_scheme())?;
+            (!container.is_empty()).then_some((engine, container))
+        })
+    }
+
+    pub(crate) fn command(&self) -> process::Command {
+        let mut command = util::command::new_smol_command(self.program());
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        command
+    }
+}
+
+/// Runs a command to completion, returning its stdout.
+pub(crate) async fn run_command(command: &mut process::Command) -> Result<String> {
+    let output = command.output().await?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(anyhow!(
+            "failed to run command: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+}
+
+/// Runs the remote server inside a container on this machine, talking to it through
+/// `docker exec` (or `podman exec`) rather than SSH.
+pub(crate) struct DockerRemoteConnection {
+    connection_options: SshConnectionOptions,
+    engine: ContainerEngine,
+    container: String,
+    home_dir: PathBuf,
+    remote_binary_path: Option<PathBuf>,
+    killed: AtomicBool,
+}
+
+#[async_trait(?Send)]
+impl RemoteConnection for DockerRemoteConnection {
+    async fn kill(&self) -> Result<()> {
+        // The container outlives the connection; the proxy processes are killed when dropped.
+        self.killed.store(true, SeqCst);
+        Ok(())
+    }
+
+    fn has_been_killed(&self) -> bool {
+        self.killed.load(SeqCst)
+    }
+
+    fn ssh_args(&self) -> Vec<String> {
+        self.exec_args(&["-it"])
+    }
+
+    fn ssh_program(&self) -> &'static str {
+        self.engine.program()
+    }
+
+    fn connection_options(&self) -> SshConnectionOptions {
+        self.connection_options.clone()
+    }
+
+    fn forward_port(&self, _: &SshPortForward, _: &AppContext) -> Task<Result<()>> {
+        Task::ready(Err(anyhow!(
+            "ports of containers are forwarded with \"forwardPorts\" in devcontainer.json"
+        )))
+    }
+
+    fn cancel_port_forward(&self, _: &SshPortForward, _: &AppContext) -> Task<Result<()>> {
+        Task::ready(Ok(()))
+    }
+
+    fn listening_ports(&self, _: &AppContext) -> Task<Result<BTreeSet<u16>>> {
+        Task::ready(Err(anyhow!(
+            "port detection is not supported for containers"
+        )))
+    }
+
+    fn upload_directory(
+        &self,
+        src_path: PathBuf,
+        dest_path: PathBuf,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let dest_path = self.home_dir.join(dest_path);
+        let exec = iter::once(self.engine.program().to_string())
+            .chain(self.exec_args(&["-i"]))
+            .map(|arg| shlex::try_quote(&arg).unwrap().into_owned())
+            .join(" ");
+        let script = format!(
+            "{} | {exec} sh -c {}",
+            shell_script!(
+                "tar -C {src_path} -cf - .",
+                src_path = &src_path.to_string_lossy()
+            ),
+            shlex::try_quote(&shell_script!(
+                "mkdir -p {dest_path} && tar -C {dest_path} -xf -",
+                dest_path = &dest_path.to_string_lossy(),
+            ))
+            .unwrap(),
+        );
+        let output = util::command::new_smol_command("sh")
+            .args(["-c", &script])
+            .output();
+
+        cx.background_executor().spawn(async move {
+            let output = output.await?;
+
+            if !output.status.success() {
+                return Err(anyhow!(
+                    "failed to upload directory {} -> {}: {}",
+                    src_path.display(),
+                    dest_path.display(),
+                    String::from_utf8_lossy(&output.stderr)
+                ));
+            }
+
+            Ok(())
+        })
+    }
+
+    fn start_proxy(
+        &self,
+        unique_identifier: String,
+        reconnect: bool,
+        incoming_tx: UnboundedSender<Envelope>,
+        outgoing_rx: UnboundedReceiver<Envelope>,
+        connection_activity_tx: Sender<()>,
+        delegate: Arc<dyn SshClientDelegate>,
+        cx: &mut AsyncAppContext,
+    ) -> Task<Result<i32>> {
+        delegate.set_status(Some("Starting proxy"), cx);
+
+        let Some(remote_binary_path) = self.remote_binary_path.clone() else {
+            return Task::ready(Err(anyhow!("Remote binary path not set")));
+        };
+
+        let mut exec_options = vec!["-i".to_string()];
+        for name in ["RUST_LOG", "RUST_BACKTRACE"] {
+            if let Ok(value) = std::env::var(name) {
+                exec_options.extend(["-e".to_string(), format!("{name}={value}")]);
+            }
+        }
+
+        let mut command = self.engine.command();
+        command
+            .args(self.exec_args(&exec_options))
+            .arg(&remote_binary_path)
+            .args(["proxy", "--identifier", &unique_identifier]);
+        if reconnect {
+            command.arg("--reconnect");
+        }
+
+        let proxy_process = match command
+            .stdin(Stdio::piped())
+            // IMPORTANT: we kill this process when we drop the task that uses it.
+            .kill_on_drop(true)
+            .spawn()
+        {
+            Ok(process) => process,
+            Err(error) => {
+                return Task::ready(Err(anyhow!("failed to spawn remote server: {}", error)))
+            }
+        };
+
+        SshRemoteConnection::multiplex(
+            proxy_process,
+            incoming_tx,
+            outgoing_rx,
+            connection_activity_tx,
+            cx,
+        )
+    }
+}
+
+impl DockerRemoteConnection {
+    pub(crate) async fn new(
+        connection_options: SshConnectionOptions,
+        delegate: Arc<dyn SshClientDelegate>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Self> {
+        let (engine, container) = connection_options
+            .container()
+            .ok_or_else(|| anyhow!("{} is not a container", connection_options.host))?;
+        let container = container.to_string();
+        anyhow::ensure!(
+            which::which(engine.program()).is_ok(),
+            "Cannot find {}, which is required to connect to containers.",
+            engine.program()
+        );
+
+        delegate.set_status(Some("Starting container"), cx);
+        run_command(engine.command().args(["start", &container]))
+            .await
+            .with_context(|| format!("failed to start container {container}"))?;
+
+        let mut this = Self {
+            connection_options,
+            engine,
+            container,
+            home_dir: PathBuf::new(),
+            remote_binary_path: None,
+            killed: AtomicBool::new(false),
+        };
+        this.home_dir = PathBuf::from(
+            this.run_command("sh", &["-c", "echo \"$HOME\""])
+                .await?
+                .trim(),
+        );
+
+        let (release_channel, version, commit) = cx.update(|cx| {
+            (
+                ReleaseChannel::global(cx),
+                AppVersion::global(cx),
+                AppCommitSha::try_global(cx),
+            )
+        })?;
+        this.remote_binary_path = Some(
+            this.ensure_server_binary(&delegate, release_channel, version, commit, cx)
+                .await?,
+        );
+
+        Ok(this)
+    }
+
+    /// The arguments to `docker` that run a command in the container.
+    fn exec_args(&self, options: &[impl AsRef<str>]) -> Vec<String> {
+        let mut args = vec!["exec".to_string()];
+        args.extend(options.iter().map(|option| option.as_ref().to_string()));
+        if let Some(user) = &self.connection_options.username {
+            args.extend(["-u".to_string(), user.clone()]);
+        }
+        args.push(self.container.clone());
+        args
+    }
+
+    async fn run_command(&self, program: &str, args: &[&str]) -> Result<String> {
+        let mut command = self.engine.command();
+        command
+            .args(self.exec_args(&[] as &[&str]))
+            .arg(program)
+            .args(args);
+        run_command(&mut command).await
+    }
+
+    async fn ensure_server_binary(
+        &self,
+        delegate: &Arc<dyn SshClientDelegate>,
+        release_channel: ReleaseChannel,
+        version: SemanticVersion,
+        commit: Option<AppCommitSha>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<PathBuf> {
+        let dst_path =
+            self.home_dir
+                .join(remote_server_binary_path(release_channel, version, commit));
+
+        if self
+            .run_command(&dst_path.to_string_lossy(), &["version"])
+            .await
+            .is_ok()
+        {
+            return Ok(dst_path);
+        }
+
+        let wanted_version = cx.update(|cx| match release_channel {
+            ReleaseChannel::Nightly => Ok(None),
+            ReleaseChannel::Dev => {
+                anyhow::bail!(
+                    "no remote server exists in the container at ({:?})",
+                    dst_path
+                )
+            }
+            _ => Ok(Some(AppVersion::global(cx))),
+        })??;
+
+        let platform = parse_platform(&self.run_command("uname", &["-sm"]).await?)?;
+
+        // Containers rarely have curl or wget installed, so the binary is always downloaded
+        // locally and streamed into the container.
+        let src_path = delegate
+            .download_server_binary_locally(platform, release_channel, wanted_version, cx)
+            .await?;
+
+        delegate.set_status(Some("Uploading remote development server"), cx);
+        let tmp_path = PathBuf::from(format!(
+            "{}-download-{}",
+            dst_path.to_string_lossy(),
+            std::process::id()
+        ));
+        self.install_server_binary(&src_path, &tmp_path, &dst_path)
+            .await
+            .context("failed to upload server binary")?;
+        Ok(dst_path)
+    }
+
+    async fn install_server_binary(
+        &self,
+        src_path: &Path,
+        tmp_path: &Path,
+        dst_path: &Path,
+    ) -> Result<()> {
+        let parent = dst_path
+            .parent()
+            .ok_or_else(|| anyhow!("invalid server binary path {:?}", dst_path))?;
+        let script = shell_script!(
+            "mkdir -p {parent} && gunzip -c > {tmp_path} && chmod 755 {tmp_path} && mv {tmp_path} {dst_path}",
+            parent = &parent.to_string_lossy(),
+            tmp_path = &tmp_path.to_string_lossy(),
+            dst_path = &dst_path.to_string_lossy(),
+        );
+
+        let src_file = std::fs::File::open(src_path)
+            .with_context(|| format!("failed to open {}", src_path.display()))?;
+        let mut command = self.engine.command();
+        command
+            .args(self.exec_args(&["-i"]))
+            .args(["sh", "-c", &script])
+            .stdin(Stdio::from(src_file));
+        run_command(&mut command).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+use anyhow::{anyhow, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt,
+};
+
+/// Ports below this are never forwarded automatically.
+const MIN_AUTO_FORWARDED_PORT: u16 = 1024;
+
+/// Set in the environment of the terminals opened on the remote host. Their processes and the
+/// processes they start inherit it, which is how the ports they listen on are detected.
+pub const REMOTE_TERMINAL_ENV_VAR: &str = "ZED_TERM";
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PortForwardKind {
+    /// Makes a port on the remote host reachable from this machine (`ssh -L`).
+    #[default]
+    Local,
+    /// Makes a port on this machine reachable from the remote host (`ssh -R`).
+    Remote,
+}
+
+/// A tunnel between a local and a remote port over the SSH connection.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct SshPortForward {
+    #[serde(default)]
+    pub kind: PortForwardKind,
+    /// The local address. Defaults to `localhost`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_host: Option<String>,
+    pub local_port: u16,
+    /// The address, as seen from the remote host. Defaults to `localhost`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_host: Option<String>,
+    pub remote_port: u16,
+}
+
+impl SshPortForward {
+    /// Forwards a remote port to the same port on this machine.
+    pub fn local(port: u16) -> Self {
+        Self {
+            kind: PortForwardKind::Local,
+            local_host: None,
+            local_port: port,
+            remote_host: None,
+            remote_port: port,
+        }
+    }
+
+    /// Parses `port`, `local_port:remote_port` or `local_port:remote_host:remote_port`.
+    pub fn parse_local(input: &str) -> Result<Self> {
+        let parse_port = |port: &str| {
+            port.trim()
+                .parse::<u16>()
+                .map_err(|_| anyhow!("invalid port {port:?}"))
+        };
+        let parts = input.trim().split(':').collect::<Vec<_>>();
+        let (local_port, remote_host, remote_port) = match parts.as_slice() {
+            [port] => (parse_port(port)?, None, parse_port(port)?),
+            [local_port, remote_port] => (parse_port(local_port)?, None, parse_port(remote_port)?),
+            [local_port, remote_host, remote_port] => (
+                parse_port(local_port)?,
+                Some(remote_host.trim().to_string()),
+                parse_port(remote_port)?,
+            ),
+            _ => anyhow::bail!("invalid port forward {input:?}"),
+        };
+        Ok(Self {
+            kind: PortForwardKind::Local,
+            local_host: None,
+            local_port,
+            remote_host,
+            remote_port,
+        })
+    }
+
+    /// The `ssh` flag and its argument that set up this forward.
+    pub fn ssh_arg(&self) -> (&'static str, String) {
+        let local_host = self.local_host.as_deref().unwrap_or("localhost");
+        let remote_host = self.remote_host.as_deref().unwrap_or("localhost");
+        match self.kind {
+            PortForwardKind::Local => (
+                "-L",
+                match &self.local_host {
+                    Some(local_host) => format!(
+                        "{local_host}:{}:{remote_host}:{}",
+                        self.local_port, self.remote_port
+                    ),
+                    None => format!("{}:{remote_host}:{}", self.local_port, self.remote_port),
+                },
+            ),
+            PortForwardKind::Remote => (
+                "-R",
+                match &self.remote_host {
+                    Some(remote_host) => format!(
+                        "{remote_host}:{}:{local_host}:{}",
+                        self.remote_port, self.local_port
+                    ),
+                    None => format!("{}:{local_host}:{}", self.remote_port, self.local_port),
+                },
+            ),
+        }
+    }
+
+    /// The url under which the forwarded service can be opened locally.
+    pub fn local_url(&self) -> Option<String> {
+        match self.kind {
+            PortForwardKind::Local => Some(format!(
+                "http://{}:{}",
+                self.local_host.as_deref().unwrap_or("localhost"),
+                self.local_port
+            )),
+            PortForwardKind::Remote => None,
+        }
+    }
+}
+
+impl fmt::Display for SshPortForward {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let local = format!(
+            "{}:{}",
+            self.local_host.as_deref().unwrap_or("localhost"),
+            self.local_port
+        );
+        let remote = format!(
+            "{}:{}",
+            self.remote_host.as_deref().unwrap_or("localhost"),
+            self.remote_port
+        );
+        match self.kind {
+            PortForwardKind::Local => write!(f, "{local} → remote {remote}"),
+            PortForwardKind::Remote => write!(f, "remote {remote} → {local}"),
+        }
+    }
+}
+
+/// Why a port is being forwarded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PortForwardSource {
+    /// Listed in the `port_forwards` of the connection's settings.
+    Configured,
+    /// A process on the remote host started listening on the port.
+    Detected,
+    /// Added from the ports view.
+    Manual,
+}
+
+#[derive(Clone, Debug)]
+pub struct ActivePortForward {
+    pub forward: SshPortForward,
+    pub source: PortForwardSource,
+    /// Why the forward could not be established, if it failed.
+    pub error: Option<String>,
+}
+
+/// Lists the sockets opened by the processes of the remote terminals, followed by the contents
+/// of `/proc/net/tcp` and `/proc/net/tcp6`. Only Linux exposes them this way, elsewhere the
+/// output is empty.
+pub(crate) fn list_terminal_sockets_command() -> String {
+    format!(
+        "for pid in /proc/[0-9]*; do \
+            if tr '\\0' '\\n' < $pid/environ 2>/dev/null | grep -qx '{REMOTE_TERMINAL_ENV_VAR}=true'; then \
+                for fd in $pid/fd/*; do readlink $fd 2>/dev/null; done; \
+            fi; \
+        done; \
+        cat /proc/net/tcp /proc/net/tcp6 2>/dev/null; \
+        true"
+    )
+}
+
+/// Parses the TCP ports that are being listened on by the processes of the remote terminals from
+/// the output of [`list_terminal_sockets_command`], ignoring sockets that are only reachable from
+/// other hosts.
+pub(crate) fn parse_listening_ports(output: &str) -> BTreeSet<u16> {
+    const LISTEN: &str = "0A";
+    let terminal_sockets = output
+        .lines()
+        .filter_map(|line| line.strip_prefix("socket:[")?.strip_suffix(']'))
+        .collect::<HashSet<_>>();
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let local_address = fields.nth(1)?;
+            let state = fields.nth(1)?;
+            let inode = fields.nth(5)?;
+            if state != LISTEN || !terminal_sockets.contains(inode) {
+                return None;
+            }
+            let (address, port) = local_address.split_once(':')?;
+            let is_reachable = address.chars().all(|c| c == '0')
+                // 127.0.0.1 and ::1, in the byte order of the kernel.
+                || address == "0100007F"
+                || address == "00000000000000000000000001000000";
+            let port = u16::from_str_radix(port, 16).ok()?;
+            (is_reachable && port >= MIN_AUTO_FORWARDED_PORT).then_some(port)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ssh_arg() {
+        assert_eq!(
+            SshPortForward::local(3000).ssh_arg(),
+            ("-L", "3000:localhost:3000".to_string())
+        );
+        assert_eq!(
+            SshPortForward::parse_local("8080:db:5432")
+                .unwrap()
+                .ssh_arg(),
+            ("-L", "8080:db:5432".to_string())
+        );
+        let forward = SshPortForward {
+            kind: PortForwardKind::Remote,
+            local_host: Some("127.0.0.1".into()),
+            local_port: 9000,
+            remote_host: None,
+            remote_port: 9001,
+        };
+        assert_eq!(forward.ssh_arg(), ("-R", "9001:127.0.0.1:9000".to_string()));
+        assert_eq!(forward.local_url(), None);
+        assert!(SshPortForward::parse_local("http").is_err());
+        assert!(SshPortForward::parse_local("1:2:3:4").is_err());
+    }
+
+    #[test]
+    fn test_parse_listening_ports() {
+        let output = "\
+/dev/pts/0
+socket:[1]
+socket:[2]
+socket:[3]
+socket:[4]
+pipe:[5]
+socket:[7]
+socket:[8]
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1 1
+   1: 0100007F:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 2 1
+   2: 0100007F:0BB9 0100007F:A1B2 01 00000000:00000000 00:00000000 00000000  1000        0 3 1
+   3: 0200A8C0:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4 1
+   4: 0100007F:0BBA 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 5 1
+   5: 0100007F:0BBB 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 6 1
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:1F91 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 7 1
+   1: 00000000000000000000000001000000:2328 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 8 1";
+        assert_eq!(
+            parse_listening_ports(output),
+            BTreeSet::from_iter([3000, 8081, 9000]),
+            "should only list the ports of the terminals' sockets, reachable from this machine"
+        );
+    }
+}
@@ -1,8 +1,13 @@
+pub mod devcontainer;
+pub mod docker_session;
 pub mod json_log;
+pub mod port_forwarding;
 pub mod protocol;
 pub mod proxy;
 pub mod ssh_session;
 
+pub use docker_session::ContainerEngine;
+pub use port_forwarding::{ActivePortForward, PortForwardKind, PortForwardSource, SshPortForward};
 pub use ssh_session::{
     ConnectionState, SshClientDelegate, SshConnectionOptions, SshPlatform, SshRemoteClient,
     SshRemoteEvent,
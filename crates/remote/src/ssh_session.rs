@@ -1,5 +1,10 @@
 use crate::{
+    docker_session::{ContainerEngine, DockerRemoteConnection},
     json_log::LogRecord,
+    port_forwarding::{
+        list_terminal_sockets_command, parse_listening_ports, ActivePortForward, PortForwardKind,
+        PortForwardSource, SshPortForward,
+    },
     protocol::{
         message_len_from_buffer, read_message_with_len, write_message, MessageId, MESSAGE_LEN_SIZE,
     },
@@ -35,7 +40,7 @@ use smol::{
 };
 use std::{
     any::TypeId,
-    collections::VecDeque,
+    collections::{BTreeSet, VecDeque},
     fmt, iter,
     ops::ControlFlow,
     path::{Path, PathBuf},
@@ -69,6 +74,10 @@ pub struct SshConnectionOptions {
 
     pub nickname: Option<String>,
     pub upload_binary_over_ssh: bool,
+    pub port_forwards: Option<Vec<SshPortForward>>,
+    /// Whether ports that remote terminal processes start listening on are forwarded automatically.
+    /// Defaults to true.
+    pub auto_forward_ports: Option<bool>,
 }
 
 #[macro_export]
@@ -162,6 +171,8 @@ impl SshConnectionOptions {
             password: None,
             nickname: None,
             upload_binary_over_ssh: false,
+            port_forwards: None,
+            auto_forward_ports: None,
         })
     }
 
@@ -179,6 +190,27 @@ impl SshConnectionOptions {
         result
     }
 
+    /// Creates options for connecting to a local container instead of an SSH host.
+    ///
+    /// The container is encoded in the host as `docker://<container>`, so that it survives
+    /// being persisted alongside SSH projects.
+    pub fn for_container(
+        engine: ContainerEngine,
+        container: &str,
+        username: Option<String>,
+    ) -> Self {
+        Self {
+            host: engine.container_url(container),
+            username,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the container engine and container name, if these options refer to a container.
+    pub fn container(&self) -> Option<(ContainerEngine, &str)> {
+        ContainerEngine::parse_container_url(&self.host)
+    }
+
     pub fn additional_args(&self) -> Option<&Vec<String>> {
         self.args.as_ref()
     }
@@ -312,6 +344,8 @@ const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(5);
 
 const MAX_RECONNECT_ATTEMPTS: usize = 3;
 
+const PORT_DETECTION_INTERVAL: Duration = Duration::from_secs(3);
+
 enum State {
     Connecting,
     Connected {
@@ -472,6 +506,10 @@ pub struct SshRemoteClient {
     unique_identifier: String,
     connection_options: SshConnectionOptions,
     state: Arc<Mutex<Option<State>>>,
+    port_forwards: Vec<ActivePortForward>,
+    /// Detected ports that the user stopped forwarding, which are not forwarded again.
+    ignored_ports: BTreeSet<u16>,
+    _detect_ports_task: Option<Task<()>>,
 }
 
 #[derive(Debug)]
@@ -536,6 +574,9 @@ impl SshRemoteClient {
                     unique_identifier: unique_identifier.clone(),
                     connection_options: connection_options.clone(),
                     state: Arc::new(Mutex::new(Some(State::Connecting))),
+                    port_forwards: Vec::new(),
+                    ignored_ports: BTreeSet::new(),
+                    _detect_ports_task: None,
                 })?;
 
                 let ssh_connection = cx
@@ -567,13 +608,28 @@ impl SshRemoteClient {
                 let heartbeat_task =
                     Self::heartbeat(this.downgrade(), connection_activity_rx, &mut cx);
 
-                this.update(&mut cx, |this, _| {
+                this.update(&mut cx, |this, cx| {
                     *this.state.lock() = Some(State::Connected {
                         ssh_connection,
                         delegate,
                         multiplex_task,
                         heartbeat_task,
                     });
+
+                    for forward in this
+                        .connection_options
+                        .port_forwards
+                        .clone()
+                        .unwrap_or_default()
+                    {
+                        this.forward_port(forward, PortForwardSource::Configured, cx)
+                            .detach_and_log_err(cx);
+                    }
+                    if this.connection_options.container().is_none()
+                        && this.connection_options.auto_forward_ports.unwrap_or(true)
+                    {
+                        this._detect_ports_task = Some(Self::detect_ports(cx));
+                    }
                 })?;
 
                 Ok(Some(this))
@@ -792,7 +848,11 @@ impl SshRemoteClient {
                     }
                 });
 
-                if this.state_is(State::is_reconnect_failed) {
+                if this.state_is(|state| matches!(state, State::Connected { .. })) {
+                    // Forwards belong to the ssh process that was replaced.
+                    this.restore_port_forwards(cx);
+                    Ok(())
+                } else if this.state_is(State::is_reconnect_failed) {
                     this.reconnect(cx)
                 } else if this.state_is(State::is_reconnect_exhausted) {
                     Ok(())
@@ -990,6 +1050,15 @@ impl SshRemoteClient {
             .map(|ssh_connection| ssh_connection.ssh_args())
     }
 
+    /// The program that [`Self::ssh_args`] should be passed to.
+    pub fn ssh_program(&self) -> Option<&'static str> {
+        self.state
+            .lock()
+            .as_ref()
+            .and_then(|state| state.ssh_connection())
+            .map(|ssh_connection| ssh_connection.ssh_program())
+    }
+
     pub fn upload_directory(
         &self,
         src_path: PathBuf,
@@ -1003,6 +1072,142 @@ impl SshRemoteClient {
         connection.upload_directory(src_path, dest_path, cx)
     }
 
+    pub fn port_forwards(&self) -> &[ActivePortForward] {
+        &self.port_forwards
+    }
+
+    /// Forwards a port over the connection. If the forward fails, it is still listed
+    /// with its error.
+    pub fn forward_port(
+        &mut self,
+        forward: SshPortForward,
+        source: PortForwardSource,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let task = self
+            .state
+            .lock()
+            .as_ref()
+            .and_then(|state| state.ssh_connection())
+            .map(|connection| connection.forward_port(&forward, cx));
+        let Some(task) = task else {
+            return Task::ready(Err(anyhow!("no ssh connection")));
+        };
+
+        cx.spawn(|this, mut cx| async move {
+            let result = task.await;
+            this.update(&mut cx, |this, cx| {
+                let error = result.as_ref().err().map(|error| error.to_string());
+                if let Some(active) = this
+                    .port_forwards
+                    .iter_mut()
+                    .find(|active| active.forward == forward)
+                {
+                    active.error = error;
+                } else {
+                    this.port_forwards.push(ActivePortForward {
+                        forward,
+                        source,
+                        error,
+                    });
+                }
+                cx.notify();
+            })?;
+            result
+        })
+    }
+
+    pub fn stop_port_forward(
+        &mut self,
+        forward: &SshPortForward,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(ix) = self
+            .port_forwards
+            .iter()
+            .position(|active| &active.forward == forward)
+        else {
+            return Task::ready(Ok(()));
+        };
+        let active = self.port_forwards.remove(ix);
+        if active.source == PortForwardSource::Detected {
+            self.ignored_ports.insert(active.forward.remote_port);
+        }
+        cx.notify();
+
+        if active.error.is_some() {
+            return Task::ready(Ok(()));
+        }
+        self.state
+            .lock()
+            .as_ref()
+            .and_then(|state| state.ssh_connection())
+            .map(|connection| connection.cancel_port_forward(&active.forward, cx))
+            .unwrap_or_else(|| Task::ready(Ok(())))
+    }
+
+    fn restore_port_forwards(&mut self, cx: &mut ModelContext<Self>) {
+        for active in self.port_forwards.clone() {
+            self.forward_port(active.forward, active.source, cx)
+                .detach_and_log_err(cx);
+        }
+    }
+
+    /// Polls the ports that are listened on by the processes of the remote terminals, and
+    /// forwards the ones that were opened since connecting, e.g. by a dev server.
+    fn detect_ports(cx: &mut ModelContext<Self>) -> Task<()> {
+        cx.spawn(|this, mut cx| async move {
+            let mut known_ports = None::<BTreeSet<u16>>;
+            loop {
+                let listening_ports = this.update(&mut cx, |this, cx| {
+                    this.state
+                        .lock()
+                        .as_ref()
+                        .and_then(|state| state.ssh_connection())
+                        .map(|connection| connection.listening_ports(cx))
+                });
+                match listening_ports {
+                    Ok(Some(task)) => match task.await {
+                        Ok(ports) => {
+                            let known_ports = known_ports.get_or_insert_with(|| ports.clone());
+                            let new_ports =
+                                ports.difference(known_ports).copied().collect::<Vec<_>>();
+                            *known_ports = ports;
+                            this.update(&mut cx, |this, cx| {
+                                for port in new_ports {
+                                    let is_forwarded = this.port_forwards.iter().any(|active| {
+                                        active.forward.kind == PortForwardKind::Local
+                                            && active.forward.remote_port == port
+                                    });
+                                    if !is_forwarded && !this.ignored_ports.contains(&port) {
+                                        log::info!("forwarding detected port {port}");
+                                        this.forward_port(
+                                            SshPortForward::local(port),
+                                            PortForwardSource::Detected,
+                                            cx,
+                                        )
+                                        .detach_and_log_err(cx);
+                                    }
+                                }
+                            })
+                            .ok();
+                        }
+                        Err(error) => {
+                            log::warn!("stopping port detection: {error:#}");
+                            return;
+                        }
+                    },
+                    // Reconnecting.
+                    Ok(None) => {}
+                    Err(_) => return,
+                }
+                cx.background_executor()
+                    .timer(PORT_DETECTION_INTERVAL)
+                    .await;
+            }
+        })
+    }
+
     pub fn proto_client(&self) -> AnyProtoClient {
         self.client.clone().into()
     }
@@ -1155,9 +1360,15 @@ impl ConnectionPool {
                 let opts = opts.clone();
                 let delegate = delegate.clone();
                 |mut cx| async move {
-                    let connection = SshRemoteConnection::new(opts.clone(), delegate, &mut cx)
-                        .await
-                        .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>);
+                    let connection = if opts.container().is_some() {
+                        DockerRemoteConnection::new(opts.clone(), delegate, &mut cx)
+                            .await
+                            .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>)
+                    } else {
+                        SshRemoteConnection::new(opts.clone(), delegate, &mut cx)
+                            .await
+                            .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>)
+                    };
 
                     cx.update_global(|pool: &mut Self, _| {
                         debug_assert!(matches!(
@@ -1195,7 +1406,7 @@ impl From<SshRemoteClient> for AnyProtoClient {
 }
 
 #[async_trait(?Send)]
-trait RemoteConnection: Send + Sync {
+pub(crate) trait RemoteConnection: Send + Sync {
     #[allow(clippy::too_many_arguments)]
     fn start_proxy(
         &self,
@@ -1216,13 +1427,18 @@ trait RemoteConnection: Send + Sync {
     async fn kill(&self) -> Result<()>;
     fn has_been_killed(&self) -> bool;
     fn ssh_args(&self) -> Vec<String>;
+    fn ssh_program(&self) -> &'static str;
     fn connection_options(&self) -> SshConnectionOptions;
+    fn forward_port(&self, forward: &SshPortForward, cx: &AppContext) -> Task<Result<()>>;
+    fn cancel_port_forward(&self, forward: &SshPortForward, cx: &AppContext) -> Task<Result<()>>;
+    /// The TCP ports that are listened on by the processes of the remote terminals.
+    fn listening_ports(&self, cx: &AppContext) -> Task<Result<BTreeSet<u16>>>;
 
     #[cfg(any(test, feature = "test-support"))]
     fn simulate_disconnect(&self, _: &AsyncAppContext) {}
 }
 
-struct SshRemoteConnection {
+pub(crate) struct SshRemoteConnection {
     socket: SshSocket,
     master_process: Mutex<Option<Child>>,
     remote_binary_path: Option<PathBuf>,
@@ -1248,10 +1464,32 @@ impl RemoteConnection for SshRemoteConnection {
         self.socket.ssh_args()
     }
 
+    fn ssh_program(&self) -> &'static str {
+        "ssh"
+    }
+
     fn connection_options(&self) -> SshConnectionOptions {
         self.socket.connection_options.clone()
     }
 
+    fn forward_port(&self, forward: &SshPortForward, cx: &AppContext) -> Task<Result<()>> {
+        self.control_port_forward("forward", forward, cx)
+    }
+
+    fn cancel_port_forward(&self, forward: &SshPortForward, cx: &AppContext) -> Task<Result<()>> {
+        self.control_port_forward("cancel", forward, cx)
+    }
+
+    fn listening_ports(&self, cx: &AppContext) -> Task<Result<BTreeSet<u16>>> {
+        let socket = self.socket.clone();
+        cx.background_executor().spawn(async move {
+            let output = socket
+                .run_command("sh", &["-c", &list_terminal_sockets_command()])
+                .await?;
+            Ok(parse_listening_ports(&output))
+        })
+    }
+
     fn upload_directory(
         &self,
         src_path: PathBuf,
@@ -1359,6 +1597,33 @@ impl RemoteConnection for SshRemoteConnection {
 }
 
 impl SshRemoteConnection {
+    /// Adds or removes a port forward on the running ssh master process.
+    fn control_port_forward(
+        &self,
+        operation: &'static str,
+        forward: &SshPortForward,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let (flag, spec) = forward.ssh_arg();
+        let mut command = util::command::new_smol_command("ssh");
+        let output = self
+            .socket
+            .ssh_options(&mut command)
+            .args(["-O", operation, flag, &spec])
+            .arg(self.socket.connection_options.ssh_url())
+            .output();
+        cx.background_executor().spawn(async move {
+            let output = output.await?;
+            if !output.status.success() {
+                return Err(anyhow!(
+                    "failed to {operation} {flag} {spec}: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            Ok(())
+        })
+    }
+
     #[cfg(not(unix))]
     async fn new(
         _connection_options: SshConnectionOptions,
@@ -1555,36 +1820,10 @@ impl SshRemoteConnection {
 
     async fn platform(&self) -> Result<SshPlatform> {
         let uname = self.socket.run_command("uname", &["-sm"]).await?;
-        let Some((os, arch)) = uname.split_once(" ") else {
-            Err(anyhow!("unknown uname: {uname:?}"))?
-        };
-
-        let os = match os.trim() {
-            "Darwin" => "macos",
-            "Linux" => "linux",
-            _ => Err(anyhow!(
-                "Prebuilt remote servers are not yet available for {os:?}. See https://zed.dev/docs/remote-development"
-            ))?,
-        };
-        // exclude armv5,6,7 as they are 32-bit.
-        let arch = if arch.starts_with("armv8")
-            || arch.starts_with("armv9")
-            || arch.starts_with("arm64")
-            || arch.starts_with("aarch64")
-        {
-            "aarch64"
-        } else if arch.starts_with("x86") {
-            "x86_64"
-        } else {
-            Err(anyhow!(
-                "Prebuilt remote servers are not yet available for {arch:?}. See https://zed.dev/docs/remote-development"
-            ))?
-        };
-
-        Ok(SshPlatform { os, arch })
+        parse_platform(&uname)
     }
 
-    fn multiplex(
+    pub(crate) fn multiplex(
         mut ssh_proxy_process: Child,
         incoming_tx: UnboundedSender<Envelope>,
         mut outgoing_rx: UnboundedReceiver<Envelope>,
@@ -1695,21 +1934,7 @@ impl SshRemoteConnection {
         commit: Option<AppCommitSha>,
         cx: &mut AsyncAppContext,
     ) -> Result<PathBuf> {
-        let version_str = match release_channel {
-            ReleaseChannel::Nightly => {
-                let commit = commit.map(|s| s.0.to_string()).unwrap_or_default();
-
-                format!("{}-{}", version, commit)
-            }
-            ReleaseChannel::Dev => "build".to_string(),
-            _ => version.to_string(),
-        };
-        let binary_name = format!(
-            "zed-remote-server-{}-{}",
-            release_channel.dev_name(),
-            version_str
-        );
-        let dst_path = paths::remote_server_dir_relative().join(binary_name);
+        let dst_path = remote_server_binary_path(release_channel, version, commit);
         let tmp_path_gz = PathBuf::from(format!(
             "{}-download-{}.gz",
             dst_path.to_string_lossy(),
@@ -2051,6 +2276,60 @@ impl SshRemoteConnection {
     }
 }
 
+/// Parses the output of `uname -sm` on a remote host.
+pub(crate) fn parse_platform(uname: &str) -> Result<SshPlatform> {
+    let Some((os, arch)) = uname.split_once(" ") else {
+        Err(anyhow!("unknown uname: {uname:?}"))?
+    };
+
+    let os = match os.trim() {
+        "Darwin" => "macos",
+        "Linux" => "linux",
+        _ => Err(anyhow!(
+            "Prebuilt remote servers are not yet available for {os:?}. See https://zed.dev/docs/remote-development"
+        ))?,
+    };
+    // exclude armv5,6,7 as they are 32-bit.
+    let arch = if arch.starts_with("armv8")
+        || arch.starts_with("armv9")
+        || arch.starts_with("arm64")
+        || arch.starts_with("aarch64")
+    {
+        "aarch64"
+    } else if arch.starts_with("x86") {
+        "x86_64"
+    } else {
+        Err(anyhow!(
+            "Prebuilt remote servers are not yet available for {arch:?}. See https://zed.dev/docs/remote-development"
+        ))?
+    };
+
+    Ok(SshPlatform { os, arch })
+}
+
+/// The path of the remote server binary on a remote host, relative to its home directory.
+pub(crate) fn remote_server_binary_path(
+    release_channel: ReleaseChannel,
+    version: SemanticVersion,
+    commit: Option<AppCommitSha>,
+) -> PathBuf {
+    let version_str = match release_channel {
+        ReleaseChannel::Nightly => {
+            let commit = commit.map(|s| s.0.to_string()).unwrap_or_default();
+
+            format!("{}-{}", version, commit)
+        }
+        ReleaseChannel::Dev => "build".to_string(),
+        _ => version.to_string(),
+    };
+    let binary_name = format!(
+        "zed-remote-server-{}-{}",
+        release_channel.dev_name(),
+        version_str
+    );
+    paths::remote_server_dir_relative().join(binary_name)
+}
+
 type ResponseChannels = Mutex<HashMap<MessageId, oneshot::Sender<(Envelope, oneshot::Sender<()>)>>>;
 
 pub struct ChannelClient {
@@ -2359,7 +2638,7 @@ impl ProtoClient for ChannelClient {
 
 #[cfg(any(test, feature = "test-support"))]
 mod fake {
-    use std::{path::PathBuf, sync::Arc};
+    use std::{collections::BTreeSet, path::PathBuf, sync::Arc};
 
     use anyhow::Result;
     use async_trait::async_trait;
@@ -2376,6 +2655,7 @@ mod fake {
 
     use super::{
         ChannelClient, RemoteConnection, SshClientDelegate, SshConnectionOptions, SshPlatform,
+        SshPortForward,
     };
 
     pub(super) struct FakeRemoteConnection {
@@ -2414,6 +2694,18 @@ mod fake {
         fn ssh_args(&self) -> Vec<String> {
             Vec::new()
         }
+        fn ssh_program(&self) -> &'static str {
+            "ssh"
+        }
+        fn forward_port(&self, _: &SshPortForward, _: &AppContext) -> Task<Result<()>> {
+            Task::ready(Ok(()))
+        }
+        fn cancel_port_forward(&self, _: &SshPortForward, _: &AppContext) -> Task<Result<()>> {
+            Task::ready(Ok(()))
+        }
+        fn listening_ports(&self, _: &AppContext) -> Task<Result<BTreeSet<u16>>> {
+            Task::ready(Ok(BTreeSet::new()))
+        }
         fn upload_directory(
             &self,
             _src_path: PathBuf,
@@ -0,0 +1,531 @@
+use std::{ops::Range, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use editor::{scroll::Autoscroll, Editor};
+use futures::StreamExt as _;
+use fuzzy::{match_strings, StringMatchCandidate};
+use gpui::{
+    actions, AnyElement, AppContext, AsyncWindowContext, DismissEvent, EventEmitter, FocusHandle,
+    FocusableView, Model, Render, SharedString, Subscription, Task, View, ViewContext,
+    VisualContext as _, WeakView,
+};
+use language::{Buffer, OffsetRangeExt as _, Point, ToPoint as _};
+use picker::{Picker, PickerDelegate, PickerPreview};
+use project::{
+    search::{SearchQuery, SearchResult},
+    Project,
+};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace};
+
+actions!(live_grep, [Toggle]);
+
+/// How long to wait for more keystrokes before searching for the query.
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(80);
+/// The number of matching lines after which the search stops.
+const MAX_MATCHES: usize = 1000;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &Toggle, cx| {
+            let project = workspace.project().clone();
+            let weak_workspace = cx.view().downgrade();
+            workspace.toggle_modal(cx, |cx| LiveGrep::new(weak_workspace, project, cx));
+        });
+    })
+    .detach();
+}
+
+/// A modal that searches the contents of the project's files as you type, listing the matching
+/// lines, optionally next to a preview of the selected one.
+pub struct LiveGrep {
+    picker: View<Picker<LiveGrepDelegate>>,
+    _subscription: Subscription,
+}
+
+impl LiveGrep {
+    fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = LiveGrepDelegate::new(workspace, project);
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        let subscription =
+            cx.subscribe(&picker, |_, _, _: &DismissEvent, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            _subscription: subscription,
+        }
+    }
+}
+
+impl EventEmitter<DismissEvent> for LiveGrep {}
+impl ModalView for LiveGrep {}
+
+impl FocusableView for LiveGrep {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for LiveGrep {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let preview_width = self.picker.read(cx).preview_width(cx).unwrap_or_default();
+
+        v_flex()
+            .key_context("LiveGrep")
+            .w(rems(34.).to_pixels(cx.rem_size()) + preview_width)
+            .child(self.picker.clone())
+    }
+}
+
+/// A line containing a match for the query.
+struct LineMatch {
+    buffer: Model<Buffer>,
+    range: Range<language::Anchor>,
+    path: SharedString,
+    row: u32,
+    /// The line, without its indentation.
+    line: SharedString,
+    /// The byte offsets of the matching characters in `line`.
+    highlights: Vec<usize>,
+}
+
+pub struct LiveGrepDelegate {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    matches: Vec<LineMatch>,
+    selected_index: usize,
+    searching: bool,
+    limit_reached: bool,
+}
+
+impl LiveGrepDelegate {
+    fn new(workspace: WeakView<Workspace>, project: Model<Project>) -> Self {
+        Self {
+            workspace,
+            project,
+            matches: Vec::new(),
+            selected_index: 0,
+            searching: false,
+            limit_reached: false,
+        }
+    }
+
+    /// The query for the lines containing the words of the input in order, with anything
+    /// between them. The search is case-sensitive only if the input contains uppercase letters.
+    fn search_query(input: &str) -> Option<SearchQuery> {
+        let words = input
+            .split_whitespace()
+            .map(regex::escape)
+            .collect::<Vec<_>>();
+        if words.is_empty() {
+            return None;
+        }
+        let case_sensitive = input.chars().any(char::is_uppercase);
+        let pattern = words.join(r"[^\n]*?");
+        SearchQuery::regex(
+            pattern,
+            false,
+            case_sensitive,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .log_err()
+    }
+
+    async fn search(
+        picker: WeakView<Picker<Self>>,
+        project: Model<Project>,
+        search_query: SearchQuery,
+        query: String,
+        show_worktree_root_name: bool,
+        cx: &mut AsyncWindowContext,
+    ) -> Result<()> {
+        let mut results = project
+            .update(cx, |project, cx| project.search(search_query, cx))?
+            .ready_chunks(64);
+
+        let mut first_chunk = true;
+        let mut limit_reached = false;
+        while let Some(results) = results.next().await {
+            picker.update(cx, |picker, cx| {
+                let delegate = &mut picker.delegate;
+                if first_chunk {
+                    delegate.matches.clear();
+                    delegate.selected_index = 0;
+                }
+                for result in results {
+                    let SearchResult::Buffer { buffer, ranges } = result else {
+                        continue;
+                    };
+                    let Some(file) = buffer.read(cx).file() else {
+                        continue;
+                    };
+                    let path = if show_worktree_root_name {
+                        file.full_path(cx)
+                    } else {
+                        file.path().to_path_buf()
+                    };
+                    let path = SharedString::from(path.to_string_lossy().to_string());
+                    delegate
+                        .matches
+                        .extend(Self::line_matches(&buffer, &ranges, path, cx));
+                }
+                if delegate.matches.len() >= MAX_MATCHES {
+                    delegate.matches.truncate(MAX_MATCHES);
+                    limit_reached = true;
+                }
+                cx.notify();
+            })?;
+            first_chunk = false;
+            if limit_reached {
+                break;
+            }
+        }
+
+        // Rank the lines by how closely they match the query, keeping the same line selected.
+        let candidates = picker.update(cx, |picker, _| {
+            if first_chunk {
+                picker.delegate.matches.clear();
+                picker.delegate.selected_index = 0;
+            }
+            picker
+                .delegate
+                .matches
+                .iter()
+                .enumerate()
+                .map(|(id, mat)| StringMatchCandidate::new(id, &mat.line))
+                .collect::<Vec<_>>()
+        })?;
+        let fuzzy_query = query.split_whitespace().collect::<String>();
+        let ranked = match_strings(
+            &candidates,
+            &fuzzy_query,
+            false,
+            candidates.len(),
+            &Default::default(),
+            cx.background_executor().clone(),
+        )
+        .await;
+
+        picker.update(cx, |picker, cx| {
+            let delegate = &mut picker.delegate;
+            let mut order = ranked
+                .iter()
+                .map(|mat| mat.candidate_id)
+                .collect::<Vec<_>>();
+            let mut is_ranked = vec![false; delegate.matches.len()];
+            for id in &order {
+                is_ranked[*id] = true;
+            }
+            order.extend((0..delegate.matches.len()).filter(|id| !is_ranked[*id]));
+
+            let mut matches = delegate.matches.drain(..).map(Some).collect::<Vec<_>>();
+            delegate.matches = order.iter().filter_map(|id| matches[*id].take()).collect();
+            delegate.selected_index = order
+                .iter()
+                .position(|id| *id == delegate.selected_index)
+                .unwrap_or(0);
+            delegate.searching = false;
+            delegate.limit_reached = limit_reached;
+            cx.notify();
+        })
+    }
+
+    fn line_matches(
+        buffer: &Model<Buffer>,
+        ranges: &[Range<language::Anchor>],
+        path: SharedString,
+        cx: &AppContext,
+    ) -> Vec<LineMatch> {
+        let snapshot = buffer.read(cx).snapshot();
+        let mut line_matches = Vec::<LineMatch>::new();
+        for range in ranges {
+            let start = range.start.to_point(&snapshot);
+            let end = range.end.to_point(&snapshot);
+            let line_end = Point::new(start.row, snapshot.line_len(start.row));
+            let line = snapshot
+                .text_for_range(Point::new(start.row, 0)..line_end)
+                .collect::<String>();
+            let indent = line.len() - line.trim_start().len();
+            let highlight_end = if end.row == start.row {
+                end.column as usize
+            } else {
+                line.len()
+            };
+            let highlights = line[start.column as usize..highlight_end]
+                .char_indices()
+                .map(|(ix, _)| start.column as usize + ix - indent)
+                .collect::<Vec<_>>();
+
+            // Several matches on the same line are shown as one.
+            if let Some(last) = line_matches.last_mut() {
+                if last.row == start.row {
+                    last.highlights.extend(highlights);
+                    continue;
+                }
+            }
+            line_matches.push(LineMatch {
+                buffer: buffer.clone(),
+                range: range.clone(),
+                path: path.clone(),
+                row: start.row,
+                line: line[indent..].to_string().into(),
+                highlights,
+            });
+        }
+        line_matches
+    }
+}
+
+impl PickerDelegate for LiveGrepDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search file contents…".into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        if self.searching {
+            "Searching…".into()
+        } else {
+            "No matches".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+        cx.notify();
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let Some(search_query) = Self::search_query(&query) else {
+            self.matches.clear();
+            self.selected_index = 0;
+            self.searching = false;
+            self.limit_reached = false;
+            return Task::ready(());
+        };
+        self.searching = true;
+        let project = self.project.clone();
+        let show_worktree_root_name = project.read(cx).visible_worktrees(cx).count() > 1;
+
+        cx.spawn(|picker, mut cx| async move {
+            // Typing drops this task before the timer fires, so only the last query is searched.
+            cx.background_executor().timer(SEARCH_DEBOUNCE).await;
+            Self::search(
+                picker,
+                project,
+                search_query,
+                query,
+                show_worktree_root_name,
+                &mut cx,
+            )
+            .await
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(mat) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        let buffer = mat.buffer.clone();
+        let range = mat.range.to_offset(buffer.read(cx));
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let pane = if secondary {
+                    workspace.adjacent_pane(cx)
+                } else {
+                    workspace.active_pane().clone()
+                };
+                let editor = workspace.open_project_item::<Editor>(pane, buffer, true, true, cx);
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([range])
+                    });
+                });
+            })
+            .log_err();
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut ViewContext<Picker<Self>>) {}
+
+    fn supports_preview(&self) -> bool {
+        true
+    }
+
+    fn preview(
+        &self,
+        ix: usize,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Task<Result<PickerPreview>>> {
+        let mat = self.matches.get(ix)?;
+        let range = mat.range.to_point(mat.buffer.read(cx));
+        Some(Task::ready(Ok(PickerPreview {
+            buffer: mat.buffer.clone(),
+            range: Some(range),
+        })))
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    v_flex()
+                        .child(
+                            HighlightedLabel::new(mat.line.clone(), mat.highlights.clone())
+                                .single_line(),
+                        )
+                        .child(
+                            Label::new(format!("{}:{}", mat.path, mat.row + 1))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .single_line(),
+                        ),
+                ),
+        )
+    }
+
+    fn render_footer(&self, _: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        self.limit_reached.then(|| {
+            h_flex()
+                .px_3()
+                .py_1()
+                .child(
+                    Label::new(format!(
+                        "Showing the first {MAX_MATCHES} matches, refine the query to see more"
+                    ))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+                )
+                .into_any_element()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_live_grep(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "fn one() -> usize {\n    let value = 1;\n    value\n}\n",
+                "two.rs": "fn two() -> usize {\n    // the value is two\n    2\n}\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        cx.dispatch_action(Toggle);
+        let live_grep = workspace.update(cx, |workspace, cx| {
+            workspace.active_modal::<LiveGrep>(cx).unwrap()
+        });
+        let picker = live_grep.read_with(cx, |live_grep, _| live_grep.picker.clone());
+
+        cx.simulate_input("let value");
+        cx.executor().advance_clock(SEARCH_DEBOUNCE);
+        cx.run_until_parked();
+        picker.update(cx, |picker, _| {
+            let matches = picker
+                .delegate
+                .matches
+                .iter()
+                .map(|mat| (mat.path.to_string(), mat.row, mat.line.to_string()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                matches,
+                [("one.rs".to_string(), 1, "let value = 1;".to_string())]
+            );
+            assert_eq!(
+                picker.delegate.matches[0].highlights,
+                (0..9).collect::<Vec<_>>()
+            );
+        });
+
+        cx.dispatch_action(picker::TogglePreview);
+        cx.executor().advance_clock(Duration::from_millis(100));
+        cx.run_until_parked();
+        picker.update(cx, |picker, cx| {
+            assert_eq!(
+                picker.preview_text(cx).as_deref(),
+                Some("fn one() -> usize {\n    let value = 1;\n    value\n}\n")
+            );
+        });
+
+        picker.update(cx, |picker, cx| picker.set_query("value", cx));
+        cx.executor().advance_clock(SEARCH_DEBOUNCE);
+        cx.run_until_parked();
+        picker.update(cx, |picker, _| {
+            assert_eq!(picker.delegate.matches.len(), 3);
+            picker.delegate.selected_index = picker
+                .delegate
+                .matches
+                .iter()
+                .position(|mat| mat.path.as_ref() == "two.rs")
+                .unwrap();
+        });
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<LiveGrep>(cx).is_none());
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            editor.update(cx, |editor, cx| {
+                assert_eq!(editor.title(cx), "two.rs");
+                let selection = editor.selections.newest::<Point>(cx);
+                assert_eq!(
+                    selection.start..selection.end,
+                    Point::new(1, 11)..Point::new(1, 16)
+                );
+            });
+        });
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            client::init_settings(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+        });
+    }
+}
@@ -0,0 +1,352 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use db::sqlez::bindable::{Bind, Column, StaticColumnCount};
+use db::sqlez::statement::Statement;
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+use language::Point;
+use workspace::{ItemId, WorkspaceDb, WorkspaceId};
+
+use crate::SearchOptions;
+
+/// A project search saved under a name, to be run again later.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+    pub options: SearchOptions,
+    pub files_to_include: String,
+    pub files_to_exclude: String,
+}
+
+impl StaticColumnCount for SavedSearch {
+    fn column_count() -> usize {
+        5
+    }
+}
+
+impl Bind for SavedSearch {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        let start_index = statement.bind(&self.name, start_index)?;
+        let start_index = statement.bind(&self.query, start_index)?;
+        let start_index = statement.bind(&self.options.bits(), start_index)?;
+        let start_index = statement.bind(&self.files_to_include, start_index)?;
+        statement.bind(&self.files_to_exclude, start_index)
+    }
+}
+
+impl Column for SavedSearch {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (name, start_index) = Column::column(statement, start_index)?;
+        let (query, start_index) = Column::column(statement, start_index)?;
+        let (options, start_index): (u16, i32) = Column::column(statement, start_index)?;
+        let (files_to_include, start_index) = Column::column(statement, start_index)?;
+        let (files_to_exclude, start_index) = Column::column(statement, start_index)?;
+        let search = Self {
+            name,
+            query,
+            options: SearchOptions::from_bits_truncate(options),
+            files_to_include,
+            files_to_exclude,
+        };
+        Ok((search, start_index))
+    }
+}
+
+/// The location of a match in a frozen set of search results.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SerializedMatch {
+    pub(crate) abs_path: PathBuf,
+    pub(crate) start: Point,
+    pub(crate) end: Point,
+}
+
+impl StaticColumnCount for SerializedMatch {
+    fn column_count() -> usize {
+        5
+    }
+}
+
+impl Bind for SerializedMatch {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        let start_index = statement.bind(&self.abs_path, start_index)?;
+        let start_index = statement.bind(&self.start.row, start_index)?;
+        let start_index = statement.bind(&self.start.column, start_index)?;
+        let start_index = statement.bind(&self.end.row, start_index)?;
+        statement.bind(&self.end.column, start_index)
+    }
+}
+
+impl Column for SerializedMatch {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (abs_path, start_index) = Column::column(statement, start_index)?;
+        let (start_row, start_index) = Column::column(statement, start_index)?;
+        let (start_column, start_index) = Column::column(statement, start_index)?;
+        let (end_row, start_index) = Column::column(statement, start_index)?;
+        let (end_column, start_index) = Column::column(statement, start_index)?;
+        let mat = Self {
+            abs_path,
+            start: Point::new(start_row, start_column),
+            end: Point::new(end_row, end_column),
+        };
+        Ok((mat, start_index))
+    }
+}
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // saved_searches(
+    //   workspace_id: usize,
+    //   name: String,
+    //   query: String,
+    //   options: u16,
+    //   files_to_include: String,
+    //   files_to_exclude: String,
+    // )
+    //
+    // search_snapshots(
+    //   workspace_id: usize,
+    //   item_id: usize,
+    //   title: String,
+    // )
+    //
+    // search_snapshot_matches(
+    //   workspace_id: usize,
+    //   item_id: usize,
+    //   position: usize,
+    //   path: PathBuf,
+    //   start_row: u32,
+    //   start_column: u32,
+    //   end_row: u32,
+    //   end_column: u32,
+    // )
+    pub static ref DB: SearchDb<WorkspaceDb> =
+        &[sql! (
+            CREATE TABLE saved_searches(
+                workspace_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                query TEXT NOT NULL,
+                options INTEGER NOT NULL,
+                files_to_include TEXT NOT NULL,
+                files_to_exclude TEXT NOT NULL,
+                PRIMARY KEY(workspace_id, name),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+
+            CREATE TABLE search_snapshots(
+                workspace_id INTEGER NOT NULL,
+                item_id INTEGER NOT NULL,
+                title TEXT NOT NULL,
+                PRIMARY KEY(workspace_id, item_id),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+
+            CREATE TABLE search_snapshot_matches(
+                workspace_id INTEGER NOT NULL,
+                item_id INTEGER NOT NULL,
+                position INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                start_row INTEGER NOT NULL,
+                start_column INTEGER NOT NULL,
+                end_row INTEGER NOT NULL,
+                end_column INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, item_id, position),
+                FOREIGN KEY(workspace_id, item_id) REFERENCES search_snapshots(workspace_id, item_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        )];
+);
+
+impl SearchDb {
+    query! {
+        pub fn get_saved_searches(workspace_id: WorkspaceId) -> Result<Vec<SavedSearch>> {
+            SELECT name, query, options, files_to_include, files_to_exclude
+            FROM saved_searches
+            WHERE workspace_id = ?
+            ORDER BY name
+        }
+    }
+
+    query! {
+        pub async fn save_search(workspace_id: WorkspaceId, search: SavedSearch) -> Result<()> {
+            INSERT OR REPLACE INTO saved_searches
+                (workspace_id, name, query, options, files_to_include, files_to_exclude)
+            VALUES
+                (?, ?, ?, ?, ?, ?)
+        }
+    }
+
+    query! {
+        pub async fn delete_saved_search(workspace_id: WorkspaceId, name: String) -> Result<()> {
+            DELETE FROM saved_searches
+            WHERE workspace_id = ? AND name = ?
+        }
+    }
+
+    query! {
+        pub(crate) fn get_snapshot_title(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<String>> {
+            SELECT title
+            FROM search_snapshots
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
+    query! {
+        pub(crate) fn get_snapshot_matches(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Vec<SerializedMatch>> {
+            SELECT path, start_row, start_column, end_row, end_column
+            FROM search_snapshot_matches
+            WHERE item_id = ? AND workspace_id = ?
+            ORDER BY position
+        }
+    }
+
+    pub(crate) async fn save_snapshot(
+        &self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        title: String,
+        matches: Vec<SerializedMatch>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_search_snapshot", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM search_snapshots WHERE workspace_id = ? AND item_id = ?
+                ))?((workspace_id, item_id))?;
+                conn.exec_bound(sql!(
+                    INSERT INTO search_snapshots (workspace_id, item_id, title)
+                    VALUES (?, ?, ?)
+                ))?((workspace_id, item_id, title))?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT INTO search_snapshot_matches
+                        (workspace_id, item_id, position, path, start_row, start_column, end_row, end_column)
+                    VALUES
+                        (?, ?, ?, ?, ?, ?, ?, ?)
+                ))?;
+                for (position, mat) in matches.into_iter().enumerate() {
+                    insert((workspace_id, item_id, position, mat))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    pub(crate) async fn delete_unloaded_snapshots(
+        &self,
+        workspace: WorkspaceId,
+        alive_items: Vec<ItemId>,
+    ) -> Result<()> {
+        let placeholders = alive_items
+            .iter()
+            .map(|_| "?")
+            .collect::<Vec<&str>>()
+            .join(", ");
+
+        let query = format!(
+            "DELETE FROM search_snapshots WHERE workspace_id = ? AND item_id NOT IN ({placeholders})"
+        );
+
+        self.write(move |conn| {
+            let mut statement = Statement::prepare(conn, query)?;
+            let mut next_index = statement.bind(&workspace, 1)?;
+            for id in alive_items {
+                next_index = statement.bind(&id, next_index)?;
+            }
+            statement.exec()
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_saved_searches() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let todo = SavedSearch {
+            name: "security todos".to_string(),
+            query: "TODO(security)".to_string(),
+            options: SearchOptions::CASE_SENSITIVE | SearchOptions::COMMENTS,
+            files_to_include: "crates/**".to_string(),
+            files_to_exclude: String::new(),
+        };
+        let unsafe_blocks = SavedSearch {
+            name: "unsafe".to_string(),
+            query: r"unsafe\s*\{".to_string(),
+            options: SearchOptions::REGEX,
+            files_to_include: "*.rs".to_string(),
+            files_to_exclude: "vendor/*".to_string(),
+        };
+
+        DB.save_search(workspace_id, unsafe_blocks.clone())
+            .await
+            .unwrap();
+        DB.save_search(workspace_id, todo.clone()).await.unwrap();
+        assert_eq!(
+            DB.get_saved_searches(workspace_id).unwrap(),
+            [todo.clone(), unsafe_blocks.clone()]
+        );
+
+        let renamed_query = SavedSearch {
+            query: "TODO(sec)".to_string(),
+            ..todo.clone()
+        };
+        DB.save_search(workspace_id, renamed_query.clone())
+            .await
+            .unwrap();
+        DB.delete_saved_search(workspace_id, unsafe_blocks.name.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_saved_searches(workspace_id).unwrap(),
+            [renamed_query]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_search_snapshots() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let matches = vec![
+            SerializedMatch {
+                abs_path: PathBuf::from("/root/a.rs"),
+                start: Point::new(1, 4),
+                end: Point::new(1, 18),
+            },
+            SerializedMatch {
+                abs_path: PathBuf::from("/root/b.rs"),
+                start: Point::new(10, 0),
+                end: Point::new(11, 2),
+            },
+        ];
+
+        DB.save_snapshot(1, workspace_id, "TODO".to_string(), matches.clone())
+            .await
+            .unwrap();
+        DB.save_snapshot(2, workspace_id, "FIXME".to_string(), matches[1..].to_vec())
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_snapshot_title(1, workspace_id).unwrap().as_deref(),
+            Some("TODO")
+        );
+        assert_eq!(DB.get_snapshot_matches(1, workspace_id).unwrap(), matches);
+
+        DB.delete_unloaded_snapshots(workspace_id, vec![2])
+            .await
+            .unwrap();
+        assert_eq!(DB.get_snapshot_title(1, workspace_id).unwrap(), None);
+        assert_eq!(DB.get_snapshot_matches(1, workspace_id).unwrap(), []);
+        assert_eq!(
+            DB.get_snapshot_matches(2, workspace_id).unwrap(),
+            &matches[1..]
+        );
+    }
+}
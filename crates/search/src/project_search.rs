@@ -1,7 +1,9 @@
 use crate::{
-    buffer_search::Deploy, BufferSearchBar, FocusSearch, NextHistoryQuery, PreviousHistoryQuery,
-    ReplaceAll, ReplaceNext, SearchOptions, SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive,
-    ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleWholeWord,
+    buffer_search::Deploy, persistence::SavedSearch, BufferSearchBar, FocusSearch,
+    NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOptions,
+    SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleCode, ToggleComments,
+    ToggleIdentifiers, ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleStrings,
+    ToggleStructural, ToggleWholeWord,
 };
 use collections::{HashMap, HashSet};
 use editor::{
@@ -12,16 +14,16 @@ use futures::StreamExt;
 use gpui::{
     actions, div, Action, AnyElement, AnyView, AppContext, Axis, Context as _, EntityId,
     EventEmitter, FocusHandle, FocusableView, Global, Hsla, InteractiveElement, IntoElement,
-    KeyContext, Model, ModelContext, ParentElement, Point, Render, SharedString, Styled,
-    Subscription, Task, TextStyle, UpdateGlobal, View, ViewContext, VisualContext, WeakModel,
-    WeakView, WindowContext,
+    KeyContext, Model, ModelContext, ParentElement, PathPromptOptions, Point, Render, SharedString,
+    Styled, Subscription, Task, TextStyle, UpdateGlobal, View, ViewContext, VisualContext,
+    WeakModel, WeakView, WindowContext,
 };
-use language::Buffer;
+use language::{Buffer, Capability};
 use menu::Confirm;
 use project::{
-    search::{SearchInputKind, SearchQuery},
+    search::{ExternalSearchRoot, SearchInputKind, SearchQuery},
     search_history::SearchHistoryCursor,
-    Project, ProjectPath,
+    DirectoryLister, Project, ProjectPath, WorktreeId,
 };
 use settings::Settings;
 use std::{
@@ -32,8 +34,8 @@ use std::{
 };
 use theme::ThemeSettings;
 use ui::{
-    h_flex, prelude::*, utils::SearchInputWidth, v_flex, Icon, IconButton, IconButtonShape,
-    IconName, KeyBinding, Label, LabelCommon, LabelSize, Toggleable, Tooltip,
+    h_flex, prelude::*, utils::SearchInputWidth, v_flex, Divider, Icon, IconButton,
+    IconButtonShape, IconName, KeyBinding, Label, LabelCommon, LabelSize, Toggleable, Tooltip,
 };
 use util::paths::PathMatcher;
 use workspace::{
@@ -45,7 +47,14 @@ use workspace::{
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        SearchOutsideProject,
+        SearchDependencySources
+    ]
 );
 
 #[derive(Default)]
@@ -74,6 +83,21 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, cx);
         });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleStructural, cx| {
+            search_bar.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleComments, cx| {
+            search_bar.toggle_search_option(SearchOptions::COMMENTS, cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleStrings, cx| {
+            search_bar.toggle_search_option(SearchOptions::STRINGS, cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleIdentifiers, cx| {
+            search_bar.toggle_search_option(SearchOptions::IDENTIFIERS, cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleCode, cx| {
+            search_bar.toggle_search_option(SearchOptions::CODE, cx);
+        });
         register_workspace_action(workspace, move |search_bar, action: &ToggleReplace, cx| {
             search_bar.toggle_replace(action, cx)
         });
@@ -112,6 +136,10 @@ pub fn init(cx: &mut AppContext) {
             ProjectSearchView::new_search(workspace, action, cx);
             cx.notify();
         });
+        workspace.register_action(ProjectSearchView::search_outside_project);
+        workspace.register_action(move |workspace, _: &SearchDependencySources, cx| {
+            ProjectSearchView::new_external_search(workspace, ExternalSearchRoot::Dependencies, cx);
+        });
     })
     .detach();
 }
@@ -122,6 +150,11 @@ fn is_contains_uppercase(str: &str) -> bool {
 
 pub struct ProjectSearch {
     project: Model<Project>,
+    /// Where to search instead of the project's worktrees.
+    external_root: Option<ExternalSearchRoot>,
+    /// The worktrees created to open the results of the external searches, removed along with
+    /// this search.
+    external_worktrees: HashSet<WorktreeId>,
     excerpts: Model<MultiBuffer>,
     pending_search: Option<Task<Option<()>>>,
     match_ranges: Vec<Range<Anchor>>,
@@ -178,6 +211,8 @@ impl ProjectSearch {
 
         Self {
             project,
+            external_root: None,
+            external_worktrees: HashSet::default(),
             excerpts: cx.new_model(|_| MultiBuffer::new(capability)),
             pending_search: Default::default(),
             match_ranges: Default::default(),
@@ -192,24 +227,93 @@ impl ProjectSearch {
         }
     }
 
+    /// A search outside of the project's worktrees, whose results are read-only.
+    pub fn external(
+        project: Model<Project>,
+        root: ExternalSearchRoot,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        cx.on_release(Self::release_external_worktrees).detach();
+        Self {
+            external_root: Some(root),
+            excerpts: cx.new_model(|_| MultiBuffer::new(Capability::ReadOnly)),
+            ..Self::new(project, cx)
+        }
+    }
+
     fn clone(&self, cx: &mut ModelContext<Self>) -> Model<Self> {
-        cx.new_model(|cx| Self {
-            project: self.project.clone(),
-            excerpts: self
-                .excerpts
-                .update(cx, |excerpts, cx| cx.new_model(|cx| excerpts.clone(cx))),
-            pending_search: Default::default(),
-            match_ranges: self.match_ranges.clone(),
-            active_query: self.active_query.clone(),
-            last_search_query_text: self.last_search_query_text.clone(),
-            search_id: self.search_id,
-            no_results: self.no_results,
-            limit_reached: self.limit_reached,
-            search_history_cursor: self.search_history_cursor.clone(),
-            search_included_history_cursor: self.search_included_history_cursor.clone(),
-            search_excluded_history_cursor: self.search_excluded_history_cursor.clone(),
+        let external_worktrees = self.project.update(cx, |project, _| {
+            self.external_worktrees
+                .iter()
+                .copied()
+                .filter(|worktree_id| project.retain_external_search_worktree(*worktree_id))
+                .collect()
+        });
+        cx.new_model(|cx| {
+            if self.external_root.is_some() {
+                cx.on_release(Self::release_external_worktrees).detach();
+            }
+            Self {
+                project: self.project.clone(),
+                external_root: self.external_root.clone(),
+                external_worktrees,
+                excerpts: self
+                    .excerpts
+                    .update(cx, |excerpts, cx| cx.new_model(|cx| excerpts.clone(cx))),
+                pending_search: Default::default(),
+                match_ranges: self.match_ranges.clone(),
+                active_query: self.active_query.clone(),
+                last_search_query_text: self.last_search_query_text.clone(),
+                search_id: self.search_id,
+                no_results: self.no_results,
+                limit_reached: self.limit_reached,
+                search_history_cursor: self.search_history_cursor.clone(),
+                search_included_history_cursor: self.search_included_history_cursor.clone(),
+                search_excluded_history_cursor: self.search_excluded_history_cursor.clone(),
+            }
         })
     }
+
+    fn release_external_worktrees(&mut self, cx: &mut AppContext) {
+        let external_worktrees = mem::take(&mut self.external_worktrees);
+        self.project.update(cx, |project, cx| {
+            for worktree_id in external_worktrees {
+                project.release_external_search_worktree(worktree_id, cx);
+            }
+        });
+    }
+
+    /// Keeps the invisible worktrees of the external search results alive until this search is
+    /// dropped.
+    fn retain_external_worktrees<'a>(
+        &mut self,
+        buffers: impl IntoIterator<Item = &'a Model<Buffer>>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if self.external_root.is_none() {
+            return;
+        }
+        for buffer in buffers {
+            let Some(worktree_id) = buffer.read(cx).file().map(|file| file.worktree_id(cx)) else {
+                continue;
+            };
+            if !self.external_worktrees.contains(&worktree_id)
+                && self.project.update(cx, |project, _| {
+                    project.retain_external_search_worktree(worktree_id)
+                })
+            {
+                self.external_worktrees.insert(worktree_id);
+            }
+        }
+    }
+
+    fn title(&self) -> String {
+        match &self.external_root {
+            Some(root) => format!("Search in {}", root.display_name()),
+            None => "Project Search".to_string(),
+        }
+    }
+
     fn cursor(&self, kind: SearchInputKind) -> &SearchHistoryCursor {
         match kind {
             SearchInputKind::Query => &self.search_history_cursor,
@@ -242,7 +346,10 @@ impl ProjectSearch {
                     .search_history_mut(SearchInputKind::Exclude)
                     .add(&mut self.search_excluded_history_cursor, excluded);
             }
-            project.search(query.clone(), cx)
+            match &self.external_root {
+                Some(root) => project.search_external(root.clone(), query.clone(), cx),
+                None => project.search(query.clone(), cx),
+            }
         });
         self.last_search_query_text = Some(query.as_str().to_string());
         self.search_id += 1;
@@ -275,6 +382,10 @@ impl ProjectSearch {
 
                 let match_ranges = this
                     .update(&mut cx, |this, cx| {
+                        this.retain_external_worktrees(
+                            buffers_with_ranges.iter().map(|(buffer, _)| buffer),
+                            cx,
+                        );
                         this.excerpts.update(cx, |excerpts, cx| {
                             excerpts.push_multiple_excerpts_with_context_lines(
                                 buffers_with_ranges,
@@ -332,12 +443,14 @@ impl Render for ProjectSearchView {
             let has_no_results = model.no_results.unwrap_or(false);
             let is_search_underway = model.pending_search.is_some();
 
-            let heading_text = if is_search_underway {
-                "Searching…"
+            let heading_text: SharedString = if is_search_underway {
+                "Searching…".into()
             } else if has_no_results {
-                "No Results"
+                "No Results".into()
+            } else if let Some(root) = &model.external_root {
+                format!("Search {}", root.display_name()).into()
             } else {
-                "Search All Files"
+                "Search All Files".into()
             };
 
             let heading_text = div()
@@ -395,7 +508,7 @@ impl Item for ProjectSearchView {
             .is_empty()
             .not()
             .then(|| query_text.into())
-            .or_else(|| Some("Project Search".into()))
+            .or_else(|| Some(self.model.read(cx).title().into()))
     }
 
     fn act_as_type<'a>(
@@ -436,7 +549,7 @@ impl Item for ProjectSearchView {
         Some(
             last_query
                 .filter(|query| !query.is_empty())
-                .unwrap_or_else(|| "Project Search".into()),
+                .unwrap_or_else(|| self.model.read(cx).title().into()),
         )
     }
 
@@ -575,6 +688,13 @@ impl ProjectSearchView {
 
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) {
         self.search_options.toggle(option);
+        if self.search_options.contains(option) {
+            if option == SearchOptions::STRUCTURAL {
+                self.search_options.remove(SearchOptions::TEXT_MATCHING);
+            } else if SearchOptions::TEXT_MATCHING.contains(option) {
+                self.search_options.remove(SearchOptions::STRUCTURAL);
+            }
+        }
         ActiveSettings::update_global(cx, |settings, cx| {
             settings.0.insert(
                 self.model.read(cx).project.downgrade(),
@@ -604,6 +724,7 @@ impl ProjectSearchView {
             self.results_editor.update(cx, |editor, cx| {
                 editor.replace(&mat, &query, cx);
             });
+            self.update_replacement_previews(cx);
             self.select_match(Direction::Next, cx)
         }
     }
@@ -634,6 +755,26 @@ impl ProjectSearchView {
         self.model.update(cx, |model, _cx| {
             model.match_ranges = match_ranges;
         });
+        self.update_replacement_previews(cx);
+    }
+
+    /// Structural replacements differ from one match to the next, so show each of them next to
+    /// its match while replacing.
+    fn update_replacement_previews(&mut self, cx: &mut ViewContext<Self>) {
+        let replacement = self.replacement(cx);
+        let query = self
+            .model
+            .read(cx)
+            .active_query
+            .clone()
+            .filter(|query| {
+                self.replace_enabled && query.is_structural() && !replacement.is_empty()
+            })
+            .map(|query| query.with_replacement(replacement));
+        let match_ranges = self.model.read(cx).match_ranges.clone();
+        self.results_editor.update(cx, |editor, cx| {
+            editor.set_replacement_previews(&match_ranges, query.as_ref(), cx);
+        });
     }
 
     pub fn new(
@@ -700,6 +841,14 @@ impl ProjectSearchView {
             }
             editor
         });
+        subscriptions.push(cx.subscribe(
+            &replacement_editor,
+            |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::Edited { .. } = event {
+                    this.update_replacement_previews(cx);
+                }
+            },
+        ));
         let results_editor = cx.new_view(|cx| {
             let mut editor = Editor::for_multibuffer(excerpts, Some(project.clone()), true, cx);
             editor.set_searchable(false);
@@ -800,6 +949,51 @@ impl ProjectSearchView {
         });
     }
 
+    /// Opens a search over a directory or archive picked by the user, outside of the project.
+    fn search_outside_project(
+        workspace: &mut Workspace,
+        _: &SearchOutsideProject,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        if !workspace.project().read(cx).is_local() {
+            return;
+        }
+        let paths = workspace.prompt_for_open_path(
+            PathPromptOptions {
+                files: true,
+                directories: true,
+                multiple: false,
+            },
+            DirectoryLister::Local(workspace.app_state().fs.clone()),
+            cx,
+        );
+        cx.spawn(|workspace, mut cx| async move {
+            let Some(path) = paths.await.ok().flatten().and_then(|mut paths| paths.pop()) else {
+                return Ok(());
+            };
+            workspace.update(&mut cx, |workspace, cx| {
+                Self::new_external_search(workspace, ExternalSearchRoot::for_path(path), cx)
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub fn new_external_search(
+        workspace: &mut Workspace,
+        root: ExternalSearchRoot,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        if !workspace.project().read(cx).is_local() {
+            return;
+        }
+        let weak_workspace = cx.view().downgrade();
+        let model =
+            cx.new_model(|cx| ProjectSearch::external(workspace.project().clone(), root, cx));
+        let search = cx.new_view(|cx| ProjectSearchView::new(weak_workspace, model, cx, None));
+        workspace.add_item_to_active_pane(Box::new(search.clone()), None, true, cx);
+        search.update(cx, |search, cx| search.focus_query_editor(cx));
+    }
+
     /// Re-activate the most recently activated search in this pane or the most recent if it has been closed.
     /// If no search exists in the workspace, create a new one.
     pub fn deploy_search(
@@ -965,7 +1159,32 @@ impl ProjectSearchView {
                 }
             };
 
-        let query = if self.search_options.contains(SearchOptions::REGEX) {
+        let query = if self.search_options.contains(SearchOptions::STRUCTURAL) {
+            match SearchQuery::structural(
+                text,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+                open_buffers,
+            ) {
+                Ok(query) => {
+                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
+                    if should_unmark_error {
+                        cx.notify();
+                    }
+
+                    Some(query)
+                }
+                Err(_e) => {
+                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
+                    if should_mark_error {
+                        cx.notify();
+                    }
+
+                    None
+                }
+            }
+        } else if self.search_options.contains(SearchOptions::REGEX) {
             match SearchQuery::regex(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
@@ -1026,7 +1245,8 @@ impl ProjectSearchView {
         if query.as_ref().is_some_and(|query| query.is_empty()) {
             return None;
         }
-        query
+        let syntax_kinds = self.search_options.syntax_kinds();
+        query.map(|query| query.with_syntax_kinds(syntax_kinds))
     }
 
     fn open_buffers(&self, cx: &mut ViewContext<Self>) -> Vec<Model<Buffer>> {
@@ -1147,6 +1367,7 @@ impl ProjectSearchView {
                 self.focus_results_editor(cx);
             }
         }
+        self.update_replacement_previews(cx);
 
         cx.emit(ViewEvent::UpdateTab);
         cx.notify();
@@ -1249,6 +1470,70 @@ impl ProjectSearchView {
     pub fn results_editor(&self) -> &View<Editor> {
         &self.results_editor
     }
+
+    /// Returns the search as it is set up in the search bar, named after its query.
+    pub(crate) fn saved_search(&self, cx: &AppContext) -> SavedSearch {
+        let query = self.query_editor.read(cx).text(cx);
+        SavedSearch {
+            name: query.clone(),
+            query,
+            options: self.search_options,
+            files_to_include: self.included_files_editor.read(cx).text(cx),
+            files_to_exclude: self.excluded_files_editor.read(cx).text(cx),
+        }
+    }
+
+    /// Opens a new search tab set up like the saved search, and runs it.
+    pub(crate) fn open_saved_search(
+        workspace: &mut Workspace,
+        search: &SavedSearch,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let settings = ProjectSearchSettings {
+            search_options: search.options,
+            filters_enabled: !search.files_to_include.is_empty()
+                || !search.files_to_exclude.is_empty(),
+        };
+        let weak_workspace = cx.view().downgrade();
+        let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+        let view =
+            cx.new_view(|cx| ProjectSearchView::new(weak_workspace, model, cx, Some(settings)));
+        workspace.add_item_to_active_pane(Box::new(view.clone()), None, true, cx);
+        view.update(cx, |view, cx| {
+            view.set_search_editor(SearchInputKind::Query, &search.query, cx);
+            view.set_search_editor(SearchInputKind::Include, &search.files_to_include, cx);
+            view.set_search_editor(SearchInputKind::Exclude, &search.files_to_exclude, cx);
+            // Setting the query may have toggled case sensitivity for smart case.
+            view.search_options = search.options;
+            view.search(cx);
+        });
+    }
+
+    /// Returns a title and the current matches grouped by buffer, to freeze them into a
+    /// [`SearchSnapshot`](crate::SearchSnapshot).
+    pub(crate) fn frozen_results(
+        &self,
+        cx: &AppContext,
+    ) -> Option<(
+        SharedString,
+        Vec<(Model<Buffer>, Vec<Range<language::Anchor>>)>,
+    )> {
+        let model = self.model.read(cx);
+        let title = model.active_query.as_ref()?.as_str().replace('\n', " ");
+        let excerpts = model.excerpts.read(cx);
+        let mut matches = Vec::<(Model<Buffer>, Vec<Range<language::Anchor>>)>::new();
+        for range in &model.match_ranges {
+            let Some(buffer) = range.start.buffer_id.and_then(|id| excerpts.buffer(id)) else {
+                continue;
+            };
+            let range = range.start.text_anchor..range.end.text_anchor;
+            match matches.last_mut() {
+                Some((last_buffer, ranges)) if *last_buffer == buffer => ranges.push(range),
+                _ => matches.push((buffer, vec![range])),
+            }
+        }
+        (!matches.is_empty()).then(|| (title.into(), matches))
+    }
 }
 
 fn buffer_search_query(
@@ -1385,6 +1670,7 @@ impl ProjectSearchBar {
                     this.query_editor.focus_handle(cx)
                 };
                 cx.focus(&editor_to_focus);
+                this.update_replacement_previews(cx);
                 cx.notify();
             });
         }
@@ -1631,7 +1917,33 @@ impl Render for ProjectSearchBar {
                         cx.listener(|this, _, cx| {
                             this.toggle_search_option(SearchOptions::REGEX, cx);
                         }),
-                    )),
+                    ))
+                    .child(SearchOptions::STRUCTURAL.as_button(
+                        self.is_option_enabled(SearchOptions::STRUCTURAL, cx),
+                        focus_handle.clone(),
+                        cx.listener(|this, _, cx| {
+                            this.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+                        }),
+                    ))
+                    .child(Divider::vertical())
+                    .children(
+                        [
+                            SearchOptions::COMMENTS,
+                            SearchOptions::STRINGS,
+                            SearchOptions::IDENTIFIERS,
+                            SearchOptions::CODE,
+                        ]
+                        .into_iter()
+                        .map(|option| {
+                            option.as_button(
+                                self.is_option_enabled(option, cx),
+                                focus_handle.clone(),
+                                cx.listener(move |this, _, cx| {
+                                    this.toggle_search_option(option, cx);
+                                }),
+                            )
+                        }),
+                    ),
             );
 
         let mode_column = h_flex()
@@ -3535,6 +3847,42 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_structural_option_excludes_text_matching_options(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree("/dir", json!({ "one.rs": "const ONE: usize = 1;" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace = window.root(cx).unwrap();
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| {
+            ProjectSearchView::new(workspace.downgrade(), search.clone(), cx, None)
+        });
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.search_options = SearchOptions::NONE;
+                search_view.toggle_search_option(SearchOptions::REGEX, cx);
+                search_view.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
+                search_view.toggle_search_option(SearchOptions::COMMENTS, cx);
+                search_view.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+                assert_eq!(
+                    search_view.search_options,
+                    SearchOptions::STRUCTURAL | SearchOptions::COMMENTS
+                );
+
+                search_view.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
+                assert_eq!(
+                    search_view.search_options,
+                    SearchOptions::WHOLE_WORD | SearchOptions::COMMENTS
+                );
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     async fn test_scroll_search_results_to_top(cx: &mut TestAppContext) {
         init_test(cx);
@@ -3610,6 +3958,86 @@ pub mod tests {
             .expect("unable to update search view");
     }
 
+    #[gpui::test]
+    async fn test_saved_search_and_frozen_results(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a": {
+                    "one.rs": "// TODO(security): check ONE\nconst ONE: usize = 1;",
+                },
+                "b": {
+                    "two.rs": "// todo(security): check TWO\nconst TWO: usize = 2;",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let mut cx = VisualTestContext::from_window(*window.deref(), cx);
+
+        let saved_search = SavedSearch {
+            name: "Security audit".to_string(),
+            query: "TODO(security)".to_string(),
+            options: SearchOptions::CASE_SENSITIVE,
+            files_to_include: String::new(),
+            files_to_exclude: "b/*".to_string(),
+        };
+        workspace.update(&mut cx, |workspace, cx| {
+            ProjectSearchView::open_saved_search(workspace, &saved_search, cx)
+        });
+        cx.run_until_parked();
+
+        let search_view = workspace
+            .update(&mut cx, |workspace, cx| {
+                workspace.active_item_as::<ProjectSearchView>(cx)
+            })
+            .expect("opening a saved search should open a project search view");
+        search_view.update(&mut cx, |search_view, cx| {
+            assert!(search_view.filters_enabled);
+            assert_eq!(
+                search_view.saved_search(cx),
+                SavedSearch {
+                    name: saved_search.query.clone(),
+                    ..saved_search.clone()
+                }
+            );
+            assert_eq!(
+                search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx)),
+                "\n\n\n// TODO(security): check ONE\nconst ONE: usize = 1;\n"
+            );
+        });
+
+        cx.dispatch_action(crate::saved_searches::FreezeSearchResults);
+        cx.run_until_parked();
+        let snapshot = workspace
+            .update(&mut cx, |workspace, cx| {
+                workspace.active_item_as::<crate::SearchSnapshot>(cx)
+            })
+            .expect("freezing search results should open a snapshot");
+        snapshot.update(&mut cx, |snapshot, cx| {
+            assert_eq!(
+                snapshot.tab_content_text(cx).as_deref(),
+                Some("TODO(security)")
+            );
+        });
+        let snapshot_editor = cx.read(|cx| {
+            snapshot
+                .act_as::<Editor>(cx)
+                .expect("snapshots contain an editor")
+        });
+        assert_eq!(
+            snapshot_editor.update(&mut cx, |editor, cx| editor.display_text(cx)),
+            "\n\n\n// TODO(security): check ONE\nconst ONE: usize = 1;\n"
+        );
+    }
+
     #[gpui::test]
     async fn test_buffer_search_query_reused(cx: &mut TestAppContext) {
         init_test(cx);
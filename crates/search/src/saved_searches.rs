@@ -0,0 +1,359 @@
+use std::sync::Arc;
+
+use editor::{Editor, EditorEvent};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render,
+    SharedString, Subscription, Task, View, ViewContext, VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace, WorkspaceId};
+
+use crate::{
+    persistence::{SavedSearch, DB},
+    search_snapshot::SearchSnapshot,
+    ProjectSearchView,
+};
+
+actions!(
+    project_search,
+    [SaveSearch, OpenSavedSearch, FreezeSearchResults]
+);
+
+pub fn init(cx: &mut AppContext) {
+    workspace::register_serializable_item::<SearchSnapshot>(cx);
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(save_search);
+        workspace.register_action(open_saved_search);
+        workspace.register_action(freeze_search_results);
+    })
+    .detach();
+}
+
+fn save_search(workspace: &mut Workspace, _: &SaveSearch, cx: &mut ViewContext<Workspace>) {
+    let Some(search_view) = workspace.active_item_as::<ProjectSearchView>(cx) else {
+        cx.propagate();
+        return;
+    };
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    let search = search_view.read(cx).saved_search(cx);
+    if search.query.is_empty() {
+        return;
+    }
+    workspace.toggle_modal(cx, |cx| SaveSearchModal::new(workspace_id, search, cx));
+}
+
+fn open_saved_search(
+    workspace: &mut Workspace,
+    _: &OpenSavedSearch,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    let searches = DB
+        .get_saved_searches(workspace_id)
+        .log_err()
+        .unwrap_or_default();
+    let weak_workspace = cx.view().downgrade();
+    workspace.toggle_modal(cx, |cx| {
+        SavedSearchesPicker::new(weak_workspace, workspace_id, searches, cx)
+    });
+}
+
+fn freeze_search_results(
+    workspace: &mut Workspace,
+    _: &FreezeSearchResults,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(search_view) = workspace.active_item_as::<ProjectSearchView>(cx) else {
+        cx.propagate();
+        return;
+    };
+    let Some((title, matches)) = search_view.read(cx).frozen_results(cx) else {
+        return;
+    };
+    let project = workspace.project().clone();
+    let snapshot = cx.new_view(|cx| SearchSnapshot::new(title, matches, project, cx));
+    workspace.add_item_to_active_pane(Box::new(snapshot), None, true, cx);
+}
+
+/// Asks for the name to save the active project search under.
+pub struct SaveSearchModal {
+    workspace_id: WorkspaceId,
+    search: SavedSearch,
+    name_editor: View<Editor>,
+    _subscription: Subscription,
+}
+
+impl SaveSearchModal {
+    fn new(workspace_id: WorkspaceId, search: SavedSearch, cx: &mut ViewContext<Self>) -> Self {
+        let name_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Search name…", cx);
+            editor.set_text(search.name.clone(), cx);
+            editor.select_all(&editor::actions::SelectAll, cx);
+            editor
+        });
+        let subscription = cx.subscribe(&name_editor, |_, _, event: &EditorEvent, cx| {
+            if let EditorEvent::Blurred = event {
+                cx.emit(DismissEvent);
+            }
+        });
+        Self {
+            workspace_id,
+            search,
+            name_editor,
+            _subscription: subscription,
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let name = self.name_editor.read(cx).text(cx).trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let search = SavedSearch {
+            name,
+            ..self.search.clone()
+        };
+        let workspace_id = self.workspace_id;
+        cx.background_executor()
+            .spawn(async move { DB.save_search(workspace_id, search).await })
+            .detach_and_log_err(cx);
+        cx.emit(DismissEvent);
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for SaveSearchModal {}
+impl ModalView for SaveSearchModal {}
+
+impl FocusableView for SaveSearchModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.name_editor.focus_handle(cx)
+    }
+}
+
+impl Render for SaveSearchModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(28.))
+            .elevation_2(cx)
+            .key_context("SaveSearchModal")
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .child(
+                div()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.name_editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_1()
+                    .child(Label::new("Save search for").color(Color::Muted))
+                    .child(Label::new(self.search.query.clone()).single_line()),
+            )
+    }
+}
+
+/// Lists the searches saved in the workspace, to run one of them again.
+pub struct SavedSearchesPicker {
+    picker: View<Picker<SavedSearchesPickerDelegate>>,
+}
+
+impl SavedSearchesPicker {
+    fn new(
+        workspace: WeakView<Workspace>,
+        workspace_id: WorkspaceId,
+        searches: Vec<SavedSearch>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = SavedSearchesPickerDelegate {
+            saved_searches_picker: cx.view().downgrade(),
+            workspace,
+            workspace_id,
+            searches,
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for SavedSearchesPicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for SavedSearchesPicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for SavedSearchesPicker {}
+impl ModalView for SavedSearchesPicker {}
+
+pub struct SavedSearchesPickerDelegate {
+    saved_searches_picker: WeakView<SavedSearchesPicker>,
+    workspace: WeakView<Workspace>,
+    workspace_id: WorkspaceId,
+    searches: Vec<SavedSearch>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PickerDelegate for SavedSearchesPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Run a saved search…".into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        "No saved searches".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .searches
+            .iter()
+            .enumerate()
+            .map(|(id, search)| StringMatchCandidate::new(id, &search.name))
+            .collect::<Vec<_>>();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    /// Runs the selected search, or deletes it when confirming with the secondary modifier.
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(search_ix) = self
+            .matches
+            .get(self.selected_index)
+            .map(|mat| mat.candidate_id)
+        else {
+            return;
+        };
+
+        if secondary {
+            let search = self.searches.remove(search_ix);
+            let workspace_id = self.workspace_id;
+            cx.background_executor()
+                .spawn(async move { DB.delete_saved_search(workspace_id, search.name).await })
+                .detach_and_log_err(cx);
+            self.matches.clear();
+            cx.spawn(|picker, mut cx| async move {
+                picker.update(&mut cx, |picker, cx| picker.refresh(cx))
+            })
+            .detach_and_log_err(cx);
+            return;
+        }
+
+        let search = self.searches[search_ix].clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::open_saved_search(workspace, &search, cx);
+            })
+            .log_err();
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.saved_searches_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let search = self.searches.get(mat.candidate_id)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(
+                    Icon::new(IconName::MagnifyingGlass)
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(HighlightedLabel::new(
+                            search.name.clone(),
+                            mat.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(search.query.clone())
+                                .color(Color::Muted)
+                                .single_line(),
+                        ),
+                ),
+        )
+    }
+}
@@ -2,7 +2,7 @@ use bitflags::bitflags;
 pub use buffer_search::BufferSearchBar;
 use editor::SearchSettings;
 use gpui::{actions, Action, AppContext, FocusHandle, IntoElement};
-use project::search::SearchQuery;
+use project::search::{SearchQuery, SyntaxKinds};
 pub use project_search::ProjectSearchView;
 use ui::{prelude::*, Tooltip};
 use ui::{ButtonStyle, IconButton, IconButtonShape};
@@ -10,13 +10,22 @@ use workspace::notifications::NotificationId;
 use workspace::{Toast, Workspace};
 
 pub mod buffer_search;
+pub mod live_grep;
+mod persistence;
 pub mod project_search;
+pub mod saved_searches;
 pub(crate) mod search_bar;
+mod search_snapshot;
+
+pub use persistence::SavedSearch;
+pub use search_snapshot::SearchSnapshot;
 
 pub fn init(cx: &mut AppContext) {
     menu::init();
     buffer_search::init(cx);
+    live_grep::init(cx);
     project_search::init(cx);
+    saved_searches::init(cx);
 }
 
 actions!(
@@ -27,6 +36,11 @@ actions!(
         ToggleCaseSensitive,
         ToggleIncludeIgnored,
         ToggleRegex,
+        ToggleStructural,
+        ToggleComments,
+        ToggleStrings,
+        ToggleIdentifiers,
+        ToggleCode,
         ToggleReplace,
         ToggleSelection,
         SelectNextMatch,
@@ -41,22 +55,37 @@ actions!(
 
 bitflags! {
     #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
-    pub struct SearchOptions: u8 {
+    pub struct SearchOptions: u16 {
         const NONE = 0b000;
         const WHOLE_WORD = 0b001;
         const CASE_SENSITIVE = 0b010;
         const INCLUDE_IGNORED = 0b100;
         const REGEX = 0b1000;
+        const STRUCTURAL = 0b10000;
+        const COMMENTS = 0b100000;
+        const STRINGS = 0b1000000;
+        const IDENTIFIERS = 0b10000000;
+        const CODE = 0b100000000;
     }
 }
 
 impl SearchOptions {
+    /// The options matching text, which structural queries match syntax nodes without.
+    pub const TEXT_MATCHING: Self = Self::WHOLE_WORD
+        .union(Self::CASE_SENSITIVE)
+        .union(Self::REGEX);
+
     pub fn label(&self) -> &'static str {
         match *self {
             SearchOptions::WHOLE_WORD => "Match Whole Words",
             SearchOptions::CASE_SENSITIVE => "Match Case Sensitively",
             SearchOptions::INCLUDE_IGNORED => "Also search files ignored by configuration",
             SearchOptions::REGEX => "Use Regular Expressions",
+            SearchOptions::STRUCTURAL => "Match Syntax Structurally ($NAME Matches Any Node)",
+            SearchOptions::COMMENTS => "Search in Comments",
+            SearchOptions::STRINGS => "Search in Strings",
+            SearchOptions::IDENTIFIERS => "Search in Identifiers",
+            SearchOptions::CODE => "Search in Code Outside of Comments",
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -67,6 +96,11 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => ui::IconName::CaseSensitive,
             SearchOptions::INCLUDE_IGNORED => ui::IconName::Sliders,
             SearchOptions::REGEX => ui::IconName::Regex,
+            SearchOptions::STRUCTURAL => ui::IconName::ListTree,
+            SearchOptions::COMMENTS => ui::IconName::MessageCircle,
+            SearchOptions::STRINGS => ui::IconName::Quote,
+            SearchOptions::IDENTIFIERS => ui::IconName::AtSign,
+            SearchOptions::CODE => ui::IconName::Code,
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -77,6 +111,11 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => Box::new(ToggleCaseSensitive),
             SearchOptions::INCLUDE_IGNORED => Box::new(ToggleIncludeIgnored),
             SearchOptions::REGEX => Box::new(ToggleRegex),
+            SearchOptions::STRUCTURAL => Box::new(ToggleStructural),
+            SearchOptions::COMMENTS => Box::new(ToggleComments),
+            SearchOptions::STRINGS => Box::new(ToggleStrings),
+            SearchOptions::IDENTIFIERS => Box::new(ToggleIdentifiers),
+            SearchOptions::CODE => Box::new(ToggleCode),
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -91,9 +130,43 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::STRUCTURAL, query.is_structural());
+        let syntax_kinds = query.syntax_kinds();
+        options.set(
+            SearchOptions::COMMENTS,
+            syntax_kinds.contains(SyntaxKinds::COMMENTS),
+        );
+        options.set(
+            SearchOptions::STRINGS,
+            syntax_kinds.contains(SyntaxKinds::STRINGS),
+        );
+        options.set(
+            SearchOptions::IDENTIFIERS,
+            syntax_kinds.contains(SyntaxKinds::IDENTIFIERS),
+        );
+        options.set(
+            SearchOptions::CODE,
+            syntax_kinds.contains(SyntaxKinds::CODE),
+        );
         options
     }
 
+    /// The kinds of syntax that matches are restricted to, if any.
+    pub fn syntax_kinds(&self) -> SyntaxKinds {
+        let mut syntax_kinds = SyntaxKinds::empty();
+        syntax_kinds.set(
+            SyntaxKinds::COMMENTS,
+            self.contains(SearchOptions::COMMENTS),
+        );
+        syntax_kinds.set(SyntaxKinds::STRINGS, self.contains(SearchOptions::STRINGS));
+        syntax_kinds.set(
+            SyntaxKinds::IDENTIFIERS,
+            self.contains(SearchOptions::IDENTIFIERS),
+        );
+        syntax_kinds.set(SyntaxKinds::CODE, self.contains(SearchOptions::CODE));
+        syntax_kinds
+    }
+
     pub fn from_settings(settings: &SearchSettings) -> SearchOptions {
         let mut options = SearchOptions::NONE;
         options.set(SearchOptions::WHOLE_WORD, settings.whole_word);
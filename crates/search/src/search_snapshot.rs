@@ -0,0 +1,313 @@
+use std::{
+    any::{Any, TypeId},
+    ops::Range,
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use editor::{Editor, EditorEvent, MultiBuffer, MAX_TAB_TITLE_LEN};
+use gpui::{
+    AnyView, AppContext, AsyncWindowContext, EntityId, EventEmitter, FocusHandle, FocusableView,
+    IntoElement, Model, Render, SharedString, Task, View, ViewContext, VisualContext, WeakView,
+    WindowContext,
+};
+use language::{Anchor, Bias, Buffer, Point, ToPoint as _};
+use project::Project;
+use ui::prelude::*;
+use util::ResultExt;
+use workspace::{
+    item::{BreadcrumbText, Item, ItemEvent, ItemHandle, SerializableItem},
+    searchable::SearchableItemHandle,
+    ItemId, ItemNavHistory, ToolbarItemLocation, Workspace, WorkspaceId,
+};
+
+use crate::persistence::{SerializedMatch, DB};
+
+const SEARCH_SNAPSHOT_KIND: &str = "SearchSnapshot";
+
+/// A frozen set of project search results, which isn't updated when the files change and is
+/// restored when the workspace is reopened.
+pub struct SearchSnapshot {
+    title: SharedString,
+    project: Model<Project>,
+    editor: View<Editor>,
+    matches: Vec<(Model<Buffer>, Vec<Range<Anchor>>)>,
+}
+
+impl SearchSnapshot {
+    pub fn new(
+        title: SharedString,
+        matches: Vec<(Model<Buffer>, Vec<Range<Anchor>>)>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let capability = project.read(cx).capability();
+        let excerpts = cx.new_model(|_| MultiBuffer::new(capability));
+        let editor = cx.new_view(|cx| {
+            let mut editor =
+                Editor::for_multibuffer(excerpts.clone(), Some(project.clone()), true, cx);
+            editor.set_searchable(false);
+            editor
+        });
+        cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+            cx.emit(event.clone())
+        })
+        .detach();
+
+        let match_ranges = excerpts.update(cx, |excerpts, cx| {
+            excerpts.push_multiple_excerpts_with_context_lines(
+                matches.clone(),
+                editor::DEFAULT_MULTIBUFFER_CONTEXT,
+                cx,
+            )
+        });
+        let editor_handle = editor.downgrade();
+        cx.spawn(|_, mut cx| async move {
+            let match_ranges = match_ranges.await;
+            editor_handle.update(&mut cx, |editor, cx| {
+                editor.highlight_background::<Self>(
+                    &match_ranges,
+                    |theme| theme.search_match_background,
+                    cx,
+                );
+            })
+        })
+        .detach_and_log_err(cx);
+
+        Self {
+            title,
+            project,
+            editor,
+            matches,
+        }
+    }
+
+    fn serialized_matches(&self, cx: &AppContext) -> Vec<SerializedMatch> {
+        let mut serialized = Vec::new();
+        for (buffer, ranges) in &self.matches {
+            let buffer = buffer.read(cx);
+            let Some(abs_path) = buffer
+                .project_path(cx)
+                .and_then(|project_path| self.project.read(cx).absolute_path(&project_path, cx))
+            else {
+                continue;
+            };
+            serialized.extend(ranges.iter().map(|range| SerializedMatch {
+                abs_path: abs_path.clone(),
+                start: range.start.to_point(buffer),
+                end: range.end.to_point(buffer),
+            }));
+        }
+        serialized
+    }
+
+    /// Opens the buffers of the serialized matches, skipping the files that no longer exist.
+    async fn load_matches(
+        project: &Model<Project>,
+        matches: Vec<SerializedMatch>,
+        cx: &mut AsyncWindowContext,
+    ) -> Result<Vec<(Model<Buffer>, Vec<Range<Anchor>>)>> {
+        // Matches are serialized grouped by file.
+        let mut ranges_by_path = Vec::<(PathBuf, Vec<Range<Point>>)>::new();
+        for mat in matches {
+            match ranges_by_path.last_mut() {
+                Some((abs_path, ranges)) if *abs_path == mat.abs_path => {
+                    ranges.push(mat.start..mat.end)
+                }
+                _ => ranges_by_path.push((mat.abs_path, vec![mat.start..mat.end])),
+            }
+        }
+
+        let mut loaded = Vec::new();
+        for (abs_path, ranges) in ranges_by_path {
+            let Some(open_buffer) = project.update(cx, |project, cx| {
+                let project_path = project.find_project_path(&abs_path, cx)?;
+                Some(project.open_buffer(project_path, cx))
+            })?
+            else {
+                continue;
+            };
+            let Some(buffer) = open_buffer.await.log_err() else {
+                continue;
+            };
+            let ranges = buffer.read_with(cx, |buffer, _| {
+                ranges
+                    .into_iter()
+                    .map(|range| {
+                        let start = buffer.clip_point(range.start, Bias::Left);
+                        let end = buffer.clip_point(range.end, Bias::Right);
+                        buffer.anchor_before(start)..buffer.anchor_after(end)
+                    })
+                    .collect()
+            })?;
+            loaded.push((buffer, ranges));
+        }
+        Ok(loaded)
+    }
+}
+
+impl EventEmitter<EditorEvent> for SearchSnapshot {}
+
+impl FocusableView for SearchSnapshot {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Render for SearchSnapshot {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        div().size_full().child(self.editor.clone())
+    }
+}
+
+impl Item for SearchSnapshot {
+    type Event = EditorEvent;
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, cx| editor.deactivated(cx));
+    }
+
+    fn navigate(&mut self, data: Box<dyn Any>, cx: &mut ViewContext<Self>) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, cx))
+    }
+
+    fn tab_tooltip_text(&self, _: &AppContext) -> Option<SharedString> {
+        Some(format!("Search Results Snapshot: {}", self.title).into())
+    }
+
+    fn tab_icon(&self, _: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::Pin))
+    }
+
+    fn tab_content_text(&self, _: &WindowContext) -> Option<SharedString> {
+        Some(util::truncate_and_trailoff(&self.title, MAX_TAB_TITLE_LEN).into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("search snapshot")
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(EntityId, &dyn project::ProjectItem),
+    ) {
+        self.editor.for_each_project_item(cx, f)
+    }
+
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        false
+    }
+
+    fn set_nav_history(&mut self, nav_history: ItemNavHistory, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>>
+    where
+        Self: Sized,
+    {
+        Some(cx.new_view(|cx| {
+            Self::new(
+                self.title.clone(),
+                self.matches.clone(),
+                self.project.clone(),
+                cx,
+            )
+        }))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn as_searchable(&self, _: &View<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn breadcrumb_location(&self, _: &AppContext) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, theme: &theme::Theme, cx: &AppContext) -> Option<Vec<BreadcrumbText>> {
+        self.editor.breadcrumbs(theme, cx)
+    }
+
+    fn added_to_workspace(&mut self, workspace: &mut Workspace, cx: &mut ViewContext<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.added_to_workspace(workspace, cx));
+    }
+}
+
+impl SerializableItem for SearchSnapshot {
+    fn serialized_item_kind() -> &'static str {
+        SEARCH_SNAPSHOT_KIND
+    }
+
+    fn deserialize(
+        project: Model<Project>,
+        _workspace: WeakView<Workspace>,
+        workspace_id: WorkspaceId,
+        item_id: ItemId,
+        cx: &mut WindowContext,
+    ) -> Task<Result<View<Self>>> {
+        cx.spawn(|mut cx| async move {
+            let title = DB
+                .get_snapshot_title(item_id, workspace_id)?
+                .ok_or_else(|| anyhow::anyhow!("No search snapshot found"))?;
+            let matches = DB.get_snapshot_matches(item_id, workspace_id)?;
+            let matches = Self::load_matches(&project, matches, &mut cx).await?;
+            cx.update(|cx| cx.new_view(|cx| Self::new(title.into(), matches, project, cx)))
+        })
+    }
+
+    fn cleanup(
+        workspace_id: WorkspaceId,
+        alive_items: Vec<ItemId>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<()>> {
+        cx.spawn(|_| DB.delete_unloaded_snapshots(workspace_id, alive_items))
+    }
+
+    fn serialize(
+        &mut self,
+        workspace: &mut Workspace,
+        item_id: ItemId,
+        _closing: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Result<()>>> {
+        let workspace_id = workspace.database_id()?;
+        let title = self.title.to_string();
+        let matches = self.serialized_matches(cx);
+        Some(cx.background_executor().spawn(async move {
+            DB.save_snapshot(item_id, workspace_id, title, matches)
+                .await
+        }))
+    }
+
+    fn should_serialize(&self, _: &Self::Event) -> bool {
+        false
+    }
+}
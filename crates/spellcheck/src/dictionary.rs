@@ -0,0 +1,609 @@
+//! A reader for Hunspell dictionaries, i.e. an `.aff` file describing the affix rules of a
+//! language together with a `.dic` file listing its stems.
+//!
+//! Only the parts of the format that are needed to check and suggest single words are supported:
+//! prefixes and suffixes (including their cross products), flag aliases, forbidden words, stems
+//! that need an affix, and the `TRY` and `REP` tables used for suggestions. Compounding and
+//! morphological analysis are ignored.
+
+use std::cmp;
+
+use anyhow::{anyhow, Result};
+use collections::{HashMap, HashSet};
+
+/// The maximum number of suggestions offered for a misspelled word.
+const MAX_SUGGESTIONS: usize = 5;
+
+type Flag = u32;
+
+pub struct Dictionary {
+    /// The stems of the dictionary along with the flags of each of their homonyms.
+    words: HashMap<String, Vec<Box<[Flag]>>>,
+    /// The prefix rules, keyed by the text they add to the stem.
+    prefixes: HashMap<String, Vec<Affix>>,
+    /// The suffix rules, keyed by the text they add to the stem.
+    suffixes: HashMap<String, Vec<Affix>>,
+    try_chars: Vec<char>,
+    replacements: Vec<(String, String)>,
+    forbidden_flag: Option<Flag>,
+    need_affix_flag: Option<Flag>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum FlagFormat {
+    /// Every character is a flag.
+    #[default]
+    Single,
+    /// Every pair of characters is a flag.
+    Long,
+    /// Flags are comma-separated numbers.
+    Numeric,
+}
+
+impl FlagFormat {
+    fn parse(self, flags: &str) -> Vec<Flag> {
+        let mut flags = match self {
+            FlagFormat::Single => flags.chars().map(|c| c as Flag).collect::<Vec<_>>(),
+            FlagFormat::Long => flags
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|pair| pair.iter().fold(0, |flag, &c| (flag << 16) | c as Flag))
+                .collect(),
+            FlagFormat::Numeric => flags
+                .split(',')
+                .filter_map(|flag| flag.trim().parse().ok())
+                .collect(),
+        };
+        flags.sort_unstable();
+        flags.dedup();
+        flags
+    }
+
+    fn parse_one(self, flag: &str) -> Option<Flag> {
+        self.parse(flag).first().copied()
+    }
+}
+
+#[derive(Debug)]
+struct Affix {
+    flag: Flag,
+    cross_product: bool,
+    /// The text removed from the stem before adding the affix.
+    strip: String,
+    condition: Condition,
+}
+
+/// The pattern a stem has to match for an affix to apply to it: a sequence of characters,
+/// `.` wildcards and `[...]` or `[^...]` character classes, matched against the start of the
+/// stem for prefixes and its end for suffixes.
+#[derive(Debug, Default)]
+struct Condition(Vec<ConditionPart>);
+
+#[derive(Debug)]
+enum ConditionPart {
+    Any,
+    Char(char),
+    Class { chars: Vec<char>, negated: bool },
+}
+
+impl ConditionPart {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            ConditionPart::Any => true,
+            ConditionPart::Char(expected) => c == *expected,
+            ConditionPart::Class { chars, negated } => chars.contains(&c) != *negated,
+        }
+    }
+}
+
+impl Condition {
+    fn parse(condition: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut chars = condition.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => parts.push(ConditionPart::Any),
+                '[' => {
+                    let mut class = Vec::new();
+                    let mut negated = false;
+                    for c in chars.by_ref() {
+                        match c {
+                            ']' => break,
+                            '^' if class.is_empty() && !negated => negated = true,
+                            c => class.push(c),
+                        }
+                    }
+                    parts.push(ConditionPart::Class {
+                        chars: class,
+                        negated,
+                    });
+                }
+                c => parts.push(ConditionPart::Char(c)),
+            }
+        }
+        Self(parts)
+    }
+
+    fn matches_start(&self, stem: &str) -> bool {
+        let mut chars = stem.chars();
+        self.0
+            .iter()
+            .all(|part| chars.next().map_or(false, |c| part.matches(c)))
+    }
+
+    fn matches_end(&self, stem: &str) -> bool {
+        let mut chars = stem.chars().rev();
+        self.0
+            .iter()
+            .rev()
+            .all(|part| chars.next().map_or(false, |c| part.matches(c)))
+    }
+}
+
+/// Decodes the contents of a dictionary file, using the encoding declared by the `SET`
+/// directive of the affix file.
+pub fn decode(bytes: &[u8], encoding: &str) -> String {
+    match encoding.to_ascii_uppercase().as_str() {
+        "ISO8859-1" | "ISO-8859-1" | "ISO8859-15" | "ISO-8859-15" => {
+            bytes.iter().map(|&byte| byte as char).collect()
+        }
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// Returns the encoding declared by the `SET` directive of an affix file, if any.
+pub fn affix_file_encoding(aff: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(aff).lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        (fields.next() == Some("SET")).then(|| fields.next().map(str::to_string))?
+    })
+}
+
+/// The number of entries still expected for a table directive such as `REP` or `PFX`.
+struct PendingTable {
+    directive: String,
+    flag: Option<Flag>,
+    cross_product: bool,
+    remaining: usize,
+}
+
+impl Dictionary {
+    pub fn parse(aff: &str, dic: &str) -> Result<Self> {
+        let mut flag_format = FlagFormat::default();
+        let mut aliases = Vec::<Vec<Flag>>::new();
+        let mut dictionary = Dictionary {
+            words: HashMap::default(),
+            prefixes: HashMap::default(),
+            suffixes: HashMap::default(),
+            try_chars: Vec::new(),
+            replacements: Vec::new(),
+            forbidden_flag: None,
+            need_affix_flag: None,
+        };
+
+        let mut pending: Option<PendingTable> = None;
+        for line in aff.lines() {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let Some(&directive) = fields.first() else {
+                continue;
+            };
+            if directive.starts_with('#') {
+                continue;
+            }
+
+            if let Some(table) = pending.as_mut().filter(|table| {
+                table.directive == directive
+                    && table.remaining > 0
+                    && (table.flag.is_none()
+                        || fields.get(1).and_then(|flag| flag_format.parse_one(flag)) == table.flag)
+            }) {
+                table.remaining -= 1;
+                match directive {
+                    "PFX" | "SFX" => {
+                        let (Some(flag), Some(strip), Some(add)) =
+                            (table.flag, fields.get(2), fields.get(3))
+                        else {
+                            continue;
+                        };
+                        // Continuation flags after the added text are not supported.
+                        let add = add.split('/').next().unwrap_or_default();
+                        let affix = Affix {
+                            flag,
+                            cross_product: table.cross_product,
+                            strip: if *strip == "0" { "" } else { strip }.to_string(),
+                            condition: Condition::parse(fields.get(4).copied().unwrap_or(".")),
+                        };
+                        let add = if add == "0" { "" } else { add }.to_string();
+                        if directive == "PFX" {
+                            dictionary.prefixes.entry(add).or_default().push(affix);
+                        } else {
+                            dictionary.suffixes.entry(add).or_default().push(affix);
+                        }
+                    }
+                    "REP" => {
+                        if let (Some(from), Some(to)) = (fields.get(1), fields.get(2)) {
+                            let from = from.trim_start_matches('^').trim_end_matches('$');
+                            let to = to.replace('_', " ");
+                            if !from.is_empty() {
+                                dictionary.replacements.push((from.replace('_', " "), to));
+                            }
+                        }
+                    }
+                    "AF" => {
+                        aliases.push(flag_format.parse(fields.get(1).copied().unwrap_or("")));
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            match directive {
+                "FLAG" => {
+                    flag_format = match fields.get(1).copied() {
+                        Some("long") => FlagFormat::Long,
+                        Some("num") => FlagFormat::Numeric,
+                        _ => FlagFormat::Single,
+                    }
+                }
+                "TRY" => {
+                    dictionary.try_chars = fields.get(1).unwrap_or(&"").chars().collect();
+                }
+                "FORBIDDENWORD" => {
+                    dictionary.forbidden_flag =
+                        fields.get(1).and_then(|flag| flag_format.parse_one(flag));
+                }
+                "NEEDAFFIX" => {
+                    dictionary.need_affix_flag =
+                        fields.get(1).and_then(|flag| flag_format.parse_one(flag));
+                }
+                "PFX" | "SFX" => {
+                    let flag = fields.get(1).and_then(|flag| flag_format.parse_one(flag));
+                    let count = fields.get(3).and_then(|count| count.parse().ok());
+                    if let (Some(flag), Some(count)) = (flag, count) {
+                        pending = Some(PendingTable {
+                            directive: directive.to_string(),
+                            flag: Some(flag),
+                            cross_product: fields.get(2) == Some(&"Y"),
+                            remaining: count,
+                        });
+                    }
+                }
+                "REP" | "AF" => {
+                    if let Some(count) = fields.get(1).and_then(|count| count.parse().ok()) {
+                        pending = Some(PendingTable {
+                            directive: directive.to_string(),
+                            flag: None,
+                            cross_product: false,
+                            remaining: count,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut lines = dic.lines();
+        let word_count = lines
+            .next()
+            .and_then(|line| line.trim().parse::<usize>().ok())
+            .ok_or_else(|| anyhow!("dictionary doesn't start with a word count"))?;
+        dictionary.words.reserve(word_count);
+        for line in lines {
+            // Morphological fields follow the entry after whitespace.
+            let Some(entry) = line.split_whitespace().next() else {
+                continue;
+            };
+            let (word, flags) = match entry.split_once('/') {
+                Some((word, flags)) if !aliases.is_empty() => {
+                    let alias = flags
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|ix| aliases.get(ix - 1));
+                    (word, alias.cloned().unwrap_or_default())
+                }
+                Some((word, flags)) => (word, flag_format.parse(flags)),
+                None => (entry, Vec::new()),
+            };
+            if !word.is_empty() {
+                dictionary
+                    .words
+                    .entry(word.to_string())
+                    .or_default()
+                    .push(flags.into());
+            }
+        }
+
+        Ok(dictionary)
+    }
+
+    /// Returns whether the word is spelled correctly, also accepting lowercase words in
+    /// capitalized or uppercase form.
+    pub fn check(&self, word: &str) -> bool {
+        case_variants(word)
+            .iter()
+            .any(|variant| self.check_exact(variant))
+    }
+
+    /// Returns the words at an edit distance of one from the given word that are spelled
+    /// correctly according to `is_correct`, starting with the replacements of the `REP` table.
+    pub fn suggestions(&self, word: &str, is_correct: impl Fn(&str) -> bool) -> Vec<String> {
+        let capitalized = is_capitalized(word);
+        let lowercase_word = if capitalized {
+            word.to_lowercase()
+        } else {
+            word.to_string()
+        };
+        let word = lowercase_word.as_str();
+
+        let mut candidates = Vec::new();
+        for (from, to) in &self.replacements {
+            for (ix, _) in word.match_indices(from.as_str()) {
+                candidates.push(format!("{}{}{}", &word[..ix], to, &word[ix + from.len()..]));
+            }
+        }
+
+        let chars = word.chars().collect::<Vec<_>>();
+        let try_chars = if self.try_chars.is_empty() {
+            ('a'..='z').collect()
+        } else {
+            self.try_chars.clone()
+        };
+        for ix in 1..chars.len() {
+            let mut candidate = chars.clone();
+            candidate.swap(ix - 1, ix);
+            candidates.push(candidate.into_iter().collect());
+        }
+        for ix in 0..chars.len() {
+            for &c in &try_chars {
+                if c != chars[ix] {
+                    let mut candidate = chars.clone();
+                    candidate[ix] = c;
+                    candidates.push(candidate.into_iter().collect());
+                }
+            }
+        }
+        for ix in 0..chars.len() {
+            let mut candidate = chars.clone();
+            candidate.remove(ix);
+            candidates.push(candidate.into_iter().collect());
+        }
+        for ix in 0..=chars.len() {
+            for &c in &try_chars {
+                let mut candidate = chars.clone();
+                candidate.insert(ix, c);
+                candidates.push(candidate.into_iter().collect());
+            }
+        }
+
+        let mut seen = HashSet::default();
+        let mut suggestions = candidates
+            .into_iter()
+            .filter(|candidate| {
+                candidate != word
+                    && !candidate.is_empty()
+                    && seen.insert(candidate.clone())
+                    && candidate.split(' ').all(&is_correct)
+            })
+            .take(MAX_SUGGESTIONS)
+            .collect::<Vec<_>>();
+
+        // Words missing a space between them.
+        for (ix, _) in word.char_indices().skip(1) {
+            if suggestions.len() >= MAX_SUGGESTIONS {
+                break;
+            }
+            let (left, right) = word.split_at(ix);
+            if cmp::min(left.len(), right.len()) > 1 && is_correct(left) && is_correct(right) {
+                suggestions.push(format!("{left} {right}"));
+            }
+        }
+
+        if capitalized {
+            for suggestion in &mut suggestions {
+                *suggestion = capitalize(suggestion);
+            }
+        }
+        suggestions
+    }
+
+    fn check_exact(&self, word: &str) -> bool {
+        // Forbidden words aren't accepted even when they can be derived from another stem.
+        let forbidden = self.forbidden_flag.map_or(false, |flag| {
+            self.words.get(word).map_or(false, |homonyms| {
+                homonyms
+                    .iter()
+                    .any(|flags| flags.binary_search(&flag).is_ok())
+            })
+        });
+        !forbidden
+            && (self.has_stem(word, |flags| {
+                self.need_affix_flag
+                    .map_or(true, |flag| flags.binary_search(&flag).is_err())
+            }) || self.check_suffixed(word, None)
+                || self.check_prefixed(word))
+    }
+
+    fn check_prefixed(&self, word: &str) -> bool {
+        word.char_indices().any(|(ix, _)| {
+            let Some(prefixes) = self.prefixes.get(&word[..ix]) else {
+                return false;
+            };
+            prefixes.iter().any(|prefix| {
+                let stem = format!("{}{}", prefix.strip, &word[ix..]);
+                prefix.condition.matches_start(&stem)
+                    && (self.has_stem(&stem, |flags| flags.binary_search(&prefix.flag).is_ok())
+                        || (prefix.cross_product && self.check_suffixed(&stem, Some(prefix))))
+            })
+        })
+    }
+
+    fn check_suffixed(&self, word: &str, prefix: Option<&Affix>) -> bool {
+        word.char_indices()
+            .skip(1)
+            .map(|(ix, _)| ix)
+            .chain([word.len()])
+            .any(|ix| {
+                let Some(suffixes) = self.suffixes.get(&word[ix..]) else {
+                    return false;
+                };
+                suffixes.iter().any(|suffix| {
+                    if prefix.is_some() && !suffix.cross_product {
+                        return false;
+                    }
+                    let stem = format!("{}{}", &word[..ix], suffix.strip);
+                    suffix.condition.matches_end(&stem)
+                        && self.has_stem(&stem, |flags| {
+                            flags.binary_search(&suffix.flag).is_ok()
+                                && prefix.map_or(true, |prefix| {
+                                    flags.binary_search(&prefix.flag).is_ok()
+                                })
+                        })
+                })
+            })
+    }
+
+    fn has_stem(&self, stem: &str, mut predicate: impl FnMut(&[Flag]) -> bool) -> bool {
+        self.words.get(stem).map_or(false, |homonyms| {
+            homonyms.iter().any(|flags| {
+                self.forbidden_flag
+                    .map_or(true, |flag| flags.binary_search(&flag).is_err())
+                    && predicate(flags)
+            })
+        })
+    }
+}
+
+/// Returns the forms of the word to look up, accepting lowercase words in capitalized or
+/// uppercase form, and capitalized words in uppercase form.
+pub fn case_variants(word: &str) -> Vec<String> {
+    let mut variants = vec![word.to_string()];
+    if is_capitalized(word) {
+        variants.push(word.to_lowercase());
+    } else if word.chars().any(char::is_uppercase) && !word.chars().any(char::is_lowercase) {
+        let lowercase = word.to_lowercase();
+        variants.push(capitalize(&lowercase));
+        variants.push(lowercase);
+    }
+    variants
+}
+
+fn is_capitalized(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars.next().map_or(false, char::is_uppercase) && !chars.any(char::is_uppercase)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_check() {
+        let dictionary = Dictionary::parse(
+            indoc! {"
+                SET UTF-8
+                TRY esianrtolcdugmphbyfvkwz
+                FORBIDDENWORD !
+
+                # Prefixes
+                PFX A Y 1
+                PFX A   0     re         .
+
+                SFX D Y 3
+                SFX D   0     d          e
+                SFX D   y     ied        [^aeiou]y
+                SFX D   0     ed         [^ey]
+
+                SFX S N 1
+                SFX S   0     s          .
+            "},
+            indoc! {"
+                5
+                create/AD
+                carry/D
+                paint/ADS
+                word/S
+                words/!
+            "},
+        )
+        .unwrap();
+
+        for word in [
+            "create",
+            "created",
+            "recreate",
+            "recreated",
+            "carried",
+            "painted",
+            "repaint",
+            "paints",
+            "Created",
+            "PAINTED",
+        ] {
+            assert!(dictionary.check(word), "{word} should be correct");
+        }
+        for word in [
+            "creat", "carryed", "worded", "words", "repaints", "cREATE", "Recreatd",
+        ] {
+            assert!(!dictionary.check(word), "{word} should be misspelled");
+        }
+    }
+
+    #[test]
+    fn test_flag_formats() {
+        let dictionary = Dictionary::parse(
+            indoc! {"
+                FLAG long
+                SFX Aa Y 1
+                SFX Aa 0 ing .
+            "},
+            "2\nsing\nwalk/AaBb\n",
+        )
+        .unwrap();
+        assert!(dictionary.check("walking"));
+        assert!(!dictionary.check("singing"));
+
+        let dictionary = Dictionary::parse(
+            indoc! {"
+                FLAG num
+                AF 1
+                AF 7,12
+                SFX 12 Y 1
+                SFX 12 0 s .
+            "},
+            "1\ncat/1\n",
+        )
+        .unwrap();
+        assert!(dictionary.check("cats"));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let dictionary = Dictionary::parse(
+            indoc! {"
+                TRY aehlnoptw
+                REP 1
+                REP f ph
+            "},
+            "6\nhello\nwhole\nphone\nphoto\nword\nworld\n",
+        )
+        .unwrap();
+        let suggest = |word: &str| dictionary.suggestions(word, |word| dictionary.check(word));
+
+        assert_eq!(suggest("fone"), ["phone"]);
+        assert_eq!(suggest("hlelo"), ["hello"]);
+        assert_eq!(suggest("Wrold"), ["World"]);
+        assert_eq!(suggest("wordl"), ["world", "word"]);
+        assert_eq!(suggest("helloworld"), ["hello world"]);
+        assert!(suggest("xyzzy").is_empty());
+    }
+}
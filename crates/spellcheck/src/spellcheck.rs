@@ -0,0 +1,950 @@
+mod dictionary;
+
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use collections::{HashMap, HashSet};
+use dictionary::{affix_file_encoding, case_variants, decode, Dictionary};
+use editor::{
+    Anchor, Editor, EditorEvent, EditorMode, ExcerptId, MultiBufferSnapshot, ToPoint as _,
+};
+use fs::Fs;
+use gpui::{
+    px, AppContext, BackgroundExecutor, Context as _, Global, HighlightStyle, IntoElement, Model,
+    ModelContext, Render, Subscription, Task, UnderlineStyle, View, ViewContext,
+    VisualContext as _, WeakView, WindowContext,
+};
+use language::{Buffer, BufferId, BufferSnapshot, Language, OffsetRangeExt as _, Point};
+use parking_lot::Mutex;
+use project::{CodeAction, Project, ProjectTransaction};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources, SettingsStore};
+use theme::ActiveTheme as _;
+use util::ResultExt as _;
+
+const CHECK_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The number of lines assumed to be visible in an editor that hasn't been laid out yet.
+const DEFAULT_VISIBLE_LINE_COUNT: u32 = 50;
+
+/// The name of the file listing the additional words accepted in a project, or by the user when
+/// it's in the config directory.
+const WORD_LIST_FILE_NAME: &str = "dictionary.txt";
+
+/// Languages whose text is checked as a whole, rather than only their comments and strings.
+const PROSE_LANGUAGES: &[&str] = &["Markdown", "Plain Text"];
+
+/// Words shorter than this are never flagged, as they're mostly abbreviations.
+const MIN_WORD_LEN: usize = 3;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SpellcheckSettings {
+    pub enabled: bool,
+    pub dictionaries: Vec<String>,
+    pub dictionary_directories: Vec<String>,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SpellcheckSettingsContent {
+    /// Whether to underline misspelled words in comments, strings and prose files.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The names of the Hunspell dictionaries to check words against, e.g. `en_US` for the
+    /// `en_US.aff` and `en_US.dic` files.
+    ///
+    /// Default: ["en_US"]
+    pub dictionaries: Option<Vec<String>>,
+    /// Additional directories to look up dictionaries in, before the `dictionaries` directory
+    /// of the config directory and the system's Hunspell directories.
+    ///
+    /// Default: []
+    pub dictionary_directories: Option<Vec<String>>,
+}
+
+impl Settings for SpellcheckSettings {
+    const KEY: Option<&'static str> = Some("spellcheck");
+
+    type FileContent = SpellcheckSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(fs: Arc<dyn Fs>, cx: &mut AppContext) {
+    SpellcheckSettings::register(cx);
+
+    let spellchecker = cx.new_model(|cx| Spellchecker::new(fs, cx));
+    cx.set_global(GlobalSpellchecker(spellchecker));
+
+    cx.observe_new_views(|editor: &mut Editor, cx: &mut ViewContext<Editor>| {
+        if editor.mode() != EditorMode::Full {
+            return;
+        }
+
+        let spellcheck = EditorSpellcheck::new(cx);
+        editor.push_code_action_provider(
+            Rc::new(SpellcheckCodeActionProvider {
+                spellcheck: spellcheck.downgrade(),
+            }),
+            cx,
+        );
+        editor.register_addon(SpellcheckAddon {
+            _spellcheck: spellcheck,
+        });
+    })
+    .detach();
+}
+
+struct GlobalSpellchecker(Model<Spellchecker>);
+
+impl Global for GlobalSpellchecker {}
+
+/// Holds the dictionaries and word lists shared by all editors, reloading them when the
+/// settings change.
+struct Spellchecker {
+    fs: Arc<dyn Fs>,
+    dictionaries: Arc<[Dictionary]>,
+    /// The dictionary names and directories that `dictionaries` was loaded from, if enabled.
+    loaded_settings: Option<(Vec<String>, Vec<String>)>,
+    word_lists: HashMap<PathBuf, Arc<HashSet<String>>>,
+    load_dictionaries_task: Task<()>,
+    _settings_subscription: Subscription,
+}
+
+impl Spellchecker {
+    fn new(fs: Arc<dyn Fs>, cx: &mut ModelContext<Self>) -> Self {
+        let mut this = Self {
+            fs,
+            dictionaries: Arc::default(),
+            loaded_settings: None,
+            word_lists: HashMap::default(),
+            load_dictionaries_task: Task::ready(()),
+            _settings_subscription: cx
+                .observe_global::<SettingsStore>(|this, cx| this.load_dictionaries(cx)),
+        };
+        this.load_dictionaries(cx);
+        this
+    }
+
+    fn global(cx: &AppContext) -> Model<Self> {
+        cx.global::<GlobalSpellchecker>().0.clone()
+    }
+
+    fn load_dictionaries(&mut self, cx: &mut ModelContext<Self>) {
+        let settings = SpellcheckSettings::get_global(cx);
+        let loaded_settings = settings.enabled.then(|| {
+            (
+                settings.dictionaries.clone(),
+                settings.dictionary_directories.clone(),
+            )
+        });
+        if loaded_settings == self.loaded_settings {
+            return;
+        }
+        self.loaded_settings = loaded_settings.clone();
+
+        let Some((names, directories)) = loaded_settings else {
+            self.dictionaries = Arc::default();
+            self.load_dictionaries_task = Task::ready(());
+            cx.notify();
+            return;
+        };
+
+        let fs = self.fs.clone();
+        self.load_dictionaries_task = cx.spawn(|this, mut cx| async move {
+            let directories = dictionary_directories(&directories);
+            let mut dictionaries = Vec::new();
+            for name in &names {
+                match load_dictionary(fs.as_ref(), name, &directories, cx.background_executor())
+                    .await
+                {
+                    Ok(dictionary) => dictionaries.push(dictionary),
+                    Err(error) => log::error!("failed to load dictionary {name:?}: {error:#}"),
+                }
+            }
+            this.update(&mut cx, |this, cx| {
+                this.dictionaries = dictionaries.into();
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    /// Returns a snapshot of the dictionaries and of the word lists that apply to the given
+    /// project, starting to load the word lists that haven't been read yet.
+    fn checker(
+        &mut self,
+        project: Option<&Model<Project>>,
+        cx: &mut ModelContext<Self>,
+    ) -> WordChecker {
+        let mut paths = vec![user_word_list_path()];
+        if let Some(project) = project.filter(|project| project.read(cx).is_local()) {
+            paths.extend(
+                project
+                    .read(cx)
+                    .visible_worktrees(cx)
+                    .filter_map(|worktree| worktree.read(cx).root_dir())
+                    .map(|root_dir| project_word_list_path(&root_dir)),
+            );
+        }
+
+        WordChecker {
+            dictionaries: self.dictionaries.clone(),
+            word_lists: paths
+                .into_iter()
+                .map(|path| self.word_list(path, cx))
+                .collect(),
+        }
+    }
+
+    fn word_list(&mut self, path: PathBuf, cx: &mut ModelContext<Self>) -> Arc<HashSet<String>> {
+        if let Some(words) = self.word_lists.get(&path) {
+            return words.clone();
+        }
+
+        self.word_lists.insert(path.clone(), Arc::default());
+        let fs = self.fs.clone();
+        cx.spawn(|this, mut cx| async move {
+            let Ok(contents) = fs.load(&path).await else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                let words = Arc::make_mut(this.word_lists.entry(path).or_default());
+                words.extend(
+                    contents
+                        .lines()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                        .map(str::to_string),
+                );
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+        Arc::default()
+    }
+
+    fn add_word(
+        &mut self,
+        path: PathBuf,
+        word: String,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        Arc::make_mut(self.word_lists.entry(path.clone()).or_default()).insert(word.clone());
+        cx.notify();
+
+        let fs = self.fs.clone();
+        cx.background_executor().spawn(async move {
+            let mut contents = fs.load(&path).await.unwrap_or_default();
+            if !contents.is_empty() && !contents.ends_with('\n') {
+                contents.push('\n');
+            }
+            contents.push_str(&word);
+            contents.push('\n');
+            if let Some(directory) = path.parent() {
+                fs.create_dir(directory).await?;
+            }
+            fs.atomic_write(path, contents).await
+        })
+    }
+}
+
+fn dictionary_directories(configured: &[String]) -> Vec<PathBuf> {
+    let mut directories = configured
+        .iter()
+        .filter_map(|directory| shellexpand::full(directory).log_err())
+        .map(|directory| PathBuf::from(directory.into_owned()))
+        .collect::<Vec<_>>();
+    directories.push(paths::config_dir().join("dictionaries"));
+    if cfg!(target_os = "macos") {
+        directories.push(util::paths::home_dir().join("Library/Spelling"));
+        directories.push(PathBuf::from("/Library/Spelling"));
+    }
+    directories.push(PathBuf::from("/usr/share/hunspell"));
+    directories.push(PathBuf::from("/usr/share/myspell"));
+    directories
+}
+
+async fn load_dictionary(
+    fs: &dyn Fs,
+    name: &str,
+    directories: &[PathBuf],
+    executor: &BackgroundExecutor,
+) -> Result<Dictionary> {
+    for directory in directories {
+        let aff_path = directory.join(format!("{name}.aff"));
+        let dic_path = directory.join(format!("{name}.dic"));
+        if !fs.is_file(&aff_path).await || !fs.is_file(&dic_path).await {
+            continue;
+        }
+
+        let aff = fs.load_bytes(&aff_path).await?;
+        let dic = fs.load_bytes(&dic_path).await?;
+        return executor
+            .spawn(async move {
+                let encoding = affix_file_encoding(&aff).unwrap_or_else(|| "UTF-8".into());
+                Dictionary::parse(&decode(&aff, &encoding), &decode(&dic, &encoding))
+            })
+            .await;
+    }
+    Err(anyhow!(
+        "no {name}.aff and {name}.dic files in {directories:?}"
+    ))
+}
+
+fn user_word_list_path() -> PathBuf {
+    paths::config_dir().join(WORD_LIST_FILE_NAME)
+}
+
+fn project_word_list_path(root_dir: &Path) -> PathBuf {
+    root_dir
+        .join(paths::local_settings_folder_relative_path())
+        .join(WORD_LIST_FILE_NAME)
+}
+
+/// The dictionaries and word lists that words are checked against, which can be sent to a
+/// background thread.
+#[derive(Clone)]
+struct WordChecker {
+    dictionaries: Arc<[Dictionary]>,
+    word_lists: Vec<Arc<HashSet<String>>>,
+}
+
+impl WordChecker {
+    fn is_enabled(&self) -> bool {
+        !self.dictionaries.is_empty()
+    }
+
+    fn check(&self, word: &str) -> bool {
+        let word = word.replace('’', "'");
+        self.dictionaries
+            .iter()
+            .any(|dictionary| dictionary.check(&word))
+            || case_variants(&word)
+                .iter()
+                .any(|variant| self.word_lists.iter().any(|words| words.contains(variant)))
+    }
+
+    fn suggestions(&self, word: &str) -> Vec<String> {
+        let word = word.replace('’', "'");
+        let mut suggestions = Vec::new();
+        for dictionary in self.dictionaries.iter() {
+            for suggestion in dictionary.suggestions(&word, |candidate| self.check(candidate)) {
+                if !suggestions.contains(&suggestion) {
+                    suggestions.push(suggestion);
+                }
+            }
+        }
+        suggestions
+    }
+}
+
+/// Returns the ranges of the words in `text` that should be spellchecked. Identifiers are split
+/// into their camelCase and snake_case parts, while words containing digits, acronyms, and
+/// anything that looks like a URL, an email address or a path are skipped.
+fn checked_words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    for chunk in runs(text, |c| !c.is_whitespace()) {
+        let chunk_text = &text[chunk.clone()];
+        if chunk_text.contains("://") || chunk_text.contains(['@', '/', '\\']) {
+            continue;
+        }
+
+        for run in runs(chunk_text, |c| c.is_alphanumeric() || is_apostrophe(c)) {
+            let run_text = &chunk_text[run.clone()];
+            if run_text.chars().any(char::is_numeric) {
+                continue;
+            }
+
+            for part in camel_case_parts(run_text) {
+                let part_text = &run_text[part.clone()];
+                let word_text = part_text.trim_start_matches(is_apostrophe);
+                let start = part.start + part_text.len() - word_text.len();
+                let word_text = word_text.trim_end_matches(is_apostrophe);
+                if word_text.chars().count() < MIN_WORD_LEN
+                    || !word_text.chars().any(char::is_lowercase)
+                {
+                    continue;
+                }
+
+                let start = chunk.start + run.start + start;
+                words.push(start..start + word_text.len());
+            }
+        }
+    }
+    words
+}
+
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '’'
+}
+
+/// Returns the maximal ranges of `text` made of characters matching `predicate`.
+fn runs(text: &str, mut predicate: impl FnMut(char) -> bool) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut run_start = None;
+    for (ix, c) in text.char_indices() {
+        match (predicate(c), run_start) {
+            (true, None) => run_start = Some(ix),
+            (false, Some(start)) => {
+                runs.push(start..ix);
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = run_start {
+        runs.push(start..text.len());
+    }
+    runs
+}
+
+/// Splits `text` before every uppercase letter that follows a lowercase one, and before the last
+/// letter of a run of uppercase ones followed by a lowercase one, e.g. `HTTPServer`.
+fn camel_case_parts(text: &str) -> Vec<Range<usize>> {
+    let chars = text.char_indices().collect::<Vec<_>>();
+    let mut parts = Vec::new();
+    let mut part_start = 0;
+    for (ix, &(offset, c)) in chars.iter().enumerate().skip(1) {
+        let previous = chars[ix - 1].1;
+        let next_is_lowercase = chars
+            .get(ix + 1)
+            .map_or(false, |(_, next)| next.is_lowercase());
+        if c.is_uppercase()
+            && (previous.is_lowercase() || (previous.is_uppercase() && next_is_lowercase))
+        {
+            parts.push(part_start..offset);
+            part_start = offset;
+        }
+    }
+    parts.push(part_start..text.len());
+    parts
+}
+
+fn is_prose(language: Option<&Arc<Language>>) -> bool {
+    language.map_or(true, |language| {
+        language.grammar().is_none() || PROSE_LANGUAGES.contains(&language.name().0.as_ref())
+    })
+}
+
+/// Whether the text of a highlight capture is spellchecked in code.
+fn is_checked_capture(name: &str) -> bool {
+    name.starts_with("comment")
+        || (name.starts_with("string")
+            && !name.starts_with("string.escape")
+            && !name.starts_with("string.regex")
+            && !name.starts_with("string.special"))
+}
+
+/// Whether the text of a highlight capture is skipped in prose, e.g. inline code and URLs.
+fn is_skipped_prose_capture(name: &str) -> bool {
+    name.starts_with("text.literal") || name.starts_with("link_uri")
+}
+
+/// Returns the ranges of the buffer that are spellchecked within the given range.
+fn checked_ranges(buffer: &BufferSnapshot, range: Range<usize>) -> Vec<Range<usize>> {
+    if !is_prose(buffer.language()) {
+        return buffer.highlight_capture_ranges(range, is_checked_capture);
+    }
+
+    let mut ranges = Vec::new();
+    let mut start = range.start;
+    for skipped in buffer.highlight_capture_ranges(range.clone(), is_skipped_prose_capture) {
+        if skipped.start > start {
+            ranges.push(start..skipped.start);
+        }
+        start = start.max(skipped.end);
+    }
+    if start < range.end {
+        ranges.push(start..range.end);
+    }
+    ranges
+}
+
+#[derive(Clone)]
+struct Misspelling {
+    range: Range<text::Anchor>,
+    word: String,
+}
+
+/// The misspellings found in the checked ranges of a buffer, which are valid until the text or
+/// the syntax of the buffer changes.
+#[derive(Clone)]
+struct BufferCheck {
+    version: clock::Global,
+    non_text_state_update_count: usize,
+    /// The checked ranges, sorted and disjoint.
+    checked_ranges: Vec<Range<usize>>,
+    /// The misspellings in the checked ranges, sorted.
+    misspellings: Vec<Misspelling>,
+}
+
+impl BufferCheck {
+    fn new(buffer: &BufferSnapshot) -> Self {
+        Self {
+            version: buffer.version().clone(),
+            non_text_state_update_count: buffer.non_text_state_update_count(),
+            checked_ranges: Vec::new(),
+            misspellings: Vec::new(),
+        }
+    }
+
+    fn is_valid(&self, buffer: &BufferSnapshot) -> bool {
+        &self.version == buffer.version()
+            && self.non_text_state_update_count == buffer.non_text_state_update_count()
+    }
+
+    /// Returns the parts of the range that haven't been checked.
+    fn unchecked_ranges(&self, range: Range<usize>) -> Vec<Range<usize>> {
+        let mut unchecked = Vec::new();
+        let mut start = range.start;
+        for checked in &self.checked_ranges {
+            if checked.end <= start {
+                continue;
+            }
+            if checked.start >= range.end {
+                break;
+            }
+            if checked.start > start {
+                unchecked.push(start..checked.start);
+            }
+            start = checked.end;
+        }
+        if start < range.end {
+            unchecked.push(start..range.end);
+        }
+        unchecked
+    }
+
+    fn insert_checked_range(&mut self, range: Range<usize>) {
+        let start_ix = self
+            .checked_ranges
+            .partition_point(|checked| checked.end < range.start);
+        let end_ix = self
+            .checked_ranges
+            .partition_point(|checked| checked.start <= range.end);
+        let merged = self.checked_ranges[start_ix..end_ix]
+            .iter()
+            .fold(range, |merged, checked| {
+                merged.start.min(checked.start)..merged.end.max(checked.end)
+            });
+        self.checked_ranges.splice(start_ix..end_ix, [merged]);
+    }
+}
+
+/// Checks the parts of the given excerpt ranges that weren't checked since their buffer last
+/// changed, and returns the checks of the buffers along with the highlights of their
+/// misspellings in all excerpts.
+fn find_misspellings(
+    snapshot: &MultiBufferSnapshot,
+    ranges: &[(ExcerptId, Range<usize>)],
+    checker: &WordChecker,
+    word_cache: &Mutex<HashMap<String, bool>>,
+    mut checks: HashMap<BufferId, Arc<BufferCheck>>,
+) -> (HashMap<BufferId, Arc<BufferCheck>>, Vec<Range<Anchor>>) {
+    let buffers = snapshot
+        .excerpts()
+        .map(|(_, buffer, _)| (buffer.remote_id(), buffer))
+        .collect::<HashMap<_, _>>();
+    checks.retain(|buffer_id, check| {
+        buffers
+            .get(buffer_id)
+            .map_or(false, |buffer| check.is_valid(buffer))
+    });
+
+    let mut word_cache = word_cache.lock();
+    for (excerpt_id, range) in ranges {
+        let Some(buffer) = snapshot.buffer_for_excerpt(*excerpt_id) else {
+            continue;
+        };
+        let check = checks
+            .entry(buffer.remote_id())
+            .or_insert_with(|| Arc::new(BufferCheck::new(buffer)));
+        let unchecked_ranges = check.unchecked_ranges(range.clone());
+        if unchecked_ranges.is_empty() {
+            continue;
+        }
+
+        let check = Arc::make_mut(check);
+        for unchecked_range in unchecked_ranges {
+            for range in checked_ranges(buffer, unchecked_range.clone()) {
+                let text = buffer.text_for_range(range.clone()).collect::<String>();
+                for word_range in checked_words(&text) {
+                    let word = &text[word_range.clone()];
+                    let is_correct = *word_cache
+                        .entry(word.to_string())
+                        .or_insert_with(|| checker.check(word));
+                    if !is_correct {
+                        check.misspellings.push(Misspelling {
+                            range: buffer.anchor_after(range.start + word_range.start)
+                                ..buffer.anchor_before(range.start + word_range.end),
+                            word: word.to_string(),
+                        });
+                    }
+                }
+            }
+            check.insert_checked_range(unchecked_range);
+        }
+        check
+            .misspellings
+            .sort_by(|a, b| a.range.start.cmp(&b.range.start, buffer));
+    }
+    drop(word_cache);
+
+    let mut highlights = Vec::new();
+    for (excerpt_id, buffer, excerpt_range) in snapshot.excerpts() {
+        let Some(check) = checks.get(&buffer.remote_id()) else {
+            continue;
+        };
+        let excerpt_range = excerpt_range.context.to_offset(buffer);
+        for misspelling in &check.misspellings {
+            let range = misspelling.range.to_offset(buffer);
+            if range.start < excerpt_range.start || range.end > excerpt_range.end {
+                continue;
+            }
+            if let Some((start, end)) = snapshot
+                .anchor_in_excerpt(excerpt_id, misspelling.range.start)
+                .zip(snapshot.anchor_in_excerpt(excerpt_id, misspelling.range.end))
+            {
+                highlights.push(start..end);
+            }
+        }
+    }
+    (checks, highlights)
+}
+
+enum MisspellingHighlight {}
+
+/// Checks the spelling of the lines around the visible ones in an editor, and underlines the
+/// misspelled words.
+struct EditorSpellcheck {
+    editor: WeakView<Editor>,
+    buffer_checks: HashMap<BufferId, Arc<BufferCheck>>,
+    /// Whether the words checked so far are correct, until the dictionaries or word lists change.
+    word_cache: Arc<Mutex<HashMap<String, bool>>>,
+    check_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EditorSpellcheck {
+    fn new(cx: &mut ViewContext<Editor>) -> View<Self> {
+        let editor = cx.view().clone();
+        let spellchecker = Spellchecker::global(cx);
+        cx.new_view(|cx| {
+            let subscriptions = vec![
+                cx.subscribe(&editor, |this, _, event: &EditorEvent, cx| match event {
+                    EditorEvent::BufferEdited
+                    | EditorEvent::ExcerptsAdded { .. }
+                    | EditorEvent::ExcerptsRemoved { .. }
+                    | EditorEvent::ExcerptsEdited { .. }
+                    | EditorEvent::ExcerptsExpanded { .. }
+                    | EditorEvent::Reparsed(_)
+                    | EditorEvent::ScrollPositionChanged { .. } => this.schedule_check(cx),
+                    _ => {}
+                }),
+                cx.observe(&spellchecker, |this, _, cx| {
+                    this.buffer_checks.clear();
+                    // A check still running keeps filling the previous cache.
+                    this.word_cache = Arc::default();
+                    this.schedule_check(cx);
+                }),
+            ];
+            let mut this = Self {
+                editor: editor.downgrade(),
+                buffer_checks: HashMap::default(),
+                word_cache: Arc::default(),
+                check_task: Task::ready(()),
+                _subscriptions: subscriptions,
+            };
+            this.schedule_check(cx);
+            this
+        })
+    }
+
+    fn schedule_check(&mut self, cx: &mut ViewContext<Self>) {
+        let editor = self.editor.clone();
+        self.check_task = cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(CHECK_DEBOUNCE).await;
+            let Ok((snapshot, ranges, checker)) = editor.update(&mut cx, |editor, cx| {
+                let project = editor
+                    .workspace()
+                    .map(|workspace| workspace.read(cx).project().clone());
+                let checker = Spellchecker::global(cx).update(cx, |spellchecker, cx| {
+                    spellchecker.checker(project.as_ref(), cx)
+                });
+
+                // Check a screen above and below the visible lines too, so that scrolling a
+                // little doesn't show unchecked text.
+                let multi_buffer = editor.buffer().read(cx);
+                let snapshot = multi_buffer.snapshot(cx);
+                let line_count = editor
+                    .visible_line_count()
+                    .map_or(DEFAULT_VISIBLE_LINE_COUNT, |line_count| {
+                        line_count.ceil() as u32
+                    });
+                let top_row = editor
+                    .scroll_manager
+                    .anchor()
+                    .anchor
+                    .to_point(&snapshot)
+                    .row;
+                let start = Point::new(top_row.saturating_sub(line_count), 0);
+                let end_row = top_row + 2 * line_count;
+                let end = if end_row > snapshot.max_point().row {
+                    snapshot.max_point()
+                } else {
+                    Point::new(end_row, 0)
+                };
+                let ranges = multi_buffer
+                    .range_to_buffer_ranges(start..end, cx)
+                    .into_iter()
+                    .filter(|(buffer, range, _)| {
+                        !range.is_empty() && !buffer.read(cx).is_large_file()
+                    })
+                    .map(|(_, range, excerpt_id)| (excerpt_id, range))
+                    .collect::<Vec<_>>();
+                (snapshot, ranges, checker)
+            }) else {
+                return;
+            };
+            let Ok((checks, word_cache)) = this.update(&mut cx, |this, _| {
+                (this.buffer_checks.clone(), this.word_cache.clone())
+            }) else {
+                return;
+            };
+
+            let (checks, highlights) = if checker.is_enabled() {
+                cx.background_executor()
+                    .spawn(async move {
+                        find_misspellings(&snapshot, &ranges, &checker, &word_cache, checks)
+                    })
+                    .await
+            } else {
+                Default::default()
+            };
+
+            editor
+                .update(&mut cx, |editor, cx| {
+                    if highlights.is_empty() {
+                        editor.clear_highlights::<MisspellingHighlight>(cx);
+                    } else {
+                        let style = HighlightStyle {
+                            underline: Some(UnderlineStyle {
+                                color: Some(cx.theme().status().info),
+                                thickness: px(1.),
+                                wavy: true,
+                            }),
+                            ..HighlightStyle::default()
+                        };
+                        editor.highlight_text::<MisspellingHighlight>(highlights, style, cx);
+                    }
+                })
+                .ok();
+            this.update(&mut cx, |this, _| this.buffer_checks = checks)
+                .ok();
+        });
+    }
+}
+
+impl Render for EditorSpellcheck {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        gpui::Empty
+    }
+}
+
+struct SpellcheckAddon {
+    _spellcheck: View<EditorSpellcheck>,
+}
+
+impl editor::Addon for SpellcheckAddon {
+    fn to_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// What a spellcheck code action does, stored in the `data` of its LSP code action.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SpellcheckAction {
+    Replace { text: String },
+    AddToWordList { word: String, path: PathBuf },
+}
+
+struct SpellcheckCodeActionProvider {
+    spellcheck: WeakView<EditorSpellcheck>,
+}
+
+impl editor::CodeActionProvider for SpellcheckCodeActionProvider {
+    fn code_actions(
+        &self,
+        buffer: &Model<Buffer>,
+        range: Range<text::Anchor>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<Vec<CodeAction>>> {
+        let Some(spellcheck) = self.spellcheck.upgrade() else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let buffer = buffer.read(cx);
+        let snapshot = buffer.snapshot();
+        let range = range.to_offset(&snapshot);
+        let spellcheck = spellcheck.read(cx);
+        let Some((word, range)) = spellcheck
+            .buffer_checks
+            .get(&buffer.remote_id())
+            .filter(|check| check.is_valid(&snapshot))
+            .and_then(|check| {
+                check.misspellings.iter().find_map(|misspelling| {
+                    let misspelling_range = misspelling.range.to_offset(&snapshot);
+                    (misspelling_range.start <= range.end && range.start <= misspelling_range.end)
+                        .then(|| (misspelling.word.clone(), misspelling.range.clone()))
+                })
+            })
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        let project = spellcheck
+            .editor
+            .upgrade()
+            .and_then(|editor| editor.read(cx).workspace())
+            .map(|workspace| workspace.read(cx).project().clone())
+            .filter(|project| project.read(cx).is_local());
+        let project_root = buffer.file().zip(project).and_then(|(file, project)| {
+            project
+                .read(cx)
+                .worktree_for_id(file.worktree_id(cx), cx)?
+                .read(cx)
+                .root_dir()
+        });
+        let (word_list_path, word_list_name) = match project_root {
+            Some(root_dir) => (project_word_list_path(&root_dir), "project"),
+            None => (user_word_list_path(), "user"),
+        };
+
+        let checker =
+            Spellchecker::global(cx).update(cx, |spellchecker, cx| spellchecker.checker(None, cx));
+        cx.background_executor().spawn(async move {
+            let code_action = |title: String, action: SpellcheckAction| CodeAction {
+                server_id: language::LanguageServerId(0),
+                range: range.clone(),
+                lsp_action: lsp::CodeAction {
+                    title,
+                    kind: Some(lsp::CodeActionKind::QUICKFIX),
+                    data: serde_json::to_value(action).log_err(),
+                    ..Default::default()
+                },
+            };
+            let mut actions = checker
+                .suggestions(&word)
+                .into_iter()
+                .map(|suggestion| {
+                    code_action(
+                        format!("Change to \"{suggestion}\""),
+                        SpellcheckAction::Replace { text: suggestion },
+                    )
+                })
+                .collect::<Vec<_>>();
+            actions.push(code_action(
+                format!("Add \"{word}\" to {word_list_name} dictionary"),
+                SpellcheckAction::AddToWordList {
+                    word,
+                    path: word_list_path,
+                },
+            ));
+            Ok(actions)
+        })
+    }
+
+    fn apply_code_action(
+        &self,
+        buffer: Model<Buffer>,
+        action: CodeAction,
+        _excerpt_id: ExcerptId,
+        _push_to_history: bool,
+        cx: &mut WindowContext,
+    ) -> Task<Result<ProjectTransaction>> {
+        let Some(spellcheck_action) = action
+            .lsp_action
+            .data
+            .and_then(|data| serde_json::from_value(data).log_err())
+        else {
+            return Task::ready(Err(anyhow!("invalid spellcheck code action")));
+        };
+
+        match spellcheck_action {
+            SpellcheckAction::Replace { text } => {
+                buffer.update(cx, |buffer, cx| {
+                    buffer.edit([(action.range, text)], None, cx)
+                });
+                Task::ready(Ok(ProjectTransaction::default()))
+            }
+            SpellcheckAction::AddToWordList { word, path } => {
+                let add_word = Spellchecker::global(cx)
+                    .update(cx, |spellchecker, cx| spellchecker.add_word(path, word, cx));
+                cx.background_executor().spawn(async move {
+                    add_word.await?;
+                    Ok(ProjectTransaction::default())
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_words() {
+        let words = |text: &str| {
+            checked_words(text)
+                .into_iter()
+                .map(|range| text[range].to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            words("// Don't parse the HTTPServer config_file twice."),
+            ["Don't", "parse", "the", "Server", "config", "file", "twice"]
+        );
+        assert_eq!(
+            words("See https://zed.dev or mail hi@zed.dev, then run src/main.rs"),
+            ["See", "mail", "then", "run"]
+        );
+        assert_eq!(
+            words("'quoted' utf8Encode getElementById JSON ok"),
+            ["quoted", "get", "Element"]
+        );
+    }
+
+    #[test]
+    fn test_checked_ranges_bookkeeping() {
+        let mut check = BufferCheck {
+            version: clock::Global::new(),
+            non_text_state_update_count: 0,
+            checked_ranges: Vec::new(),
+            misspellings: Vec::new(),
+        };
+        check.insert_checked_range(10..20);
+        check.insert_checked_range(30..40);
+        assert_eq!(check.unchecked_ranges(0..50), [0..10, 20..30, 40..50]);
+        assert!(check.unchecked_ranges(12..18).is_empty());
+
+        check.insert_checked_range(20..30);
+        assert_eq!(check.checked_ranges, [10..40]);
+        check.insert_checked_range(0..5);
+        check.insert_checked_range(35..45);
+        assert_eq!(check.checked_ranges, [0..5, 10..45]);
+        assert_eq!(check.unchecked_ranges(0..50), [5..10, 45..50]);
+    }
+}
@@ -2,17 +2,19 @@
 mod tab_switcher_tests;
 
 use collections::HashMap;
-use editor::items::entry_git_aware_label_color;
+use db::frecency::{Frecencies, FrecencyKind, FrecencyScope, FRECENCY};
+use editor::{items::entry_git_aware_label_color, Editor};
 use gpui::{
     actions, impl_actions, rems, Action, AnyElement, AppContext, DismissEvent, EntityId,
     EventEmitter, FocusHandle, FocusableView, Model, Modifiers, ModifiersChangedEvent, MouseButton,
     MouseUpEvent, ParentElement, Render, Styled, Task, View, ViewContext, VisualContext, WeakView,
 };
-use picker::{Picker, PickerDelegate};
-use project::Project;
+use language::Point;
+use picker::{Picker, PickerDelegate, PickerPreview};
+use project::{Project, ProjectPath};
 use serde::Deserialize;
 use settings::Settings;
-use std::sync::Arc;
+use std::{cmp::Ordering, sync::Arc};
 use ui::{prelude::*, ListItem, ListItemSpacing, Tooltip};
 use util::ResultExt;
 use workspace::{
@@ -80,16 +82,47 @@ impl TabSwitcher {
         }
 
         let project = workspace.project().clone();
+        let frecency_scope = workspace
+            .database_id()
+            .map(|id| FrecencyScope::Workspace(id.into()));
         workspace.toggle_modal(cx, |cx| {
-            let delegate =
-                TabSwitcherDelegate::new(project, action, cx.view().downgrade(), weak_pane, cx);
+            let delegate = TabSwitcherDelegate::new(
+                project,
+                action,
+                cx.view().downgrade(),
+                weak_pane,
+                frecency_scope,
+                cx,
+            );
             TabSwitcher::new(delegate, cx)
         });
     }
 
     fn new(delegate: TabSwitcherDelegate, cx: &mut ViewContext<Self>) -> Self {
+        let frecency_scope = delegate.frecency_scope;
+        let picker = cx.new_view(|cx| Picker::nonsearchable_uniform_list(delegate, cx));
+        if let Some(scope) = frecency_scope {
+            let frecencies = cx
+                .background_executor()
+                .spawn(async move { FRECENCY.frecencies(FrecencyKind::File, scope) });
+            let weak_picker = picker.downgrade();
+            cx.spawn(|_, mut cx| async move {
+                let frecencies = frecencies.await?;
+                weak_picker.update(&mut cx, |picker, cx| {
+                    let selected_item_id = picker.delegate.selected_item_id();
+                    picker.delegate.frecencies = frecencies;
+                    picker.delegate.update_matches(cx);
+                    if let Some(item_id) = selected_item_id {
+                        picker.delegate.select_item(item_id, cx);
+                    }
+                    cx.notify();
+                })
+            })
+            .detach_and_log_err(cx);
+        }
+
         Self {
-            picker: cx.new_view(|cx| Picker::nonsearchable_uniform_list(delegate, cx)),
+            picker,
             init_modifiers: cx.modifiers().modified().then_some(cx.modifiers()),
         }
     }
@@ -131,9 +164,11 @@ impl FocusableView for TabSwitcher {
 
 impl Render for TabSwitcher {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let preview_width = self.picker.read(cx).preview_width(cx).unwrap_or_default();
+
         v_flex()
             .key_context("TabSwitcher")
-            .w(rems(PANEL_WIDTH_REMS))
+            .w(rems(PANEL_WIDTH_REMS).to_pixels(cx.rem_size()) + preview_width)
             .on_modifiers_changed(cx.listener(Self::handle_modifiers_changed))
             .on_action(cx.listener(Self::handle_close_selected_item))
             .child(self.picker.clone())
@@ -154,6 +189,10 @@ pub struct TabSwitcherDelegate {
     pane: WeakView<Pane>,
     project: Model<Project>,
     matches: Vec<TabMatch>,
+    frecency_scope: Option<FrecencyScope>,
+    /// Orders the tabs that weren't activated in this session, e.g. the restored ones. Loaded in
+    /// the background once the switcher is open.
+    frecencies: Frecencies,
 }
 
 impl TabSwitcherDelegate {
@@ -162,6 +201,7 @@ impl TabSwitcherDelegate {
         action: &Toggle,
         tab_switcher: WeakView<TabSwitcher>,
         pane: WeakView<Pane>,
+        frecency_scope: Option<FrecencyScope>,
         cx: &mut ViewContext<TabSwitcher>,
     ) -> Self {
         Self::subscribe_to_updates(&pane, cx);
@@ -172,6 +212,8 @@ impl TabSwitcherDelegate {
             pane,
             project,
             matches: Vec::new(),
+            frecency_scope,
+            frecencies: Frecencies::default(),
         }
     }
 
@@ -224,15 +266,33 @@ impl TabSwitcherDelegate {
             })
             .for_each(|tab_match| self.matches.push(tab_match));
 
-        let non_history_base = history_indices.len();
+        let frecencies = self
+            .matches
+            .iter()
+            .filter_map(|tab_match| {
+                let project_path = tab_match.item.project_path(cx)?;
+                let frecency = self.frecencies.get(&frecency_key(&project_path));
+                Some((tab_match.item.item_id(), frecency))
+            })
+            .collect::<HashMap<_, _>>();
+        let frecency = move |tab_match: &TabMatch| {
+            frecencies
+                .get(&tab_match.item.item_id())
+                .copied()
+                .unwrap_or_default()
+        };
         self.matches.sort_by(move |a, b| {
-            let a_score = *history_indices
-                .get(&a.item.item_id())
-                .unwrap_or(&(a.item_index + non_history_base));
-            let b_score = *history_indices
-                .get(&b.item.item_id())
-                .unwrap_or(&(b.item_index + non_history_base));
-            a_score.cmp(&b_score)
+            match (
+                history_indices.get(&a.item.item_id()),
+                history_indices.get(&b.item.item_id()),
+            ) {
+                (Some(a_index), Some(b_index)) => a_index.cmp(b_index),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => frecency(b)
+                    .total_cmp(&frecency(a))
+                    .then(a.item_index.cmp(&b.item_index)),
+            }
         });
 
         if self.matches.len() > 1 {
@@ -277,6 +337,10 @@ impl TabSwitcherDelegate {
     }
 }
 
+fn frecency_key(project_path: &ProjectPath) -> String {
+    project_path.path.to_string_lossy().into_owned()
+}
+
 impl PickerDelegate for TabSwitcherDelegate {
     type ListItem = ListItem;
 
@@ -321,11 +385,41 @@ impl PickerDelegate for TabSwitcherDelegate {
         let Some(selected_match) = self.matches.get(self.selected_index()) else {
             return;
         };
+        if let Some((scope, project_path)) = self
+            .frecency_scope
+            .zip(selected_match.item.project_path(cx))
+        {
+            let key = frecency_key(&project_path);
+            db::write_and_log(cx, move || {
+                FRECENCY.record_use(FrecencyKind::File, scope, key)
+            });
+        }
         pane.update(cx, |pane, cx| {
             pane.activate_item(selected_match.item_index, true, true, cx);
         });
     }
 
+    fn supports_preview(&self) -> bool {
+        true
+    }
+
+    /// Previews the tabs showing a single buffer, at their cursor position.
+    fn preview(
+        &self,
+        ix: usize,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Task<anyhow::Result<PickerPreview>>> {
+        let editor = self.matches.get(ix)?.item.act_as::<Editor>(cx)?;
+        editor.update(cx, |editor, cx| {
+            let buffer = editor.buffer().read(cx).as_singleton()?;
+            let cursor = editor.selections.newest::<Point>(cx).head();
+            Some(Task::ready(Ok(PickerPreview {
+                buffer,
+                range: Some(cursor..cursor),
+            })))
+        })
+    }
+
     fn dismissed(&mut self, cx: &mut ViewContext<Picker<TabSwitcherDelegate>>) {
         self.tab_switcher
             .update(cx, |_, cx| cx.emit(DismissEvent))
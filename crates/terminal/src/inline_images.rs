@@ -0,0 +1,979 @@
+//! Support for images printed inline with the terminal output.
+//!
+//! Three protocols are understood:
+//!
+//! ```text
+//! APC G <key>=<value>,... ; <base64 data> ST          the Kitty graphics protocol
+//! OSC 1337 ; File=<key>=<value>;... : <base64 file> ST   iTerm2 inline images
+//! DCS <params> q <sixel data> ST                        DEC sixel graphics
+//! ```
+//!
+//! Alacritty ignores all of them, so the PTY output is scanned for them next to the
+//! semantic prompt marks, see [`crate::shell_integration`]. An image is anchored to the
+//! cell the cursor was at when it was printed, and the cursor is moved past it, so that
+//! the image scrolls together with the text around it.
+
+use std::{collections::VecDeque, iter::Peekable, mem, sync::Arc};
+
+use alacritty_terminal::{
+    event::WindowSize, grid::Dimensions, term::TermMode, vte::ansi::Handler, Term,
+};
+use anyhow::{anyhow, bail, Context as _, Result};
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine as _,
+};
+use gpui::{hsla, size, RenderImage, Rgba, Size};
+use image::{DynamicImage, Frame, RgbImage, RgbaImage};
+use util::ResultExt as _;
+
+use crate::{shell_integration::MarkPoint, ZedListener};
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+const ITERM_FILE_PREFIX: &[u8] = b"1337;File=";
+/// The longest escape sequence we buffer, anything longer is dropped.
+const MAX_SEQUENCE_LEN: usize = 64 * 1024 * 1024;
+/// The largest width or height of an image, in pixels.
+const MAX_IMAGE_SIDE: u32 = 10_000;
+/// How many images are remembered per terminal, the oldest ones are dropped first.
+const MAX_IMAGES: usize = 256;
+/// How many images transmitted with the Kitty protocol are kept around to be placed later.
+const MAX_KITTY_IMAGES: usize = 64;
+
+/// Both Kitty and iTerm2 clients are not consistent about padding the base64 data.
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new()
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// An image printed to the terminal.
+#[derive(Clone, Debug)]
+pub struct InlineImage {
+    pub image: Arc<RenderImage>,
+    /// The cell the top left corner of the image is anchored to.
+    pub origin: MarkPoint,
+    /// The size the image is displayed at, in cells. Images rarely fill their last cells
+    /// exactly, so this is fractional.
+    pub size: Size<f32>,
+    alt_screen: bool,
+    kitty_id: Option<u32>,
+}
+
+impl InlineImage {
+    /// How many lines the image covers.
+    pub fn line_count(&self) -> usize {
+        (self.size.height.ceil() as usize).max(1)
+    }
+
+    /// Whether the image was printed while the alternate screen was active.
+    pub fn alt_screen(&self) -> bool {
+        self.alt_screen
+    }
+}
+
+/// The images printed to a terminal, oldest first.
+#[derive(Debug, Default)]
+pub struct InlineImages {
+    images: VecDeque<InlineImage>,
+}
+
+impl InlineImages {
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &InlineImage> {
+        self.images.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.images.clear();
+    }
+
+    /// Removes the images printed to the alternate screen, once the application using it exits.
+    pub(crate) fn clear_alt_screen(&mut self) {
+        self.images.retain(|image| !image.alt_screen);
+    }
+
+    /// Removes the images of the normal screen, when its scrollback is cleared.
+    pub(crate) fn clear_normal_screen(&mut self) {
+        self.images.retain(|image| image.alt_screen);
+    }
+
+    pub(crate) fn remove_kitty_images(&mut self, kitty_id: Option<u32>) {
+        match kitty_id {
+            Some(kitty_id) => self.images.retain(|image| image.kitty_id != Some(kitty_id)),
+            None => self.images.retain(|image| image.kitty_id.is_none()),
+        }
+    }
+
+    /// Anchors the image at the cursor, and moves the cursor past it unless asked not to.
+    pub(crate) fn place(
+        &mut self,
+        term: &mut Term<ZedListener>,
+        placement: Placement,
+        window_size: WindowSize,
+    ) {
+        let cell_width = f32::from(window_size.cell_width.max(1));
+        let cell_height = f32::from(window_size.cell_height.max(1));
+        let origin = MarkPoint::from_cursor(term);
+        let available_columns = term.columns().saturating_sub(origin.column).max(1);
+
+        let image_size = placement.image.size(0);
+        let natural_width = (image_size.width.0 as f32).max(1.);
+        let natural_height = (image_size.height.0 as f32).max(1.);
+        let requested_width = placement
+            .width
+            .to_pixels(cell_width, term.columns() as f32 * cell_width);
+        let requested_height = placement
+            .height
+            .to_pixels(cell_height, term.screen_lines() as f32 * cell_height);
+        let (mut width, mut height) = match (requested_width, requested_height) {
+            (None, None) => (natural_width, natural_height),
+            (Some(width), None) => (width, natural_height * width / natural_width),
+            (None, Some(height)) => (natural_width * height / natural_height, height),
+            (Some(width), Some(height)) if placement.preserve_aspect_ratio => {
+                let scale = (width / natural_width).min(height / natural_height);
+                (natural_width * scale, natural_height * scale)
+            }
+            (Some(width), Some(height)) => (width, height),
+        };
+        // Images wider than the terminal are scaled down instead of being cut off.
+        let max_width = available_columns as f32 * cell_width;
+        if width > max_width {
+            height *= max_width / width;
+            width = max_width;
+        }
+
+        let image = InlineImage {
+            image: placement.image,
+            origin,
+            size: size(width / cell_width, height / cell_height),
+            alt_screen: term.mode().contains(TermMode::ALT_SCREEN),
+            kitty_id: placement.kitty_id,
+        };
+        let line_count = image.line_count();
+        let column_count = (image.size.width.ceil() as usize).max(1);
+        if self.images.len() == MAX_IMAGES {
+            self.images.pop_front();
+        }
+        self.images.push_back(image);
+
+        match placement.cursor_movement {
+            CursorMovement::None => {}
+            CursorMovement::AfterImage => {
+                for _ in 1..line_count {
+                    term.linefeed();
+                }
+                term.move_forward(column_count);
+            }
+            CursorMovement::BelowImage => {
+                for _ in 0..line_count {
+                    term.linefeed();
+                }
+                term.carriage_return();
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SequenceKind {
+    Apc,
+    Osc,
+    Dcs,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScannerState {
+    Ground,
+    Escape,
+    String(SequenceKind),
+    StringEscape(SequenceKind),
+}
+
+/// An escape sequence that may carry an image, with its introducer stripped.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ImageSequence {
+    Kitty(Vec<u8>),
+    ITerm(Vec<u8>),
+    Sixel(Vec<u8>),
+}
+
+/// Finds image escape sequences in a stream of bytes, which may be split at arbitrary points.
+#[derive(Debug)]
+pub(crate) struct ImageScanner {
+    state: ScannerState,
+    payload: Vec<u8>,
+    ignored: bool,
+}
+
+impl Default for ImageScanner {
+    fn default() -> Self {
+        Self {
+            state: ScannerState::Ground,
+            payload: Vec::new(),
+            ignored: false,
+        }
+    }
+}
+
+impl ImageScanner {
+    pub(crate) fn advance(&mut self, byte: u8) -> Option<ImageSequence> {
+        match (self.state, byte) {
+            (ScannerState::Ground, ESC) => self.state = ScannerState::Escape,
+            (ScannerState::Ground, _) => {}
+            (ScannerState::Escape, b'_') => self.start(SequenceKind::Apc),
+            (ScannerState::Escape, b']') => self.start(SequenceKind::Osc),
+            (ScannerState::Escape, b'P') => self.start(SequenceKind::Dcs),
+            (ScannerState::Escape, ESC) => {}
+            (ScannerState::Escape, _) => self.state = ScannerState::Ground,
+            (ScannerState::String(SequenceKind::Osc), BEL) => {
+                return self.finish(SequenceKind::Osc)
+            }
+            (ScannerState::String(kind), ESC) => self.state = ScannerState::StringEscape(kind),
+            (ScannerState::String(kind), _) => self.push(kind, byte),
+            (ScannerState::StringEscape(kind), b'\\') => return self.finish(kind),
+            // Any other escape sequence aborts the string, and starts a new sequence.
+            (ScannerState::StringEscape(_), _) => {
+                self.state = ScannerState::Escape;
+                return self.advance(byte);
+            }
+        }
+        None
+    }
+
+    fn start(&mut self, kind: SequenceKind) {
+        self.state = ScannerState::String(kind);
+        self.payload.clear();
+        self.ignored = false;
+    }
+
+    fn push(&mut self, kind: SequenceKind, byte: u8) {
+        if self.ignored {
+            return;
+        }
+        if self.payload.len() == MAX_SEQUENCE_LEN {
+            self.ignore();
+            return;
+        }
+        self.payload.push(byte);
+        if !may_carry_image(kind, &self.payload) {
+            self.ignore();
+        }
+    }
+
+    fn ignore(&mut self) {
+        self.ignored = true;
+        self.payload = Vec::new();
+    }
+
+    fn finish(&mut self, kind: SequenceKind) -> Option<ImageSequence> {
+        self.state = ScannerState::Ground;
+        if self.ignored {
+            return None;
+        }
+        let mut payload = mem::take(&mut self.payload);
+        match kind {
+            SequenceKind::Apc => {
+                payload.first().filter(|&&byte| byte == b'G')?;
+                payload.drain(..1);
+                Some(ImageSequence::Kitty(payload))
+            }
+            SequenceKind::Osc => {
+                if !payload.starts_with(ITERM_FILE_PREFIX) {
+                    return None;
+                }
+                payload.drain(..ITERM_FILE_PREFIX.len());
+                Some(ImageSequence::ITerm(payload))
+            }
+            SequenceKind::Dcs => {
+                let introducer_end = payload.iter().position(|&byte| byte == b'q')?;
+                payload.drain(..=introducer_end);
+                Some(ImageSequence::Sixel(payload))
+            }
+        }
+    }
+}
+
+/// Whether a sequence starting with these bytes may still turn out to be an image.
+fn may_carry_image(kind: SequenceKind, payload: &[u8]) -> bool {
+    match kind {
+        SequenceKind::Apc => payload[0] == b'G',
+        SequenceKind::Osc => {
+            let len = payload.len().min(ITERM_FILE_PREFIX.len());
+            payload[..len] == ITERM_FILE_PREFIX[..len]
+        }
+        // Sixel data is introduced by numeric parameters and a `q`.
+        SequenceKind::Dcs => payload
+            .iter()
+            .take_while(|&&byte| byte != b'q')
+            .all(|&byte| byte.is_ascii_digit() || byte == b';'),
+    }
+}
+
+/// How one dimension of an image was requested to be displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum ImageDimension {
+    #[default]
+    Auto,
+    Cells(u32),
+    Pixels(u32),
+    Percent(u32),
+}
+
+impl ImageDimension {
+    /// Parses the iTerm2 notation: `N`, `Npx`, `N%` or `auto`.
+    fn parse(value: &str) -> Self {
+        let parsed = if let Some(pixels) = value.strip_suffix("px") {
+            pixels.parse().map(Self::Pixels)
+        } else if let Some(percent) = value.strip_suffix('%') {
+            percent.parse().map(Self::Percent)
+        } else {
+            value.parse().map(Self::Cells)
+        };
+        parsed.unwrap_or_default()
+    }
+
+    fn to_pixels(self, cell_size: f32, terminal_size: f32) -> Option<f32> {
+        match self {
+            Self::Auto | Self::Cells(0) | Self::Pixels(0) | Self::Percent(0) => None,
+            Self::Cells(cells) => Some(cells as f32 * cell_size),
+            Self::Pixels(pixels) => Some(pixels as f32),
+            Self::Percent(percent) => Some(terminal_size * percent.min(100) as f32 / 100.),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CursorMovement {
+    None,
+    /// To the cell after the last line of the image, as Kitty and iTerm2 do.
+    AfterImage,
+    /// To the start of the line below the image, as sixel terminals do.
+    BelowImage,
+}
+
+/// A decoded image, and how it should be displayed.
+#[derive(Debug)]
+pub(crate) struct Placement {
+    image: Arc<RenderImage>,
+    width: ImageDimension,
+    height: ImageDimension,
+    preserve_aspect_ratio: bool,
+    cursor_movement: CursorMovement,
+    kitty_id: Option<u32>,
+}
+
+/// What has to be done after an image sequence was decoded.
+#[derive(Debug)]
+pub(crate) enum ImageAction {
+    Place(Placement),
+    /// Removes the images placed with the Kitty protocol, either all of them or those with the id.
+    DeleteKittyImages(Option<u32>),
+    /// A reply to send back to the application.
+    Respond(String),
+}
+
+/// The image data transmitted with the Kitty protocol so far.
+#[derive(Debug)]
+struct KittyTransmission {
+    command: KittyCommand,
+    data: Vec<u8>,
+}
+
+/// Turns image sequences into images, keeping the state of Kitty protocol transmissions.
+#[derive(Debug, Default)]
+pub(crate) struct ImageDecoder {
+    kitty_transmission: Option<KittyTransmission>,
+    kitty_images: VecDeque<(u32, Arc<RenderImage>)>,
+}
+
+impl ImageDecoder {
+    pub(crate) fn decode(&mut self, sequence: ImageSequence) -> Vec<ImageAction> {
+        match sequence {
+            ImageSequence::Kitty(payload) => self.decode_kitty(&payload),
+            ImageSequence::ITerm(payload) => decode_iterm(&payload)
+                .log_err()
+                .flatten()
+                .map(ImageAction::Place)
+                .into_iter()
+                .collect(),
+            ImageSequence::Sixel(data) => decode_sixel(&data)
+                .and_then(render_image)
+                .log_err()
+                .map(|image| {
+                    ImageAction::Place(Placement {
+                        image: Arc::new(image),
+                        width: ImageDimension::Auto,
+                        height: ImageDimension::Auto,
+                        preserve_aspect_ratio: true,
+                        cursor_movement: CursorMovement::BelowImage,
+                        kitty_id: None,
+                    })
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    fn decode_kitty(&mut self, payload: &[u8]) -> Vec<ImageAction> {
+        let (control, data) = match payload.iter().position(|&byte| byte == b';') {
+            Some(ix) => (&payload[..ix], &payload[ix + 1..]),
+            None => (payload, &[][..]),
+        };
+        let Some(command) = KittyCommand::parse(control).log_err() else {
+            return Vec::new();
+        };
+
+        // Large images are sent in chunks, only the first of which carries the command.
+        if let Some(transmission) = self.kitty_transmission.as_mut() {
+            transmission.data.extend_from_slice(data);
+            if command.more_chunks {
+                return Vec::new();
+            }
+            let transmission = self.kitty_transmission.take().unwrap();
+            return self.run_kitty_command(transmission.command, &transmission.data);
+        }
+        if command.more_chunks {
+            self.kitty_transmission = Some(KittyTransmission {
+                command,
+                data: data.to_vec(),
+            });
+            return Vec::new();
+        }
+        self.run_kitty_command(command, data)
+    }
+
+    fn run_kitty_command(&mut self, command: KittyCommand, data: &[u8]) -> Vec<ImageAction> {
+        let mut actions = Vec::new();
+        match command.action {
+            b't' | b'T' | b'q' => match command.decode_image(data) {
+                Ok(image) => {
+                    if command.action != b'q' {
+                        let image = Arc::new(image);
+                        if let Some(id) = command.id {
+                            self.store_kitty_image(id, image.clone());
+                        }
+                        if command.action == b'T' {
+                            actions.push(ImageAction::Place(command.placement(image)));
+                        }
+                    }
+                    actions.extend(command.response(Ok(())).map(ImageAction::Respond));
+                }
+                Err(error) => {
+                    actions.extend(command.response(Err(error)).map(ImageAction::Respond));
+                }
+            },
+            b'p' => {
+                let image = command.id.and_then(|id| {
+                    self.kitty_images
+                        .iter()
+                        .find(|(stored_id, _)| *stored_id == id)
+                        .map(|(_, image)| image.clone())
+                });
+                match image {
+                    Some(image) => {
+                        actions.push(ImageAction::Place(command.placement(image)));
+                        actions.extend(command.response(Ok(())).map(ImageAction::Respond));
+                    }
+                    None => actions.extend(
+                        command
+                            .response(Err(anyhow!("ENOENT:no such image")))
+                            .map(ImageAction::Respond),
+                    ),
+                }
+            }
+            b'd' => {
+                // Upper case targets also free the transmitted image data.
+                let free_data = command.delete.is_ascii_uppercase();
+                match command.delete.to_ascii_lowercase() {
+                    b'a' => {
+                        if free_data {
+                            self.kitty_images.clear();
+                        }
+                        actions.push(ImageAction::DeleteKittyImages(None));
+                    }
+                    b'i' => {
+                        if let Some(id) = command.id {
+                            if free_data {
+                                self.kitty_images.retain(|(stored_id, _)| *stored_id != id);
+                            }
+                            actions.push(ImageAction::DeleteKittyImages(Some(id)));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        actions
+    }
+
+    fn store_kitty_image(&mut self, id: u32, image: Arc<RenderImage>) {
+        self.kitty_images.retain(|(stored_id, _)| *stored_id != id);
+        if self.kitty_images.len() == MAX_KITTY_IMAGES {
+            self.kitty_images.pop_front();
+        }
+        self.kitty_images.push_back((id, image));
+    }
+}
+
+/// The control data of a Kitty graphics protocol command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct KittyCommand {
+    action: u8,
+    format: u32,
+    medium: u8,
+    compressed: bool,
+    pixel_width: u32,
+    pixel_height: u32,
+    id: Option<u32>,
+    more_chunks: bool,
+    columns: u32,
+    rows: u32,
+    quiet: u32,
+    move_cursor: bool,
+    delete: u8,
+}
+
+impl Default for KittyCommand {
+    fn default() -> Self {
+        Self {
+            action: b't',
+            format: 32,
+            medium: b'd',
+            compressed: false,
+            pixel_width: 0,
+            pixel_height: 0,
+            id: None,
+            more_chunks: false,
+            columns: 0,
+            rows: 0,
+            quiet: 0,
+            move_cursor: true,
+            delete: b'a',
+        }
+    }
+}
+
+impl KittyCommand {
+    fn parse(control: &[u8]) -> Result<Self> {
+        let mut command = Self::default();
+        for pair in control.split(|&byte| byte == b',') {
+            let [key, b'=', value @ ..] = pair else {
+                continue;
+            };
+            let number = || -> Result<u32> {
+                std::str::from_utf8(value)?
+                    .parse()
+                    .with_context(|| format!("invalid value for key {}", *key as char))
+            };
+            let character = || value.first().copied().unwrap_or_default();
+            match *key {
+                b'a' => command.action = character(),
+                b'f' => command.format = number()?,
+                b't' => command.medium = character(),
+                b'o' => command.compressed = !value.is_empty(),
+                b's' => command.pixel_width = number()?,
+                b'v' => command.pixel_height = number()?,
+                b'i' => command.id = Some(number()?),
+                b'm' => command.more_chunks = number()? == 1,
+                b'c' => command.columns = number()?,
+                b'r' => command.rows = number()?,
+                b'q' => command.quiet = number()?,
+                b'C' => command.move_cursor = number()? == 0,
+                b'd' => command.delete = character(),
+                _ => {}
+            }
+        }
+        Ok(command)
+    }
+
+    fn decode_image(&self, data: &[u8]) -> Result<RenderImage> {
+        if self.medium != b'd' {
+            bail!("ENOTSUP:only direct transmission is supported");
+        }
+        if self.compressed {
+            bail!("ENOTSUP:compressed image data is not supported");
+        }
+        let bytes = BASE64.decode(data).context("EINVAL:invalid base64 data")?;
+        let image = match self.format {
+            100 => image::load_from_memory_with_format(&bytes, image::ImageFormat::Png)
+                .context("EBADPNG:failed to decode PNG data")?
+                .into_rgba8(),
+            32 => RgbaImage::from_raw(self.pixel_width, self.pixel_height, bytes)
+                .context("ENODATA:image data does not match the image size")?,
+            24 => {
+                let image = RgbImage::from_raw(self.pixel_width, self.pixel_height, bytes)
+                    .context("ENODATA:image data does not match the image size")?;
+                DynamicImage::ImageRgb8(image).into_rgba8()
+            }
+            format => bail!("EINVAL:unknown image format {format}"),
+        };
+        render_image(image)
+    }
+
+    fn placement(&self, image: Arc<RenderImage>) -> Placement {
+        Placement {
+            image,
+            width: ImageDimension::Cells(self.columns),
+            height: ImageDimension::Cells(self.rows),
+            // Kitty stretches images when both the columns and rows are given.
+            preserve_aspect_ratio: false,
+            cursor_movement: if self.move_cursor {
+                CursorMovement::AfterImage
+            } else {
+                CursorMovement::None
+            },
+            kitty_id: self.id,
+        }
+    }
+
+    /// The reply to the command, Kitty only replies to commands that specify an image id.
+    fn response(&self, result: Result<()>) -> Option<String> {
+        let id = self.id?;
+        let message = match result {
+            Ok(()) if self.quiet == 0 => "OK".to_string(),
+            Ok(()) => return None,
+            Err(_) if self.quiet >= 2 => return None,
+            Err(error) => {
+                let message = error.to_string();
+                if message.contains(':') {
+                    message
+                } else {
+                    format!("EINVAL:{message}")
+                }
+            }
+        };
+        Some(format!("\x1b_Gi={id};{message}\x1b\\"))
+    }
+}
+
+/// Decodes the arguments and contents of an iTerm2 `File=` sequence, if it is to be displayed.
+fn decode_iterm(payload: &[u8]) -> Result<Option<Placement>> {
+    let separator = payload
+        .iter()
+        .position(|&byte| byte == b':')
+        .context("missing image data")?;
+    let arguments = std::str::from_utf8(&payload[..separator])?;
+
+    let mut inline = false;
+    let mut width = ImageDimension::Auto;
+    let mut height = ImageDimension::Auto;
+    let mut preserve_aspect_ratio = true;
+    for argument in arguments.split(';') {
+        let Some((key, value)) = argument.split_once('=') else {
+            continue;
+        };
+        match key {
+            "inline" => inline = value == "1",
+            "width" => width = ImageDimension::parse(value),
+            "height" => height = ImageDimension::parse(value),
+            "preserveAspectRatio" => preserve_aspect_ratio = value != "0",
+            _ => {}
+        }
+    }
+    // Files that are not inline are meant to be downloaded, which is not supported.
+    if !inline {
+        return Ok(None);
+    }
+
+    let bytes = BASE64.decode(&payload[separator + 1..])?;
+    let image = image::load_from_memory(&bytes)?.into_rgba8();
+    Ok(Some(Placement {
+        image: Arc::new(render_image(image)?),
+        width,
+        height,
+        preserve_aspect_ratio,
+        cursor_movement: CursorMovement::AfterImage,
+        kitty_id: None,
+    }))
+}
+
+/// The default palette of the VT340, in percent.
+const SIXEL_PALETTE: [[u32; 3]; 16] = [
+    [0, 0, 0],
+    [20, 20, 80],
+    [80, 13, 13],
+    [20, 80, 20],
+    [80, 20, 80],
+    [20, 80, 80],
+    [80, 80, 20],
+    [53, 53, 53],
+    [26, 26, 26],
+    [33, 33, 60],
+    [60, 26, 26],
+    [33, 60, 33],
+    [60, 33, 60],
+    [33, 60, 60],
+    [60, 60, 33],
+    [80, 80, 80],
+];
+const SIXEL_PALETTE_SIZE: usize = 256;
+const TRANSPARENT: [u8; 4] = [0, 0, 0, 0];
+
+/// Decodes sixel data, the part of the `DCS` sequence after the `q`.
+fn decode_sixel(data: &[u8]) -> Result<RgbaImage> {
+    let mut palette = vec![[0, 0, 0, 255]; SIXEL_PALETTE_SIZE];
+    for (color, [r, g, b]) in palette.iter_mut().zip(SIXEL_PALETTE) {
+        *color = rgb_from_percent(r, g, b);
+    }
+    let mut color = 0;
+    let mut x = 0;
+    let mut y = 0;
+    let mut width = 0;
+    let mut height = 0;
+    let mut rows: Vec<Vec<[u8; 4]>> = Vec::new();
+
+    let mut bytes = data.iter().copied().peekable();
+    while let Some(byte) = bytes.next() {
+        let (sixel, count) = match byte {
+            b'?'..=b'~' => (byte - b'?', 1),
+            b'!' => {
+                let count = read_number(&mut bytes).unwrap_or(1).max(1) as usize;
+                match bytes.next() {
+                    Some(byte @ b'?'..=b'~') => (byte - b'?', count),
+                    _ => continue,
+                }
+            }
+            b'$' => {
+                x = 0;
+                continue;
+            }
+            b'-' => {
+                x = 0;
+                y += 6;
+                continue;
+            }
+            b'#' => {
+                match read_parameters(&mut bytes)[..] {
+                    [index] => color = index as usize % SIXEL_PALETTE_SIZE,
+                    [index, 1, h, l, s] => {
+                        color = index as usize % SIXEL_PALETTE_SIZE;
+                        palette[color] = rgb_from_hls(h, l, s);
+                    }
+                    [index, 2, r, g, b] => {
+                        color = index as usize % SIXEL_PALETTE_SIZE;
+                        palette[color] = rgb_from_percent(r, g, b);
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            // Raster attributes: pixel aspect ratio, and the size of the image.
+            b'"' => {
+                if let [_, _, raster_width, raster_height] = read_parameters(&mut bytes)[..] {
+                    width = width.max(raster_width as usize);
+                    height = height.max(raster_height as usize);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        if x + count > MAX_IMAGE_SIDE as usize || y + 6 > MAX_IMAGE_SIDE as usize {
+            bail!("sixel image is too large");
+        }
+        for bit in 0..6 {
+            if sixel & (1 << bit) == 0 {
+                continue;
+            }
+            let row = y + bit;
+            if rows.len() <= row {
+                rows.resize_with(row + 1, Vec::new);
+            }
+            let row = &mut rows[row];
+            if row.len() < x + count {
+                row.resize(x + count, TRANSPARENT);
+            }
+            row[x..x + count].fill(palette[color]);
+        }
+        x += count;
+        width = width.max(x);
+    }
+    height = height.max(rows.len());
+
+    if width == 0 || height == 0 || width > MAX_IMAGE_SIDE as usize {
+        bail!("sixel image has an invalid size {width}x{height}");
+    }
+    let mut image = RgbaImage::new(width as u32, height.min(MAX_IMAGE_SIDE as usize) as u32);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, pixel) in row.iter().enumerate() {
+            image.put_pixel(x as u32, y as u32, image::Rgba(*pixel));
+        }
+    }
+    Ok(image)
+}
+
+fn read_number(bytes: &mut Peekable<impl Iterator<Item = u8>>) -> Option<u32> {
+    let mut number: Option<u32> = None;
+    while let Some(digit) = bytes.next_if(u8::is_ascii_digit) {
+        number = Some(
+            number
+                .unwrap_or(0)
+                .saturating_mul(10)
+                .saturating_add(u32::from(digit - b'0')),
+        );
+    }
+    number
+}
+
+fn read_parameters(bytes: &mut Peekable<impl Iterator<Item = u8>>) -> Vec<u32> {
+    let mut parameters = vec![read_number(bytes).unwrap_or(0)];
+    while bytes.next_if_eq(&b';').is_some() {
+        parameters.push(read_number(bytes).unwrap_or(0));
+    }
+    parameters
+}
+
+fn rgb_from_percent(r: u32, g: u32, b: u32) -> [u8; 4] {
+    let channel = |value: u32| (value.min(100) * 255 / 100) as u8;
+    [channel(r), channel(g), channel(b), 255]
+}
+
+/// Sixel hues start at blue instead of red.
+fn rgb_from_hls(h: u32, l: u32, s: u32) -> [u8; 4] {
+    let hue = ((h + 240) % 360) as f32 / 360.;
+    let Rgba { r, g, b, .. } =
+        hsla(hue, s.min(100) as f32 / 100., l.min(100) as f32 / 100., 1.).to_rgb();
+    let channel = |value: f32| (value * 255.).round() as u8;
+    [channel(r), channel(g), channel(b), 255]
+}
+
+/// Converts a decoded image into the format GPUI renders.
+fn render_image(mut image: RgbaImage) -> Result<RenderImage> {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 || width > MAX_IMAGE_SIDE || height > MAX_IMAGE_SIDE {
+        bail!("EINVAL:invalid image size {width}x{height}");
+    }
+    // Convert from RGBA to BGRA.
+    for pixel in image.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    Ok(RenderImage::new(vec![Frame::new(image)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(input: &[u8]) -> Vec<ImageSequence> {
+        let mut scanner = ImageScanner::default();
+        input
+            .iter()
+            .filter_map(|&byte| scanner.advance(byte))
+            .collect()
+    }
+
+    #[test]
+    fn test_scanning_image_sequences() {
+        assert_eq!(
+            scan(b"a\x1b_Ga=T,f=100;AAAA\x1b\\b"),
+            vec![ImageSequence::Kitty(b"a=T,f=100;AAAA".to_vec())]
+        );
+        assert_eq!(
+            scan(b"\x1b]1337;File=inline=1:AAAA\x07"),
+            vec![ImageSequence::ITerm(b"inline=1:AAAA".to_vec())]
+        );
+        assert_eq!(
+            scan(b"\x1bP0;1;0q#0~~-\x1b\\"),
+            vec![ImageSequence::Sixel(b"#0~~-".to_vec())]
+        );
+        // Other strings are skipped.
+        assert_eq!(
+            scan(b"\x1b]0;title\x07\x1b]133;A\x1b\\\x1bP$qm\x1b\\\x1b_Xyz\x1b\\"),
+            vec![]
+        );
+        // An interrupted sequence is dropped.
+        assert_eq!(
+            scan(b"\x1b_Ga=T;AA\x1b[0m\x1b_Gi=1\x1b\\"),
+            vec![ImageSequence::Kitty(b"i=1".to_vec())]
+        );
+    }
+
+    #[test]
+    fn test_kitty_commands() {
+        let command = KittyCommand::parse(b"a=T,f=24,s=2,v=1,i=7,C=1,q=1").unwrap();
+        assert_eq!(
+            command,
+            KittyCommand {
+                action: b'T',
+                format: 24,
+                pixel_width: 2,
+                pixel_height: 1,
+                id: Some(7),
+                quiet: 1,
+                move_cursor: false,
+                ..KittyCommand::default()
+            }
+        );
+        assert!(KittyCommand::parse(b"s=x").is_err());
+
+        let mut decoder = ImageDecoder::default();
+        // A 1x1 red pixel, sent in two chunks.
+        assert!(decoder
+            .decode_kitty(b"a=T,f=24,s=1,v=1,i=3,m=1;/w")
+            .is_empty());
+        let actions = decoder.decode_kitty(b"m=0;AA");
+        assert!(matches!(
+            &actions[..],
+            [ImageAction::Place(placement), ImageAction::Respond(response)]
+                if placement.kitty_id == Some(3) && response == "\x1b_Gi=3;OK\x1b\\"
+        ));
+
+        let actions = decoder.decode_kitty(b"a=p,i=3");
+        assert!(matches!(
+            &actions[..],
+            [ImageAction::Place(_), ImageAction::Respond(_)]
+        ));
+        let actions = decoder.decode_kitty(b"a=p,i=4");
+        assert!(matches!(
+            &actions[..],
+            [ImageAction::Respond(response)] if response.starts_with("\x1b_Gi=4;ENOENT:")
+        ));
+
+        let actions = decoder.decode_kitty(b"a=q,f=32,s=2,v=2,i=31;AAAA");
+        assert!(matches!(
+            &actions[..],
+            [ImageAction::Respond(response)] if response.starts_with("\x1b_Gi=31;ENODATA:")
+        ));
+
+        let actions = decoder.decode_kitty(b"a=d,d=I,i=3");
+        assert!(matches!(
+            &actions[..],
+            [ImageAction::DeleteKittyImages(Some(3))]
+        ));
+        assert!(decoder.kitty_images.is_empty());
+    }
+
+    #[test]
+    fn test_image_dimensions() {
+        assert_eq!(ImageDimension::parse("auto"), ImageDimension::Auto);
+        assert_eq!(ImageDimension::parse("10"), ImageDimension::Cells(10));
+        assert_eq!(ImageDimension::parse("200px"), ImageDimension::Pixels(200));
+        assert_eq!(ImageDimension::parse("50%"), ImageDimension::Percent(50));
+        assert_eq!(ImageDimension::Cells(3).to_pixels(8., 800.), Some(24.));
+        assert_eq!(ImageDimension::Percent(50).to_pixels(8., 800.), Some(400.));
+        assert_eq!(ImageDimension::Cells(0).to_pixels(8., 800.), None);
+    }
+
+    #[test]
+    fn test_decoding_sixel() {
+        // Two columns of red, the second one repeated, over one band of six pixels.
+        let image = decode_sixel(b"#1;2;100;0;0#1~!2~").unwrap();
+        assert_eq!(image.dimensions(), (3, 6));
+        assert_eq!(image.get_pixel(2, 5).0, [255, 0, 0, 255]);
+
+        // Only the top pixel of the second band is set.
+        let image = decode_sixel(b"#0~-@").unwrap();
+        assert_eq!(image.dimensions(), (1, 7));
+        assert_eq!(image.get_pixel(0, 6).0, [0, 0, 0, 255]);
+
+        assert!(decode_sixel(b"").is_err());
+    }
+}
@@ -0,0 +1,694 @@
+//! Support for the FinalTerm semantic prompt protocol (`OSC 133`).
+//!
+//! Shells configured for shell integration (fish, and zsh/bash with the usual
+//! prompt hooks) wrap every prompt and command in marks:
+//!
+//! ```text
+//! OSC 133 ; A ST   prompt starts
+//! OSC 133 ; B ST   prompt ends, the user types the command
+//! OSC 133 ; C ST   the command was submitted, its output starts
+//! OSC 133 ; D [; exit code] ST   the command finished
+//! ```
+//!
+//! Alacritty ignores these sequences, so we watch the PTY output ourselves and
+//! record the grid position each mark was emitted at.
+
+use std::{
+    collections::VecDeque,
+    io::{self, Read},
+    ops::Range,
+    sync::Arc,
+};
+#[cfg(unix)]
+use std::{io::Write, os::unix::net::UnixStream};
+
+use alacritty_terminal::{
+    event::{Event as AlacTermEvent, EventListener, OnResize, WindowSize},
+    grid::Dimensions,
+    index::{Column, Line, Point as AlacPoint},
+    sync::FairMutex,
+    term::TermMode,
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+    Term,
+};
+use parking_lot::Mutex;
+use polling::{Event as PollingEvent, PollMode, Poller};
+
+use crate::{
+    inline_images::{ImageAction, ImageDecoder, ImageScanner, InlineImages},
+    TerminalSize, ZedListener,
+};
+
+/// The longest `OSC` payload we bother buffering, anything longer is certainly not a mark.
+const MAX_MARK_LEN: usize = 64;
+/// How many finished commands are remembered per terminal.
+const MAX_COMMAND_BLOCKS: usize = 1_000;
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+/// A semantic prompt mark, as emitted by the shell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SemanticMark {
+    PromptStart,
+    CommandStart,
+    CommandExecuted,
+    CommandFinished { exit_code: Option<i32> },
+}
+
+impl SemanticMark {
+    /// Parses the payload of an `OSC` sequence, e.g. `133;D;1`.
+    fn parse(payload: &[u8]) -> Option<Self> {
+        let payload = std::str::from_utf8(payload).ok()?;
+        let mut params = payload.strip_prefix("133;")?.split(';');
+        match params.next()? {
+            "A" => Some(Self::PromptStart),
+            "B" => Some(Self::CommandStart),
+            "C" => Some(Self::CommandExecuted),
+            "D" => Some(Self::CommandFinished {
+                exit_code: params.next().and_then(|code| code.parse().ok()),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A position in the terminal grid which stays stable while the content scrolls.
+///
+/// `line` is counted from the top of the scrollback, rather than from the top of
+/// the screen like alacritty's [`Line`]. Alacritty does not report how many lines
+/// were dropped once the scrollback is full, so from then on the points lag behind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MarkPoint {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl MarkPoint {
+    pub(crate) fn from_cursor<T>(term: &Term<T>) -> Self {
+        let cursor = term.grid().cursor.point;
+        Self {
+            line: (term.grid().history_size() as i32 + cursor.line.0).max(0) as usize,
+            column: cursor.column.0,
+        }
+    }
+
+    /// Converts the point into the alacritty grid coordinates, if it is still in the grid.
+    pub fn to_alac_point<T>(self, term: &Term<T>) -> Option<AlacPoint> {
+        let line = Line(self.line as i32 - term.grid().history_size() as i32);
+        if line < term.topmost_line() || line > term.bottommost_line() {
+            return None;
+        }
+        Some(AlacPoint::new(
+            line,
+            Column(self.column.min(term.last_column().0)),
+        ))
+    }
+}
+
+/// The status of a command run in the shell, as reported by the `D` mark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandStatus {
+    /// The shell is showing the prompt, or the command is still running.
+    Running,
+    Succeeded,
+    Failed {
+        exit_code: i32,
+    },
+    /// The command finished but the shell did not report its exit code.
+    Finished,
+}
+
+/// A single prompt, together with the command typed into it and its output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandBlock {
+    pub prompt_start: MarkPoint,
+    pub command_start: Option<MarkPoint>,
+    pub output_start: Option<MarkPoint>,
+    pub command_end: Option<MarkPoint>,
+    pub status: CommandStatus,
+}
+
+impl CommandBlock {
+    fn new(prompt_start: MarkPoint) -> Self {
+        Self {
+            prompt_start,
+            command_start: None,
+            output_start: None,
+            command_end: None,
+            status: CommandStatus::Running,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.command_end.is_some()
+    }
+
+    /// The last line belonging to this block, if it is known already.
+    pub fn last_line(&self) -> Option<usize> {
+        let end = self.command_end?;
+        if end.column == 0 && end.line > self.prompt_start.line {
+            Some(end.line - 1)
+        } else {
+            Some(end.line)
+        }
+    }
+}
+
+/// Command blocks of a terminal, built from the marks the shell emitted.
+#[derive(Debug, Default)]
+pub struct CommandBlocks {
+    blocks: VecDeque<CommandBlock>,
+}
+
+impl CommandBlocks {
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &CommandBlock> {
+        self.blocks.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// The most recent block whose command has finished running.
+    pub fn last_finished(&self) -> Option<&CommandBlock> {
+        self.blocks.iter().rev().find(|block| block.is_finished())
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+    }
+
+    /// Forgets the blocks that start below `line`, e.g. after the screen was cleared.
+    pub(crate) fn truncate_after(&mut self, line: usize) {
+        while self
+            .blocks
+            .back()
+            .map_or(false, |block| block.prompt_start.line > line)
+        {
+            self.blocks.pop_back();
+        }
+    }
+
+    pub(crate) fn push_mark(&mut self, mark: SemanticMark, point: MarkPoint) {
+        match mark {
+            SemanticMark::PromptStart => {
+                // Shells redraw the prompt (e.g. on resize) without running a command,
+                // replace the unfinished block instead of adding an empty one.
+                if self.blocks.back().map_or(false, |block| {
+                    block.output_start.is_none() && !block.is_finished()
+                }) {
+                    self.blocks.pop_back();
+                }
+                self.blocks.push_back(CommandBlock::new(point));
+                if self.blocks.len() > MAX_COMMAND_BLOCKS {
+                    self.blocks.pop_front();
+                }
+            }
+            SemanticMark::CommandStart => {
+                if let Some(block) = self.unfinished_block() {
+                    block.command_start = Some(point);
+                }
+            }
+            SemanticMark::CommandExecuted => {
+                if let Some(block) = self.unfinished_block() {
+                    block.output_start = Some(point);
+                }
+            }
+            SemanticMark::CommandFinished { exit_code } => {
+                if let Some(block) = self.unfinished_block() {
+                    block.command_end = Some(point);
+                    block.status = match exit_code {
+                        Some(0) => CommandStatus::Succeeded,
+                        Some(exit_code) => CommandStatus::Failed { exit_code },
+                        None => CommandStatus::Finished,
+                    };
+                }
+            }
+        }
+    }
+
+    fn unfinished_block(&mut self) -> Option<&mut CommandBlock> {
+        self.blocks.back_mut().filter(|block| !block.is_finished())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScannerState {
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// Finds `OSC 133` sequences in a stream of bytes, which may be split at arbitrary points.
+#[derive(Debug)]
+pub(crate) struct MarkScanner {
+    state: ScannerState,
+    payload: Vec<u8>,
+}
+
+impl Default for MarkScanner {
+    fn default() -> Self {
+        Self {
+            state: ScannerState::Ground,
+            payload: Vec::with_capacity(MAX_MARK_LEN),
+        }
+    }
+}
+
+impl MarkScanner {
+    pub(crate) fn advance(&mut self, byte: u8) -> Option<SemanticMark> {
+        match (self.state, byte) {
+            (ScannerState::Ground, ESC) => self.state = ScannerState::Escape,
+            (ScannerState::Ground, _) => {}
+            (ScannerState::Escape, b']') => {
+                self.payload.clear();
+                self.state = ScannerState::Osc;
+            }
+            (ScannerState::Escape, ESC) => {}
+            (ScannerState::Escape, _) => self.state = ScannerState::Ground,
+            (ScannerState::Osc, BEL) => return self.finish(),
+            (ScannerState::Osc, ESC) => self.state = ScannerState::OscEscape,
+            (ScannerState::Osc, _) => {
+                if self.payload.len() < MAX_MARK_LEN {
+                    self.payload.push(byte);
+                }
+            }
+            (ScannerState::OscEscape, b'\\') => return self.finish(),
+            // Any other escape sequence aborts the string, and starts a new sequence.
+            (ScannerState::OscEscape, b']') => {
+                self.payload.clear();
+                self.state = ScannerState::Osc;
+            }
+            (ScannerState::OscEscape, ESC) => self.state = ScannerState::Escape,
+            (ScannerState::OscEscape, _) => self.state = ScannerState::Ground,
+        }
+        None
+    }
+
+    fn finish(&mut self) -> Option<SemanticMark> {
+        self.state = ScannerState::Ground;
+        if self.payload.len() >= MAX_MARK_LEN {
+            return None;
+        }
+        SemanticMark::parse(&self.payload)
+    }
+}
+
+/// Something the shell emitted, to be applied to the terminal once alacritty parsed the
+/// output before it.
+enum PtyEvent {
+    Mark(SemanticMark),
+    Image(Vec<ImageAction>),
+}
+
+/// Wraps the PTY so that its output can be scanned for semantic prompt marks and inline images.
+///
+/// Parsing is left to alacritty's event loop. Reads end right after each mark or image
+/// sequence, and the next read, once the event loop parsed everything before it, records
+/// the cursor position or places the image. Marks emitted during a synchronized update
+/// are recorded where the cursor was before the update.
+pub(crate) struct ShellIntegrationPty<T> {
+    pty: T,
+    term: Arc<FairMutex<Term<ZedListener>>>,
+    listener: ZedListener,
+    scanner: MarkScanner,
+    marks: Arc<Mutex<Vec<(SemanticMark, MarkPoint)>>>,
+    image_scanner: ImageScanner,
+    image_decoder: ImageDecoder,
+    images: Arc<Mutex<InlineImages>>,
+    events: Vec<PtyEvent>,
+    window_size: WindowSize,
+    buffer: Box<[u8]>,
+    /// The bytes of `buffer` read from the PTY but not handed to the event loop yet.
+    pending: Range<usize>,
+    /// The largest buffer the event loop read into, i.e. its buffer when it parsed
+    /// everything it was given.
+    read_len: usize,
+    waker: ReadWaker,
+}
+
+impl<T> ShellIntegrationPty<T> {
+    pub(crate) fn new(
+        pty: T,
+        term: Arc<FairMutex<Term<ZedListener>>>,
+        listener: ZedListener,
+        marks: Arc<Mutex<Vec<(SemanticMark, MarkPoint)>>>,
+        images: Arc<Mutex<InlineImages>>,
+    ) -> Self {
+        Self {
+            pty,
+            term,
+            listener,
+            scanner: MarkScanner::default(),
+            marks,
+            image_scanner: ImageScanner::default(),
+            image_decoder: ImageDecoder::default(),
+            images,
+            events: Vec::new(),
+            window_size: TerminalSize::default().into(),
+            buffer: vec![0; 0x10000].into_boxed_slice(),
+            pending: 0..0,
+            read_len: 0,
+            waker: ReadWaker::default(),
+        }
+    }
+
+    fn apply_events(&mut self, term: &mut Term<ZedListener>) {
+        for event in self.events.drain(..) {
+            match event {
+                PtyEvent::Mark(mark) => {
+                    if !term.mode().contains(TermMode::ALT_SCREEN) {
+                        self.marks.lock().push((mark, MarkPoint::from_cursor(term)));
+                    }
+                }
+                PtyEvent::Image(actions) => {
+                    for action in actions {
+                        match action {
+                            ImageAction::Place(placement) => {
+                                self.images.lock().place(term, placement, self.window_size)
+                            }
+                            ImageAction::DeleteKittyImages(kitty_id) => {
+                                self.images.lock().remove_kitty_images(kitty_id)
+                            }
+                            ImageAction::Respond(response) => {
+                                self.listener.send_event(AlacTermEvent::PtyWrite(response))
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        self.listener.send_event(AlacTermEvent::Wakeup);
+    }
+}
+
+impl<T: EventedReadWrite> Read for ShellIntegrationPty<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.waker.reset();
+        self.read_len = self.read_len.max(buf.len());
+
+        if !self.events.is_empty() {
+            // The event loop keeps the bytes it could not parse yet at the start of its buffer.
+            if buf.len() < self.read_len {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            match self.term.try_lock_unfair() {
+                Some(mut term) => self.apply_events(&mut term),
+                None => {
+                    // Either the event loop keeps the terminal locked while it reads on, or
+                    // the UI has it. Stop this read so that the lock is released, and ask
+                    // to be read from again.
+                    self.waker.wake()?;
+                    return Err(io::ErrorKind::WouldBlock.into());
+                }
+            }
+        }
+
+        if self.pending.is_empty() {
+            let len = self.pty.reader().read(&mut self.buffer)?;
+            if len == 0 {
+                return Ok(0);
+            }
+            self.pending = 0..len;
+        }
+
+        let start = self.pending.start;
+        let mut end = start;
+        let limit = self.pending.end.min(start + buf.len());
+        while end < limit && self.events.is_empty() {
+            let byte = self.buffer[end];
+            end += 1;
+            if let Some(mark) = self.scanner.advance(byte) {
+                self.events.push(PtyEvent::Mark(mark));
+            }
+            if let Some(sequence) = self.image_scanner.advance(byte) {
+                // Decode before locking the terminal, this may take a while.
+                let actions = self.image_decoder.decode(sequence);
+                self.events.push(PtyEvent::Image(actions));
+            }
+        }
+        buf[..end - start].copy_from_slice(&self.buffer[start..end]);
+        self.pending.start = end;
+        Ok(end - start)
+    }
+}
+
+impl<T: EventedReadWrite> EventedReadWrite for ShellIntegrationPty<T> {
+    type Reader = Self;
+    type Writer = T::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poller: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.register(poller, interest, mode)?;
+        self.waker.register(poller, interest.key, mode)
+    }
+
+    fn reregister(
+        &mut self,
+        poller: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poller, interest, mode)?;
+        self.waker.reregister(mode)
+    }
+
+    fn deregister(&mut self, poller: &Arc<Poller>) -> io::Result<()> {
+        self.waker.deregister()?;
+        self.pty.deregister(poller)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl<T: EventedPty> EventedPty for ShellIntegrationPty<T> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl<T: OnResize> OnResize for ShellIntegrationPty<T> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.window_size = window_size;
+        self.pty.on_resize(window_size)
+    }
+}
+
+/// Reports the PTY as readable to the event loop's poller, so that it reads the output
+/// held back by [`ShellIntegrationPty`] even when the shell wrote nothing new.
+#[derive(Default)]
+struct ReadWaker {
+    poller: Option<(Arc<Poller>, usize)>,
+    /// A socket registered with the PTY's key, and the socket writing to it.
+    #[cfg(unix)]
+    sockets: Option<(UnixStream, UnixStream)>,
+    woken: bool,
+}
+
+impl ReadWaker {
+    fn register(&mut self, poller: &Arc<Poller>, key: usize, mode: PollMode) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            let (receiver, sender) = UnixStream::pair()?;
+            receiver.set_nonblocking(true)?;
+            sender.set_nonblocking(true)?;
+            // SAFETY: the socket is removed from the poller in `deregister`, or dropped
+            // together with the poller when the event loop exits.
+            unsafe { poller.add_with_mode(&receiver, PollingEvent::readable(key), mode)? };
+            self.sockets = Some((receiver, sender));
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+        self.poller = Some((poller.clone(), key));
+        Ok(())
+    }
+
+    fn reregister(&mut self, mode: PollMode) -> io::Result<()> {
+        #[cfg(unix)]
+        if let (Some((poller, key)), Some((receiver, _))) = (&self.poller, &self.sockets) {
+            poller.modify_with_mode(receiver, PollingEvent::readable(*key), mode)?;
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+        Ok(())
+    }
+
+    fn deregister(&mut self) -> io::Result<()> {
+        #[cfg(unix)]
+        if let (Some((poller, _)), Some((receiver, _))) = (&self.poller, self.sockets.take()) {
+            poller.delete(&receiver)?;
+        }
+        self.poller = None;
+        Ok(())
+    }
+
+    fn wake(&mut self) -> io::Result<()> {
+        if self.woken {
+            return Ok(());
+        }
+        #[cfg(unix)]
+        if let Some((_, sender)) = &mut self.sockets {
+            sender.write_all(&[0])?;
+        }
+        #[cfg(windows)]
+        if let Some((poller, key)) = &self.poller {
+            use polling::os::iocp::{CompletionPacket, PollerIocpExt};
+            poller.post(CompletionPacket::new(PollingEvent::readable(*key)))?;
+        }
+        self.woken = true;
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        if !std::mem::take(&mut self.woken) {
+            return;
+        }
+        #[cfg(unix)]
+        if let Some((receiver, _)) = &mut self.sockets {
+            let mut byte = [0];
+            while let Ok(1) = receiver.read(&mut byte) {}
+        }
+    }
+}
+
+/// Returns the text between two points of the grid, with trailing blank lines removed.
+pub(crate) fn text_between<T>(term: &Term<T>, start: AlacPoint, end: AlacPoint) -> String {
+    if end < start {
+        return String::new();
+    }
+    let text = term.bounds_to_string(start, end);
+    text.trim_end().to_string()
+}
+
+/// The last grid cell before the given mark.
+pub(crate) fn point_before<T>(term: &Term<T>, point: AlacPoint) -> AlacPoint {
+    if point.column.0 > 0 {
+        AlacPoint::new(point.line, Column(point.column.0 - 1))
+    } else {
+        AlacPoint::new(Line(point.line.0 - 1), term.last_column())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(input: &[u8]) -> Vec<SemanticMark> {
+        let mut scanner = MarkScanner::default();
+        input
+            .iter()
+            .filter_map(|byte| scanner.advance(*byte))
+            .collect()
+    }
+
+    #[test]
+    fn test_scanning_marks() {
+        assert_eq!(
+            scan(b"\x1b]133;A\x07~/zed $ \x1b]133;B\x07"),
+            vec![SemanticMark::PromptStart, SemanticMark::CommandStart]
+        );
+        assert_eq!(
+            scan(b"\x1b]133;C\x1b\\output\r\n\x1b]133;D;127\x1b\\"),
+            vec![
+                SemanticMark::CommandExecuted,
+                SemanticMark::CommandFinished {
+                    exit_code: Some(127)
+                }
+            ]
+        );
+        assert_eq!(
+            scan(b"\x1b]133;D\x07\x1b]133;A;cl=m;aid=42\x07"),
+            vec![
+                SemanticMark::CommandFinished { exit_code: None },
+                SemanticMark::PromptStart
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scanning_ignores_other_sequences() {
+        assert_eq!(
+            scan(b"\x1b]0;title\x07\x1b]8;;http://zed.dev\x1b\\"),
+            vec![]
+        );
+        assert_eq!(scan(b"\x1b[31m133;A\x07"), vec![]);
+        assert_eq!(scan(b"\x1b]133;Z\x07"), vec![]);
+
+        let mut long_osc = b"\x1b]133;A;".to_vec();
+        long_osc.extend(std::iter::repeat(b'x').take(MAX_MARK_LEN));
+        long_osc.push(BEL);
+        assert_eq!(scan(&long_osc), vec![]);
+    }
+
+    #[test]
+    fn test_scanning_split_input() {
+        let mut scanner = MarkScanner::default();
+        let input = b"\x1b]133;D;0\x1b\\";
+        let (first, second) = input.split_at(4);
+        assert!(first.iter().all(|byte| scanner.advance(*byte).is_none()));
+        let marks: Vec<_> = second
+            .iter()
+            .filter_map(|byte| scanner.advance(*byte))
+            .collect();
+        assert_eq!(
+            marks,
+            vec![SemanticMark::CommandFinished { exit_code: Some(0) }]
+        );
+    }
+
+    #[test]
+    fn test_command_blocks() {
+        let point = |line, column| MarkPoint { line, column };
+        let mut blocks = CommandBlocks::default();
+
+        blocks.push_mark(SemanticMark::PromptStart, point(0, 0));
+        blocks.push_mark(SemanticMark::CommandStart, point(0, 8));
+        blocks.push_mark(SemanticMark::CommandExecuted, point(1, 0));
+        blocks.push_mark(
+            SemanticMark::CommandFinished { exit_code: Some(1) },
+            point(4, 0),
+        );
+        // A redrawn prompt replaces the previous, empty one.
+        blocks.push_mark(SemanticMark::PromptStart, point(4, 0));
+        blocks.push_mark(SemanticMark::CommandStart, point(4, 8));
+        blocks.push_mark(SemanticMark::PromptStart, point(5, 0));
+        blocks.push_mark(SemanticMark::CommandStart, point(5, 8));
+
+        let all_blocks = blocks.iter().cloned().collect::<Vec<_>>();
+        assert_eq!(all_blocks.len(), 2);
+        assert_eq!(all_blocks[0].status, CommandStatus::Failed { exit_code: 1 });
+        assert_eq!(all_blocks[0].last_line(), Some(3));
+        assert_eq!(all_blocks[1].prompt_start, point(5, 0));
+        assert_eq!(all_blocks[1].status, CommandStatus::Running);
+        assert_eq!(blocks.last_finished(), Some(&all_blocks[0]));
+
+        // A second `D` mark without a new prompt is ignored.
+        blocks.push_mark(
+            SemanticMark::CommandFinished { exit_code: None },
+            point(6, 0),
+        );
+        blocks.push_mark(
+            SemanticMark::CommandFinished { exit_code: Some(0) },
+            point(7, 0),
+        );
+        assert_eq!(
+            blocks.last_finished().map(|block| block.status),
+            Some(CommandStatus::Finished)
+        );
+
+        blocks.truncate_after(4);
+        assert_eq!(blocks.iter().count(), 1);
+    }
+}
@@ -2,7 +2,9 @@ pub mod mappings;
 
 pub use alacritty_terminal;
 
+pub mod inline_images;
 mod pty_info;
+pub mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -13,7 +15,7 @@ use alacritty_terminal::{
     selection::{Selection, SelectionRange, SelectionType},
     sync::FairMutex,
     term::{
-        cell::Cell,
+        cell::{Cell, Flags},
         search::{Match, RegexIter, RegexSearch},
         Config, RenderableCursor, TermMode,
     },
@@ -38,9 +40,15 @@ use mappings::mouse::{
 
 use collections::{HashMap, VecDeque};
 use futures::StreamExt;
+use inline_images::InlineImages;
+use parking_lot::Mutex;
 use pty_info::PtyProcessInfo;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{
+    point_before, text_between, CommandBlocks, CommandStatus, MarkPoint, SemanticMark,
+    ShellIntegrationPty,
+};
 use smol::channel::{Receiver, Sender};
 use task::{HideStrategy, Shell, TaskId};
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
@@ -50,7 +58,7 @@ use util::{paths::home_dir, truncate_and_trailoff};
 use std::{
     cmp::{self, min},
     fmt::Display,
-    ops::{Deref, Index, RangeInclusive},
+    ops::{Deref, Index, Range, RangeInclusive},
     path::PathBuf,
     sync::Arc,
     time::Duration,
@@ -60,7 +68,7 @@ use thiserror::Error;
 use gpui::{
     actions, black, px, AnyWindowHandle, AppContext, Bounds, ClipboardItem, EventEmitter, Hsla,
     Keystroke, ModelContext, Modifiers, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
-    Pixels, Point, Rgba, ScrollWheelEvent, SharedString, Size, Task, TouchPhase,
+    Pixels, Point, RenderImage, Rgba, ScrollWheelEvent, SharedString, Size, Task, TouchPhase,
 };
 
 use crate::mappings::{colors::to_alac_rgb, keys::to_esc_str};
@@ -79,6 +87,11 @@ actions!(
         ScrollPageDown,
         ScrollToTop,
         ScrollToBottom,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        CopyLastCommandOutput,
+        RerunLastCommand,
+        FilterScrollback,
         ToggleViMode,
     ]
 );
@@ -91,6 +104,9 @@ const SCROLL_MULTIPLIER: f32 = 4.;
 #[cfg(not(target_os = "macos"))]
 const SCROLL_MULTIPLIER: f32 = 1.;
 const MAX_SEARCH_LINES: usize = 100;
+/// How many lines [`Terminal::filter_lines`] searches before it lets the PTY reader lock the
+/// terminal again.
+const FILTER_CHUNK_LINES: usize = 1_000;
 const DEBUG_TERMINAL_WIDTH: Pixels = px(500.);
 const DEBUG_TERMINAL_HEIGHT: Pixels = px(30.);
 const DEBUG_CELL_WIDTH: Pixels = px(5.);
@@ -136,6 +152,8 @@ enum InternalEvent {
     // FocusNextMatch,
     Scroll(AlacScroll),
     ScrollToAlacPoint(AlacPoint),
+    ScrollToPreviousPrompt,
+    ScrollToNextPrompt,
     SetSelection(Option<(Selection, AlacPoint)>),
     UpdateSelection(Point<Pixels>),
     // Adjusted mouse position, should open
@@ -434,6 +452,15 @@ impl TerminalBuilder {
         };
 
         let pty_info = PtyProcessInfo::new(&pty);
+        let shell_marks = Arc::new(Mutex::new(Vec::new()));
+        let inline_images = Arc::new(Mutex::new(InlineImages::default()));
+        let pty = ShellIntegrationPty::new(
+            pty,
+            term.clone(),
+            ZedListener(events_tx.clone()),
+            shell_marks.clone(),
+            inline_images.clone(),
+        );
 
         //And connect them together
         let event_loop = EventLoop::new(
@@ -473,6 +500,10 @@ impl TerminalBuilder {
             vi_mode_enabled: false,
             is_ssh_terminal,
             python_venv_directory,
+            shell_marks,
+            command_blocks: CommandBlocks::default(),
+            history_size: 0,
+            inline_images,
         };
 
         Ok(TerminalBuilder {
@@ -571,6 +602,35 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub size: TerminalSize,
     pub last_hovered_word: Option<HoveredWord>,
+    pub command_blocks: Vec<VisibleCommandBlock>,
+    pub images: Vec<VisibleImage>,
+}
+
+/// A command block overlapping the viewport, as reported by the shell integration.
+#[derive(Clone, Debug)]
+pub struct VisibleCommandBlock {
+    pub lines: RangeInclusive<Line>,
+    pub status: CommandStatus,
+}
+
+/// An inline image overlapping the viewport.
+#[derive(Clone, Debug)]
+pub struct VisibleImage {
+    pub image: Arc<RenderImage>,
+    /// The cell of the image's top left corner, which may be above the viewport.
+    pub origin: AlacPoint,
+    /// The size of the image, in cells.
+    pub size: Size<f32>,
+}
+
+/// A line of the terminal that matched a search, see [`Terminal::filter_lines`].
+#[derive(Clone, Debug)]
+pub struct FilteredLine {
+    pub text: String,
+    /// The byte ranges of the matches in `text`.
+    pub match_ranges: Vec<Range<usize>>,
+    /// The first match on the line. The points stay valid while more output scrolls in.
+    pub first_match: RangeInclusive<MarkPoint>,
 }
 
 #[derive(Clone)]
@@ -595,6 +655,8 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             size: Default::default(),
             last_hovered_word: None,
+            command_blocks: Vec::new(),
+            images: Vec::new(),
         }
     }
 }
@@ -632,6 +694,10 @@ pub struct Terminal {
     task: Option<TaskState>,
     vi_mode_enabled: bool,
     is_ssh_terminal: bool,
+    shell_marks: Arc<Mutex<Vec<(SemanticMark, MarkPoint)>>>,
+    command_blocks: CommandBlocks,
+    history_size: usize,
+    inline_images: Arc<Mutex<InlineImages>>,
 }
 
 pub struct TaskState {
@@ -712,6 +778,7 @@ impl Terminal {
                 //NOOP, Handled in render
             }
             AlacTermEvent::Wakeup => {
+                self.sync_command_blocks();
                 cx.emit(Event::Wakeup);
 
                 if self.pty_info.has_changed() {
@@ -790,6 +857,10 @@ impl Terminal {
                     term.grid_mut().reset_region((new_cursor.line + 1)..);
                 }
 
+                self.command_blocks.clear();
+                self.inline_images.lock().clear_normal_screen();
+                self.history_size = 0;
+
                 cx.emit(Event::Wakeup);
             }
             InternalEvent::Scroll(scroll) => {
@@ -876,6 +947,24 @@ impl Terminal {
                 term.scroll_to_point(*point);
                 self.refresh_hovered_word();
             }
+            InternalEvent::ScrollToPreviousPrompt | InternalEvent::ScrollToNextPrompt => {
+                let viewport_top = Line(-(term.grid().display_offset() as i32));
+                let mut prompt_lines = self
+                    .command_blocks
+                    .iter()
+                    .filter_map(|block| block.prompt_start.to_alac_point(term))
+                    .map(|point| point.line);
+                let target = if matches!(event, InternalEvent::ScrollToPreviousPrompt) {
+                    prompt_lines.filter(|line| *line < viewport_top).last()
+                } else {
+                    prompt_lines.find(|line| *line > viewport_top)
+                };
+
+                if let Some(target) = target {
+                    term.scroll_display(AlacScroll::Delta(viewport_top.0 - target.0));
+                    self.refresh_hovered_word();
+                }
+            }
             InternalEvent::ToggleViMode => {
                 self.vi_mode_enabled = !self.vi_mode_enabled;
                 term.toggle_vi_mode();
@@ -1136,6 +1225,14 @@ impl Terminal {
             .push_back(InternalEvent::Scroll(AlacScroll::Bottom));
     }
 
+    pub fn scroll_to_previous_prompt(&mut self) {
+        self.events.push_back(InternalEvent::ScrollToPreviousPrompt);
+    }
+
+    pub fn scroll_to_next_prompt(&mut self) {
+        self.events.push_back(InternalEvent::ScrollToNextPrompt);
+    }
+
     ///Resize the terminal and the PTY.
     pub fn set_size(&mut self, new_size: TerminalSize) {
         if self.last_content.size != new_size {
@@ -1308,11 +1405,85 @@ impl Terminal {
             self.process_terminal_event(&e, &mut terminal, cx)
         }
 
-        self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content = Self::make_content(
+            &terminal,
+            &self.last_content,
+            &self.command_blocks,
+            &self.inline_images.lock(),
+        );
+    }
+
+    /// Moves the semantic prompt marks reported by the PTY reader into the command blocks,
+    /// and drops the marks and images that are no longer shown.
+    fn sync_command_blocks(&mut self) {
+        let term = self.term.clone();
+        let terminal = term.lock_unfair();
+        // The alternate screen has no scrollback, the normal one is left as is meanwhile.
+        if terminal.mode().contains(TermMode::ALT_SCREEN) {
+            return;
+        }
+        self.inline_images.lock().clear_alt_screen();
+
+        let history_size = terminal.grid().history_size();
+        // The scrollback was cleared, the lines recorded so far point to unrelated content.
+        if history_size < self.history_size {
+            self.command_blocks.clear();
+            self.inline_images.lock().clear_normal_screen();
+        }
+        self.history_size = history_size;
+
+        for (mark, point) in self.shell_marks.lock().drain(..) {
+            if mark == SemanticMark::PromptStart {
+                self.command_blocks.truncate_after(point.line);
+            }
+            self.command_blocks.push_mark(mark, point);
+        }
     }
 
-    fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
+    fn make_content(
+        term: &Term<ZedListener>,
+        last_content: &TerminalContent,
+        command_blocks: &CommandBlocks,
+        inline_images: &InlineImages,
+    ) -> TerminalContent {
         let content = term.renderable_content();
+        let viewport_top = Line(-(content.display_offset as i32));
+        let viewport_bottom = viewport_top + term.bottommost_line();
+        let command_blocks = command_blocks
+            .iter()
+            .filter_map(|block| {
+                let start = block.prompt_start.to_alac_point(term)?.line;
+                let end = match block.last_line() {
+                    Some(line) => MarkPoint { line, column: 0 }.to_alac_point(term)?.line,
+                    None => term.grid().cursor.point.line,
+                };
+                if start > viewport_bottom || end < viewport_top {
+                    return None;
+                }
+                Some(VisibleCommandBlock {
+                    lines: start..=end.max(start),
+                    status: block.status,
+                })
+            })
+            .collect();
+        let alt_screen = content.mode.contains(TermMode::ALT_SCREEN);
+        let history_size = term.grid().history_size() as i32;
+        let images = inline_images
+            .iter()
+            .filter(|image| image.alt_screen() == alt_screen)
+            .filter_map(|image| {
+                let start = Line(image.origin.line as i32 - history_size);
+                let end = start + (image.line_count() as i32 - 1);
+                if start > viewport_bottom || end < viewport_top {
+                    return None;
+                }
+                Some(VisibleImage {
+                    image: image.image.clone(),
+                    origin: AlacPoint::new(start, Column(image.origin.column)),
+                    size: image.size,
+                })
+            })
+            .collect();
         TerminalContent {
             cells: content
                 .display_iter
@@ -1336,6 +1507,50 @@ impl Terminal {
             cursor_char: term.grid()[content.cursor.point].c,
             size: last_content.size,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            command_blocks,
+            images,
+        }
+    }
+
+    pub fn command_blocks(&self) -> &CommandBlocks {
+        &self.command_blocks
+    }
+
+    /// The command line of the last command that finished running in the shell.
+    ///
+    /// Only available when the shell reports its prompts with `OSC 133` marks.
+    pub fn last_command(&self) -> Option<String> {
+        let block = self.command_blocks.last_finished()?;
+        let term = self.term.lock_unfair();
+        let start = block.command_start?.to_alac_point(&term)?;
+        let end = point_before(&term, block.output_start?.to_alac_point(&term)?);
+        let command = text_between(&term, start, end);
+        let command = command.trim();
+        (!command.is_empty()).then(|| command.to_string())
+    }
+
+    /// The output printed by the last command that finished running in the shell.
+    pub fn last_command_output(&self) -> Option<String> {
+        let block = self.command_blocks.last_finished()?;
+        let term = self.term.lock_unfair();
+        let start = block.output_start?.to_alac_point(&term)?;
+        let end = point_before(&term, block.command_end?.to_alac_point(&term)?);
+        Some(text_between(&term, start, end))
+    }
+
+    pub fn last_command_status(&self) -> Option<CommandStatus> {
+        Some(self.command_blocks.last_finished()?.status)
+    }
+
+    pub fn copy_last_command_output(&mut self, cx: &mut ModelContext<Self>) {
+        if let Some(output) = self.last_command_output() {
+            cx.write_to_clipboard(ClipboardItem::new_string(output));
+        }
+    }
+
+    pub fn rerun_last_command(&mut self) {
+        if let Some(command) = self.last_command() {
+            self.input(format!("{command}\r"));
         }
     }
 
@@ -1643,6 +1858,38 @@ impl Terminal {
         })
     }
 
+    /// Collects the lines of the whole scrollback that contain a match.
+    pub fn filter_lines(
+        &self,
+        mut searcher: RegexSearch,
+        cx: &ModelContext<Self>,
+    ) -> Task<Vec<FilteredLine>> {
+        let term = self.term.clone();
+        cx.background_executor()
+            .spawn(async move { filter_lines(&term, &mut searcher, FILTER_CHUNK_LINES) })
+    }
+
+    /// Scrolls to and selects the first match of a line returned by [`Terminal::filter_lines`].
+    pub fn jump_to_filtered_line(&mut self, line: &FilteredLine) {
+        let term = self.term.clone();
+        let (start, end) = {
+            let term = term.lock_unfair();
+            let Some((start, end)) = line
+                .first_match
+                .start()
+                .to_alac_point(&term)
+                .zip(line.first_match.end().to_alac_point(&term))
+            else {
+                return;
+            };
+            (start, end)
+        };
+
+        self.set_selection(Some((make_selection(&(start..=end)), end)));
+        self.events
+            .push_back(InternalEvent::ScrollToAlacPoint(start));
+    }
+
     pub fn working_directory(&self) -> Option<PathBuf> {
         if self.is_ssh_terminal {
             // We can't yet reliably detect the working directory of a shell on the
@@ -1898,6 +2145,93 @@ fn all_search_matches<'a, T>(
     RegexIter::new(start, end, AlacDirection::Right, term, regex)
 }
 
+/// Searches the grid a chunk of lines at a time, only locking the terminal while searching
+/// each chunk.
+fn filter_lines<T>(
+    term: &FairMutex<Term<T>>,
+    searcher: &mut RegexSearch,
+    chunk_lines: usize,
+) -> Vec<FilteredLine> {
+    let mut lines: Vec<FilteredLine> = Vec::new();
+    let mut column_offsets = Vec::new();
+    // Counted from the top of the scrollback, so that it stays put while output scrolls in.
+    let mut next_line = 0;
+    loop {
+        let term = term.lock();
+        let grid = term.grid();
+        let history_size = grid.history_size() as i32;
+        let to_mark_point = |point: AlacPoint| MarkPoint {
+            line: (history_size + point.line.0).max(0) as usize,
+            column: point.column.0,
+        };
+
+        let start_line = Line(next_line as i32 - history_size).max(grid.topmost_line());
+        if start_line > grid.bottommost_line() {
+            break;
+        }
+        let mut end_line = Line(start_line.0 + chunk_lines as i32 - 1).min(grid.bottommost_line());
+        // Matches may continue on the next line of a wrapped one.
+        while end_line < grid.bottommost_line()
+            && grid[end_line][grid.last_column()]
+                .flags
+                .contains(Flags::WRAPLINE)
+        {
+            end_line = Line(end_line.0 + 1);
+        }
+        let start = AlacPoint::new(start_line, Column(0));
+        let end = AlacPoint::new(end_line, grid.last_column());
+        next_line = to_mark_point(end).line + 1;
+
+        let mut current_line = None;
+        for search_match in RegexIter::new(start, end, AlacDirection::Right, &term, searcher) {
+            let (start, end) = (*search_match.start(), *search_match.end());
+            if current_line != Some(start.line) {
+                current_line = Some(start.line);
+                let text = line_text(&term, start.line, &mut column_offsets);
+                lines.push(FilteredLine {
+                    text,
+                    match_ranges: Vec::new(),
+                    first_match: to_mark_point(start)..=to_mark_point(end),
+                });
+            }
+
+            let line = lines.last_mut().unwrap();
+            let start_offset = column_offsets[start.column.0].min(line.text.len());
+            let end_offset = if end.line == start.line {
+                column_offsets[end.column.0 + 1].min(line.text.len())
+            } else {
+                line.text.len()
+            };
+            if start_offset < end_offset {
+                line.match_ranges.push(start_offset..end_offset);
+            }
+        }
+    }
+    lines
+}
+
+/// The text of a grid line without trailing whitespace, and the byte offset of each column in it.
+fn line_text<T>(term: &Term<T>, line: Line, column_offsets: &mut Vec<usize>) -> String {
+    let mut text = String::new();
+    column_offsets.clear();
+    for cell in &term.grid()[line] {
+        column_offsets.push(text.len());
+        if cell
+            .flags
+            .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+        {
+            continue;
+        }
+        text.push(cell.c);
+        if let Some(zerowidth) = cell.zerowidth() {
+            text.extend(zerowidth);
+        }
+    }
+    column_offsets.push(text.len());
+    text.truncate(text.trim_end().len());
+    text
+}
+
 fn content_index_for_mouse(pos: Point<Pixels>, size: &TerminalSize) -> usize {
     let col = (pos.x / size.cell_width()).round() as usize;
     let clamped_col = min(col, size.columns() - 1);
@@ -1993,14 +2327,19 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
 #[cfg(test)]
 mod tests {
     use alacritty_terminal::{
+        event::VoidListener,
         index::{Column, Line, Point as AlacPoint},
-        term::cell::Cell,
+        sync::FairMutex,
+        term::{cell::Cell, search::RegexSearch, Config},
+        vte::ansi::Handler,
+        Term,
     };
     use gpui::{point, size, Pixels};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        content_index_for_mouse, filter_lines, rgb_for_index, IndexedCell, MarkPoint,
+        TerminalContent, TerminalSize,
     };
 
     #[test]
@@ -2125,6 +2464,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_filter_lines() {
+        // The default size has 6 lines, most of the output ends up in the scrollback.
+        let mut term = Term::new(Config::default(), &TerminalSize::default(), VoidListener);
+        for line in 0..20 {
+            let status = if line % 3 == 0 { "error" } else { "ok" };
+            for c in format!("line {line}: {status}, {status}").chars() {
+                term.input(c);
+            }
+            term.carriage_return();
+            term.linefeed();
+        }
+        let term = FairMutex::new(term);
+
+        let mut searcher = RegexSearch::new("err").unwrap();
+        let lines = filter_lines(&term, &mut searcher, 4);
+        assert_eq!(
+            lines
+                .iter()
+                .map(|line| line.text.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "line 0: error, error",
+                "line 3: error, error",
+                "line 6: error, error",
+                "line 9: error, error",
+                "line 12: error, error",
+                "line 15: error, error",
+                "line 18: error, error",
+            ]
+        );
+        assert_eq!(lines[1].match_ranges, [8..11, 15..18]);
+        assert_eq!(
+            lines[1].first_match,
+            MarkPoint { line: 3, column: 8 }..=MarkPoint {
+                line: 3,
+                column: 10
+            }
+        );
+
+        let mut searcher = RegexSearch::new("missing").unwrap();
+        assert!(filter_lines(&term, &mut searcher, 4).is_empty());
+    }
+
     fn re_test(re: &str, hay: &str, expected: Vec<&str>) {
         let results: Vec<_> = regex::Regex::new(re)
             .unwrap()
@@ -0,0 +1,312 @@
+use std::sync::Arc;
+
+use editor::{Editor, EditorSettings};
+use gpui::{
+    rems, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, HighlightStyle,
+    Model, Render, StyledText, Task, View, ViewContext, WeakView, WindowContext,
+};
+use itertools::Itertools;
+use picker::{Picker, PickerDelegate};
+use regex::Regex;
+use search::{SearchOptions, ToggleCaseSensitive, ToggleRegex};
+use settings::Settings;
+use terminal::{alacritty_terminal::term::search::RegexSearch, FilteredLine, Terminal};
+use theme::color_alpha;
+use ui::{prelude::*, KeyBinding, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::{regex_to_literal, TerminalView};
+
+/// Lists every line of the terminal's scrollback that matches a regex or text query,
+/// like running `grep` over the terminal history.
+pub struct ScrollbackFilter {
+    picker: View<Picker<ScrollbackFilterDelegate>>,
+}
+
+impl ScrollbackFilter {
+    pub(crate) fn new(
+        terminal_view: WeakView<TerminalView>,
+        terminal: Model<Terminal>,
+        workspace: WeakView<Workspace>,
+        query: String,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let options = SearchOptions::from_settings(&EditorSettings::get_global(cx).search)
+            & (SearchOptions::REGEX | SearchOptions::CASE_SENSITIVE);
+        let delegate = ScrollbackFilterDelegate {
+            scrollback_filter: cx.view().downgrade(),
+            terminal_view,
+            terminal,
+            workspace,
+            options,
+            lines: Vec::new(),
+            selected_index: 0,
+        };
+        let picker =
+            cx.new_view(|cx| Picker::uniform_list(delegate, cx).max_height(Some(vh(0.75, cx))));
+        picker.update(cx, |picker, cx| picker.set_query(query, cx));
+        Self { picker }
+    }
+
+    fn toggle_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.options.toggle(option);
+            picker.refresh(cx);
+        });
+    }
+}
+
+impl FocusableView for ScrollbackFilter {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for ScrollbackFilter {}
+impl ModalView for ScrollbackFilter {}
+
+impl Render for ScrollbackFilter {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("ScrollbackFilter")
+            .w(rems(40.))
+            .on_action(
+                cx.listener(|this, _: &ToggleRegex, cx| {
+                    this.toggle_option(SearchOptions::REGEX, cx)
+                }),
+            )
+            .on_action(cx.listener(|this, _: &ToggleCaseSensitive, cx| {
+                this.toggle_option(SearchOptions::CASE_SENSITIVE, cx)
+            }))
+            .child(self.picker.clone())
+    }
+}
+
+pub struct ScrollbackFilterDelegate {
+    scrollback_filter: WeakView<ScrollbackFilter>,
+    terminal_view: WeakView<TerminalView>,
+    terminal: Model<Terminal>,
+    workspace: WeakView<Workspace>,
+    options: SearchOptions,
+    lines: Vec<FilteredLine>,
+    selected_index: usize,
+}
+
+impl ScrollbackFilterDelegate {
+    fn jump_to_selected_line(&self, cx: &mut WindowContext) {
+        if let Some(line) = self.lines.get(self.selected_index) {
+            self.terminal.update(cx, |terminal, cx| {
+                terminal.jump_to_filtered_line(line);
+                cx.notify();
+            });
+        }
+    }
+
+    /// Opens the matching lines in a new buffer.
+    fn export_to_buffer(&self, cx: &mut WindowContext) {
+        let text = self.lines.iter().map(|line| line.text.as_str()).join("\n");
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let project = workspace.project().clone();
+                let buffer = project.update(cx, |project, cx| {
+                    project.create_local_buffer(&text, None, cx)
+                });
+                let editor = cx.new_view(|cx| Editor::for_buffer(buffer, Some(project), cx));
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+            })
+            .log_err();
+    }
+}
+
+impl PickerDelegate for ScrollbackFilterDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        if self.options.contains(SearchOptions::REGEX) {
+            "Filter terminal lines by regex...".into()
+        } else {
+            "Filter terminal lines...".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+        self.jump_to_selected_line(cx);
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let searcher = if query.is_empty() {
+            None
+        } else {
+            regex_search(
+                &query,
+                self.options.contains(SearchOptions::REGEX),
+                self.options.contains(SearchOptions::CASE_SENSITIVE),
+            )
+        };
+        let Some(searcher) = searcher else {
+            self.lines.clear();
+            self.selected_index = 0;
+            return Task::ready(());
+        };
+
+        let lines = self
+            .terminal
+            .update(cx, |terminal, cx| terminal.filter_lines(searcher, cx));
+        cx.spawn(|picker, mut cx| async move {
+            let lines = lines.await;
+            picker
+                .update(&mut cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    delegate.lines = lines;
+                    // The last line is the closest one to the cursor.
+                    delegate.selected_index = delegate.lines.len().saturating_sub(1);
+                    delegate.jump_to_selected_line(cx);
+                    cx.notify();
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if secondary {
+            self.export_to_buffer(cx);
+        } else {
+            self.jump_to_selected_line(cx);
+            self.terminal_view
+                .update(cx, |_, cx| cx.focus_self())
+                .log_err();
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.scrollback_filter
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let line = self.lines.get(ix)?;
+        let highlight_style = HighlightStyle {
+            background_color: Some(color_alpha(cx.theme().colors().text_accent, 0.3)),
+            ..Default::default()
+        };
+        let text = StyledText::new(line.text.clone()).with_highlights(
+            &cx.text_style(),
+            line.match_ranges
+                .iter()
+                .map(|range| (range.clone(), highlight_style)),
+        );
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            Label::new((line.first_match.start().line + 1).to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .child(div().text_ui(cx).font_buffer(cx).child(text)),
+                ),
+        )
+    }
+
+    fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        let focus_handle = self.scrollback_filter.upgrade()?.read(cx).focus_handle(cx);
+        let option_button = |option: SearchOptions| {
+            let scrollback_filter = self.scrollback_filter.clone();
+            option.as_button(
+                self.options.contains(option),
+                focus_handle.clone(),
+                move |_, cx| {
+                    scrollback_filter
+                        .update(cx, |this, cx| this.toggle_option(option, cx))
+                        .log_err();
+                },
+            )
+        };
+
+        Some(
+            h_flex()
+                .w_full()
+                .p_2()
+                .gap_1()
+                .justify_between()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(option_button(SearchOptions::CASE_SENSITIVE))
+                        .child(option_button(SearchOptions::REGEX))
+                        .child(
+                            Label::new(format!("{} lines", self.lines.len()))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                )
+                .child(
+                    Button::new("export", "Export to Buffer")
+                        .label_size(LabelSize::Small)
+                        .disabled(self.lines.is_empty())
+                        .key_binding(KeyBinding::for_action(&menu::SecondaryConfirm, cx))
+                        .on_click(|_, cx| cx.dispatch_action(menu::SecondaryConfirm.boxed_clone())),
+                )
+                .into_any(),
+        )
+    }
+}
+
+/// Builds the searcher for the filter query, applying the filter's own case sensitivity
+/// instead of the smart case of the buffer search.
+///
+/// Patterns that match an empty string are rejected, as they would match every line.
+fn regex_search(query: &str, regex: bool, case_sensitive: bool) -> Option<RegexSearch> {
+    let pattern = if regex {
+        query.to_string()
+    } else {
+        regex_to_literal(query)
+    };
+    let flags = if case_sensitive { "(?-i)" } else { "(?i)" };
+    let pattern = format!("{flags}{pattern}");
+    if Regex::new(&pattern).map_or(true, |compiled| compiled.is_match("")) {
+        return None;
+    }
+    RegexSearch::new(&pattern).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_matching_regexes_are_rejected() {
+        assert!(regex_search(".", true, false).is_some());
+        assert!(regex_search(".*", true, false).is_none());
+        assert!(regex_search("a?|b", true, false).is_none());
+        assert!(regex_search("^", true, false).is_none());
+        assert!(regex_search(".*", false, false).is_some());
+        assert!(regex_search("(", true, true).is_none());
+        assert!(regex_search("(", false, true).is_some());
+    }
+}
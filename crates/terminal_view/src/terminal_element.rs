@@ -1,6 +1,6 @@
 use editor::{CursorLayout, HighlightedRange, HighlightedRangeLine};
 use gpui::{
-    div, fill, point, px, relative, size, AnyElement, AvailableSpace, Bounds, ContentMask,
+    div, fill, point, px, relative, size, AnyElement, AvailableSpace, Bounds, ContentMask, Corners,
     DispatchPhase, Element, ElementId, FocusHandle, Font, FontStyle, FontWeight, GlobalElementId,
     HighlightStyle, Hitbox, Hsla, InputHandler, InteractiveElement, Interactivity, IntoElement,
     LayoutId, Model, ModelContext, ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels,
@@ -20,11 +20,13 @@ use terminal::{
             CursorShape as AlacCursorShape, NamedColor,
         },
     },
+    shell_integration::CommandStatus,
     terminal_settings::TerminalSettings,
-    HoveredWord, IndexedCell, Terminal, TerminalContent, TerminalSize,
+    HoveredWord, IndexedCell, Terminal, TerminalContent, TerminalSize, VisibleImage,
 };
 use theme::{ActiveTheme, Theme, ThemeSettings};
 use ui::{ParentElement, Tooltip};
+use util::ResultExt;
 use workspace::Workspace;
 
 use std::mem;
@@ -47,6 +49,8 @@ pub struct LayoutState {
     gutter: Pixels,
     last_hovered_word: Option<HoveredWord>,
     block_below_cursor_element: Option<AnyElement>,
+    command_markers: Vec<CommandMarker>,
+    images: Vec<VisibleImage>,
 }
 
 /// Helper struct for converting data between Alacritty's cursor points, and displayed cursor points.
@@ -145,6 +149,58 @@ impl LayoutRect {
     }
 }
 
+/// A bar in the gutter, spanning the lines of a shell command and colored by its exit status.
+struct CommandMarker {
+    lines: RangeInclusive<i32>,
+    color: Hsla,
+}
+
+impl CommandMarker {
+    fn paint(
+        &self,
+        origin: Point<Pixels>,
+        gutter: Pixels,
+        dimensions: &TerminalSize,
+        cx: &mut WindowContext,
+    ) {
+        let width = (gutter * 0.25).max(px(2.));
+        let position = point(
+            origin.x - (gutter + width) / 2.,
+            origin.y + *self.lines.start() as f32 * dimensions.line_height,
+        );
+        let line_count = self.lines.end() - self.lines.start() + 1;
+        let marker_size = size(width, dimensions.line_height * line_count as f32);
+
+        cx.paint_quad(fill(Bounds::new(position, marker_size), self.color));
+    }
+}
+
+fn paint_image(
+    image: &VisibleImage,
+    origin: Point<Pixels>,
+    display_offset: usize,
+    dimensions: &TerminalSize,
+    cx: &mut WindowContext,
+) {
+    let line = image.origin.line.0 + display_offset as i32;
+    let position = point(
+        origin.x + image.origin.column.0 as f32 * dimensions.cell_width,
+        origin.y + line as f32 * dimensions.line_height,
+    );
+    let image_size = size(
+        dimensions.cell_width * image.size.width,
+        dimensions.line_height * image.size.height,
+    );
+    cx.paint_image(
+        Bounds::new(position, image_size),
+        Corners::default(),
+        image.image.clone(),
+        0,
+        false,
+    )
+    .log_err();
+}
+
 /// The GPUI element that paints the terminal.
 /// We need to keep a reference to the view for mouse events, do we need it for any other terminal stuff, or can we move that to connection?
 pub struct TerminalElement {
@@ -735,10 +791,31 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    command_blocks,
+                    images,
                     ..
                 } = &self.terminal.read(cx).last_content;
                 let mode = *mode;
                 let display_offset = *display_offset;
+                let images = images.clone();
+
+                let command_markers = command_blocks
+                    .iter()
+                    .filter_map(|block| {
+                        let color = match block.status {
+                            CommandStatus::Running => return None,
+                            CommandStatus::Succeeded => theme.status().success,
+                            CommandStatus::Failed { .. } => theme.status().error,
+                            CommandStatus::Finished => theme.colors().text_muted,
+                        };
+                        let start = block.lines.start().0 + display_offset as i32;
+                        let end = block.lines.end().0 + display_offset as i32;
+                        Some(CommandMarker {
+                            lines: start..=end,
+                            color,
+                        })
+                    })
+                    .collect();
 
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
@@ -857,6 +934,8 @@ impl Element for TerminalElement {
                     gutter,
                     last_hovered_word,
                     block_below_cursor_element,
+                    command_markers,
+                    images,
                 }
             })
     }
@@ -919,6 +998,10 @@ impl Element for TerminalElement {
                         rect.paint(origin, &layout.dimensions, cx);
                     }
 
+                    for marker in &layout.command_markers {
+                        marker.paint(origin, layout.gutter, &layout.dimensions, cx);
+                    }
+
                     for (relative_highlighted_range, color) in
                         layout.relative_highlighted_ranges.iter()
                     {
@@ -940,6 +1023,10 @@ impl Element for TerminalElement {
                         cell.paint(origin, &layout.dimensions, bounds, cx);
                     }
 
+                    for image in &layout.images {
+                        paint_image(image, origin, layout.display_offset, &layout.dimensions, cx);
+                    }
+
                     if self.cursor_visible {
                         if let Some(mut cursor) = cursor {
                             cursor.paint(origin, cx);
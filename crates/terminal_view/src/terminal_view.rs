@@ -1,4 +1,5 @@
 mod persistence;
+pub mod scrollback_filter;
 pub mod terminal_element;
 pub mod terminal_panel;
 
@@ -14,15 +15,17 @@ use gpui::{
 use language::Bias;
 use persistence::TERMINAL_DB;
 use project::{search::SearchQuery, terminals::TerminalKind, Fs, Metadata, Project};
+use scrollback_filter::ScrollbackFilter;
 use terminal::{
     alacritty_terminal::{
         index::Point,
         term::{search::RegexSearch, TermMode},
     },
     terminal_settings::{CursorShape, TerminalBlink, TerminalSettings, WorkingDirectory},
-    Clear, Copy, Event, MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp, ScrollPageDown,
-    ScrollPageUp, ScrollToBottom, ScrollToTop, ShowCharacterPalette, TaskStatus, Terminal,
-    TerminalSize, ToggleViMode,
+    Clear, Copy, CopyLastCommandOutput, Event, FilterScrollback, MaybeNavigationTarget, Paste,
+    RerunLastCommand, ScrollLineDown, ScrollLineUp, ScrollPageDown, ScrollPageUp, ScrollToBottom,
+    ScrollToNextPrompt, ScrollToPreviousPrompt, ScrollToTop, ShowCharacterPalette, TaskStatus,
+    Terminal, TerminalSize, ToggleViMode,
 };
 use terminal_element::{is_blank, TerminalElement};
 use terminal_panel::TerminalPanel;
@@ -210,6 +213,12 @@ impl TerminalView {
             .map_or(false, |terminal_panel| {
                 terminal_panel.read(cx).assistant_enabled()
             });
+        let has_finished_command = self
+            .terminal
+            .read(cx)
+            .command_blocks()
+            .last_finished()
+            .is_some();
         let context_menu = ContextMenu::build(cx, |menu, _| {
             menu.context(self.focus_handle.clone())
                 .action("New Terminal", Box::new(NewTerminal))
@@ -218,6 +227,12 @@ impl TerminalView {
                 .action("Paste", Box::new(Paste))
                 .action("Select All", Box::new(SelectAll))
                 .action("Clear", Box::new(Clear))
+                .action("Filter Scrollback…", Box::new(FilterScrollback))
+                .when(has_finished_command, |menu| {
+                    menu.separator()
+                        .action("Copy Last Command Output", Box::new(CopyLastCommandOutput))
+                        .action("Rerun Last Command", Box::new(RerunLastCommand))
+                })
                 .when(assistant_enabled, |menu| {
                     menu.separator()
                         .action("Inline Assist", Box::new(InlineAssist::default()))
@@ -415,6 +430,53 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(&mut self, _: &ScrollToNextPrompt, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn copy_last_command_output(&mut self, _: &CopyLastCommandOutput, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, cx| term.copy_last_command_output(cx));
+    }
+
+    fn rerun_last_command(&mut self, _: &RerunLastCommand, cx: &mut ViewContext<Self>) {
+        self.clear_bell(cx);
+        self.terminal
+            .update(cx, |term, _| term.rerun_last_command());
+    }
+
+    fn filter_scrollback(&mut self, _: &FilterScrollback, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let terminal_view = cx.view().downgrade();
+        let terminal = self.terminal.clone();
+        let query = terminal
+            .read(cx)
+            .last_content
+            .selection_text
+            .clone()
+            .unwrap_or_default();
+        let weak_workspace = self.workspace.clone();
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(cx, |cx| {
+                ScrollbackFilter::new(terminal_view, terminal, weak_workspace, query, cx)
+            });
+        });
+    }
+
     fn toggle_vi_mode(&mut self, _: &ToggleViMode, cx: &mut ViewContext<Self>) {
         self.terminal.update(cx, |term, _| term.toggle_vi_mode());
         cx.notify();
@@ -957,6 +1019,11 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::scroll_page_down))
             .on_action(cx.listener(TerminalView::scroll_to_top))
             .on_action(cx.listener(TerminalView::scroll_to_bottom))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
+            .on_action(cx.listener(TerminalView::rerun_last_command))
+            .on_action(cx.listener(TerminalView::filter_scrollback))
             .on_action(cx.listener(TerminalView::toggle_vi_mode))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
@@ -386,6 +386,9 @@ pub fn init(app_state: Arc<AppState>, cx: &mut AppContext) {
 #[derive(Clone, Default, Deref, DerefMut)]
 struct ProjectItemOpeners(Vec<ProjectItemOpener>);
 
+#[derive(Clone, Default, Deref, DerefMut)]
+struct ProjectItemFallbackOpeners(Vec<ProjectItemOpener>);
+
 type ProjectItemOpener = fn(
     &Model<Project>,
     &ProjectPath,
@@ -396,26 +399,40 @@ type ProjectItemOpener = fn(
 type WorkspaceItemBuilder = Box<dyn FnOnce(&mut ViewContext<Pane>) -> Box<dyn ItemHandle>>;
 
 impl Global for ProjectItemOpeners {}
+impl Global for ProjectItemFallbackOpeners {}
 
 /// Registers a [ProjectItem] for the app. When opening a file, all the registered
 /// items will get a chance to open the file, starting from the project item that
 /// was added last.
 pub fn register_project_item<I: ProjectItem>(cx: &mut AppContext) {
     let builders = cx.default_global::<ProjectItemOpeners>();
-    builders.push(|project, project_path, cx| {
-        let project_item = <I::Item as project::ProjectItem>::try_open(project, project_path, cx)?;
-        let project = project.clone();
-        Some(cx.spawn(|cx| async move {
-            let project_item = project_item.await?;
-            let project_entry_id: Option<ProjectEntryId> =
-                project_item.read_with(&cx, project::ProjectItem::entry_id)?;
-            let build_workspace_item = Box::new(|cx: &mut ViewContext<Pane>| {
-                Box::new(cx.new_view(|cx| I::for_project_item(project, project_item, cx)))
-                    as Box<dyn ItemHandle>
-            }) as Box<_>;
-            Ok((project_entry_id, build_workspace_item))
-        }))
-    });
+    builders.push(project_item_opener::<I>);
+}
+
+/// Registers a [ProjectItem] that gets a chance to open a file when the project item
+/// that would normally open it fails to load it, e.g. because it isn't valid UTF-8.
+pub fn register_project_item_fallback<I: ProjectItem>(cx: &mut AppContext) {
+    let builders = cx.default_global::<ProjectItemFallbackOpeners>();
+    builders.push(project_item_opener::<I>);
+}
+
+fn project_item_opener<I: ProjectItem>(
+    project: &Model<Project>,
+    project_path: &ProjectPath,
+    cx: &mut WindowContext,
+) -> Option<Task<Result<(Option<ProjectEntryId>, WorkspaceItemBuilder)>>> {
+    let project_item = <I::Item as project::ProjectItem>::try_open(project, project_path, cx)?;
+    let project = project.clone();
+    Some(cx.spawn(|cx| async move {
+        let project_item = project_item.await?;
+        let project_entry_id: Option<ProjectEntryId> =
+            project_item.read_with(&cx, project::ProjectItem::entry_id)?;
+        let build_workspace_item = Box::new(|cx: &mut ViewContext<Pane>| {
+            Box::new(cx.new_view(|cx| I::for_project_item(project, project_item, cx)))
+                as Box<dyn ItemHandle>
+        }) as Box<_>;
+        Ok((project_entry_id, build_workspace_item))
+    }))
 }
 
 #[derive(Default)]
@@ -2722,7 +2739,28 @@ impl Workspace {
         else {
             return Task::ready(Err(anyhow!("cannot open file {:?}", path.path)));
         };
-        open_project_item
+
+        let fallback_builders = cx.default_global::<ProjectItemFallbackOpeners>().clone();
+        if fallback_builders.is_empty() {
+            return open_project_item;
+        }
+        cx.spawn(|mut cx| async move {
+            let error = match open_project_item.await {
+                Ok(item) => return Ok(item),
+                Err(error) => error,
+            };
+            let open_fallback_item = cx.update(|cx| {
+                fallback_builders
+                    .iter()
+                    .rev()
+                    .find_map(|open_project_item| open_project_item(&project, &path, cx))
+            })?;
+            match open_fallback_item {
+                // Report why the file couldn't be opened normally if the fallback fails too.
+                Some(open_fallback_item) => open_fallback_item.await.map_err(|_| error),
+                None => Err(error),
+            }
+        })
     }
 
     pub fn find_project_item<T>(
@@ -20,6 +20,7 @@ pub struct WorkspaceSettings {
     pub drop_target_size: f32,
     pub when_closing_with_no_tabs: CloseWindowWhenNoItems,
     pub use_system_path_prompts: bool,
+    pub show_picker_preview: bool,
     pub command_aliases: HashMap<String, String>,
     pub show_user_picture: bool,
     pub max_tabs: Option<NonZeroUsize>,
@@ -127,6 +128,11 @@ pub struct WorkspaceSettingsContent {
     ///
     /// Default: true
     pub use_system_path_prompts: Option<bool>,
+    /// Whether the file finder, symbol pickers, live grep and tab switcher show a preview
+    /// of the selected match next to the list.
+    ///
+    /// Default: false
+    pub show_picker_preview: Option<bool>,
     /// Aliases for the command palette. When you type a key in this map,
     /// it will be assumed to equal the value.
     ///
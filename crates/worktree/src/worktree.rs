@@ -53,6 +53,7 @@ use std::{
     ffi::OsStr,
     fmt,
     future::Future,
+    io::{self, Read as _},
     mem,
     ops::{AddAssign, Deref, DerefMut, Sub},
     path::{Path, PathBuf},
@@ -111,6 +112,13 @@ pub struct LoadedBinaryFile {
     pub content: Vec<u8>,
 }
 
+pub struct LoadedLargeFile {
+    pub file: Arc<File>,
+    /// The text of the file, with its line endings normalized.
+    pub text: Rope,
+    pub line_ending: LineEnding,
+}
+
 pub struct LocalWorktree {
     snapshot: LocalSnapshot,
     scan_requests_tx: channel::Sender<ScanRequest>,
@@ -709,6 +717,37 @@ impl Worktree {
         }
     }
 
+    /// Loads a file in chunks straight into a rope, without holding a copy of its whole text.
+    pub fn load_large_file(
+        &self,
+        path: &Path,
+        cx: &ModelContext<Worktree>,
+    ) -> Task<Result<LoadedLargeFile>> {
+        match self {
+            Worktree::Local(this) => this.load_large_file(path, cx),
+            Worktree::Remote(_) => {
+                Task::ready(Err(anyhow!("remote worktrees can't yet load files")))
+            }
+        }
+    }
+
+    /// Returns the size of a file on disk without loading it, or `None` if it doesn't exist.
+    pub fn file_size(&self, path: &Path, cx: &AppContext) -> Task<Result<Option<u64>>> {
+        match self {
+            Worktree::Local(this) => {
+                let fs = this.fs.clone();
+                let abs_path = this.absolutize(path);
+                cx.background_executor().spawn(async move {
+                    let metadata = fs.metadata(&abs_path?).await?;
+                    Ok(metadata.map(|metadata| metadata.len))
+                })
+            }
+            Worktree::Remote(_) => {
+                Task::ready(Err(anyhow!("remote worktrees can't yet load files")))
+            }
+        }
+    }
+
     pub fn load_staged_file(&self, path: &Path, cx: &AppContext) -> Task<Result<Option<String>>> {
         match self {
             Worktree::Local(this) => {
@@ -761,6 +800,20 @@ impl Worktree {
         }
     }
 
+    pub fn write_binary_file(
+        &self,
+        path: &Path,
+        content: Vec<u8>,
+        cx: &ModelContext<Worktree>,
+    ) -> Task<Result<Arc<File>>> {
+        match self {
+            Worktree::Local(this) => this.write_binary_file(path, content, cx),
+            Worktree::Remote(_) => {
+                Task::ready(Err(anyhow!("remote worktree can't yet write binary files")))
+            }
+        }
+    }
+
     pub fn create_entry(
         &mut self,
         path: impl Into<Arc<Path>>,
@@ -1381,6 +1434,61 @@ impl LocalWorktree {
         })
     }
 
+    fn load_large_file(
+        &self,
+        path: &Path,
+        cx: &ModelContext<Worktree>,
+    ) -> Task<Result<LoadedLargeFile>> {
+        let path = Arc::from(path);
+        let abs_path = self.absolutize(&path);
+        let fs = self.fs.clone();
+        let entry = self.refresh_entry(path.clone(), None, cx);
+        let is_private = self.is_path_private(path.as_ref());
+
+        let worktree = cx.weak_model();
+        cx.background_executor().spawn(async move {
+            let abs_path = abs_path?;
+            let (text, line_ending) = {
+                let reader = fs.open_sync(&abs_path).await?;
+                read_normalized_rope(reader)?
+            };
+
+            let worktree = worktree
+                .upgrade()
+                .ok_or_else(|| anyhow!("worktree was dropped"))?;
+            let file = match entry.await? {
+                Some(entry) => File::for_entry(entry, worktree),
+                None => {
+                    let metadata = fs
+                        .metadata(&abs_path)
+                        .await
+                        .with_context(|| {
+                            format!("Loading metadata for excluded file {abs_path:?}")
+                        })?
+                        .with_context(|| {
+                            format!("Excluded file {abs_path:?} got removed during loading")
+                        })?;
+                    Arc::new(File {
+                        entry_id: None,
+                        worktree,
+                        path,
+                        disk_state: DiskState::Present {
+                            mtime: metadata.mtime,
+                        },
+                        is_local: true,
+                        is_private,
+                    })
+                }
+            };
+
+            Ok(LoadedLargeFile {
+                file,
+                text,
+                line_ending,
+            })
+        })
+    }
+
     fn load_file(&self, path: &Path, cx: &ModelContext<Worktree>) -> Task<Result<LoadedFile>> {
         let path = Arc::from(path);
         let abs_path = self.absolutize(&path);
@@ -1508,18 +1616,42 @@ impl LocalWorktree {
         line_ending: LineEnding,
         cx: &ModelContext<Worktree>,
     ) -> Task<Result<Arc<File>>> {
-        let path = path.into();
+        self.write_contents(path.into(), cx, move |fs, abs_path| async move {
+            fs.save(&abs_path, &text, line_ending).await
+        })
+    }
+
+    fn write_binary_file(
+        &self,
+        path: impl Into<Arc<Path>>,
+        content: Vec<u8>,
+        cx: &ModelContext<Worktree>,
+    ) -> Task<Result<Arc<File>>> {
+        self.write_contents(path.into(), cx, move |fs, abs_path| async move {
+            fs.create_file_with(&abs_path, Pin::new(&mut content.as_slice()))
+                .await
+        })
+    }
+
+    fn write_contents<F, Fut>(
+        &self,
+        path: Arc<Path>,
+        cx: &ModelContext<Worktree>,
+        write: F,
+    ) -> Task<Result<Arc<File>>>
+    where
+        F: 'static + Send + FnOnce(Arc<dyn Fs>, PathBuf) -> Fut,
+        Fut: 'static + Send + Future<Output = Result<()>>,
+    {
         let fs = self.fs.clone();
         let is_private = self.is_path_private(&path);
         let Ok(abs_path) = self.absolutize(&path) else {
             return Task::ready(Err(anyhow!("invalid path {path:?}")));
         };
 
-        let write = cx.background_executor().spawn({
-            let fs = fs.clone();
-            let abs_path = abs_path.clone();
-            async move { fs.save(&abs_path, &text, line_ending).await }
-        });
+        let write = cx
+            .background_executor()
+            .spawn(write(fs.clone(), abs_path.clone()));
 
         cx.spawn(move |this, mut cx| async move {
             write.await?;
@@ -5100,6 +5232,42 @@ fn swap_to_front(child_paths: &mut Vec<PathBuf>, file: &OsStr) {
     }
 }
 
+/// Reads text into a rope one chunk at a time, normalizing its line endings like
+/// [`text::Buffer::new`] does.
+fn read_normalized_rope(mut reader: impl io::Read) -> Result<(Rope, LineEnding)> {
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    let mut rope = Rope::new();
+    let mut line_ending = None;
+    let mut pending = Vec::with_capacity(CHUNK_SIZE);
+    loop {
+        let read = (&mut reader)
+            .take(CHUNK_SIZE as u64)
+            .read_to_end(&mut pending)?;
+        let done = read == 0;
+        // Leave a character split between chunks, and a carriage return that may be followed by
+        // a line feed, for the next chunk.
+        let mut valid_len = match std::str::from_utf8(&pending) {
+            Ok(_) => pending.len(),
+            Err(error) if error.error_len().is_none() && !done => error.valid_up_to(),
+            Err(error) => return Err(error.into()),
+        };
+        if !done && pending[..valid_len].ends_with(b"\r") {
+            valid_len -= 1;
+        }
+
+        let mut text = String::from_utf8(pending.drain(..valid_len).collect())?;
+        if line_ending.is_none() && (!text.is_empty() || done) {
+            line_ending = Some(LineEnding::detect(&text));
+        }
+        LineEnding::normalize(&mut text);
+        rope.push(&text);
+        if done {
+            return Ok((rope, line_ending.unwrap_or_default()));
+        }
+    }
+}
+
 fn char_bag_for_path(root_char_bag: CharBag, path: &Path) -> CharBag {
     let mut result = root_char_bag;
     result.extend(
@@ -19,6 +19,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
+use text::LineEnding;
 use util::{test::temp_tree, ResultExt};
 
 #[gpui::test]
@@ -890,6 +891,52 @@ async fn test_write_file(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_load_large_file(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    // Line endings and multi-byte characters end up split between the chunks the file is read in.
+    let text = "héllo\r\nwörld\r".repeat(20_000);
+    fs.insert_tree("/root", json!({ "large.txt": text.clone() }))
+        .await;
+
+    let tree = Worktree::local(
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    let size = tree
+        .read_with(cx, |tree, cx| tree.file_size("large.txt".as_ref(), cx))
+        .await
+        .unwrap();
+    assert_eq!(size, Some(text.len() as u64));
+    let missing_size = tree
+        .read_with(cx, |tree, cx| tree.file_size("missing.txt".as_ref(), cx))
+        .await
+        .unwrap();
+    assert_eq!(missing_size, None);
+
+    let loaded = tree
+        .update(cx, |tree, cx| {
+            tree.load_large_file("large.txt".as_ref(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(loaded.file.path.as_ref(), Path::new("large.txt"));
+    assert_eq!(loaded.line_ending, LineEnding::Windows);
+    assert_eq!(
+        loaded.text.to_string(),
+        text.replace("\r\n", "\n").replace('\r', "\n")
+    );
+}
+
 #[gpui::test]
 async fn test_file_scan_inclusions(cx: &mut TestAppContext) {
     init_test(cx);
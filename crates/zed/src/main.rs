@@ -431,6 +431,7 @@ fn main() {
         app_state.languages.set_theme(cx.theme().clone());
         editor::init(cx);
         image_viewer::init(cx);
+        hex_editor::init(cx);
         repl::notebook::init(cx);
         diagnostics::init(cx);
 
@@ -447,11 +448,13 @@ fn main() {
         outline_panel::init(Assets, cx);
         tasks_ui::init(cx);
         snippets_ui::init(cx);
+        spellcheck::init(app_state.fs.clone(), cx);
         channel::init(&app_state.client.clone(), app_state.user_store.clone(), cx);
         search::init(cx);
         vim::init(cx);
         terminal_view::init(cx);
         journal::init(app_state.clone(), cx);
+        jump_list::init(cx);
         language_selector::init(cx);
         toolchain_selector::init(cx);
         theme_selector::init(cx);
@@ -680,6 +680,8 @@ fn initialize_pane(workspace: &Workspace, pane: &View<Pane>, cx: &mut ViewContex
 
             let proposed_change_bar = cx.new_view(|_| ProposedChangesEditorToolbar::new());
             toolbar.add_item(proposed_change_bar, cx);
+            let large_file_banner = cx.new_view(|_| editor::LargeFileBanner::new());
+            toolbar.add_item(large_file_banner, cx);
             let quick_action_bar =
                 cx.new_view(|cx| QuickActionBar::new(buffer_search_bar, workspace, cx));
             toolbar.add_item(quick_action_bar, cx);
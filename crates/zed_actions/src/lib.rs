@@ -89,7 +89,7 @@ pub struct OpenRecent {
     pub create_new_window: bool,
 }
 gpui::impl_actions!(projects, [OpenRecent]);
-gpui::actions!(projects, [OpenRemote]);
+gpui::actions!(projects, [OpenRemote, OpenPorts]);
 
 /// Where to spawn the task in the UI.
 #[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]